@@ -0,0 +1,705 @@
+# generated by the stub test in src/py/stub.rs, do not edit
+from __future__ import annotations
+
+from collections.abc import Iterator
+from os import PathLike
+from typing import Any, ClassVar
+
+
+class ElectricPotential:
+    def __init__(self, unit: str) -> None: ...
+    def convert(self, unit: str | ElectricPotential, value: float) -> float: ...
+    @staticmethod
+    def variants() -> list[str]: ...
+    def as_si(self) -> float: ...
+    def __getnewargs__(self) -> tuple[str]: ...
+    def __hash__(self) -> int: ...
+    def __eq__(self, other: object) -> bool: ...
+    def __ne__(self, other: object) -> bool: ...
+    def __lt__(self, other: ElectricPotential) -> bool: ...
+    def __le__(self, other: ElectricPotential) -> bool: ...
+    def __gt__(self, other: ElectricPotential) -> bool: ...
+    def __ge__(self, other: ElectricPotential) -> bool: ...
+    YV: ClassVar[ElectricPotential]
+    ZV: ClassVar[ElectricPotential]
+    EV: ClassVar[ElectricPotential]
+    PV: ClassVar[ElectricPotential]
+    TV: ClassVar[ElectricPotential]
+    GV: ClassVar[ElectricPotential]
+    MV: ClassVar[ElectricPotential]
+    kV: ClassVar[ElectricPotential]
+    hV: ClassVar[ElectricPotential]
+    daV: ClassVar[ElectricPotential]
+    V: ClassVar[ElectricPotential]
+    dV: ClassVar[ElectricPotential]
+    cV: ClassVar[ElectricPotential]
+    mV: ClassVar[ElectricPotential]
+    uV: ClassVar[ElectricPotential]
+    nV: ClassVar[ElectricPotential]
+    pV: ClassVar[ElectricPotential]
+    fV: ClassVar[ElectricPotential]
+    aV: ClassVar[ElectricPotential]
+    zV: ClassVar[ElectricPotential]
+    yV: ClassVar[ElectricPotential]
+
+class Frequency:
+    def __init__(self, unit: str) -> None: ...
+    def convert(self, unit: str | Frequency, value: float) -> float: ...
+    @staticmethod
+    def variants() -> list[str]: ...
+    def as_si(self) -> float: ...
+    def __getnewargs__(self) -> tuple[str]: ...
+    def __hash__(self) -> int: ...
+    def __eq__(self, other: object) -> bool: ...
+    def __ne__(self, other: object) -> bool: ...
+    def __lt__(self, other: Frequency) -> bool: ...
+    def __le__(self, other: Frequency) -> bool: ...
+    def __gt__(self, other: Frequency) -> bool: ...
+    def __ge__(self, other: Frequency) -> bool: ...
+    YHz: ClassVar[Frequency]
+    ZHz: ClassVar[Frequency]
+    EHz: ClassVar[Frequency]
+    PHz: ClassVar[Frequency]
+    THz: ClassVar[Frequency]
+    GHz: ClassVar[Frequency]
+    MHz: ClassVar[Frequency]
+    kHz: ClassVar[Frequency]
+    hHz: ClassVar[Frequency]
+    daHz: ClassVar[Frequency]
+    Hz: ClassVar[Frequency]
+    dHz: ClassVar[Frequency]
+    cHz: ClassVar[Frequency]
+    mHz: ClassVar[Frequency]
+    uHz: ClassVar[Frequency]
+    nHz: ClassVar[Frequency]
+    pHz: ClassVar[Frequency]
+    fHz: ClassVar[Frequency]
+    aHz: ClassVar[Frequency]
+    zHz: ClassVar[Frequency]
+    yHz: ClassVar[Frequency]
+
+class Length:
+    def __init__(self, unit: str) -> None: ...
+    def convert(self, unit: str | Length, value: float) -> float: ...
+    @staticmethod
+    def variants() -> list[str]: ...
+    def as_si(self) -> float: ...
+    def __getnewargs__(self) -> tuple[str]: ...
+    def __hash__(self) -> int: ...
+    def __eq__(self, other: object) -> bool: ...
+    def __ne__(self, other: object) -> bool: ...
+    def __lt__(self, other: Length) -> bool: ...
+    def __le__(self, other: Length) -> bool: ...
+    def __gt__(self, other: Length) -> bool: ...
+    def __ge__(self, other: Length) -> bool: ...
+    Ym: ClassVar[Length]
+    Zm: ClassVar[Length]
+    Em: ClassVar[Length]
+    Pm: ClassVar[Length]
+    Tm: ClassVar[Length]
+    Gm: ClassVar[Length]
+    Mm: ClassVar[Length]
+    km: ClassVar[Length]
+    hm: ClassVar[Length]
+    dam: ClassVar[Length]
+    m: ClassVar[Length]
+    dm: ClassVar[Length]
+    cm: ClassVar[Length]
+    mm: ClassVar[Length]
+    um: ClassVar[Length]
+    nm: ClassVar[Length]
+    pm: ClassVar[Length]
+    fm: ClassVar[Length]
+    am: ClassVar[Length]
+    zm: ClassVar[Length]
+    ym: ClassVar[Length]
+    A: ClassVar[Length]
+    Thou: ClassVar[Length]
+    Li: ClassVar[Length]
+    In: ClassVar[Length]
+    Ft: ClassVar[Length]
+    Yd: ClassVar[Length]
+    Mi: ClassVar[Length]
+    Ua: ClassVar[Length]
+    Ly: ClassVar[Length]
+    Pc: ClassVar[Length]
+    Pt: ClassVar[Length]
+    Pixel: ClassVar[Length]
+    ReferenceFrame: ClassVar[Length]
+
+class Power:
+    def __init__(self, unit: str) -> None: ...
+    def convert(self, unit: str | Power, value: float) -> float: ...
+    @staticmethod
+    def variants() -> list[str]: ...
+    def as_si(self) -> float: ...
+    def __getnewargs__(self) -> tuple[str]: ...
+    def __hash__(self) -> int: ...
+    def __eq__(self, other: object) -> bool: ...
+    def __ne__(self, other: object) -> bool: ...
+    def __lt__(self, other: Power) -> bool: ...
+    def __le__(self, other: Power) -> bool: ...
+    def __gt__(self, other: Power) -> bool: ...
+    def __ge__(self, other: Power) -> bool: ...
+    YW: ClassVar[Power]
+    ZW: ClassVar[Power]
+    EW: ClassVar[Power]
+    PW: ClassVar[Power]
+    TW: ClassVar[Power]
+    GW: ClassVar[Power]
+    MW: ClassVar[Power]
+    kW: ClassVar[Power]
+    hW: ClassVar[Power]
+    daW: ClassVar[Power]
+    W: ClassVar[Power]
+    dW: ClassVar[Power]
+    cW: ClassVar[Power]
+    mW: ClassVar[Power]
+    uW: ClassVar[Power]
+    nW: ClassVar[Power]
+    pW: ClassVar[Power]
+    fW: ClassVar[Power]
+    aW: ClassVar[Power]
+    zW: ClassVar[Power]
+    yW: ClassVar[Power]
+
+class Pressure:
+    def __init__(self, unit: str) -> None: ...
+    def convert(self, unit: str | Pressure, value: float) -> float: ...
+    @staticmethod
+    def variants() -> list[str]: ...
+    def as_si(self) -> float: ...
+    def __getnewargs__(self) -> tuple[str]: ...
+    def __hash__(self) -> int: ...
+    def __eq__(self, other: object) -> bool: ...
+    def __ne__(self, other: object) -> bool: ...
+    def __lt__(self, other: Pressure) -> bool: ...
+    def __le__(self, other: Pressure) -> bool: ...
+    def __gt__(self, other: Pressure) -> bool: ...
+    def __ge__(self, other: Pressure) -> bool: ...
+    YPa: ClassVar[Pressure]
+    ZPa: ClassVar[Pressure]
+    EPa: ClassVar[Pressure]
+    PPa: ClassVar[Pressure]
+    TPa: ClassVar[Pressure]
+    GPa: ClassVar[Pressure]
+    MPa: ClassVar[Pressure]
+    kPa: ClassVar[Pressure]
+    hPa: ClassVar[Pressure]
+    daPa: ClassVar[Pressure]
+    Pa: ClassVar[Pressure]
+    dPa: ClassVar[Pressure]
+    cPa: ClassVar[Pressure]
+    mPa: ClassVar[Pressure]
+    uPa: ClassVar[Pressure]
+    nPa: ClassVar[Pressure]
+    pPa: ClassVar[Pressure]
+    fPa: ClassVar[Pressure]
+    aPa: ClassVar[Pressure]
+    zPa: ClassVar[Pressure]
+    yPa: ClassVar[Pressure]
+    bar: ClassVar[Pressure]
+    Mbar: ClassVar[Pressure]
+    kbar: ClassVar[Pressure]
+    dbar: ClassVar[Pressure]
+    cbar: ClassVar[Pressure]
+    mbar: ClassVar[Pressure]
+    atm: ClassVar[Pressure]
+    psi: ClassVar[Pressure]
+    Torr: ClassVar[Pressure]
+    mTorr: ClassVar[Pressure]
+    mmHg: ClassVar[Pressure]
+
+class Temperature:
+    def __init__(self, unit: str) -> None: ...
+    def convert(self, unit: str | Temperature, value: float) -> float: ...
+    @staticmethod
+    def variants() -> list[str]: ...
+    def as_si(self) -> float: ...
+    def __getnewargs__(self) -> tuple[str]: ...
+    def __hash__(self) -> int: ...
+    def __eq__(self, other: object) -> bool: ...
+    def __ne__(self, other: object) -> bool: ...
+    def __lt__(self, other: Temperature) -> bool: ...
+    def __le__(self, other: Temperature) -> bool: ...
+    def __gt__(self, other: Temperature) -> bool: ...
+    def __ge__(self, other: Temperature) -> bool: ...
+    C: ClassVar[Temperature]
+    F: ClassVar[Temperature]
+    K: ClassVar[Temperature]
+    R: ClassVar[Temperature]
+
+class Time:
+    def __init__(self, unit: str) -> None: ...
+    def convert(self, unit: str | Time, value: float) -> float: ...
+    @staticmethod
+    def variants() -> list[str]: ...
+    def as_si(self) -> float: ...
+    def __getnewargs__(self) -> tuple[str]: ...
+    def __hash__(self) -> int: ...
+    def __eq__(self, other: object) -> bool: ...
+    def __ne__(self, other: object) -> bool: ...
+    def __lt__(self, other: Time) -> bool: ...
+    def __le__(self, other: Time) -> bool: ...
+    def __gt__(self, other: Time) -> bool: ...
+    def __ge__(self, other: Time) -> bool: ...
+    Ys: ClassVar[Time]
+    Zs: ClassVar[Time]
+    Es: ClassVar[Time]
+    Ps: ClassVar[Time]
+    Ts: ClassVar[Time]
+    Gs: ClassVar[Time]
+    Ms: ClassVar[Time]
+    ks: ClassVar[Time]
+    hs: ClassVar[Time]
+    das: ClassVar[Time]
+    s: ClassVar[Time]
+    ds: ClassVar[Time]
+    cs: ClassVar[Time]
+    ms: ClassVar[Time]
+    us: ClassVar[Time]
+    ns: ClassVar[Time]
+    ps: ClassVar[Time]
+    fs: ClassVar[Time]
+    zs: ClassVar[Time]
+    ys: ClassVar[Time]
+    min: ClassVar[Time]
+    h: ClassVar[Time]
+    d: ClassVar[Time]
+
+class Ome:
+    """the root of the metadata, get one with Ome.from_xml"""
+    @property
+    def uuid(self) -> str | None: ...
+    @property
+    def creator(self) -> str | None: ...
+    @property
+    def rights(self) -> dict[str, Any] | None: ...
+    @property
+    def project(self) -> list[dict[str, Any]]: ...
+    @property
+    def dataset(self) -> list[dict[str, Any]]: ...
+    @property
+    def folder(self) -> list[dict[str, Any]]: ...
+    @property
+    def experiment(self) -> list[dict[str, Any]]: ...
+    @property
+    def plate(self) -> list[dict[str, Any]]: ...
+    @property
+    def screen(self) -> list[dict[str, Any]]: ...
+    @property
+    def experimenter(self) -> list[dict[str, Any]]: ...
+    @property
+    def experimenter_group(self) -> list[dict[str, Any]]: ...
+    @property
+    def instrument(self) -> list[Instrument]: ...
+    @property
+    def instruments(self) -> list[Instrument]: ...
+    @property
+    def image(self) -> list[Image]: ...
+    @property
+    def images(self) -> list[Image]: ...
+    @property
+    def structured_annotations(self) -> dict[str, Any] | None: ...
+    @property
+    def roi(self) -> list[dict[str, Any]]: ...
+    @property
+    def binary_only(self) -> dict[str, Any] | None: ...
+    @property
+    def original_namespace(self) -> str | None: ...
+    @property
+    def unknown_xml(self) -> list[dict[str, Any]]: ...
+    def to_dict(self) -> dict[str, Any]: ...
+    def __getitem__(self, key: str) -> Any: ...
+    def keys(self) -> list[str]: ...
+    def __iter__(self) -> Iterator[str]: ...
+    def __contains__(self, key: str) -> bool: ...
+    @staticmethod
+    def from_xml(xml: str | bytes) -> Ome: ...
+    def __reduce__(self) -> tuple[Any, tuple[str]]: ...
+    @staticmethod
+    def _from_pickle(xml: str) -> Ome: ...
+    def validate(self, *, raise_on_error: bool = False) -> list[dict[str, Any]]: ...
+    @staticmethod
+    def from_dict(dict: dict[str, Any]) -> Ome: ...
+    @staticmethod
+    def from_file(path: str | PathLike[str]) -> Ome: ...
+    def to_xml(self, *, pretty: bool = False, write_default_units: bool = True) -> str: ...
+
+class Image:
+    @property
+    def id(self) -> str: ...
+    @property
+    def name(self) -> str | None: ...
+    @property
+    def acquisition_date(self) -> str | None: ...
+    @property
+    def experimenter_ref(self) -> dict[str, Any] | None: ...
+    @property
+    def description(self) -> str | None: ...
+    @property
+    def experiment_ref(self) -> dict[str, Any] | None: ...
+    @property
+    def experimenter_group_ref(self) -> dict[str, Any] | None: ...
+    @property
+    def instrument_ref(self) -> dict[str, Any] | None: ...
+    @property
+    def objective_settings(self) -> dict[str, Any] | None: ...
+    @property
+    def imaging_environment(self) -> dict[str, Any] | None: ...
+    @property
+    def stage_label(self) -> dict[str, Any] | None: ...
+    @property
+    def pixels(self) -> Pixels: ...
+    @property
+    def roi_ref(self) -> list[dict[str, Any]]: ...
+    @property
+    def microbeam_manipulation_ref(self) -> list[dict[str, Any]]: ...
+    @property
+    def annotation_ref(self) -> list[dict[str, Any]]: ...
+    def to_dict(self) -> dict[str, Any]: ...
+    def __getitem__(self, key: str) -> Any: ...
+    def keys(self) -> list[str]: ...
+    def __iter__(self) -> Iterator[str]: ...
+    def __contains__(self, key: str) -> bool: ...
+
+class Pixels:
+    @property
+    def id(self) -> str: ...
+    @property
+    def dimension_order(self) -> str: ...
+    @property
+    def type(self) -> str: ...
+    @property
+    def significant_bits(self) -> int | None: ...
+    @property
+    def interleaved(self) -> bool | None: ...
+    @property
+    def big_endian(self) -> bool | None: ...
+    @property
+    def size_x(self) -> int: ...
+    @property
+    def size_y(self) -> int: ...
+    @property
+    def size_z(self) -> int: ...
+    @property
+    def size_c(self) -> int: ...
+    @property
+    def size_t(self) -> int: ...
+    @property
+    def physical_size_x(self) -> float | None: ...
+    @property
+    def physical_size_x_unit(self) -> Length: ...
+    @property
+    def physical_size_y(self) -> float | None: ...
+    @property
+    def physical_size_y_unit(self) -> Length: ...
+    @property
+    def physical_size_z(self) -> float | None: ...
+    @property
+    def physical_size_z_unit(self) -> Length: ...
+    @property
+    def time_increment(self) -> float | None: ...
+    @property
+    def time_increment_unit(self) -> Time: ...
+    @property
+    def channel(self) -> list[Channel]: ...
+    @property
+    def channels(self) -> list[Channel]: ...
+    @property
+    def bin_data(self) -> list[dict[str, Any]]: ...
+    @property
+    def tiff_data(self) -> list[dict[str, Any]]: ...
+    @property
+    def metadata_only(self) -> int | None: ...
+    @property
+    def plane(self) -> list[Plane]: ...
+    @property
+    def planes(self) -> list[Plane]: ...
+    def to_dict(self) -> dict[str, Any]: ...
+    def __getitem__(self, key: str) -> Any: ...
+    def keys(self) -> list[str]: ...
+    def __iter__(self) -> Iterator[str]: ...
+    def __contains__(self, key: str) -> bool: ...
+
+class Channel:
+    @property
+    def id(self) -> str: ...
+    @property
+    def name(self) -> str | None: ...
+    @property
+    def samples_per_pixel(self) -> int | None: ...
+    @property
+    def illumination_type(self) -> str | None: ...
+    @property
+    def pinhole_size(self) -> float | None: ...
+    @property
+    def pinhole_size_unit(self) -> Length: ...
+    @property
+    def acquisition_mode(self) -> str | None: ...
+    @property
+    def contrast_method(self) -> str | None: ...
+    @property
+    def excitation_wavelength(self) -> float | None: ...
+    @property
+    def excitation_wavelength_unit(self) -> Length: ...
+    @property
+    def emission_wavelength(self) -> float | None: ...
+    @property
+    def emission_wavelength_unit(self) -> Length: ...
+    @property
+    def fluor(self) -> str | None: ...
+    @property
+    def nd_filter(self) -> float | None: ...
+    @property
+    def pockel_cell_setting(self) -> int | None: ...
+    @property
+    def color(self) -> int: ...
+    @property
+    def light_source_settings(self) -> dict[str, Any] | None: ...
+    @property
+    def detector_settings(self) -> dict[str, Any] | None: ...
+    @property
+    def filter_set_ref(self) -> dict[str, Any] | None: ...
+    @property
+    def annotation_ref(self) -> list[dict[str, Any]]: ...
+    @property
+    def light_path(self) -> dict[str, Any] | None: ...
+    def to_dict(self) -> dict[str, Any]: ...
+    def __getitem__(self, key: str) -> Any: ...
+    def keys(self) -> list[str]: ...
+    def __iter__(self) -> Iterator[str]: ...
+    def __contains__(self, key: str) -> bool: ...
+
+class Plane:
+    @property
+    def the_z(self) -> int: ...
+    @property
+    def the_t(self) -> int: ...
+    @property
+    def the_c(self) -> int: ...
+    @property
+    def delta_t(self) -> float | None: ...
+    @property
+    def delta_t_unit(self) -> Time: ...
+    @property
+    def exposure_time(self) -> float | None: ...
+    @property
+    def exposure_time_unit(self) -> Time: ...
+    @property
+    def position_x(self) -> float | None: ...
+    @property
+    def position_x_unit(self) -> Length: ...
+    @property
+    def position_y(self) -> float | None: ...
+    @property
+    def position_y_unit(self) -> Length: ...
+    @property
+    def position_z(self) -> float | None: ...
+    @property
+    def position_z_unit(self) -> Length: ...
+    @property
+    def hash_sha1(self) -> str | None: ...
+    @property
+    def annotation_ref(self) -> list[dict[str, Any]]: ...
+    def to_dict(self) -> dict[str, Any]: ...
+    def __getitem__(self, key: str) -> Any: ...
+    def keys(self) -> list[str]: ...
+    def __iter__(self) -> Iterator[str]: ...
+    def __contains__(self, key: str) -> bool: ...
+
+class Instrument:
+    @property
+    def id(self) -> str: ...
+    @property
+    def microscope(self) -> dict[str, Any] | None: ...
+    @property
+    def light_source_group(self) -> list[dict[str, Any]]: ...
+    @property
+    def detector(self) -> list[Detector]: ...
+    @property
+    def detectors(self) -> list[Detector]: ...
+    @property
+    def objective(self) -> list[Objective]: ...
+    @property
+    def objectives(self) -> list[Objective]: ...
+    @property
+    def filter_set(self) -> list[dict[str, Any]]: ...
+    @property
+    def filter(self) -> list[dict[str, Any]]: ...
+    @property
+    def dichroic(self) -> list[dict[str, Any]]: ...
+    @property
+    def annotation_ref(self) -> list[dict[str, Any]]: ...
+    def to_dict(self) -> dict[str, Any]: ...
+    def __getitem__(self, key: str) -> Any: ...
+    def keys(self) -> list[str]: ...
+    def __iter__(self) -> Iterator[str]: ...
+    def __contains__(self, key: str) -> bool: ...
+
+class Objective:
+    @property
+    def manufacturer(self) -> str | None: ...
+    @property
+    def model(self) -> str | None: ...
+    @property
+    def serial_number(self) -> str | None: ...
+    @property
+    def lot_number(self) -> str | None: ...
+    @property
+    def id(self) -> str: ...
+    @property
+    def correction(self) -> str | None: ...
+    @property
+    def immersion(self) -> str | None: ...
+    @property
+    def lens_na(self) -> float | None: ...
+    @property
+    def nominal_magnification(self) -> float | None: ...
+    @property
+    def calibrated_magnification(self) -> float | None: ...
+    @property
+    def working_distance(self) -> float | None: ...
+    @property
+    def working_distance_unit(self) -> Length: ...
+    @property
+    def iris(self) -> bool | None: ...
+    @property
+    def annotation_ref(self) -> list[dict[str, Any]]: ...
+    def to_dict(self) -> dict[str, Any]: ...
+    def __getitem__(self, key: str) -> Any: ...
+    def keys(self) -> list[str]: ...
+    def __iter__(self) -> Iterator[str]: ...
+    def __contains__(self, key: str) -> bool: ...
+
+class Detector:
+    @property
+    def manufacturer(self) -> str | None: ...
+    @property
+    def model(self) -> str | None: ...
+    @property
+    def serial_number(self) -> str | None: ...
+    @property
+    def lot_number(self) -> str | None: ...
+    @property
+    def gain(self) -> float | None: ...
+    @property
+    def voltage(self) -> float | None: ...
+    @property
+    def voltage_unit(self) -> ElectricPotential: ...
+    @property
+    def offset(self) -> float | None: ...
+    @property
+    def zoom(self) -> float | None: ...
+    @property
+    def amplification_gain(self) -> float | None: ...
+    @property
+    def id(self) -> str: ...
+    @property
+    def type(self) -> str | None: ...
+    @property
+    def annotation_ref(self) -> list[dict[str, Any]]: ...
+    def to_dict(self) -> dict[str, Any]: ...
+    def __getitem__(self, key: str) -> Any: ...
+    def keys(self) -> list[str]: ...
+    def __iter__(self) -> Iterator[str]: ...
+    def __contains__(self, key: str) -> bool: ...
+
+class PixelType:
+    """the type of the pixels, from a name like "uint16\""""
+    def __init__(self, name: str) -> None: ...
+    def numpy_dtype(self, big_endian: bool = False) -> str: ...
+
+_Unit = ElectricPotential | Frequency | Length | Power | Pressure | Temperature | Time
+
+class Quantity:
+    """a value with a unit of one of the unit classes, like Quantity(0.65, Length.um)"""
+    def __init__(self, value: float, unit: _Unit) -> None: ...
+    @property
+    def value(self) -> float: ...
+    @property
+    def unit(self) -> _Unit: ...
+    def to(self, unit: _Unit | str) -> Quantity: ...
+    def __add__(self, other: Quantity) -> Quantity: ...
+    def __sub__(self, other: Quantity) -> Quantity: ...
+    def __mul__(self, factor: float) -> Quantity: ...
+    def __rmul__(self, factor: float) -> Quantity: ...
+    def __truediv__(self, other: Quantity | float) -> Quantity | float: ...
+    def __neg__(self) -> Quantity: ...
+    def __abs__(self) -> Quantity: ...
+    def __eq__(self, other: object) -> bool: ...
+    def __ne__(self, other: object) -> bool: ...
+    def __lt__(self, other: Quantity) -> bool: ...
+    def __le__(self, other: Quantity) -> bool: ...
+    def __gt__(self, other: Quantity) -> bool: ...
+    def __ge__(self, other: Quantity) -> bool: ...
+    def __getnewargs__(self) -> tuple[float, _Unit]: ...
+
+class OmeError(ValueError): ...
+class OmeParseError(OmeError): ...
+class OmeUnitError(OmeError): ...
+class OmeValidationError(OmeError):
+    report: list[dict[str, Any]]
+
+def ome(text: str | bytes) -> dict[str, Any]: ...
+def pixel_numpy_dtype(ome: Ome, image_index: int = 0) -> str: ...
+def image_shape(ome: Ome, image_index: int = 0, order: str = "TCZYX") -> tuple[int, ...]: ...
+def ome_from_file(path: str | PathLike[str]) -> dict[str, Any]: ...
+def plane(
+    ome: Ome | dict[str, Any],
+    image: int | str,
+    z: int,
+    c: int,
+    t: int,
+) -> Plane | dict[str, Any] | None: ...
+def well_name(ome: Ome | dict[str, Any], plate: int | str, row: int, column: int) -> str: ...
+def image_for_well(
+    ome: Ome | dict[str, Any],
+    plate: int | str,
+    well: str,
+) -> Image | dict[str, Any] | None: ...
+def ifd_for(ome: Ome | dict[str, Any], image: int | str, z: int, c: int, t: int) -> int | None: ...
+def to_json(ome: Ome | dict[str, Any], indent: int | None = None) -> str: ...
+def from_json(text: str) -> Ome: ...
+def to_ome_types_dict(ome: Ome | dict[str, Any]) -> dict[str, Any]: ...
+def to_xml(
+    ome: Ome | dict[str, Any],
+    *,
+    pretty: bool = False,
+    write_default_units: bool = True,
+) -> str: ...
+def new_image(
+    *,
+    size_x: int,
+    size_y: int,
+    size_z: int = 1,
+    size_c: int = 1,
+    size_t: int = 1,
+    pixel_type: str = "uint16",
+    dimension_order: str = "XYCZT",
+    name: str | None = None,
+    channels: list[str] | None = None,
+    colors: list[int] | None = None,
+    physical_size_x: float | None = None,
+    physical_size_y: float | None = None,
+    physical_size_z: float | None = None,
+    time_increment: float | None = None,
+    description: str | None = None,
+    acquisition_date: str | None = None,
+) -> Image: ...
+def new_ome(*, images: list[Image] = ..., creator: str | None = None) -> Ome: ...
+def pixel_size(
+    ome: Ome | dict[str, Any],
+    image: int | str = 0,
+    axis: str = "x",
+) -> Quantity | None: ...
+def exposure_time(
+    ome: Ome | dict[str, Any],
+    image: int | str,
+    plane_index: int,
+) -> Quantity | None: ...
+def stage_position(
+    ome: Ome | dict[str, Any],
+    image: int | str,
+    plane_index: int,
+) -> tuple[Quantity | None, Quantity | None, Quantity | None]: ...
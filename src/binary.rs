@@ -0,0 +1,647 @@
+#[cfg(feature = "binary")]
+use crate::error::Error;
+use crate::ome::BinaryFile;
+#[cfg(feature = "binary")]
+use crate::ome::{
+    BinData, BinDataCompressionType, BinaryFileContent, External, FileAnnotation, Ome, PixelType,
+    Pixels, ShapeGroup, StructuredAnnotationsContent,
+};
+#[cfg(feature = "binary")]
+use sha1::{Digest, Sha1};
+#[cfg(feature = "binary")]
+use std::io::{Read, Write};
+#[cfg(feature = "binary")]
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "binary")]
+fn compress(compression: &BinDataCompressionType, bytes: &[u8]) -> Result<Vec<u8>, Error> {
+    match compression {
+        BinDataCompressionType::None => Ok(bytes.to_vec()),
+        BinDataCompressionType::Zlib => {
+            let mut encoder =
+                flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder.write_all(bytes)?;
+            Ok(encoder.finish()?)
+        }
+        BinDataCompressionType::Bzip2 => {
+            let mut encoder =
+                bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::default());
+            encoder.write_all(bytes)?;
+            Ok(encoder.finish()?)
+        }
+    }
+}
+
+#[cfg(feature = "binary")]
+fn decompress(compression: &BinDataCompressionType, bytes: Vec<u8>) -> Result<Vec<u8>, Error> {
+    let mut out = Vec::new();
+    match compression {
+        BinDataCompressionType::None => return Ok(bytes),
+        BinDataCompressionType::Zlib => {
+            flate2::read::ZlibDecoder::new(bytes.as_slice()).read_to_end(&mut out)?;
+        }
+        BinDataCompressionType::Bzip2 => {
+            bzip2::read::BzDecoder::new(bytes.as_slice()).read_to_end(&mut out)?;
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(feature = "binary")]
+fn sha1_hex(bytes: &[u8]) -> String {
+    Sha1::digest(bytes)
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+/// the bytes without ASCII whitespace, so that base64 can be decoded as a stream
+#[cfg(feature = "binary")]
+struct WithoutWhitespace<'a>(&'a [u8]);
+
+#[cfg(feature = "binary")]
+impl Read for WithoutWhitespace<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let mut n = 0;
+        while n < buf.len() {
+            let Some((&byte, rest)) = self.0.split_first() else {
+                break;
+            };
+            self.0 = rest;
+            if !byte.is_ascii_whitespace() {
+                buf[n] = byte;
+                n += 1;
+            }
+        }
+        Ok(n)
+    }
+}
+
+#[cfg(feature = "binary")]
+impl BinData {
+    /// compress and base64 encode bytes, the length is set to the length of the encoded data
+    pub fn from_bytes(
+        bytes: &[u8],
+        compression: BinDataCompressionType,
+        big_endian: bool,
+    ) -> Result<Self, Error> {
+        use base64::Engine;
+
+        let content =
+            base64::engine::general_purpose::STANDARD.encode(compress(&compression, bytes)?);
+        Ok(BinData {
+            compression,
+            big_endian,
+            length: content.len() as i64,
+            content,
+        })
+    }
+
+    /// decode the base64 content and decompress it
+    pub fn decode(&self) -> Result<Vec<u8>, Error> {
+        use base64::Engine;
+
+        let content: Vec<u8> = self
+            .content
+            .bytes()
+            .filter(|b| !b.is_ascii_whitespace())
+            .collect();
+        let bytes = base64::engine::general_purpose::STANDARD.decode(content)?;
+        decompress(&self.compression, bytes)
+    }
+
+    /// decode and decompress at most the first len bytes of the content, without decoding the
+    /// rest
+    pub fn decode_prefix(&self, len: usize) -> Result<Vec<u8>, Error> {
+        let base64 = base64::read::DecoderReader::new(
+            WithoutWhitespace(self.content.as_bytes()),
+            &base64::engine::general_purpose::STANDARD,
+        );
+        let reader: Box<dyn Read> = match self.compression {
+            BinDataCompressionType::None => Box::new(base64),
+            BinDataCompressionType::Zlib => Box::new(flate2::read::ZlibDecoder::new(base64)),
+            BinDataCompressionType::Bzip2 => Box::new(bzip2::read::BzDecoder::new(base64)),
+        };
+        let mut out = Vec::new();
+        reader.take(len as u64).read_to_end(&mut out)?;
+        Ok(out)
+    }
+
+    /// set the length to the number of base64 characters in the content
+    pub fn refresh_length(&mut self) {
+        self.length = self
+            .content
+            .bytes()
+            .filter(|b| !b.is_ascii_whitespace())
+            .count() as i64;
+    }
+}
+
+#[cfg(feature = "binary")]
+impl BinaryFile {
+    /// embed bytes as BinData, the size is set to the size of the uncompressed data
+    pub fn from_bytes(
+        file_name: &str,
+        bytes: &[u8],
+        compression: BinDataCompressionType,
+    ) -> Result<Self, Error> {
+        Ok(BinaryFile {
+            file_name: file_name.to_string(),
+            size: bytes.len() as i64,
+            mime_type: None,
+            content: BinaryFileContent::BinData(BinData::from_bytes(bytes, compression, false)?),
+        })
+    }
+}
+
+#[cfg(feature = "binary")]
+impl External {
+    fn path(&self, base_dir: Option<&Path>) -> PathBuf {
+        match base_dir {
+            Some(dir) => dir.join(&self.href),
+            None => PathBuf::from(&self.href),
+        }
+    }
+
+    /// read and decompress the external file, href is taken relative to base_dir if given
+    pub fn read(&self, base_dir: Option<&Path>) -> Result<Vec<u8>, Error> {
+        decompress(&self.compression, std::fs::read(self.path(base_dir))?)
+    }
+
+    /// the SHA1 digest of the external file as it is on disk
+    pub fn sha1(&self, base_dir: Option<&Path>) -> Result<String, Error> {
+        Ok(sha1_hex(&std::fs::read(self.path(base_dir))?))
+    }
+}
+
+#[cfg(feature = "binary")]
+impl FileAnnotation {
+    /// the content of the file, either embedded or read from disk
+    pub fn bytes(&self, base_dir: Option<&Path>) -> Result<Vec<u8>, Error> {
+        match &self.binary_file.content {
+            BinaryFileContent::BinData(bin_data) => bin_data.decode(),
+            BinaryFileContent::External(external) => external.read(base_dir),
+        }
+    }
+}
+
+/// numeric types in which pixel data can be read, see [Pixels::read_plane]
+#[cfg(feature = "binary")]
+pub trait PixelValue: Sized {
+    const PIXEL_TYPE: PixelType;
+
+    /// interpret exactly size_of::<Self>() bytes
+    fn from_bytes(bytes: &[u8], big_endian: bool) -> Self;
+}
+
+#[cfg(feature = "binary")]
+macro_rules! impl_pixel_value {
+    ($($t:ty: $pixel_type:ident $(,)?)*) => {
+        $(
+            impl PixelValue for $t {
+                const PIXEL_TYPE: PixelType = PixelType::$pixel_type;
+
+                fn from_bytes(bytes: &[u8], big_endian: bool) -> Self {
+                    let bytes = bytes.try_into().expect("wrong number of bytes");
+                    if big_endian {
+                        <$t>::from_be_bytes(bytes)
+                    } else {
+                        <$t>::from_le_bytes(bytes)
+                    }
+                }
+            }
+        )*
+    };
+}
+
+#[cfg(feature = "binary")]
+impl_pixel_value! {
+    i8: Int8,
+    i16: Int16,
+    i32: Int32,
+    u8: Uint8,
+    u16: Uint16,
+    u32: Uint32,
+    f32: Float,
+    f64: Double,
+}
+
+#[cfg(feature = "binary")]
+impl Pixels {
+    /// decode one plane of embedded pixel data, either from the BinData at plane_index, or from
+    /// a single BinData holding all planes
+    pub fn read_plane<T: PixelValue>(&self, plane_index: usize) -> Result<Vec<T>, Error> {
+        if self.r#type != T::PIXEL_TYPE {
+            return Err(Error::PixelTypeMismatch(
+                self.r#type.to_string(),
+                T::PIXEL_TYPE.to_string(),
+            ));
+        }
+        let not_found = || Error::PlaneNotFound(plane_index);
+        let plane_size = usize::try_from(self.size_x)
+            .ok()
+            .zip(usize::try_from(self.size_y).ok())
+            .and_then(|(x, y)| x.checked_mul(y)?.checked_mul(size_of::<T>()))
+            .ok_or_else(not_found)?;
+        let (bin_data, offset) = if self.bin_data.len() == 1 {
+            let offset = plane_index.checked_mul(plane_size).ok_or_else(not_found)?;
+            (&self.bin_data[0], offset)
+        } else {
+            let bin_data = self.bin_data.get(plane_index).ok_or_else(not_found)?;
+            (bin_data, 0)
+        };
+        let end = offset.checked_add(plane_size).ok_or_else(not_found)?;
+        let bytes = bin_data.decode()?;
+        let plane = bytes.get(offset..end).ok_or_else(not_found)?;
+        Ok(plane
+            .chunks_exact(size_of::<T>())
+            .map(|bytes| T::from_bytes(bytes, bin_data.big_endian))
+            .collect())
+    }
+}
+
+/// an External element of which the SHA1 does not match the file it refers to
+#[cfg(feature = "binary")]
+#[derive(Clone, Debug)]
+pub struct Sha1Mismatch {
+    pub href: String,
+    pub expected: String,
+    pub actual: String,
+}
+
+/// the problems found by [Ome::refresh_binary_metadata]
+#[cfg(feature = "binary")]
+#[derive(Clone, Debug, Default)]
+pub struct BinaryMetadataReport {
+    pub sha1_mismatches: Vec<Sha1Mismatch>,
+    /// the IDs of the FileAnnotations of which the BinData cannot be decoded, their size is kept
+    pub undecodable: Vec<String>,
+}
+
+#[cfg(feature = "binary")]
+impl Ome {
+    /// recompute BinData lengths and BinaryFile sizes of all embedded content, and check the SHA1
+    /// of external files, mismatches and BinData which cannot be decoded are reported but not
+    /// fixed, missing files are skipped
+    pub fn refresh_binary_metadata(&mut self, base_dir: Option<&Path>) -> BinaryMetadataReport {
+        for image in &mut self.image {
+            image
+                .pixels
+                .bin_data
+                .iter_mut()
+                .for_each(BinData::refresh_length);
+        }
+        for roi in &mut self.roi {
+            if let Some(union) = &mut roi.union {
+                for shape in &mut union.shape_group {
+                    if let ShapeGroup::Mask(mask) = shape {
+                        mask.bin_data.refresh_length();
+                    }
+                }
+            }
+        }
+        let mut report = BinaryMetadataReport::default();
+        if let Some(structured_annotations) = &mut self.structured_annotations {
+            for annotation in structured_annotations.content.iter_mut() {
+                if let StructuredAnnotationsContent::FileAnnotation(file_annotation) = annotation {
+                    let binary_file = &mut file_annotation.binary_file;
+                    match &mut binary_file.content {
+                        BinaryFileContent::BinData(bin_data) => {
+                            bin_data.refresh_length();
+                            match bin_data.decode() {
+                                Ok(bytes) => binary_file.size = bytes.len() as i64,
+                                Err(_) => report.undecodable.push(file_annotation.id.clone()),
+                            }
+                        }
+                        BinaryFileContent::External(external) => {
+                            if let Ok(actual) = external.sha1(base_dir) {
+                                if !actual.eq_ignore_ascii_case(&external.sha_1) {
+                                    report.sha1_mismatches.push(Sha1Mismatch {
+                                        href: external.href.clone(),
+                                        expected: external.sha_1.clone(),
+                                        actual,
+                                    });
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        report
+    }
+}
+
+fn mime_from_extension(file_name: &str) -> Option<&'static str> {
+    let (_, extension) = file_name.rsplit_once('.')?;
+    match extension.to_ascii_lowercase().as_str() {
+        "png" => Some("image/png"),
+        "tif" | "tiff" => Some("image/tiff"),
+        "pdf" => Some("application/pdf"),
+        "csv" => Some("text/csv"),
+        "json" => Some("application/json"),
+        "zip" => Some("application/zip"),
+        _ => None,
+    }
+}
+
+/// the number of bytes of embedded content from which the mime type is guessed
+#[cfg(feature = "binary")]
+const MIME_PREFIX_LEN: usize = 8192;
+
+/// guess the mime type from the content, which is cut off after MIME_PREFIX_LEN bytes, the bool
+/// is true when the guess is definitive
+#[cfg(feature = "binary")]
+fn mime_from_content(bytes: &[u8]) -> Option<(&'static str, bool)> {
+    const MAGIC: [(&[u8], &str); 8] = [
+        (b"\x89PNG\r\n\x1a\n", "image/png"),
+        (b"II*\0", "image/tiff"),
+        (b"MM\0*", "image/tiff"),
+        (b"II+\0", "image/tiff"),
+        (b"MM\0+", "image/tiff"),
+        (b"%PDF-", "application/pdf"),
+        (b"PK\x03\x04", "application/zip"),
+        (b"PK\x05\x06", "application/zip"),
+    ];
+    if let Some((_, mime)) = MAGIC.iter().find(|(magic, _)| bytes.starts_with(magic)) {
+        return Some((mime, true));
+    }
+    let complete = bytes.len() <= MIME_PREFIX_LEN;
+    let text = if complete {
+        std::str::from_utf8(bytes).ok()?.trim()
+    } else {
+        // the text is cut off, possibly within a character, so its last line is dropped
+        let text = match std::str::from_utf8(&bytes[..MIME_PREFIX_LEN]) {
+            Ok(text) => text,
+            Err(error) if error.error_len().is_none() => {
+                std::str::from_utf8(&bytes[..error.valid_up_to()]).ok()?
+            }
+            Err(_) => return None,
+        };
+        let text = text.trim_start();
+        text.rsplit_once('\n').map_or(text, |(text, _)| text)
+    };
+    let json =
+        |open: char, close: char| text.starts_with(open) && (!complete || text.ends_with(close));
+    if json('{', '}') || json('[', ']') {
+        Some(("application/json", false))
+    } else {
+        let mut columns = text.lines().map(|line| line.split(',').count());
+        let n = columns.next()?;
+        (n > 1 && columns.all(|c| c == n)).then_some(("text/csv", false))
+    }
+}
+
+impl BinaryFile {
+    /// guess the mime type from the file name, and from the content of embedded data if the
+    /// binary feature is enabled, the extension wins unless the content is unambiguous
+    pub fn inferred_mime(&self) -> Option<&'static str> {
+        let extension = mime_from_extension(&self.file_name);
+        #[cfg(feature = "binary")]
+        {
+            let content = match &self.content {
+                BinaryFileContent::BinData(bin_data) => bin_data
+                    .decode_prefix(MIME_PREFIX_LEN + 1)
+                    .ok()
+                    .and_then(|bytes| mime_from_content(&bytes)),
+                BinaryFileContent::External(_) => None,
+            };
+            match content {
+                Some((mime, true)) => Some(mime),
+                Some((mime, false)) => extension.or(Some(mime)),
+                None => extension,
+            }
+        }
+        #[cfg(not(feature = "binary"))]
+        extension
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "binary")]
+    use crate::XmlOptions;
+
+    fn binary_file(file_name: &str, content: &str) -> BinaryFile {
+        let xml = format!(
+            r#"<BinaryFile FileName="{file_name}" Size="0"><BinData BigEndian="false" Length="0">{content}</BinData></BinaryFile>"#
+        );
+        quick_xml::de::from_str(&xml).unwrap()
+    }
+
+    #[test]
+    fn mime_from_file_name() {
+        assert_eq!(
+            binary_file("calibration.CSV", "AAAA").inferred_mime(),
+            Some("text/csv")
+        );
+        assert_eq!(
+            binary_file("stack.ome.tif", "AAAA").inferred_mime(),
+            Some("image/tiff")
+        );
+        assert_eq!(binary_file("notes", "AAAA").inferred_mime(), None);
+    }
+
+    #[cfg(feature = "binary")]
+    #[test]
+    fn mime_from_content_disagreeing() -> Result<(), Error> {
+        // "a,b\n1,2" looks like csv, but that is not definitive, so the extension wins
+        assert_eq!(
+            binary_file("table.json", "YSxiCjEsMg==").inferred_mime(),
+            Some("application/json")
+        );
+        assert_eq!(
+            binary_file("table", "YSxiCjEsMg==").inferred_mime(),
+            Some("text/csv")
+        );
+        // a png signature is definitive
+        assert_eq!(
+            binary_file("table.csv", "iVBORw0KGgoAAAAA").inferred_mime(),
+            Some("image/png")
+        );
+
+        // only the start of large content is decoded
+        let large = |bytes: &[u8]| {
+            let bin_data = BinData::from_bytes(bytes, BinDataCompressionType::Zlib, false)?;
+            let mut binary_file = binary_file("table", &bin_data.content);
+            if let BinaryFileContent::BinData(content) = &mut binary_file.content {
+                content.compression = BinDataCompressionType::Zlib;
+            }
+            Ok::<_, Error>(binary_file)
+        };
+        let csv = "a,b,c\n".to_string() + &"1,2,3\n".repeat(10000);
+        assert_eq!(large(csv.as_bytes())?.inferred_mime(), Some("text/csv"));
+        let json = format!("[{}1]", "1, ".repeat(10000));
+        assert_eq!(
+            large(json.as_bytes())?.inferred_mime(),
+            Some("application/json")
+        );
+        let bin_data = BinData::from_bytes(csv.as_bytes(), BinDataCompressionType::Bzip2, false)?;
+        assert_eq!(bin_data.decode_prefix(12)?, b"a,b,c\n1,2,3\n");
+        assert_eq!(bin_data.decode_prefix(csv.len() + 1)?, csv.as_bytes());
+        Ok(())
+    }
+
+    #[cfg(feature = "binary")]
+    #[test]
+    fn refresh_binary_metadata() -> Result<(), Error> {
+        let dir = std::env::temp_dir().join(format!(
+            "ome_metadata_refresh_binary_metadata_{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir)?;
+        std::fs::write(dir.join("calibration.csv"), b"a,b\n1,2")?;
+        let pixels = BinData::from_bytes(&[0, 1, 2, 3], BinDataCompressionType::Zlib, false)?;
+        let xml = format!(
+            r#"<OME>
+                <Image ID="Image:0">
+                    <Pixels ID="Pixels:0" DimensionOrder="XYCZT" Type="uint8" SizeX="2" SizeY="2" SizeZ="1" SizeC="1" SizeT="1">
+                        <BinData Compression="zlib" BigEndian="false" Length="{}">{}</BinData>
+                    </Pixels>
+                </Image>
+                <StructuredAnnotations>
+                    <FileAnnotation ID="Annotation:0">
+                        <BinaryFile FileName="calibration.csv" Size="7">
+                            <External href="calibration.csv" SHA1="0000000000000000000000000000000000000000"/>
+                        </BinaryFile>
+                    </FileAnnotation>
+                    <FileAnnotation ID="Annotation:1">
+                        <BinaryFile FileName="notes.txt" Size="0">
+                            <BinData Compression="bzip2" BigEndian="false" Length="0">{}</BinData>
+                        </BinaryFile>
+                    </FileAnnotation>
+                    <FileAnnotation ID="Annotation:2">
+                        <BinaryFile FileName="broken.txt" Size="3">
+                            <BinData BigEndian="false" Length="0">!!!!</BinData>
+                        </BinaryFile>
+                    </FileAnnotation>
+                </StructuredAnnotations>
+            </OME>"#,
+            pixels.length,
+            pixels.content,
+            BinData::from_bytes(b"some notes", BinDataCompressionType::Bzip2, false)?.content
+        );
+        let mut ome: Ome = xml.parse()?;
+        assert_eq!(ome.image[0].pixels.bin_data[0].decode()?, vec![0, 1, 2, 3]);
+
+        let replacement = BinData::from_bytes(&[0; 64], BinDataCompressionType::None, false)?;
+        ome.image[0].pixels.bin_data[0].content = replacement.content.clone();
+        let report = ome.refresh_binary_metadata(Some(&dir));
+        assert_eq!(ome.image[0].pixels.bin_data[0].length, replacement.length);
+        let mismatches = &report.sha1_mismatches;
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(mismatches[0].href, "calibration.csv");
+        assert_eq!(mismatches[0].actual, sha1_hex(b"a,b\n1,2"));
+        assert_eq!(report.undecodable, ["Annotation:2"]);
+        let sizes = ome
+            .annotations()
+            .map(|annotation| match annotation {
+                StructuredAnnotationsContent::FileAnnotation(a) => a.binary_file.size,
+                _ => unreachable!(),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(sizes, [7, 10, 3]);
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[cfg(feature = "binary")]
+    #[test]
+    fn read_plane() -> Result<(), Error> {
+        let values: [u16; 8] = [1, 256, 513, 65535, 0, 2, 4, 8];
+        let bytes: Vec<u8> = values.iter().flat_map(|v| v.to_be_bytes()).collect();
+        let bin_data = BinData::from_bytes(&bytes, BinDataCompressionType::None, true)?;
+        let xml = format!(
+            r#"<Pixels ID="Pixels:0" DimensionOrder="XYCZT" Type="uint16" SizeX="2" SizeY="2" SizeZ="2" SizeC="1" SizeT="1">
+                <BinData BigEndian="true" Length="{}">{}</BinData>
+            </Pixels>"#,
+            bin_data.length, bin_data.content
+        );
+        let pixels: Pixels = quick_xml::de::from_str(&xml)?;
+        assert_eq!(pixels.read_plane::<u16>(0)?, [1, 256, 513, 65535]);
+        assert_eq!(pixels.read_plane::<u16>(1)?, [0, 2, 4, 8]);
+        assert!(matches!(
+            pixels.read_plane::<u16>(2),
+            Err(Error::PlaneNotFound(2))
+        ));
+        assert!(matches!(
+            pixels.read_plane::<u16>(usize::MAX / 2),
+            Err(Error::PlaneNotFound(_))
+        ));
+        assert!(matches!(
+            pixels.read_plane::<i16>(0),
+            Err(Error::PixelTypeMismatch(..))
+        ));
+        Ok(())
+    }
+
+    #[cfg(feature = "binary")]
+    #[test]
+    fn binary_file_from_bytes() -> Result<(), Error> {
+        let bytes = b"some calibration data".repeat(10);
+        let binary_file =
+            BinaryFile::from_bytes("data.bin", &bytes, BinDataCompressionType::Bzip2)?;
+        assert_eq!(binary_file.size, bytes.len() as i64);
+        let BinaryFileContent::BinData(bin_data) = &binary_file.content else {
+            panic!("not embedded");
+        };
+        assert_eq!(bin_data.length, bin_data.content.len() as i64);
+        assert_eq!(bin_data.decode()?, bytes);
+        Ok(())
+    }
+
+    #[cfg(feature = "binary")]
+    #[test]
+    fn decode_line_breaks() -> Result<(), Error> {
+        let xml = r#"<OME>
+            <StructuredAnnotations>
+                <FileAnnotation ID="Annotation:0">
+                    <BinaryFile FileName="table.csv" Size="7">
+                        <BinData BigEndian="false" Length="0">
+                            YSxi
+                            CjEs
+                            Mg==
+                        </BinData>
+                    </BinaryFile>
+                </FileAnnotation>
+            </StructuredAnnotations>
+        </OME>"#;
+        let ome: Ome = xml.parse()?;
+        let bin_data = |ome: &Ome| {
+            let Some(StructuredAnnotationsContent::FileAnnotation(annotation)) =
+                ome.annotations().next()
+            else {
+                panic!("no file annotation");
+            };
+            let BinaryFileContent::BinData(bin_data) = &annotation.binary_file.content else {
+                panic!("not embedded");
+            };
+            bin_data.clone()
+        };
+        let mut parsed = bin_data(&ome);
+        assert!(parsed.content.contains('\n'));
+        assert_eq!(parsed.decode()?, b"a,b\n1,2");
+        parsed.refresh_length();
+        assert_eq!(parsed.length, 12);
+        let pretty = XmlOptions {
+            pretty: true,
+            ..XmlOptions::default()
+        };
+        for xml in [ome.to_xml()?, ome.to_xml_with(pretty)?] {
+            let written = bin_data(&xml.parse()?);
+            assert_eq!(written.content, parsed.content);
+            assert_eq!(written.decode()?, b"a,b\n1,2");
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "binary")]
+    #[test]
+    fn file_annotation_bytes() -> Result<(), Error> {
+        let xml = r#"<FileAnnotation ID="Annotation:0"><BinaryFile FileName="table.csv" Size="7"><BinData BigEndian="false" Length="12">YSxiCjEsMg==</BinData></BinaryFile></FileAnnotation>"#;
+        let annotation: FileAnnotation = quick_xml::de::from_str(xml)?;
+        assert_eq!(annotation.bytes(None)?, b"a,b\n1,2");
+        Ok(())
+    }
+}
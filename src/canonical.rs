@@ -0,0 +1,89 @@
+use crate::ome::{LightSourceGroup, Ome, Ref, StructuredAnnotationsContent};
+
+fn sort_by_id<T>(items: &mut [T], id: fn(&T) -> &str) {
+    items.sort_by(|a, b| id(a).cmp(id(b)));
+}
+
+fn sort_refs<R: Ref>(refs: &mut [R]) {
+    sort_by_id(refs, R::id);
+}
+
+impl Ome {
+    /// sort all elements with an ID, and all references, by ID
+    fn canonicalize(&mut self) {
+        sort_by_id(&mut self.project, |p| &p.id);
+        sort_by_id(&mut self.dataset, |d| &d.id);
+        sort_by_id(&mut self.folder, |f| &f.id);
+        sort_by_id(&mut self.experiment, |e| &e.id);
+        sort_by_id(&mut self.plate, |p| &p.id);
+        sort_by_id(&mut self.screen, |s| &s.id);
+        sort_by_id(&mut self.experimenter, |e| &e.id);
+        sort_by_id(&mut self.experimenter_group, |e| &e.id);
+        sort_by_id(&mut self.instrument, |i| &i.id);
+        sort_by_id(&mut self.image, |i| &i.id);
+        sort_by_id(&mut self.roi, |r| &r.id);
+        for project in &mut self.project {
+            sort_refs(&mut project.dataset_ref);
+        }
+        for dataset in &mut self.dataset {
+            sort_refs(&mut dataset.image_ref);
+        }
+        for folder in &mut self.folder {
+            sort_refs(&mut folder.folder_ref);
+            sort_refs(&mut folder.image_ref);
+            sort_refs(&mut folder.roi_ref);
+        }
+        for plate in &mut self.plate {
+            sort_by_id(&mut plate.well, |w| &w.id);
+            for well in &mut plate.well {
+                sort_by_id(&mut well.well_sample, |w| &w.id);
+            }
+        }
+        for screen in &mut self.screen {
+            sort_refs(&mut screen.plate_ref);
+        }
+        for group in &mut self.experimenter_group {
+            sort_refs(&mut group.experimenter_ref);
+            sort_refs(&mut group.leader);
+        }
+        for instrument in &mut self.instrument {
+            sort_by_id(&mut instrument.detector, |d| &d.id);
+            sort_by_id(&mut instrument.objective, |o| &o.id);
+            sort_by_id(&mut instrument.filter_set, |f| &f.id);
+            sort_by_id(&mut instrument.filter, |f| &f.id);
+            sort_by_id(&mut instrument.dichroic, |d| &d.id);
+            sort_by_id(&mut instrument.light_source_group, LightSourceGroup::id);
+        }
+        if let Some(structured_annotations) = &mut self.structured_annotations {
+            sort_by_id(
+                &mut structured_annotations.content,
+                StructuredAnnotationsContent::id,
+            );
+        }
+        for annotation_refs in self.annotation_refs_mut() {
+            sort_refs(annotation_refs);
+        }
+        for image in &mut self.image {
+            sort_refs(&mut image.roi_ref);
+            sort_refs(&mut image.microbeam_manipulation_ref);
+        }
+    }
+
+    /// Compare two documents while ignoring the order of elements that are identified by their
+    /// ID, like images, instruments (and their components), ROIs, plates or annotations, and
+    /// ignoring the order of references. The order of elements of which the position is meaningful, like channels,
+    /// planes and TiffData, is still compared.
+    pub fn semantically_eq(&self, other: &Ome) -> bool {
+        let mut a = self.clone();
+        let mut b = other.clone();
+        a.canonicalize();
+        b.canonicalize();
+        match (
+            quick_xml::se::to_string_with_root("OME", &a),
+            quick_xml::se::to_string_with_root("OME", &b),
+        ) {
+            (Ok(a), Ok(b)) => a == b,
+            _ => false,
+        }
+    }
+}
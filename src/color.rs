@@ -0,0 +1,156 @@
+use crate::ome::Channel;
+
+/// a color as stored in OME: a signed 32 bit integer packing red, green, blue and alpha, with red
+/// in the most significant byte, -1 is opaque white
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Rgba {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+fn to_u8(value: f32) -> u8 {
+    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+impl Rgba {
+    pub fn new(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Rgba { r, g, b, a }
+    }
+
+    pub fn from_i32(color: i32) -> Self {
+        let [r, g, b, a] = color.to_be_bytes();
+        Rgba { r, g, b, a }
+    }
+
+    pub fn to_i32(&self) -> i32 {
+        i32::from_be_bytes([self.r, self.g, self.b, self.a])
+    }
+
+    /// red, green and blue as hexadecimal digits, like "FF0000" for red, without the alpha
+    pub fn to_hex_rgb(&self) -> String {
+        format!("{:02X}{:02X}{:02X}", self.r, self.g, self.b)
+    }
+
+    /// red, green, blue and alpha in the range 0.0 - 1.0
+    pub fn to_f32_array(&self) -> [f32; 4] {
+        [self.r, self.g, self.b, self.a].map(|c| c as f32 / 255.0)
+    }
+
+    /// values outside the range 0.0 - 1.0 are clamped
+    pub fn from_f32_array([r, g, b, a]: [f32; 4]) -> Self {
+        Rgba::new(to_u8(r), to_u8(g), to_u8(b), to_u8(a))
+    }
+
+    /// like [Rgba::to_f32_array], with red, green and blue multiplied by alpha
+    pub fn to_premultiplied_f32_array(&self) -> [f32; 4] {
+        let [r, g, b, a] = self.to_f32_array();
+        [r * a, g * a, b * a, a]
+    }
+
+    /// the inverse of [Rgba::to_premultiplied_f32_array], colors with zero alpha become black
+    pub fn from_premultiplied_f32_array([r, g, b, a]: [f32; 4]) -> Self {
+        if a <= 0.0 {
+            Rgba::new(0, 0, 0, 0)
+        } else {
+            Rgba::from_f32_array([r / a, g / a, b / a, a])
+        }
+    }
+
+    /// this color composited over the background, both with straight alpha
+    pub fn blend_over(&self, background: Rgba) -> Rgba {
+        let source = self.to_premultiplied_f32_array();
+        let background = background.to_premultiplied_f32_array();
+        let transmitted = 1.0 - source[3];
+        Rgba::from_premultiplied_f32_array(std::array::from_fn(|i| {
+            source[i] + background[i] * transmitted
+        }))
+    }
+}
+
+impl From<i32> for Rgba {
+    fn from(color: i32) -> Self {
+        Rgba::from_i32(color)
+    }
+}
+
+impl From<Rgba> for i32 {
+    fn from(color: Rgba) -> Self {
+        color.to_i32()
+    }
+}
+
+impl Channel {
+    pub fn rgba(&self) -> Rgba {
+        Rgba::from_i32(self.color)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn packed() {
+        assert_eq!(Rgba::from_i32(-1), Rgba::new(255, 255, 255, 255));
+        assert_eq!(Rgba::from_i32(-16776961), Rgba::new(255, 0, 0, 255));
+        assert_eq!(Rgba::new(0, 255, 0, 255).to_i32(), 16711935);
+    }
+
+    #[test]
+    fn hex_rgb() {
+        assert_eq!(Rgba::from_i32(-1).to_hex_rgb(), "FFFFFF");
+        assert_eq!(Rgba::from_i32(-16776961).to_hex_rgb(), "FF0000");
+        assert_eq!(Rgba::from_i32(0x12345678).to_hex_rgb(), "123456");
+        assert_eq!(Rgba::new(0, 10, 255, 0).to_hex_rgb(), "000AFF");
+    }
+
+    #[test]
+    fn f32_round_trip() {
+        for color in [-1, 0, -16776961, 16711935, 0x12345678, -2023406815] {
+            let rgba = Rgba::from(color);
+            assert_eq!(i32::from(Rgba::from_f32_array(rgba.to_f32_array())), color);
+        }
+        assert_eq!(
+            Rgba::from_i32(-16776961).to_f32_array(),
+            [1.0, 0.0, 0.0, 1.0]
+        );
+        assert_eq!(
+            Rgba::from_f32_array([2.0, -1.0, 0.5, 1.0]),
+            Rgba::new(255, 0, 128, 255)
+        );
+    }
+
+    #[test]
+    fn premultiplied() {
+        let rgba = Rgba::new(255, 128, 0, 128);
+        let [r, g, b, a] = rgba.to_premultiplied_f32_array();
+        assert!((r - a).abs() < 1e-6);
+        assert!((g - 128.0 / 255.0 * a).abs() < 1e-6);
+        assert_eq!(b, 0.0);
+        assert_eq!(Rgba::from_premultiplied_f32_array([r, g, b, a]), rgba);
+        assert_eq!(
+            Rgba::from_premultiplied_f32_array([0.5, 0.5, 0.5, 0.0]),
+            Rgba::default()
+        );
+    }
+
+    #[test]
+    fn blend_over() {
+        let red = Rgba::new(255, 0, 0, 128);
+        let blue = Rgba::new(0, 0, 255, 255);
+        assert_eq!(red.blend_over(blue), Rgba::new(128, 0, 127, 255));
+        assert_eq!(
+            Rgba::new(0, 255, 0, 255).blend_over(blue),
+            Rgba::new(0, 255, 0, 255)
+        );
+        assert_eq!(Rgba::new(0, 255, 0, 0).blend_over(blue), blue);
+        assert_eq!(Rgba::default().blend_over(Rgba::default()), Rgba::default());
+        // the color of the result is not multiplied by its alpha of 0.75
+        assert_eq!(
+            red.blend_over(Rgba::new(0, 0, 255, 128)),
+            Rgba::new(170, 0, 85, 192)
+        );
+    }
+}
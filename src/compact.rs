@@ -0,0 +1,548 @@
+use crate::error::Error;
+use crate::ome::{
+    Arc, BinData, Channel, Detector, DetectorSettings, Ellipse, External, Filament,
+    GenericExcitationSource, ImagingEnvironment, Label, Laser, LightEmittingDiode,
+    LightSourceSettings, LightSourceType, Line, Mask, NAMESPACE, Objective, Ome, Pixels, Plane,
+    Plate, Polygon, Polyline, Rectangle, ShapeType, StageLabel, TiffData, TransmittanceRange, Well,
+    WellSample,
+};
+use serde::ser::{
+    SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant, SerializeTuple,
+    SerializeTupleStruct, SerializeTupleVariant,
+};
+use serde::{Serialize, Serializer};
+use std::fmt::{self, Write};
+
+/// a scalar value as it is written in an attribute, serde serializes those to a formatter
+struct Text<'a, T: ?Sized>(&'a T);
+
+impl<T: Serialize + ?Sized> fmt::Display for Text<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.serialize(f)
+    }
+}
+
+/// the text of a value, None if it is not a scalar
+fn text<T: Serialize + ?Sized>(value: &T) -> Option<String> {
+    let mut text = String::new();
+    write!(text, "{}", Text(value)).ok()?;
+    Some(text)
+}
+
+macro_rules! defaults {
+    ($($struct:ident { $($field:literal => $default:ident),* $(,)? })*) => {
+        /// the text of the default value of a field of a struct in xml, None if the field has no
+        /// default which compact xml can leave out
+        fn default_text(r#struct: &str, field: &str) -> Option<String> {
+            match (r#struct, field) {
+                $($((stringify!($struct), $field) => text(&$struct::$default()),)*)*
+                _ => None,
+            }
+        }
+    };
+}
+
+defaults! {
+    Arc {
+        "@PowerUnit" => default_power_unit,
+    }
+    BinData {
+        "@Compression" => default_compression,
+    }
+    Channel {
+        "@PinholeSizeUnit" => default_pinhole_size_unit,
+        "@ExcitationWavelengthUnit" => default_excitation_wavelength_unit,
+        "@EmissionWavelengthUnit" => default_emission_wavelength_unit,
+        "@Color" => default_color,
+    }
+    Detector {
+        "@VoltageUnit" => default_voltage_unit,
+    }
+    DetectorSettings {
+        "@VoltageUnit" => default_voltage_unit,
+        "@ReadOutRateUnit" => default_read_out_rate_unit,
+    }
+    Ellipse {
+        "@StrokeWidthUnit" => default_stroke_width_unit,
+        "@FontSizeUnit" => default_font_size_unit,
+    }
+    External {
+        "@Compression" => default_compression,
+    }
+    Filament {
+        "@PowerUnit" => default_power_unit,
+    }
+    GenericExcitationSource {
+        "@PowerUnit" => default_power_unit,
+    }
+    ImagingEnvironment {
+        "@TemperatureUnit" => default_temperature_unit,
+        "@AirPressureUnit" => default_air_pressure_unit,
+    }
+    Label {
+        "@StrokeWidthUnit" => default_stroke_width_unit,
+        "@FontSizeUnit" => default_font_size_unit,
+    }
+    Laser {
+        "@PowerUnit" => default_power_unit,
+        "@WavelengthUnit" => default_wavelength_unit,
+        "@RepetitionRateUnit" => default_repetition_rate_unit,
+    }
+    LightEmittingDiode {
+        "@PowerUnit" => default_power_unit,
+    }
+    LightSourceSettings {
+        "@WavelengthUnit" => default_wavelength_unit,
+    }
+    LightSourceType {
+        "@PowerUnit" => default_power_unit,
+    }
+    Line {
+        "@StrokeWidthUnit" => default_stroke_width_unit,
+        "@FontSizeUnit" => default_font_size_unit,
+    }
+    Mask {
+        "@StrokeWidthUnit" => default_stroke_width_unit,
+        "@FontSizeUnit" => default_font_size_unit,
+    }
+    Objective {
+        "@WorkingDistanceUnit" => default_working_distance_unit,
+    }
+    Pixels {
+        "@PhysicalSizeXUnit" => default_physical_size_x_unit,
+        "@PhysicalSizeYUnit" => default_physical_size_y_unit,
+        "@PhysicalSizeZUnit" => default_physical_size_z_unit,
+        "@TimeIncrementUnit" => default_time_increment_unit,
+    }
+    Plane {
+        "@DeltaTUnit" => default_delta_t_unit,
+        "@ExposureTimeUnit" => default_exposure_time_unit,
+        "@PositionXUnit" => default_position_x_unit,
+        "@PositionYUnit" => default_position_y_unit,
+        "@PositionZUnit" => default_position_z_unit,
+    }
+    Plate {
+        "@WellOriginXUnit" => default_well_origin_x_unit,
+        "@WellOriginYUnit" => default_well_origin_y_unit,
+    }
+    Polygon {
+        "@StrokeWidthUnit" => default_stroke_width_unit,
+        "@FontSizeUnit" => default_font_size_unit,
+    }
+    Polyline {
+        "@StrokeWidthUnit" => default_stroke_width_unit,
+        "@FontSizeUnit" => default_font_size_unit,
+    }
+    Rectangle {
+        "@StrokeWidthUnit" => default_stroke_width_unit,
+        "@FontSizeUnit" => default_font_size_unit,
+    }
+    ShapeType {
+        "@StrokeWidthUnit" => default_stroke_width_unit,
+        "@FontSizeUnit" => default_font_size_unit,
+    }
+    StageLabel {
+        "@XUnit" => default_x_unit,
+        "@YUnit" => default_y_unit,
+        "@ZUnit" => default_z_unit,
+    }
+    TiffData {
+        "@IFD" => default_ifd,
+        "@FirstZ" => default_first_z,
+        "@FirstT" => default_first_t,
+        "@FirstC" => default_first_c,
+    }
+    TransmittanceRange {
+        "@CutInUnit" => default_cut_in_unit,
+        "@CutOutUnit" => default_cut_out_unit,
+        "@CutInToleranceUnit" => default_cut_in_tolerance_unit,
+        "@CutOutToleranceUnit" => default_cut_out_tolerance_unit,
+    }
+    Well {
+        "@Color" => default_color,
+    }
+    WellSample {
+        "@PositionXUnit" => default_position_x_unit,
+        "@PositionYUnit" => default_position_y_unit,
+    }
+}
+
+/// a serializer which leaves out the fields of structs which have their default value
+struct Compact<S>(S);
+
+/// a value serialized with [Compact]
+struct WithCompact<'a, T: ?Sized>(&'a T);
+
+impl<T: Serialize + ?Sized> Serialize for WithCompact<'_, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(Compact(serializer))
+    }
+}
+
+/// the fields of a struct serialized with [Compact], with the name of the struct
+struct CompactFields<S> {
+    fields: S,
+    r#struct: &'static str,
+}
+
+macro_rules! forward_serialize {
+    ($($fn:ident($t:ty)),* $(,)?) => {
+        $(
+            fn $fn(self, v: $t) -> Result<Self::Ok, Self::Error> {
+                self.0.$fn(v)
+            }
+        )*
+    };
+}
+
+impl<S: Serializer> Serializer for Compact<S> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+    type SerializeSeq = Compact<S::SerializeSeq>;
+    type SerializeTuple = Compact<S::SerializeTuple>;
+    type SerializeTupleStruct = Compact<S::SerializeTupleStruct>;
+    type SerializeTupleVariant = Compact<S::SerializeTupleVariant>;
+    type SerializeMap = Compact<S::SerializeMap>;
+    type SerializeStruct = CompactFields<S::SerializeStruct>;
+    type SerializeStructVariant = CompactFields<S::SerializeStructVariant>;
+
+    forward_serialize!(
+        serialize_bool(bool),
+        serialize_i8(i8),
+        serialize_i16(i16),
+        serialize_i32(i32),
+        serialize_i64(i64),
+        serialize_u8(u8),
+        serialize_u16(u16),
+        serialize_u32(u32),
+        serialize_u64(u64),
+        serialize_f32(f32),
+        serialize_f64(f64),
+        serialize_char(char),
+        serialize_str(&str),
+        serialize_bytes(&[u8]),
+        serialize_unit_struct(&'static str),
+    );
+
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        self.0.serialize_none()
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Self::Ok, Self::Error> {
+        self.0.serialize_some(&WithCompact(value))
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        self.0.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok, Self::Error> {
+        self.0.serialize_unit_variant(name, variant_index, variant)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error> {
+        self.0.serialize_newtype_struct(name, &WithCompact(value))
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error> {
+        self.0
+            .serialize_newtype_variant(name, variant_index, variant, &WithCompact(value))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        self.0.serialize_seq(len).map(Compact)
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        self.0.serialize_tuple(len).map(Compact)
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        self.0.serialize_tuple_struct(name, len).map(Compact)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        self.0
+            .serialize_tuple_variant(name, variant_index, variant, len)
+            .map(Compact)
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        self.0.serialize_map(len).map(Compact)
+    }
+
+    fn serialize_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        let fields = self.0.serialize_struct(name, len)?;
+        Ok(CompactFields {
+            fields,
+            r#struct: name,
+        })
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        let fields = self
+            .0
+            .serialize_struct_variant(name, variant_index, variant, len)?;
+        Ok(CompactFields {
+            fields,
+            r#struct: variant,
+        })
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.0.is_human_readable()
+    }
+}
+
+macro_rules! impl_serialize_elements {
+    ($($trait:ident::$fn:ident),* $(,)?) => {
+        $(
+            impl<S: $trait> $trait for Compact<S> {
+                type Ok = S::Ok;
+                type Error = S::Error;
+
+                fn $fn<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+                    self.0.$fn(&WithCompact(value))
+                }
+
+                fn end(self) -> Result<Self::Ok, Self::Error> {
+                    self.0.end()
+                }
+            }
+        )*
+    };
+}
+
+impl_serialize_elements!(
+    SerializeSeq::serialize_element,
+    SerializeTuple::serialize_element,
+    SerializeTupleStruct::serialize_field,
+    SerializeTupleVariant::serialize_field,
+);
+
+impl<S: SerializeMap> SerializeMap for Compact<S> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Self::Error> {
+        self.0.serialize_key(key)
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.0.serialize_value(&WithCompact(value))
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.0.end()
+    }
+}
+
+macro_rules! impl_serialize_fields {
+    ($($trait:ident),* $(,)?) => {
+        $(
+            impl<S: $trait> $trait for CompactFields<S> {
+                type Ok = S::Ok;
+                type Error = S::Error;
+
+                fn serialize_field<T: Serialize + ?Sized>(
+                    &mut self,
+                    key: &'static str,
+                    value: &T,
+                ) -> Result<(), Self::Error> {
+                    match default_text(self.r#struct, key) {
+                        Some(default) if text(value).as_ref() == Some(&default) => {
+                            self.fields.skip_field(key)
+                        }
+                        _ => self.fields.serialize_field(key, &WithCompact(value)),
+                    }
+                }
+
+                fn skip_field(&mut self, key: &'static str) -> Result<(), Self::Error> {
+                    self.fields.skip_field(key)
+                }
+
+                fn end(self) -> Result<Self::Ok, Self::Error> {
+                    self.fields.end()
+                }
+            }
+        )*
+    };
+}
+
+impl_serialize_fields!(SerializeStruct, SerializeStructVariant);
+
+/// how [Ome::to_xml_with] writes xml
+#[derive(Clone, Copy, Debug)]
+pub struct XmlOptions {
+    /// indent nested elements by two spaces
+    pub pretty: bool,
+    /// write attributes which have their default value, like PhysicalSizeXUnit="µm"
+    pub write_default_units: bool,
+}
+
+impl Default for XmlOptions {
+    fn default() -> Self {
+        XmlOptions {
+            pretty: false,
+            write_default_units: true,
+        }
+    }
+}
+
+impl Ome {
+    /// serialize to xml in the namespace of the 2016-06 schema
+    pub fn to_xml(&self) -> Result<String, Error> {
+        self.to_xml_with(XmlOptions::default())
+    }
+
+    pub fn to_xml_with(&self, options: XmlOptions) -> Result<String, Error> {
+        let mut xml = String::new();
+        let mut serializer = quick_xml::se::Serializer::with_root(&mut xml, Some("OME"))?;
+        if options.pretty {
+            serializer.indent(' ', 2);
+        }
+        if options.write_default_units {
+            self.serialize(serializer)?;
+        } else {
+            self.serialize(Compact(serializer))?;
+        }
+        let xml = xml.replacen("<OME", &format!(r#"<OME xmlns="{NAMESPACE}""#), 1);
+        crate::preserve::write_verbatim_values(self, xml)
+    }
+
+    /// serialize to xml, leaving out attributes which have their default value,
+    /// like PhysicalSizeXUnit="µm"
+    pub fn to_compact_xml(&self) -> Result<String, Error> {
+        self.to_xml_with(XmlOptions {
+            write_default_units: false,
+            ..XmlOptions::default()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn to_compact_xml() -> Result<(), Error> {
+        let mut reductions = Vec::new();
+        for entry in std::fs::read_dir("tests")? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|e| e == "xml") {
+                let ome: Ome = std::fs::read_to_string(&path)?.parse()?;
+                let full = quick_xml::se::to_string_with_root("OME", &ome)?;
+                let compact = ome.to_compact_xml()?;
+                let parsed: Ome = compact.parse()?;
+                assert!(ome.semantically_eq(&parsed), "{path:?}");
+                assert!(parsed.original_namespace.is_none());
+                reductions.push(1.0 - compact.len() as f64 / full.len() as f64);
+            }
+        }
+        let mean = reductions.iter().sum::<f64>() / reductions.len() as f64;
+        assert!(
+            mean >= 0.1,
+            "compact xml is only {:.1}% smaller",
+            100.0 * mean
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn explicit_values() -> Result<(), Error> {
+        let ome: Ome = r#"<OME>
+            <Instrument ID="Instrument:0">
+                <Filter ID="Filter:0">
+                    <TransmittanceRange CutIn="5e-7" CutInUnit="m" CutOut="550" CutOutUnit="nm"/>
+                </Filter>
+            </Instrument>
+            <Image ID="Image:0">
+                <Pixels ID="Pixels:0" DimensionOrder="XYCZT" Type="uint8" SizeX="1" SizeY="1" SizeZ="1" SizeC="2" SizeT="1">
+                    <Channel ID="Channel:0:0" Color="0"/>
+                    <Channel ID="Channel:0:1"/>
+                    <MetadataOnly/>
+                </Pixels>
+            </Image>
+        </OME>"#
+            .parse()?;
+        let compact = ome.to_compact_xml()?;
+        assert!(compact.contains(r#"<Channel ID="Channel:0:0" Color="0"/>"#));
+        assert!(compact.contains(r#"<Channel ID="Channel:0:1"/>"#));
+        assert!(compact.contains(r#"CutInUnit="m""#));
+        assert!(!compact.contains("CutOutUnit"));
+        let parsed: Ome = compact.parse()?;
+        assert_eq!(parsed.image[0].pixels.channel[0].color, 0);
+        assert_eq!(parsed.image[0].pixels.channel[1].color, -1);
+        assert!(ome.semantically_eq(&parsed));
+        Ok(())
+    }
+
+    #[test]
+    fn to_xml_with() -> Result<(), Error> {
+        for file in [
+            "test.xml",
+            "annotations.xml",
+            "YTL1849A131_2023_05_04__13_36_36.xml",
+        ] {
+            let ome: Ome = std::fs::read_to_string(format!("tests/{file}"))?.parse()?;
+            let xml = ome.to_xml()?;
+            // only the xml in XMLAnnotations, which is written as it was read, has new lines
+            let outside = ome
+                .annotations()
+                .filter_map(|annotation| match annotation {
+                    crate::ome::StructuredAnnotationsContent::XmlAnnotation(annotation) => {
+                        annotation.value.verbatim_text()
+                    }
+                    _ => None,
+                })
+                .fold(xml.clone(), |xml, verbatim| xml.replace(verbatim, ""));
+            assert!(!outside.contains('\n'));
+            assert!(xml.starts_with(&format!(r#"<OME xmlns="{NAMESPACE}""#)));
+            let pretty = ome.to_xml_with(XmlOptions {
+                pretty: true,
+                write_default_units: false,
+            })?;
+            assert!(pretty.contains("\n  <Image"));
+            for xml in [xml, pretty] {
+                let parsed: Ome = xml.parse()?;
+                assert!(ome.semantically_eq(&parsed), "{file}");
+            }
+        }
+        Ok(())
+    }
+}
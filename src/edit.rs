@@ -0,0 +1,962 @@
+use crate::error::Error;
+use crate::ids::{IdKind, IdSite};
+use crate::ome::{
+    AnnotationRef, Channel, Dataset, Experimenter, Folder, Image, Instrument, LightSourceGroup,
+    Ome, Pixels, Plane, Plate, Ref, Roi, Screen, ShapeGroup, StructuredAnnotations,
+    StructuredAnnotationsContent,
+};
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+
+/// add_* methods which push an element onto a list of the document and return the document for
+/// chaining
+macro_rules! impl_add {
+    ($($fn:ident($field:ident: $t:ty)),* $(,)?) => {
+        impl Ome {
+            $(
+                pub fn $fn(&mut self, $field: $t) -> &mut Self {
+                    self.$field.push($field);
+                    self
+                }
+            )*
+        }
+    };
+}
+
+impl_add!(
+    add_dataset(dataset: Dataset),
+    add_experimenter(experimenter: Experimenter),
+    add_folder(folder: Folder),
+    add_image(image: Image),
+    add_instrument(instrument: Instrument),
+    add_plate(plate: Plate),
+    add_roi(roi: Roi),
+    add_screen(screen: Screen),
+);
+
+impl Ome {
+    /// add an annotation, creating the StructuredAnnotations if there are none yet
+    pub fn add_annotation(&mut self, annotation: StructuredAnnotationsContent) -> &mut Self {
+        self.structured_annotations
+            .get_or_insert_with(|| StructuredAnnotations {
+                content: Vec::new(),
+            })
+            .content
+            .push(annotation);
+        self
+    }
+
+    /// keep only the images for which predicate is true, and remove the ImageRefs to the removed
+    /// images from datasets, folders and well samples
+    pub fn retain_images<F: Fn(&Image) -> bool>(&mut self, predicate: F) {
+        let removed: HashSet<String> = self
+            .image
+            .iter()
+            .filter(|image| !predicate(image))
+            .map(|image| image.id.clone())
+            .collect();
+        if removed.is_empty() {
+            return;
+        }
+        self.image.retain(|image| !removed.contains(&image.id));
+        self.remove_image_refs(&removed);
+    }
+
+    /// remove the image with this id, and the ImageRefs to it from datasets, folders and well
+    /// samples
+    pub fn remove_image_by_id(&mut self, id: &str) -> Option<Image> {
+        let index = self.image.iter().position(|image| image.id == id)?;
+        self.remove_image_refs(&HashSet::from([id.to_string()]));
+        Some(self.image.remove(index))
+    }
+
+    /// remove the ROI with this id, and the ROIRefs to it from images, folders and microbeam
+    /// manipulations
+    pub fn remove_roi_by_id(&mut self, id: &str) -> Option<Roi> {
+        let index = self.roi.iter().position(|roi| roi.id == id)?;
+        for image in &mut self.image {
+            image.roi_ref.retain(|roi_ref| roi_ref.id() != id);
+        }
+        for folder in &mut self.folder {
+            folder.roi_ref.retain(|roi_ref| roi_ref.id() != id);
+        }
+        for manipulation in self
+            .experiment
+            .iter_mut()
+            .flat_map(|experiment| &mut experiment.microbeam_manipulation)
+        {
+            manipulation.roi_ref.retain(|roi_ref| roi_ref.id() != id);
+        }
+        Some(self.roi.remove(index))
+    }
+
+    /// remove the instrument with this id, the InstrumentRefs to it from images, and the
+    /// settings and refs of images, channels and microbeam manipulations which refer to its
+    /// objectives, light sources, detectors, filter sets, filters or dichroics
+    pub fn remove_instrument_by_id(&mut self, id: &str) -> Option<Instrument> {
+        let index = self
+            .instrument
+            .iter()
+            .position(|instrument| instrument.id == id)?;
+        let removed = Ome {
+            instrument: vec![self.instrument.remove(index)],
+            ..Ome::default()
+        };
+        let mut defined = HashSet::new();
+        removed.visit_ids(|site, id| {
+            if let IdSite::Definition(kind) = site {
+                defined.insert((kind, id.as_str()));
+            }
+        });
+        let removed_id = |kind, id: &str| defined.contains(&(kind, id));
+        for image in &mut self.image {
+            if image
+                .instrument_ref
+                .as_ref()
+                .is_some_and(|r| removed_id(IdKind::Instrument, r.id()))
+            {
+                image.instrument_ref = None;
+            }
+            if image
+                .objective_settings
+                .as_ref()
+                .is_some_and(|settings| removed_id(IdKind::Objective, &settings.id))
+            {
+                image.objective_settings = None;
+            }
+            for channel in &mut image.pixels.channel {
+                if channel
+                    .light_source_settings
+                    .as_ref()
+                    .is_some_and(|settings| removed_id(IdKind::LightSource, &settings.id))
+                {
+                    channel.light_source_settings = None;
+                }
+                if channel
+                    .detector_settings
+                    .as_ref()
+                    .is_some_and(|settings| removed_id(IdKind::Detector, &settings.id))
+                {
+                    channel.detector_settings = None;
+                }
+                if channel
+                    .filter_set_ref
+                    .as_ref()
+                    .is_some_and(|r| removed_id(IdKind::FilterSet, r.id()))
+                {
+                    channel.filter_set_ref = None;
+                }
+                if let Some(light_path) = &mut channel.light_path {
+                    light_path
+                        .excitation_filter_ref
+                        .retain(|r| !removed_id(IdKind::Filter, r.id()));
+                    light_path
+                        .emission_filter_ref
+                        .retain(|r| !removed_id(IdKind::Filter, r.id()));
+                    if light_path
+                        .dichroic_ref
+                        .as_ref()
+                        .is_some_and(|r| removed_id(IdKind::Dichroic, r.id()))
+                    {
+                        light_path.dichroic_ref = None;
+                    }
+                }
+            }
+        }
+        for manipulation in self
+            .experiment
+            .iter_mut()
+            .flat_map(|experiment| &mut experiment.microbeam_manipulation)
+        {
+            manipulation
+                .light_source_settings
+                .retain(|settings| !removed_id(IdKind::LightSource, &settings.id));
+        }
+        removed.instrument.into_iter().next()
+    }
+
+    /// remove the plate with this id, and the PlateRefs to it from screens
+    pub fn remove_plate_by_id(&mut self, id: &str) -> Option<Plate> {
+        let index = self.plate.iter().position(|plate| plate.id == id)?;
+        for screen in &mut self.screen {
+            screen.plate_ref.retain(|plate_ref| plate_ref.id() != id);
+        }
+        Some(self.plate.remove(index))
+    }
+
+    /// remove the experimenter with this id, and the ExperimenterRefs to it from projects,
+    /// datasets, experiments, groups and images, the required ExperimenterRefs of microbeam
+    /// manipulations are kept
+    pub fn remove_experimenter_by_id(&mut self, id: &str) -> Option<Experimenter> {
+        let index = self
+            .experimenter
+            .iter()
+            .position(|experimenter| experimenter.id == id)?;
+        let experimenter_refs = self
+            .project
+            .iter_mut()
+            .map(|project| &mut project.experimenter_ref)
+            .chain(
+                self.dataset
+                    .iter_mut()
+                    .map(|dataset| &mut dataset.experimenter_ref),
+            )
+            .chain(
+                self.experiment
+                    .iter_mut()
+                    .map(|experiment| &mut experiment.experimenter_ref),
+            )
+            .chain(
+                self.image
+                    .iter_mut()
+                    .map(|image| &mut image.experimenter_ref),
+            );
+        for experimenter_ref in experimenter_refs {
+            if experimenter_ref.as_ref().is_some_and(|r| r.id() == id) {
+                *experimenter_ref = None;
+            }
+        }
+        for group in &mut self.experimenter_group {
+            group.experimenter_ref.retain(|r| r.id() != id);
+            group.leader.retain(|r| r.id() != id);
+        }
+        Some(self.experimenter.remove(index))
+    }
+
+    /// remove the annotation with this id, and the AnnotationRefs to it from all elements
+    pub fn remove_annotation_by_id(&mut self, id: &str) -> Option<StructuredAnnotationsContent> {
+        let content = &mut self.structured_annotations.as_mut()?.content;
+        let index = content
+            .iter()
+            .position(|annotation| annotation.id() == id)?;
+        let annotation = content.remove(index);
+        for annotation_refs in self.annotation_refs_mut() {
+            annotation_refs.retain(|annotation_ref| annotation_ref.id() != id);
+        }
+        Some(annotation)
+    }
+
+    /// remove the annotations which are an exact copy of an earlier annotation, IDs included,
+    /// returns the number of removed annotations, the document is unchanged if an annotation
+    /// cannot be serialized
+    pub fn dedup_annotations(&mut self) -> Result<usize, Error> {
+        let Some(structured_annotations) = &mut self.structured_annotations else {
+            return Ok(0);
+        };
+        let mut contents = annotation_contents(&structured_annotations.content, true)?.into_iter();
+        let mut seen = HashSet::new();
+        let before = structured_annotations.content.len();
+        structured_annotations
+            .content
+            .retain(|_| contents.next().is_some_and(|content| seen.insert(content)));
+        Ok(before - structured_annotations.content.len())
+    }
+
+    /// remove the annotations which have the same content as an earlier annotation, even if their
+    /// IDs differ, the AnnotationRefs to a removed annotation then refer to the annotation that
+    /// was kept, returns the number of removed annotations, the document is unchanged if an
+    /// annotation cannot be serialized
+    pub fn dedup_annotations_by_content(&mut self) -> Result<usize, Error> {
+        let Some(structured_annotations) = &mut self.structured_annotations else {
+            return Ok(0);
+        };
+        let mut contents = annotation_contents(&structured_annotations.content, false)?.into_iter();
+        let mut survivors = HashMap::new();
+        let mut replaced = HashMap::new();
+        let before = structured_annotations.content.len();
+        structured_annotations.content.retain(|annotation| {
+            let Some(content) = contents.next() else {
+                return true;
+            };
+            match survivors.entry(content) {
+                Entry::Vacant(entry) => {
+                    entry.insert(annotation.id().to_string());
+                    true
+                }
+                Entry::Occupied(entry) => {
+                    if entry.get() != annotation.id() {
+                        replaced.insert(annotation.id().to_string(), entry.get().clone());
+                    }
+                    false
+                }
+            }
+        });
+        let removed = before - structured_annotations.content.len();
+        if !replaced.is_empty() {
+            for annotation_refs in self.annotation_refs_mut() {
+                for annotation_ref in annotation_refs.iter_mut() {
+                    if let Some(survivor) = replaced.get(&annotation_ref.id) {
+                        annotation_ref.id = survivor.clone();
+                    }
+                }
+                let mut seen = HashSet::new();
+                annotation_refs.retain(|annotation_ref| seen.insert(annotation_ref.id.clone()));
+            }
+        }
+        Ok(removed)
+    }
+
+    /// replace old_prefix by new_prefix in every ID that starts with it, both where the element
+    /// is defined and where it is referred to, so Instrument:0 becomes Inst2024:0 with the prefixes
+    /// Instrument and Inst2024, the document is unchanged if this would give two elements the
+    /// same ID
+    pub fn remap_ids(&mut self, old_prefix: &str, new_prefix: &str) -> Result<(), Error> {
+        if old_prefix == new_prefix {
+            return Err(Error::SamePrefix(old_prefix.to_string()));
+        }
+        self.map_ids(|id| {
+            id.strip_prefix(old_prefix)
+                .map(|suffix| format!("{new_prefix}{suffix}"))
+        })
+    }
+
+    /// replace the IDs which are a key in map by their value, both where the element is defined
+    /// and where it is referred to, the document is unchanged if this would give two elements the
+    /// same ID
+    pub fn remap_all_ids(&mut self, map: &HashMap<String, String>) -> Result<(), Error> {
+        self.map_ids(|id| map.get(id).cloned())
+    }
+
+    /// change the IDs where elements are defined and where they are referred to, after checking
+    /// that no two elements get the same ID
+    fn map_ids(&mut self, new_id: impl Fn(&str) -> Option<String>) -> Result<(), Error> {
+        let mut old_ids = HashMap::new();
+        let mut clash = None;
+        self.visit_ids(|site, id| {
+            if let IdSite::Definition(_) = site {
+                let new = new_id(id).unwrap_or_else(|| id.clone());
+                if let Some(old) = old_ids.insert(new.clone(), id.clone())
+                    && &old != id
+                {
+                    clash.get_or_insert(new);
+                }
+            }
+        });
+        if let Some(new) = clash {
+            return Err(Error::IdClash(new));
+        }
+        self.visit_ids_mut(|_, id| {
+            if let Some(new) = new_id(id) {
+                *id = new;
+            }
+        });
+        Ok(())
+    }
+
+    fn remove_image_refs(&mut self, removed: &HashSet<String>) {
+        for dataset in &mut self.dataset {
+            dataset
+                .image_ref
+                .retain(|image_ref| !removed.contains(image_ref.id()));
+        }
+        for folder in &mut self.folder {
+            folder
+                .image_ref
+                .retain(|image_ref| !removed.contains(image_ref.id()));
+        }
+        for well_sample in self
+            .plate
+            .iter_mut()
+            .flat_map(|plate| &mut plate.well)
+            .flat_map(|well| &mut well.well_sample)
+        {
+            if well_sample
+                .image_ref
+                .as_ref()
+                .is_some_and(|image_ref| removed.contains(image_ref.id()))
+            {
+                well_sample.image_ref = None;
+            }
+        }
+    }
+
+    /// the AnnotationRefs of every element that can have them
+    pub(crate) fn annotation_refs_mut(&mut self) -> Vec<&mut Vec<AnnotationRef>> {
+        let mut refs = Vec::new();
+        refs.extend(self.project.iter_mut().map(|p| &mut p.annotation_ref));
+        refs.extend(self.dataset.iter_mut().map(|d| &mut d.annotation_ref));
+        refs.extend(self.folder.iter_mut().map(|f| &mut f.annotation_ref));
+        for plate in &mut self.plate {
+            refs.push(&mut plate.annotation_ref);
+            refs.extend(plate.well.iter_mut().map(|w| &mut w.annotation_ref));
+            refs.extend(
+                plate
+                    .plate_acquisition
+                    .iter_mut()
+                    .map(|a| &mut a.annotation_ref),
+            );
+        }
+        for screen in &mut self.screen {
+            refs.push(&mut screen.annotation_ref);
+            refs.extend(screen.reagent.iter_mut().map(|r| &mut r.annotation_ref));
+        }
+        refs.extend(self.experimenter.iter_mut().map(|e| &mut e.annotation_ref));
+        refs.extend(
+            self.experimenter_group
+                .iter_mut()
+                .map(|g| &mut g.annotation_ref),
+        );
+        for instrument in &mut self.instrument {
+            refs.push(&mut instrument.annotation_ref);
+            refs.extend(
+                instrument
+                    .light_source_group
+                    .iter_mut()
+                    .map(|light_source| match light_source {
+                        LightSourceGroup::Laser(l) => &mut l.annotation_ref,
+                        LightSourceGroup::Arc(a) => &mut a.annotation_ref,
+                        LightSourceGroup::Filament(f) => &mut f.annotation_ref,
+                        LightSourceGroup::LightEmittingDiode(l) => &mut l.annotation_ref,
+                        LightSourceGroup::GenericExcitationSource(g) => &mut g.annotation_ref,
+                    }),
+            );
+            refs.extend(
+                instrument
+                    .detector
+                    .iter_mut()
+                    .map(|d| &mut d.annotation_ref),
+            );
+            refs.extend(
+                instrument
+                    .objective
+                    .iter_mut()
+                    .map(|o| &mut o.annotation_ref),
+            );
+            refs.extend(instrument.filter.iter_mut().map(|f| &mut f.annotation_ref));
+            refs.extend(
+                instrument
+                    .dichroic
+                    .iter_mut()
+                    .map(|d| &mut d.annotation_ref),
+            );
+        }
+        for image in &mut self.image {
+            refs.push(&mut image.annotation_ref);
+            for channel in &mut image.pixels.channel {
+                refs.push(&mut channel.annotation_ref);
+                refs.extend(channel.light_path.iter_mut().map(|l| &mut l.annotation_ref));
+            }
+            refs.extend(image.pixels.plane.iter_mut().map(|p| &mut p.annotation_ref));
+        }
+        for roi in &mut self.roi {
+            refs.push(&mut roi.annotation_ref);
+            for shape in roi.union.iter_mut().flat_map(|u| &mut u.shape_group) {
+                refs.push(match shape {
+                    ShapeGroup::Rectangle(r) => &mut r.annotation_ref,
+                    ShapeGroup::Mask(m) => &mut m.annotation_ref,
+                    ShapeGroup::Point(p) => &mut p.annotation_ref,
+                    ShapeGroup::Ellipse(e) => &mut e.annotation_ref,
+                    ShapeGroup::Line(l) => &mut l.annotation_ref,
+                    ShapeGroup::Polyline(p) => &mut p.annotation_ref,
+                    ShapeGroup::Polygon(p) => &mut p.annotation_ref,
+                    ShapeGroup::Label(l) => &mut l.annotation_ref,
+                });
+            }
+        }
+        for annotation in self
+            .structured_annotations
+            .iter_mut()
+            .flat_map(|s| &mut s.content)
+        {
+            refs.push(match annotation {
+                StructuredAnnotationsContent::XmlAnnotation(a) => &mut a.annotation_ref,
+                StructuredAnnotationsContent::FileAnnotation(a) => &mut a.annotation_ref,
+                StructuredAnnotationsContent::ListAnnotation(a) => &mut a.annotation_ref,
+                StructuredAnnotationsContent::LongAnnotation(a) => &mut a.annotation_ref,
+                StructuredAnnotationsContent::DoubleAnnotation(a) => &mut a.annotation_ref,
+                StructuredAnnotationsContent::CommentAnnotation(a) => &mut a.annotation_ref,
+                StructuredAnnotationsContent::BooleanAnnotation(a) => &mut a.annotation_ref,
+                StructuredAnnotationsContent::TimestampAnnotation(a) => &mut a.annotation_ref,
+                StructuredAnnotationsContent::TagAnnotation(a) => &mut a.annotation_ref,
+                StructuredAnnotationsContent::TermAnnotation(a) => &mut a.annotation_ref,
+                StructuredAnnotationsContent::MapAnnotation(a) => &mut a.annotation_ref,
+            });
+        }
+        refs
+    }
+}
+
+/// the XML of each annotation, without its ID unless with_id, to compare the content of
+/// annotations
+fn annotation_contents(
+    annotations: &[StructuredAnnotationsContent],
+    with_id: bool,
+) -> Result<Vec<String>, Error> {
+    annotations
+        .iter()
+        .map(|annotation| {
+            if with_id {
+                return Ok(quick_xml::se::to_string(annotation)?);
+            }
+            let mut annotation = annotation.clone();
+            annotation.id_mut().clear();
+            Ok(quick_xml::se::to_string(&annotation)?)
+        })
+        .collect()
+}
+
+/// consuming setters for chaining
+impl Ome {
+    pub fn with_creator(mut self, creator: &str) -> Self {
+        self.creator = Some(creator.to_string());
+        self
+    }
+
+    pub fn with_uuid(mut self, uuid: &str) -> Self {
+        self.uuid = Some(uuid.to_string());
+        self
+    }
+
+    pub fn without_uuid(mut self) -> Self {
+        self.uuid = None;
+        self
+    }
+}
+
+/// consuming setters for chaining
+impl Image {
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = Some(name.to_string());
+        self
+    }
+
+    pub fn with_acquisition_date(mut self, date: &str) -> Self {
+        self.acquisition_date = Some(date.to_string());
+        self
+    }
+
+    pub fn with_description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+}
+
+impl Pixels {
+    /// add a channel, size_c is increased by its SamplesPerPixel like [Pixels::remove_channel_by_id]
+    /// decreases it, or set to it if this is the first channel
+    pub fn add_channel(&mut self, channel: Channel) {
+        let samples = channel.samples_per_pixel.unwrap_or(1).max(1);
+        self.size_c = if self.channel.is_empty() {
+            samples
+        } else {
+            self.size_c + samples
+        };
+        self.channel.push(channel);
+    }
+
+    /// remove the channel with this id and its planes, a channel with SamplesPerPixel n has the
+    /// planes of n consecutive values of the_c, the planes of the channels after it get a the_c
+    /// that is n lower and size_c is decreased by n
+    pub fn remove_channel_by_id(&mut self, id: &str) -> Option<Channel> {
+        let samples = |channel: &Channel| channel.samples_per_pixel.unwrap_or(1).max(1);
+        let index = self.channel.iter().position(|channel| channel.id == id)?;
+        let first: i32 = self.channel[..index].iter().map(samples).sum();
+        let count = samples(&self.channel[index]);
+        self.plane
+            .retain(|plane| !(first..first + count).contains(&plane.the_c));
+        for plane in &mut self.plane {
+            if plane.the_c >= first + count {
+                plane.the_c -= count;
+            }
+        }
+        self.size_c = (self.size_c - count).max(1);
+        Some(self.channel.remove(index))
+    }
+
+    /// add a plane, replacing the plane with the same z, c and t if there is one
+    pub fn add_plane(&mut self, plane: Plane) {
+        self.remove_plane(plane.the_z, plane.the_c, plane.the_t);
+        self.plane.push(plane);
+    }
+
+    /// remove the plane with this z, c and t, the first one if there is more than one
+    pub fn remove_plane(&mut self, z: i32, c: i32, t: i32) -> Option<Plane> {
+        let index = self
+            .plane
+            .iter()
+            .position(|plane| (plane.the_z, plane.the_c, plane.the_t) == (z, c, t))?;
+        Some(self.plane.remove(index))
+    }
+
+    /// sort the planes with z changing fastest, then c, then t, irrespective of dimension_order,
+    /// see [Pixels::sort_planes] for sorting in storage order
+    pub fn sort_planes_by_zct(&mut self) {
+        self.plane
+            .sort_by_key(|plane| (plane.the_t, plane.the_c, plane.the_z));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+    use crate::validation::ValidationError;
+    use quick_xml::de::from_str;
+
+    #[test]
+    fn add() -> Result<(), Error> {
+        let mut ome = Ome::default();
+        ome.add_image(from_str(
+            r#"<Image ID="Image:0"><AnnotationRef ID="Annotation:0"/>
+                <Pixels ID="Pixels:0" DimensionOrder="XYCZT" Type="uint8" SizeX="1" SizeY="1" SizeZ="1" SizeC="1" SizeT="1">
+                    <MetadataOnly/>
+                </Pixels>
+            </Image>"#,
+        )?)
+        .add_instrument(from_str(r#"<Instrument ID="Instrument:0"/>"#)?)
+        .add_roi(from_str(
+            r#"<ROI ID="ROI:0"><Union><Point ID="Shape:0:0" X="1" Y="2"/></Union></ROI>"#,
+        )?)
+        .add_plate(from_str(r#"<Plate ID="Plate:0"/>"#)?)
+        .add_screen(from_str(r#"<Screen ID="Screen:0"><PlateRef ID="Plate:0"/></Screen>"#)?)
+        .add_experimenter(from_str(r#"<Experimenter ID="Experimenter:0"/>"#)?)
+        .add_dataset(from_str(
+            r#"<Dataset ID="Dataset:0"><ImageRef ID="Image:0"/></Dataset>"#,
+        )?)
+        .add_folder(from_str(r#"<Folder ID="Folder:0"><ROIRef ID="ROI:0"/></Folder>"#)?)
+        .add_annotation(from_str(
+            r#"<CommentAnnotation ID="Annotation:0"><Value>built</Value></CommentAnnotation>"#,
+        )?)
+        .add_annotation(from_str(
+            r#"<LongAnnotation ID="Annotation:1"><Value>1</Value></LongAnnotation>"#,
+        )?);
+        assert_eq!(ome.total_annotation_count(), 2);
+        assert!(ome.validate().is_empty());
+
+        let xml = quick_xml::se::to_string_with_root("OME", &ome)?;
+        let round_trip: Ome = xml.parse()?;
+        assert!(ome.semantically_eq(&round_trip));
+        assert_eq!(round_trip.image.len(), 1);
+        assert_eq!(round_trip.instrument.len(), 1);
+        assert_eq!(round_trip.roi.len(), 1);
+        assert_eq!(round_trip.plate.len(), 1);
+        assert_eq!(round_trip.screen.len(), 1);
+        assert_eq!(round_trip.experimenter.len(), 1);
+        assert_eq!(round_trip.dataset.len(), 1);
+        assert_eq!(round_trip.folder.len(), 1);
+        assert_eq!(round_trip.total_annotation_count(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn remove_by_id() -> Result<(), Error> {
+        let mut ome: Ome = std::fs::read_to_string("tests/plate.xml")?.parse()?;
+        let image = ome.remove_image_by_id("Image:1").unwrap();
+        assert_eq!(image.name.as_deref(), Some("A2"));
+        assert!(ome.remove_image_by_id("Image:1").is_none());
+        assert_eq!(ome.image.len(), 1);
+        assert_eq!(ome.dataset[0].image_ref.len(), 1);
+        assert_eq!(ome.dataset[0].image_ref[0].id(), "Image:0");
+        assert!(ome.folder[0].image_ref.is_empty());
+        assert!(ome.validate_references().is_empty());
+        assert!(ome.remove_plate_by_id("Plate:0").is_some());
+        assert!(ome.plate.is_empty());
+
+        let mut ome: Ome = std::fs::read_to_string("tests/roi_annotation_refs.xml")?.parse()?;
+        let annotation = ome.remove_annotation_by_id("Annotation:0").unwrap();
+        assert_eq!(annotation.type_name(), "TagAnnotation");
+        assert_eq!(ome.total_annotation_count(), 1);
+        let refs = |ome: &Ome| {
+            let plane = &ome.image[0].pixels.plane[0].annotation_ref;
+            let roi = &ome.roi[0].annotation_ref;
+            (plane.len(), roi.len())
+        };
+        assert_eq!(refs(&ome), (1, 1));
+        assert_eq!(ome.roi[0].annotation_ref[0].id(), "Annotation:1");
+        assert!(ome.remove_annotation_by_id("Annotation:0").is_none());
+
+        assert!(ome.remove_roi_by_id("ROI:0").is_some());
+        assert!(ome.roi.is_empty());
+        assert!(ome.image[0].roi_ref.is_empty());
+
+        let mut ome: Ome =
+            std::fs::read_to_string("tests/YTL1849A131_2023_05_04__13_36_36.xml")?.parse()?;
+        let id = ome.instrument[0].id.clone();
+        assert!(ome.remove_instrument_by_id(&id).is_some());
+        assert!(ome.image[0].instrument_ref.is_none());
+        assert!(ome.image[0].objective_settings.is_none());
+        let channels = &ome.image[0].pixels.channel;
+        assert!(channels.iter().all(|c| c.detector_settings.is_none()));
+        assert!(ome.validate_references().is_empty());
+
+        let mut ome: Ome = std::fs::read_to_string("tests/legacy_light_source.xml")?.parse()?;
+        assert!(ome.remove_instrument_by_id(&id).is_some());
+        let channels = &ome.image[0].pixels.channel;
+        assert!(channels.iter().all(|c| c.light_source_settings.is_none()));
+        assert!(ome.validate_references().is_empty());
+
+        let mut ome: Ome = r#"<OME>
+            <Dataset ID="Dataset:0"><ExperimenterRef ID="Experimenter:0"/></Dataset>
+            <Experimenter ID="Experimenter:0"/>
+            <ExperimenterGroup ID="ExperimenterGroup:0">
+                <ExperimenterRef ID="Experimenter:0"/><Leader ID="Experimenter:0"/>
+            </ExperimenterGroup>
+        </OME>"#
+            .parse()?;
+        assert!(ome.remove_experimenter_by_id("Experimenter:0").is_some());
+        assert!(ome.dataset[0].experimenter_ref.is_none());
+        assert!(ome.experimenter_group[0].experimenter_ref.is_empty());
+        assert!(ome.experimenter_group[0].leader.is_empty());
+        assert!(ome.undefined_experimenter_ids().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn dedup_annotations() -> Result<(), Error> {
+        let comment = |id: &str| {
+            from_str::<StructuredAnnotationsContent>(&format!(
+                r#"<CommentAnnotation ID="{id}" Namespace="ns"><Value>same</Value></CommentAnnotation>"#
+            ))
+        };
+        let mut ome = Ome::default();
+        for _ in 0..3 {
+            ome.add_annotation(comment("Annotation:0")?);
+        }
+        ome.add_annotation(from_str(
+            r#"<CommentAnnotation ID="Annotation:0" Namespace="ns"><Value>other</Value></CommentAnnotation>"#,
+        )?);
+        assert_eq!(ome.dedup_annotations()?, 2);
+        assert_eq!(ome.total_annotation_count(), 2);
+        assert_eq!(ome.dedup_annotations()?, 0);
+
+        let mut ome: Ome = r#"<OME>
+            <Dataset ID="Dataset:0"><AnnotationRef ID="Annotation:1"/></Dataset>
+            <Dataset ID="Dataset:1">
+                <AnnotationRef ID="Annotation:0"/><AnnotationRef ID="Annotation:2"/>
+            </Dataset>
+        </OME>"#
+            .parse()?;
+        for i in 0..3 {
+            ome.add_annotation(comment(&format!("Annotation:{i}"))?);
+        }
+        assert_eq!(ome.dedup_annotations()?, 0);
+        assert_eq!(ome.dedup_annotations_by_content()?, 2);
+        assert_eq!(ome.total_annotation_count(), 1);
+        let ids = |dataset: &Dataset| {
+            dataset
+                .annotation_ref
+                .iter()
+                .map(|annotation_ref| annotation_ref.id().to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ids(&ome.dataset[0]), ["Annotation:0"]);
+        assert_eq!(ids(&ome.dataset[1]), ["Annotation:0"]);
+        assert!(ome.validate_references().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn remap_ids() -> Result<(), Error> {
+        let ome: Ome = std::fs::read_to_string("tests/YTL378_JF552.xml")?.parse()?;
+        let mut remapped = ome.clone();
+        remapped.remap_ids("Instrument", "Inst2024")?;
+        assert_eq!(remapped.instrument.len(), 4);
+        for (i, (instrument, image)) in remapped.instrument.iter().zip(&remapped.image).enumerate()
+        {
+            assert_eq!(instrument.id, format!("Inst2024:{i}"));
+            assert_eq!(
+                image.instrument_ref.as_ref().map(|r| r.id()),
+                Some(instrument.id.as_str())
+            );
+        }
+        assert_eq!(remapped.image[0].id, ome.image[0].id);
+        assert!(remapped.validate_references().is_empty());
+        assert!(matches!(
+            remapped.remap_ids("Image", "Image"),
+            Err(Error::SamePrefix(_))
+        ));
+
+        let map = HashMap::from([
+            ("Inst2024:0".to_string(), "Inst2024:1".to_string()),
+            ("Inst2024:1".to_string(), "Inst2024:0".to_string()),
+        ]);
+        remapped.remap_all_ids(&map)?;
+        assert_eq!(remapped.instrument[0].id, "Inst2024:1");
+        assert_eq!(
+            remapped.image[0].instrument_ref.as_ref().map(|r| r.id()),
+            Some("Inst2024:1")
+        );
+        remapped.remap_all_ids(&map)?;
+        remapped.remap_ids("Inst2024", "Instrument")?;
+        assert!(remapped.semantically_eq(&ome));
+
+        let map = HashMap::from([("Instrument:0".to_string(), "Instrument:1".to_string())]);
+        assert!(matches!(
+            remapped.remap_all_ids(&map),
+            Err(Error::IdClash(id)) if id == "Instrument:1"
+        ));
+        assert!(remapped.semantically_eq(&ome));
+
+        // IDs in the content of an XMLAnnotation are not those of OME elements
+        let mut ome: Ome = r#"<OME>
+            <Instrument ID="Instrument:0"/>
+            <StructuredAnnotations>
+                <XMLAnnotation ID="Annotation:0">
+                    <Value><Other ID="Instrument:9"/><Other ID="a"/><Again ID="a"/></Value>
+                </XMLAnnotation>
+            </StructuredAnnotations>
+        </OME>"#
+            .parse()?;
+        ome.remap_ids("Instrument", "Inst2024")?;
+        ome.remap_all_ids(&HashMap::from([("a".to_string(), "b".to_string())]))?;
+        assert_eq!(ome.instrument[0].id, "Inst2024:0");
+        let StructuredAnnotationsContent::XmlAnnotation(annotation) =
+            &ome.annotations().next().unwrap()
+        else {
+            panic!("not an XMLAnnotation");
+        };
+        assert_eq!(
+            annotation.value.to_string(),
+            r#"<Other ID="Instrument:9"/><Other ID="a"/><Again ID="a"/>"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn retain_images() -> Result<(), Error> {
+        let mut ome: Ome = std::fs::read_to_string("tests/plate.xml")?.parse()?;
+        assert!(ome.validate_references().is_empty());
+        ome.image.pop();
+        assert!(matches!(
+            ome.validate_references()[..],
+            [
+                ValidationError::UnresolvedImageRef { .. },
+                ValidationError::UnresolvedImageRef { .. },
+                ValidationError::UnresolvedImageRef { .. }
+            ]
+        ));
+
+        let mut ome: Ome = std::fs::read_to_string("tests/plate.xml")?.parse()?;
+        ome.retain_images(|image| image.name.as_deref() == Some("A1"));
+        assert_eq!(ome.image.len(), 1);
+        assert_eq!(ome.image[0].id, "Image:0");
+        assert!(ome.validate_references().is_empty());
+        assert_eq!(ome.dataset[0].image_ref.len(), 1);
+        assert_eq!(ome.dataset[0].image_ref[0].id(), "Image:0");
+        assert!(ome.folder[0].image_ref.is_empty());
+        let wells = &ome.plate[0].well;
+        assert!(wells[0].well_sample[0].image_ref.is_some());
+        assert!(wells[1].well_sample[0].image_ref.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn pixels_channels_and_planes() -> Result<(), Error> {
+        let mut pixels: Pixels = from_str(
+            r#"<Pixels ID="Pixels:0" DimensionOrder="XYCZT" Type="uint8" SizeX="1" SizeY="1" SizeZ="2" SizeC="2" SizeT="1">
+                <Channel ID="Channel:0:0"/><Channel ID="Channel:0:1"/>
+                <MetadataOnly/>
+                <Plane TheZ="0" TheC="0" TheT="0"/><Plane TheZ="0" TheC="1" TheT="0"/>
+                <Plane TheZ="1" TheC="0" TheT="0"/><Plane TheZ="1" TheC="1" TheT="0"/>
+            </Pixels>"#,
+        )?;
+        pixels.add_channel(from_str(r#"<Channel ID="Channel:0:2" Name="third"/>"#)?);
+        assert_eq!(pixels.size_c, 3);
+        assert_eq!(pixels.channel.len(), 3);
+        for z in [1, 0] {
+            pixels.add_plane(from_str(&format!(
+                r#"<Plane TheZ="{z}" TheC="2" TheT="0"/>"#
+            ))?);
+        }
+        pixels.add_plane(from_str(
+            r#"<Plane TheZ="0" TheC="2" TheT="0" DeltaT="1"/>"#,
+        )?);
+        assert_eq!(pixels.plane.len(), 6);
+        pixels.sort_planes_by_zct();
+        let zct = |pixels: &Pixels| {
+            pixels
+                .plane
+                .iter()
+                .map(|plane| (plane.the_z, plane.the_c, plane.the_t))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            zct(&pixels),
+            [
+                (0, 0, 0),
+                (1, 0, 0),
+                (0, 1, 0),
+                (1, 1, 0),
+                (0, 2, 0),
+                (1, 2, 0)
+            ]
+        );
+        assert_eq!(pixels.plane[4].delta_t, Some(1.0));
+
+        let channel = pixels.remove_channel_by_id("Channel:0:1").unwrap();
+        assert_eq!(channel.id, "Channel:0:1");
+        assert_eq!(pixels.size_c, 2);
+        assert_eq!(zct(&pixels), [(0, 0, 0), (1, 0, 0), (0, 1, 0), (1, 1, 0)]);
+        assert_eq!(pixels.channel[1].name.as_deref(), Some("third"));
+        assert!(pixels.remove_plane(1, 1, 0).is_some());
+        assert!(pixels.remove_plane(1, 1, 0).is_none());
+        assert_eq!(pixels.plane.len(), 3);
+
+        let mut ome: Ome = r#"<OME><Image ID="Image:0">
+            <Pixels ID="Pixels:0" DimensionOrder="XYCZT" Type="uint8" SizeX="1" SizeY="1" SizeZ="1" SizeC="4" SizeT="1">
+                <Channel ID="Channel:0:0" SamplesPerPixel="3"/><Channel ID="Channel:0:1"/>
+                <MetadataOnly/>
+                <Plane TheZ="0" TheC="0" TheT="0"/><Plane TheZ="0" TheC="1" TheT="0"/>
+                <Plane TheZ="0" TheC="2" TheT="0"/><Plane TheZ="0" TheC="3" TheT="0"/>
+            </Pixels>
+        </Image></OME>"#
+            .parse()?;
+        let pixels = &mut ome.image[0].pixels;
+        assert!(pixels.remove_channel_by_id("Channel:0:0").is_some());
+        assert_eq!(pixels.size_c, 1);
+        assert_eq!(zct(pixels), [(0, 0, 0)]);
+
+        // adding and removing an RGB channel gives the same size_c
+        pixels.add_channel(from_str(
+            r#"<Channel ID="Channel:0:2" SamplesPerPixel="3"/>"#,
+        )?);
+        assert_eq!(pixels.size_c, 4);
+        assert!(pixels.remove_channel_by_id("Channel:0:2").is_some());
+        assert_eq!(pixels.size_c, 1);
+        pixels.channel.clear();
+        pixels.add_channel(from_str(
+            r#"<Channel ID="Channel:0:0" SamplesPerPixel="3"/>"#,
+        )?);
+        assert_eq!(pixels.size_c, 3);
+        Ok(())
+    }
+
+    #[test]
+    fn with() -> Result<(), Error> {
+        let ome: Ome = std::fs::read_to_string("tests/zct.ome.xml")?.parse()?;
+        let changed = ome
+            .clone()
+            .with_creator("MyApp 1.0")
+            .with_uuid("urn:uuid:0f8fad5b-d9cb-469f-a165-70867728950e");
+        assert_eq!(changed.creator.as_deref(), Some("MyApp 1.0"));
+        assert_eq!(
+            changed.uuid.as_deref(),
+            Some("urn:uuid:0f8fad5b-d9cb-469f-a165-70867728950e")
+        );
+        let mut expected = ome.clone();
+        expected.creator = changed.creator.clone();
+        expected.uuid = changed.uuid.clone();
+        assert!(changed.semantically_eq(&expected));
+        assert!(changed.without_uuid().uuid.is_none());
+
+        let image = ome.image[0].clone();
+        let changed = image
+            .clone()
+            .with_name("renamed")
+            .with_acquisition_date("2025-01-29T14:42:42")
+            .with_description("a description");
+        assert_eq!(changed.name.as_deref(), Some("renamed"));
+        assert_eq!(
+            changed.acquisition_date.as_deref(),
+            Some("2025-01-29T14:42:42")
+        );
+        assert_eq!(changed.description.as_deref(), Some("a description"));
+        let with_image = |image: Image| Ome::default().add_image(image).clone();
+        let mut expected = image;
+        expected.name = changed.name.clone();
+        expected.acquisition_date = changed.acquisition_date.clone();
+        expected.description = changed.description.clone();
+        assert!(with_image(changed).semantically_eq(&with_image(expected)));
+        Ok(())
+    }
+}
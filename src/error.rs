@@ -5,9 +5,68 @@ pub enum Error {
     #[error(transparent)]
     IO(#[from] std::io::Error),
     #[error(transparent)]
+    Xml(#[from] quick_xml::Error),
+    #[error(transparent)]
     SerdeXml(#[from] quick_xml::DeError),
+    #[error(transparent)]
+    SerdeXmlSerialize(#[from] quick_xml::SeError),
+    #[cfg(feature = "binary")]
+    #[error(transparent)]
+    Base64(#[from] base64::DecodeError),
+    #[cfg(feature = "chrono")]
+    #[error(transparent)]
+    Chrono(#[from] chrono::ParseError),
+    #[cfg(feature = "json")]
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error("{source} at line {line}, column {column}: {snippet}")]
+    Parse {
+        line: usize,
+        column: usize,
+        snippet: String,
+        source: quick_xml::DeError,
+    },
+    #[error("{}: {source}", path.display())]
+    Path {
+        path: std::path::PathBuf,
+        source: Box<Error>,
+    },
+    #[error("not a TIFF file")]
+    NotTiff,
+    #[error("the first IFD of the TIFF has no ImageDescription")]
+    NoImageDescription,
+    #[error("a classic TIFF cannot be larger than 4 GiB")]
+    TiffTooLarge,
+    #[error("the metadata is in the companion file {metadata_file} with UUID {uuid}")]
+    BinaryOnly { metadata_file: String, uuid: String },
+    #[error("text is not valid {0}")]
+    Encoding(&'static str),
+    #[error("{0} is not a UUID of the form urn:uuid:xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx")]
+    InvalidUuid(String),
+    #[error("folder {0} contains itself")]
+    FolderCycle(String),
+    #[error("the old and the new prefix of the IDs are both {0}")]
+    SamePrefix(String),
+    #[error("more than one ID would become {0}")]
+    IdClash(String),
+    #[error("reference to unknown element {0}")]
+    UnresolvedReference(String),
+    #[error("pixel type is {0}, not {1}")]
+    PixelTypeMismatch(String, String),
+    #[error("plane {0} is not in the pixel data")]
+    PlaneNotFound(usize),
+    #[error(
+        "the DeltaT of the plane at z: {0}, c: {1}, t: {2} is not after that of the previous time point"
+    )]
+    TimepointNotMonotonic(i32, i32, i32),
+    #[error("pixel type {0} has no numpy dtype")]
+    NoNumpyDtype(String),
+    #[error("axis order {0} is not made of distinct axes from XYZCT")]
+    InvalidAxisOrder(String),
     #[error("size of {0} is unknown")]
     SizeOfUnknown(String),
+    #[error("the factor of the unit {0} to SI is unknown")]
+    UnknownUnitFactor(String),
     #[error("no conversion to K by multiplication only")]
     TemparatureConversion,
 }
@@ -0,0 +1,97 @@
+use crate::ome::{AffineTransform, Ellipse, Rectangle};
+
+impl AffineTransform {
+    /// map a point with this transform
+    pub fn apply(&self, x: f32, y: f32) -> (f32, f32) {
+        (
+            self.a00 * x + self.a01 * y + self.a02,
+            self.a10 * x + self.a11 * y + self.a12,
+        )
+    }
+
+    /// the transform which undoes this one, None if this transform is singular
+    pub fn inverse(&self) -> Option<AffineTransform> {
+        let det = self.a00 * self.a11 - self.a01 * self.a10;
+        if det == 0.0 || !det.is_finite() {
+            return None;
+        }
+        Some(AffineTransform {
+            a00: self.a11 / det,
+            a01: -self.a01 / det,
+            a02: (self.a01 * self.a12 - self.a11 * self.a02) / det,
+            a10: -self.a10 / det,
+            a11: self.a00 / det,
+            a12: (self.a10 * self.a02 - self.a00 * self.a12) / det,
+        })
+    }
+}
+
+/// map a point into the coordinates of a shape before its transform, no transform is the identity
+fn untransform(transform: &Option<AffineTransform>, x: f32, y: f32) -> Option<(f32, f32)> {
+    match transform {
+        Some(transform) => Some(transform.inverse()?.apply(x, y)),
+        None => Some((x, y)),
+    }
+}
+
+impl Rectangle {
+    /// whether the point is inside the rectangle or on its edge
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        untransform(&self.transform, x, y).is_some_and(|(x, y)| {
+            (self.x..=self.x + self.width).contains(&x)
+                && (self.y..=self.y + self.height).contains(&y)
+        })
+    }
+}
+
+impl Ellipse {
+    /// whether the point is inside the ellipse or on its edge
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        untransform(&self.transform, x, y).is_some_and(|(x, y)| {
+            let dx = (x - self.x) / self.radius_x;
+            let dy = (y - self.y) / self.radius_y;
+            dx * dx + dy * dy <= 1.0
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+    use quick_xml::de::from_str;
+
+    #[test]
+    fn rotated_rectangle() -> Result<(), Error> {
+        let mut rectangle: Rectangle =
+            from_str(r#"<Rectangle ID="Shape:0" X="0" Y="0" Width="10" Height="2"/>"#)?;
+        assert!(rectangle.contains(5.0, 1.0));
+        assert!(!rectangle.contains(-1.0, 5.0));
+        // rotate by 90 degrees around the origin, so the rectangle covers -2 <= x <= 0, 0 <= y <= 10
+        rectangle.transform = Some(from_str(
+            r#"<Transform A00="0" A01="-1" A02="0" A10="1" A11="0" A12="0"/>"#,
+        )?);
+        assert!(rectangle.contains(-1.0, 5.0));
+        assert!(!rectangle.contains(5.0, 1.0));
+        assert!(!rectangle.contains(-1.0, 11.0));
+        rectangle.transform = Some(from_str(
+            r#"<Transform A00="1" A01="1" A02="0" A10="1" A11="1" A12="0"/>"#,
+        )?);
+        assert!(!rectangle.contains(5.0, 1.0));
+        Ok(())
+    }
+
+    #[test]
+    fn translated_ellipse() -> Result<(), Error> {
+        let mut ellipse: Ellipse =
+            from_str(r#"<Ellipse ID="Shape:0" X="0" Y="0" RadiusX="4" RadiusY="1"/>"#)?;
+        assert!(ellipse.contains(3.9, 0.0));
+        assert!(!ellipse.contains(3.0, 0.9));
+        ellipse.transform = Some(from_str(
+            r#"<Transform A00="1" A01="0" A02="10" A10="0" A11="1" A12="-5"/>"#,
+        )?);
+        assert!(ellipse.contains(13.9, -5.0));
+        assert!(!ellipse.contains(3.9, 0.0));
+        Ok(())
+    }
+}
@@ -0,0 +1,387 @@
+use crate::ome::{LightSourceGroup, Ome, ShapeGroup, StructuredAnnotationsContent};
+use std::fmt;
+
+/// the kind of element that an ID identifies, light sources, shapes and annotations each share
+/// one kind because references do not say which type they refer to
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum IdKind {
+    Annotation,
+    Channel,
+    Dataset,
+    Detector,
+    Dichroic,
+    Experiment,
+    Experimenter,
+    ExperimenterGroup,
+    Filter,
+    FilterSet,
+    Folder,
+    Image,
+    Instrument,
+    LightSource,
+    MicrobeamManipulation,
+    Objective,
+    Pixels,
+    Plate,
+    PlateAcquisition,
+    Project,
+    Reagent,
+    Roi,
+    Screen,
+    Shape,
+    Well,
+    WellSample,
+}
+
+impl fmt::Display for IdKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+/// where an ID occurs, on the element it identifies or on a reference to an element of a kind
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum IdSite {
+    Definition(IdKind),
+    Reference(IdKind),
+}
+
+/// visit every ID of an Ome, $iter is iter or iter_mut and $ref & or &mut
+macro_rules! walk_ids {
+    ($ome:expr, $visit:ident, $iter:ident, $($ref:tt)+) => {{
+        use IdKind::*;
+        use IdSite::{Definition, Reference};
+        for project in $ome.project.$iter() {
+            $visit(Definition(Project), $($ref)+ project.id);
+            for r in project.experimenter_ref.$iter() {
+                $visit(Reference(Experimenter), $($ref)+ r.0.id);
+            }
+            for r in project.experimenter_group_ref.$iter() {
+                $visit(Reference(ExperimenterGroup), $($ref)+ r.0.id);
+            }
+            for r in project.dataset_ref.$iter() {
+                $visit(Reference(Dataset), $($ref)+ r.0.id);
+            }
+            for r in project.annotation_ref.$iter() {
+                $visit(Reference(Annotation), $($ref)+ r.id);
+            }
+        }
+        for dataset in $ome.dataset.$iter() {
+            $visit(Definition(Dataset), $($ref)+ dataset.id);
+            for r in dataset.experimenter_ref.$iter() {
+                $visit(Reference(Experimenter), $($ref)+ r.0.id);
+            }
+            for r in dataset.experimenter_group_ref.$iter() {
+                $visit(Reference(ExperimenterGroup), $($ref)+ r.0.id);
+            }
+            for r in dataset.image_ref.$iter() {
+                $visit(Reference(Image), $($ref)+ r.0.id);
+            }
+            for r in dataset.annotation_ref.$iter() {
+                $visit(Reference(Annotation), $($ref)+ r.id);
+            }
+        }
+        for folder in $ome.folder.$iter() {
+            $visit(Definition(Folder), $($ref)+ folder.id);
+            for r in folder.folder_ref.$iter() {
+                $visit(Reference(Folder), $($ref)+ r.0.id);
+            }
+            for r in folder.image_ref.$iter() {
+                $visit(Reference(Image), $($ref)+ r.0.id);
+            }
+            for r in folder.roi_ref.$iter() {
+                $visit(Reference(Roi), $($ref)+ r.0.id);
+            }
+            for r in folder.annotation_ref.$iter() {
+                $visit(Reference(Annotation), $($ref)+ r.id);
+            }
+        }
+        for experiment in $ome.experiment.$iter() {
+            $visit(Definition(Experiment), $($ref)+ experiment.id);
+            for r in experiment.experimenter_ref.$iter() {
+                $visit(Reference(Experimenter), $($ref)+ r.0.id);
+            }
+            for manipulation in experiment.microbeam_manipulation.$iter() {
+                $visit(Definition(MicrobeamManipulation), $($ref)+ manipulation.id);
+                for r in manipulation.roi_ref.$iter() {
+                    $visit(Reference(Roi), $($ref)+ r.0.id);
+                }
+                $visit(Reference(Experimenter), $($ref)+ manipulation.experimenter_ref.0.id);
+                for settings in manipulation.light_source_settings.$iter() {
+                    $visit(Reference(LightSource), $($ref)+ settings.id);
+                }
+            }
+        }
+        for plate in $ome.plate.$iter() {
+            $visit(Definition(Plate), $($ref)+ plate.id);
+            for r in plate.annotation_ref.$iter() {
+                $visit(Reference(Annotation), $($ref)+ r.id);
+            }
+            for well in plate.well.$iter() {
+                $visit(Definition(Well), $($ref)+ well.id);
+                for r in well.reagent_ref.$iter() {
+                    $visit(Reference(Reagent), $($ref)+ r.0.id);
+                }
+                for r in well.annotation_ref.$iter() {
+                    $visit(Reference(Annotation), $($ref)+ r.id);
+                }
+                for sample in well.well_sample.$iter() {
+                    $visit(Definition(WellSample), $($ref)+ sample.id);
+                    for r in sample.image_ref.$iter() {
+                        $visit(Reference(Image), $($ref)+ r.0.id);
+                    }
+                }
+            }
+            for acquisition in plate.plate_acquisition.$iter() {
+                $visit(Definition(PlateAcquisition), $($ref)+ acquisition.id);
+                for r in acquisition.well_sample_ref.$iter() {
+                    $visit(Reference(WellSample), $($ref)+ r.0.id);
+                }
+                for r in acquisition.annotation_ref.$iter() {
+                    $visit(Reference(Annotation), $($ref)+ r.id);
+                }
+            }
+        }
+        for screen in $ome.screen.$iter() {
+            $visit(Definition(Screen), $($ref)+ screen.id);
+            for r in screen.plate_ref.$iter() {
+                $visit(Reference(Plate), $($ref)+ r.0.id);
+            }
+            for r in screen.annotation_ref.$iter() {
+                $visit(Reference(Annotation), $($ref)+ r.id);
+            }
+            for reagent in screen.reagent.$iter() {
+                $visit(Definition(Reagent), $($ref)+ reagent.id);
+                for r in reagent.annotation_ref.$iter() {
+                    $visit(Reference(Annotation), $($ref)+ r.id);
+                }
+            }
+        }
+        for experimenter in $ome.experimenter.$iter() {
+            $visit(Definition(Experimenter), $($ref)+ experimenter.id);
+            for r in experimenter.annotation_ref.$iter() {
+                $visit(Reference(Annotation), $($ref)+ r.id);
+            }
+        }
+        for group in $ome.experimenter_group.$iter() {
+            $visit(Definition(ExperimenterGroup), $($ref)+ group.id);
+            for r in group.experimenter_ref.$iter().chain(group.leader.$iter()) {
+                $visit(Reference(Experimenter), $($ref)+ r.0.id);
+            }
+            for r in group.annotation_ref.$iter() {
+                $visit(Reference(Annotation), $($ref)+ r.id);
+            }
+        }
+        for instrument in $ome.instrument.$iter() {
+            $visit(Definition(Instrument), $($ref)+ instrument.id);
+            for r in instrument.annotation_ref.$iter() {
+                $visit(Reference(Annotation), $($ref)+ r.id);
+            }
+            for light_source in instrument.light_source_group.$iter() {
+                let (id, annotation_refs, pump) = match light_source {
+                    LightSourceGroup::Laser(l) => {
+                        ($($ref)+ l.id, $($ref)+ l.annotation_ref, l.pump.$iter().next())
+                    }
+                    LightSourceGroup::Arc(a) => ($($ref)+ a.id, $($ref)+ a.annotation_ref, None),
+                    LightSourceGroup::Filament(f) => {
+                        ($($ref)+ f.id, $($ref)+ f.annotation_ref, None)
+                    }
+                    LightSourceGroup::LightEmittingDiode(l) => {
+                        ($($ref)+ l.id, $($ref)+ l.annotation_ref, None)
+                    }
+                    LightSourceGroup::GenericExcitationSource(g) => {
+                        ($($ref)+ g.id, $($ref)+ g.annotation_ref, None)
+                    }
+                };
+                $visit(Definition(LightSource), id);
+                for r in annotation_refs.$iter() {
+                    $visit(Reference(Annotation), $($ref)+ r.id);
+                }
+                if let Some(pump) = pump {
+                    $visit(Reference(LightSource), $($ref)+ pump.0.id);
+                }
+            }
+            for detector in instrument.detector.$iter() {
+                $visit(Definition(Detector), $($ref)+ detector.id);
+                for r in detector.annotation_ref.$iter() {
+                    $visit(Reference(Annotation), $($ref)+ r.id);
+                }
+            }
+            for objective in instrument.objective.$iter() {
+                $visit(Definition(Objective), $($ref)+ objective.id);
+                for r in objective.annotation_ref.$iter() {
+                    $visit(Reference(Annotation), $($ref)+ r.id);
+                }
+            }
+            for filter_set in instrument.filter_set.$iter() {
+                $visit(Definition(FilterSet), $($ref)+ filter_set.id);
+                for r in filter_set
+                    .excitation_filter_ref
+                    .$iter()
+                    .chain(filter_set.emission_filter_ref.$iter())
+                {
+                    $visit(Reference(Filter), $($ref)+ r.0.id);
+                }
+                for r in filter_set.dichroic_ref.$iter() {
+                    $visit(Reference(Dichroic), $($ref)+ r.0.id);
+                }
+            }
+            for filter in instrument.filter.$iter() {
+                $visit(Definition(Filter), $($ref)+ filter.id);
+                for r in filter.annotation_ref.$iter() {
+                    $visit(Reference(Annotation), $($ref)+ r.id);
+                }
+            }
+            for dichroic in instrument.dichroic.$iter() {
+                $visit(Definition(Dichroic), $($ref)+ dichroic.id);
+                for r in dichroic.annotation_ref.$iter() {
+                    $visit(Reference(Annotation), $($ref)+ r.id);
+                }
+            }
+        }
+        for image in $ome.image.$iter() {
+            $visit(Definition(Image), $($ref)+ image.id);
+            for r in image.experimenter_ref.$iter() {
+                $visit(Reference(Experimenter), $($ref)+ r.0.id);
+            }
+            for r in image.experiment_ref.$iter() {
+                $visit(Reference(Experiment), $($ref)+ r.0.id);
+            }
+            for r in image.experimenter_group_ref.$iter() {
+                $visit(Reference(ExperimenterGroup), $($ref)+ r.0.id);
+            }
+            for r in image.instrument_ref.$iter() {
+                $visit(Reference(Instrument), $($ref)+ r.0.id);
+            }
+            for settings in image.objective_settings.$iter() {
+                $visit(Reference(Objective), $($ref)+ settings.id);
+            }
+            for r in image.roi_ref.$iter() {
+                $visit(Reference(Roi), $($ref)+ r.0.id);
+            }
+            for r in image.microbeam_manipulation_ref.$iter() {
+                $visit(Reference(MicrobeamManipulation), $($ref)+ r.0.id);
+            }
+            for r in image.annotation_ref.$iter() {
+                $visit(Reference(Annotation), $($ref)+ r.id);
+            }
+            let pixels = $($ref)+ image.pixels;
+            $visit(Definition(Pixels), $($ref)+ pixels.id);
+            for r in pixels.plane.$iter().flat_map(|plane| plane.annotation_ref.$iter()) {
+                $visit(Reference(Annotation), $($ref)+ r.id);
+            }
+            for channel in pixels.channel.$iter() {
+                $visit(Definition(Channel), $($ref)+ channel.id);
+                for settings in channel.light_source_settings.$iter() {
+                    $visit(Reference(LightSource), $($ref)+ settings.id);
+                }
+                for settings in channel.detector_settings.$iter() {
+                    $visit(Reference(Detector), $($ref)+ settings.id);
+                }
+                for r in channel.filter_set_ref.$iter() {
+                    $visit(Reference(FilterSet), $($ref)+ r.0.id);
+                }
+                for r in channel.annotation_ref.$iter() {
+                    $visit(Reference(Annotation), $($ref)+ r.id);
+                }
+                for light_path in channel.light_path.$iter() {
+                    for r in light_path
+                        .excitation_filter_ref
+                        .$iter()
+                        .chain(light_path.emission_filter_ref.$iter())
+                    {
+                        $visit(Reference(Filter), $($ref)+ r.0.id);
+                    }
+                    for r in light_path.dichroic_ref.$iter() {
+                        $visit(Reference(Dichroic), $($ref)+ r.0.id);
+                    }
+                    for r in light_path.annotation_ref.$iter() {
+                        $visit(Reference(Annotation), $($ref)+ r.id);
+                    }
+                }
+            }
+        }
+        for roi in $ome.roi.$iter() {
+            $visit(Definition(Roi), $($ref)+ roi.id);
+            for r in roi.annotation_ref.$iter() {
+                $visit(Reference(Annotation), $($ref)+ r.id);
+            }
+            for shape in roi.union.$iter().flat_map(|union| union.shape_group.$iter()) {
+                let (id, annotation_refs) = match shape {
+                    ShapeGroup::Rectangle(s) => ($($ref)+ s.id, $($ref)+ s.annotation_ref),
+                    ShapeGroup::Mask(s) => ($($ref)+ s.id, $($ref)+ s.annotation_ref),
+                    ShapeGroup::Point(s) => ($($ref)+ s.id, $($ref)+ s.annotation_ref),
+                    ShapeGroup::Ellipse(s) => ($($ref)+ s.id, $($ref)+ s.annotation_ref),
+                    ShapeGroup::Line(s) => ($($ref)+ s.id, $($ref)+ s.annotation_ref),
+                    ShapeGroup::Polyline(s) => ($($ref)+ s.id, $($ref)+ s.annotation_ref),
+                    ShapeGroup::Polygon(s) => ($($ref)+ s.id, $($ref)+ s.annotation_ref),
+                    ShapeGroup::Label(s) => ($($ref)+ s.id, $($ref)+ s.annotation_ref),
+                };
+                $visit(Definition(Shape), id);
+                for r in annotation_refs.$iter() {
+                    $visit(Reference(Annotation), $($ref)+ r.id);
+                }
+            }
+        }
+        for annotation in $ome
+            .structured_annotations
+            .$iter()
+            .flat_map(|annotations| annotations.content.$iter())
+        {
+            let (id, annotation_refs) = match annotation {
+                StructuredAnnotationsContent::XmlAnnotation(a) => {
+                    ($($ref)+ a.id, $($ref)+ a.annotation_ref)
+                }
+                StructuredAnnotationsContent::FileAnnotation(a) => {
+                    ($($ref)+ a.id, $($ref)+ a.annotation_ref)
+                }
+                StructuredAnnotationsContent::ListAnnotation(a) => {
+                    ($($ref)+ a.id, $($ref)+ a.annotation_ref)
+                }
+                StructuredAnnotationsContent::LongAnnotation(a) => {
+                    ($($ref)+ a.id, $($ref)+ a.annotation_ref)
+                }
+                StructuredAnnotationsContent::DoubleAnnotation(a) => {
+                    ($($ref)+ a.id, $($ref)+ a.annotation_ref)
+                }
+                StructuredAnnotationsContent::CommentAnnotation(a) => {
+                    ($($ref)+ a.id, $($ref)+ a.annotation_ref)
+                }
+                StructuredAnnotationsContent::BooleanAnnotation(a) => {
+                    ($($ref)+ a.id, $($ref)+ a.annotation_ref)
+                }
+                StructuredAnnotationsContent::TimestampAnnotation(a) => {
+                    ($($ref)+ a.id, $($ref)+ a.annotation_ref)
+                }
+                StructuredAnnotationsContent::TagAnnotation(a) => {
+                    ($($ref)+ a.id, $($ref)+ a.annotation_ref)
+                }
+                StructuredAnnotationsContent::TermAnnotation(a) => {
+                    ($($ref)+ a.id, $($ref)+ a.annotation_ref)
+                }
+                StructuredAnnotationsContent::MapAnnotation(a) => {
+                    ($($ref)+ a.id, $($ref)+ a.annotation_ref)
+                }
+            };
+            $visit(Definition(Annotation), id);
+            for r in annotation_refs.$iter() {
+                $visit(Reference(Annotation), $($ref)+ r.id);
+            }
+        }
+    }};
+}
+
+impl Ome {
+    /// call visit for every ID in the typed fields of the document, both where an element is
+    /// defined and where it is referred to, the references of an element come after its
+    /// definition and before the definitions of the elements inside it, IDs in the content of
+    /// XMLAnnotations and in unknown xml are not visited
+    pub(crate) fn visit_ids<'a>(&'a self, mut visit: impl FnMut(IdSite, &'a String)) {
+        walk_ids!(self, visit, iter, &)
+    }
+
+    /// like [Ome::visit_ids], with the IDs mutable
+    pub(crate) fn visit_ids_mut(&mut self, mut visit: impl FnMut(IdSite, &mut String)) {
+        walk_ids!(self, visit, iter_mut, &mut)
+    }
+}
@@ -0,0 +1,478 @@
+use crate::error::Error;
+use crate::ome::{Ome, XmlAnnotationValue};
+use serde::de::value::{MapDeserializer, SeqDeserializer, StringDeserializer};
+use serde::de::{self, DeserializeSeed, EnumAccess, IntoDeserializer, VariantAccess, Visitor};
+use serde::ser::{
+    SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant, SerializeTuple,
+    SerializeTupleStruct, SerializeTupleVariant,
+};
+use serde::{Deserialize, Serialize, Serializer};
+use serde_json::Value;
+use std::cell::Cell;
+
+thread_local! {
+    static XML_AS_TEXT: Cell<bool> = const { Cell::new(false) };
+}
+
+/// whether the content of XMLAnnotations is (de)serialized as a string of xml, because JSON
+/// objects cannot have the same key more than once like xml can have the same element
+pub(crate) fn xml_as_text() -> bool {
+    XML_AS_TEXT.get()
+}
+
+/// turns xml as text off again, also when (de)serialization panics
+pub(crate) struct XmlAsTextGuard;
+
+impl XmlAsTextGuard {
+    pub(crate) fn new() -> Self {
+        XML_AS_TEXT.set(true);
+        XmlAsTextGuard
+    }
+}
+
+impl Drop for XmlAsTextGuard {
+    fn drop(&mut self) {
+        XML_AS_TEXT.set(false);
+    }
+}
+
+/// the content of the Value of an XMLAnnotation from its inner xml
+pub(crate) fn parse_xml_annotation_value(xml: &str) -> Result<XmlAnnotationValue, Error> {
+    let value = crate::preserve::parse_tree(&format!("<Value>{xml}</Value>"))?;
+    Ok(XmlAnnotationValue {
+        attributes: value.attributes,
+        children: value.children,
+        verbatim: Some(xml.to_string()),
+    })
+}
+
+/// the JSON key of a field, without the @ of attributes, and value for the text or the content of
+/// an element
+fn json_key(key: &'static str) -> &'static str {
+    match key {
+        "$text" | "$value" => "value",
+        key => key.strip_prefix('@').unwrap_or(key),
+    }
+}
+
+impl Ome {
+    /// serialize to JSON with the keys of the XML without the @ of attributes, like "ID", and
+    /// with "value" for text content and choices of elements, the content of an XMLAnnotation is
+    /// a string with its xml
+    pub fn to_json(&self) -> Result<String, Error> {
+        self.write_json(serde_json::ser::CompactFormatter)
+    }
+
+    /// like [Ome::to_json], indented by two spaces
+    pub fn to_json_pretty(&self) -> Result<String, Error> {
+        self.to_json_indented(2)
+    }
+
+    /// like [Ome::to_json], indented by indent spaces
+    pub fn to_json_indented(&self, indent: usize) -> Result<String, Error> {
+        let indent = " ".repeat(indent);
+        self.write_json(serde_json::ser::PrettyFormatter::with_indent(
+            indent.as_bytes(),
+        ))
+    }
+
+    fn write_json<F: serde_json::ser::Formatter>(&self, formatter: F) -> Result<String, Error> {
+        let _guard = XmlAsTextGuard::new();
+        let mut json = Vec::new();
+        self.serialize(JsonKeys(&mut serde_json::Serializer::with_formatter(
+            &mut json, formatter,
+        )))?;
+        Ok(String::from_utf8(json).expect("serde_json writes UTF-8"))
+    }
+
+    /// parse JSON made by [Ome::to_json]
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        let _guard = XmlAsTextGuard::new();
+        Ok(Ome::deserialize(JsonValue(serde_json::from_str(json)?))?)
+    }
+}
+
+/// a serializer which renames the fields of structs with [json_key]
+struct JsonKeys<S>(S);
+
+/// a value serialized with [JsonKeys]
+struct WithJsonKeys<'a, T: ?Sized>(&'a T);
+
+impl<T: Serialize + ?Sized> Serialize for WithJsonKeys<'_, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(JsonKeys(serializer))
+    }
+}
+
+macro_rules! forward_serialize {
+    ($($fn:ident($t:ty)),* $(,)?) => {
+        $(
+            fn $fn(self, v: $t) -> Result<Self::Ok, Self::Error> {
+                self.0.$fn(v)
+            }
+        )*
+    };
+}
+
+impl<S: Serializer> Serializer for JsonKeys<S> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+    type SerializeSeq = JsonKeys<S::SerializeSeq>;
+    type SerializeTuple = JsonKeys<S::SerializeTuple>;
+    type SerializeTupleStruct = JsonKeys<S::SerializeTupleStruct>;
+    type SerializeTupleVariant = JsonKeys<S::SerializeTupleVariant>;
+    type SerializeMap = JsonKeys<S::SerializeMap>;
+    type SerializeStruct = JsonKeys<S::SerializeStruct>;
+    type SerializeStructVariant = JsonKeys<S::SerializeStructVariant>;
+
+    forward_serialize!(
+        serialize_bool(bool),
+        serialize_i8(i8),
+        serialize_i16(i16),
+        serialize_i32(i32),
+        serialize_i64(i64),
+        serialize_u8(u8),
+        serialize_u16(u16),
+        serialize_u32(u32),
+        serialize_u64(u64),
+        serialize_f32(f32),
+        serialize_f64(f64),
+        serialize_char(char),
+        serialize_str(&str),
+        serialize_bytes(&[u8]),
+        serialize_unit_struct(&'static str),
+    );
+
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        self.0.serialize_none()
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Self::Ok, Self::Error> {
+        self.0.serialize_some(&WithJsonKeys(value))
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        self.0.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok, Self::Error> {
+        self.0.serialize_unit_variant(name, variant_index, variant)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error> {
+        self.0.serialize_newtype_struct(name, &WithJsonKeys(value))
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error> {
+        self.0
+            .serialize_newtype_variant(name, variant_index, variant, &WithJsonKeys(value))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        self.0.serialize_seq(len).map(JsonKeys)
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        self.0.serialize_tuple(len).map(JsonKeys)
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        self.0.serialize_tuple_struct(name, len).map(JsonKeys)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        self.0
+            .serialize_tuple_variant(name, variant_index, variant, len)
+            .map(JsonKeys)
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        self.0.serialize_map(len).map(JsonKeys)
+    }
+
+    fn serialize_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        self.0.serialize_struct(name, len).map(JsonKeys)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        self.0
+            .serialize_struct_variant(name, variant_index, variant, len)
+            .map(JsonKeys)
+    }
+}
+
+macro_rules! impl_serialize_elements {
+    ($($trait:ident::$fn:ident),* $(,)?) => {
+        $(
+            impl<S: $trait> $trait for JsonKeys<S> {
+                type Ok = S::Ok;
+                type Error = S::Error;
+
+                fn $fn<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+                    self.0.$fn(&WithJsonKeys(value))
+                }
+
+                fn end(self) -> Result<Self::Ok, Self::Error> {
+                    self.0.end()
+                }
+            }
+        )*
+    };
+}
+
+impl_serialize_elements!(
+    SerializeSeq::serialize_element,
+    SerializeTuple::serialize_element,
+    SerializeTupleStruct::serialize_field,
+    SerializeTupleVariant::serialize_field,
+);
+
+/// the keys of maps are kept as they are
+impl<S: SerializeMap> SerializeMap for JsonKeys<S> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Self::Error> {
+        self.0.serialize_key(key)
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.0.serialize_value(&WithJsonKeys(value))
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.0.end()
+    }
+}
+
+macro_rules! impl_serialize_fields {
+    ($($trait:ident),* $(,)?) => {
+        $(
+            impl<S: $trait> $trait for JsonKeys<S> {
+                type Ok = S::Ok;
+                type Error = S::Error;
+
+                fn serialize_field<T: Serialize + ?Sized>(
+                    &mut self,
+                    key: &'static str,
+                    value: &T,
+                ) -> Result<(), Self::Error> {
+                    self.0.serialize_field(json_key(key), &WithJsonKeys(value))
+                }
+
+                fn skip_field(&mut self, key: &'static str) -> Result<(), Self::Error> {
+                    self.0.skip_field(json_key(key))
+                }
+
+                fn end(self) -> Result<Self::Ok, Self::Error> {
+                    self.0.end()
+                }
+            }
+        )*
+    };
+}
+
+impl_serialize_fields!(SerializeStruct, SerializeStructVariant);
+
+/// a JSON value which is deserialized with the keys of structs renamed back from [json_key] to
+/// the names of the fields
+struct JsonValue(Value);
+
+impl<'de> IntoDeserializer<'de, serde_json::Error> for JsonValue {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
+
+fn map_deserializer(
+    entries: impl IntoIterator<Item = (String, Value)>,
+) -> MapDeserializer<'static, impl Iterator<Item = (String, JsonValue)>, serde_json::Error> {
+    MapDeserializer::new(
+        entries
+            .into_iter()
+            .map(|(key, value)| (key, JsonValue(value))),
+    )
+}
+
+impl<'de> de::Deserializer<'de> for JsonValue {
+    type Error = serde_json::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.0 {
+            Value::Object(object) => visitor.visit_map(map_deserializer(object)),
+            Value::Array(items) => {
+                visitor.visit_seq(SeqDeserializer::new(items.into_iter().map(JsonValue)))
+            }
+            value => value.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.0 {
+            Value::Null => visitor.visit_none(),
+            value => visitor.visit_some(JsonValue(value)),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        let Value::Object(object) = self.0 else {
+            return self.deserialize_any(visitor);
+        };
+        visitor.visit_map(map_deserializer(object.into_iter().map(|(key, value)| {
+            let field = fields.iter().find(|field| json_key(field) == key);
+            (field.map_or(key, |field| field.to_string()), value)
+        })))
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        match self.0 {
+            Value::Object(object) if object.len() == 1 => {
+                let (variant, value) = object.into_iter().next().expect("one entry");
+                visitor.visit_enum(JsonVariant(variant, JsonValue(value)))
+            }
+            value => value.deserialize_enum(name, variants, visitor),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf unit
+        unit_struct seq tuple tuple_struct map identifier ignored_any
+    }
+}
+
+/// an enum like {"Rectangle": {...}}
+struct JsonVariant(String, JsonValue);
+
+impl<'de> EnumAccess<'de> for JsonVariant {
+    type Error = serde_json::Error;
+    type Variant = JsonValue;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Self::Variant), Self::Error> {
+        let variant: StringDeserializer<serde_json::Error> = self.0.into_deserializer();
+        Ok((seed.deserialize(variant)?, self.1))
+    }
+}
+
+impl<'de> VariantAccess<'de> for JsonValue {
+    type Error = serde_json::Error;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        de::Deserialize::deserialize(self)
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<T::Value, Self::Error> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        de::Deserializer::deserialize_seq(self, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        de::Deserializer::deserialize_struct(self, "", fields, visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() -> Result<(), Error> {
+        for entry in std::fs::read_dir("tests")? {
+            let path = entry?.path();
+            if path.extension().is_none_or(|extension| extension != "xml") {
+                continue;
+            }
+            let ome: Ome = std::fs::read_to_string(&path)?.parse()?;
+            let json = ome.to_json()?;
+            assert!(!json.contains("\"@"), "{}", path.display());
+            assert!(!json.contains("\"$value\""), "{}", path.display());
+            let round_trip = Ome::from_json(&json)?;
+            assert!(ome.semantically_eq(&round_trip), "{}", path.display());
+            assert!(ome.semantically_eq(&Ome::from_json(&ome.to_json_pretty()?)?));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn snapshot() -> Result<(), Error> {
+        let ome: Ome = std::fs::read_to_string("tests/test.xml")?.parse()?;
+        let json = ome.to_json_pretty()? + "\n";
+        crate::tests::assert_snapshot("tests/test.json", &json);
+        let value: Value = serde_json::from_str(&json)?;
+        let pixels = &value["Image"][0]["Pixels"];
+        assert_eq!(pixels["ID"], "Pixels:0");
+        assert_eq!(pixels["PhysicalSizeXUnit"], "µm");
+        Ok(())
+    }
+}
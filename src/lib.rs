@@ -1,35 +1,310 @@
 #![allow(non_camel_case_types)]
 pub mod ome;
 
+pub mod binary;
+mod canonical;
+pub mod color;
+mod compact;
+pub mod edit;
 pub mod error;
+pub mod geometry;
+mod ids;
+#[cfg(feature = "json")]
+mod json;
+pub mod navigation;
+#[cfg(feature = "ngff")]
+mod ngff;
+pub mod preserve;
+#[cfg(feature = "uuid")]
+pub mod provenance;
 #[cfg(feature = "python")]
 mod py;
+pub mod roi;
+pub mod stats;
+pub mod summary;
+#[cfg(feature = "tiff")]
+pub mod tiff;
+pub mod validation;
 
 use crate::error::Error;
+pub use compact::XmlOptions;
 pub use ome::Ome;
-use quick_xml::de::from_str;
+use quick_xml::DeError;
+use quick_xml::de::Deserializer;
+use quick_xml::events::{BytesEnd, BytesStart, Event};
+use quick_xml::reader::Reader;
+use quick_xml::writer::Writer;
+use serde::Deserialize;
+use std::borrow::Cow;
+use std::path::Path;
 use std::str::FromStr;
 
+/// the default namespace declared on the root element
+fn root_namespace(s: &str) -> Option<String> {
+    let mut reader = Reader::from_str(s);
+    loop {
+        match reader.read_event().ok()? {
+            Event::Start(e) | Event::Empty(e) => {
+                let xmlns = e.try_get_attribute("xmlns").ok()??;
+                return Some(xmlns.unescape_value().ok()?.to_string());
+            }
+            Event::Eof => return None,
+            _ => {}
+        }
+    }
+}
+
+/// abstract elements of which the concrete type can be given by xsi:type instead of by the
+/// element name
+const ABSTRACT_ELEMENTS: [&str; 2] = ["Shape", "LightSource"];
+
+/// `<Shape xsi:type="Rectangle" ...>` becomes `<Rectangle ...>`, the new name is None if the
+/// element is left as is
+fn resolve_xsi_type(element: BytesStart) -> Result<(BytesStart, Option<String>), Error> {
+    if !ABSTRACT_ELEMENTS.contains(&String::from_utf8_lossy(element.local_name().as_ref()).as_ref())
+    {
+        return Ok((element, None));
+    }
+    let Some(xsi_type) = element
+        .try_get_attribute("xsi:type")
+        .map_err(quick_xml::Error::from)?
+    else {
+        return Ok((element, None));
+    };
+    let xsi_type = xsi_type.unescape_value()?;
+    // the type may be qualified with the prefix of the OME namespace
+    let xsi_type = xsi_type.rsplit(':').next().unwrap_or_default();
+    let name = match element.name().prefix() {
+        Some(prefix) => format!("{}:{xsi_type}", String::from_utf8_lossy(prefix.as_ref())),
+        None => xsi_type.to_string(),
+    };
+    let mut resolved = BytesStart::new(name.clone());
+    for attribute in element.attributes() {
+        let attribute = attribute.map_err(quick_xml::Error::from)?;
+        if attribute.key.as_ref() != b"xsi:type" {
+            resolved.push_attribute(attribute);
+        }
+    }
+    Ok((resolved.into_owned(), Some(name)))
+}
+
+/// the end tag to write for a start tag
+enum EndTag {
+    Keep,
+    Rename(String),
+    /// the start tag was not written
+    Skip,
+}
+
+/// whether an element is a LightSource wrapping the concrete light source, as in documents from
+/// before 2016 which had no xsi:type
+fn is_light_source_wrapper(element: &BytesStart) -> Result<bool, Error> {
+    Ok(element.local_name().as_ref() == b"LightSource"
+        && element
+            .try_get_attribute("xsi:type")
+            .map_err(quick_xml::Error::from)?
+            .is_none())
+}
+
+/// the attributes of a LightSource wrapper are added to those of the light source inside it,
+/// where the light source does not have them itself
+fn unwrap_light_source<'a>(
+    wrapper: Option<BytesStart<'static>>,
+    element: BytesStart<'a>,
+) -> Result<BytesStart<'a>, Error> {
+    let Some(wrapper) = wrapper else {
+        return Ok(element);
+    };
+    let mut merged = element.clone();
+    for attribute in wrapper.attributes() {
+        let attribute = attribute.map_err(quick_xml::Error::from)?;
+        if element
+            .try_get_attribute(attribute.key)
+            .map_err(quick_xml::Error::from)?
+            .is_none()
+        {
+            merged.push_attribute(attribute);
+        }
+    }
+    Ok(merged)
+}
+
+/// rename abstract elements to their concrete type given by xsi:type, and replace LightSource
+/// wrappers by the light source inside them, so that they can be deserialized in the same way as
+/// elements named after their type
+pub(crate) fn resolve_abstract_elements(s: &str) -> Result<Cow<'_, str>, Error> {
+    let has_wrapper = s.match_indices("<LightSource").any(|(i, tag)| {
+        s[i + tag.len()..]
+            .chars()
+            .next()
+            .is_some_and(|c| c == '>' || c.is_whitespace())
+    });
+    if !s.contains("xsi:type") && !has_wrapper {
+        return Ok(Cow::Borrowed(s));
+    }
+    let mut reader = Reader::from_str(s);
+    let mut writer = Writer::new(Vec::new());
+    let mut end_tags = Vec::new();
+    let mut wrapper = None;
+    loop {
+        match reader.read_event()? {
+            Event::Start(element) if is_light_source_wrapper(&element)? => {
+                wrapper = Some(element.into_owned());
+                end_tags.push(EndTag::Skip);
+            }
+            Event::Start(element) => {
+                let element = unwrap_light_source(wrapper.take(), element)?;
+                let (element, name) = resolve_xsi_type(element)?;
+                end_tags.push(name.map_or(EndTag::Keep, EndTag::Rename));
+                writer.write_event(Event::Start(element))?;
+            }
+            Event::Empty(element) => {
+                let element = unwrap_light_source(wrapper.take(), element)?;
+                writer.write_event(Event::Empty(resolve_xsi_type(element)?.0))?;
+            }
+            Event::End(element) => match end_tags.pop() {
+                Some(EndTag::Skip) => wrapper = None,
+                Some(EndTag::Rename(name)) => {
+                    writer.write_event(Event::End(BytesEnd::new(name)))?
+                }
+                _ => writer.write_event(Event::End(element))?,
+            },
+            Event::Eof => break,
+            event => writer.write_event(event)?,
+        }
+    }
+    Ok(Cow::Owned(
+        String::from_utf8_lossy(&writer.into_inner()).into_owned(),
+    ))
+}
+
+/// the 1-based line and column of a byte offset, and the text around it on its line
+fn position(s: &str, offset: usize) -> (usize, usize, String) {
+    let mut offset = offset.min(s.len());
+    while !s.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    let start = s[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let end = s[offset..].find('\n').map_or(s.len(), |i| offset + i);
+    let before = s[start..offset].chars().rev().take(30).collect::<Vec<_>>();
+    let after = s[offset..end].chars().take(30);
+    let snippet = before
+        .iter()
+        .rev()
+        .copied()
+        .chain(after)
+        .collect::<String>();
+    (
+        s[..start].matches('\n').count() + 1,
+        s[start..offset].chars().count() + 1,
+        snippet.trim().to_string(),
+    )
+}
+
+/// deserialize with the position where the xml is malformed, or else where deserialization
+/// stopped, in the error
+fn from_str<T: for<'de> Deserialize<'de>>(s: &str) -> Result<T, Error> {
+    let mut deserializer = Deserializer::from_str(s);
+    T::deserialize(&mut deserializer).map_err(|source| {
+        let reader = deserializer.get_ref().get_ref();
+        let offset = match source {
+            DeError::InvalidXml(_) => reader.error_position(),
+            _ => reader.buffer_position(),
+        };
+        let (line, column, snippet) = position(s, offset as usize);
+        Error::Parse {
+            line,
+            column,
+            snippet,
+            source,
+        }
+    })
+}
+
 impl FromStr for Ome {
     type Err = Error;
 
+    /// errors include the position of the problem, which is approximate when abstract elements
+    /// are resolved first
     fn from_str(s: &str) -> Result<Self, Error> {
-        Ok(from_str(s)?)
+        let mut ome: Ome = from_str(&resolve_abstract_elements(s)?)?;
+        preserve::read_xml_annotation_values(&mut ome, s)?;
+        ome.original_namespace = root_namespace(s).filter(|namespace| namespace != ome::NAMESPACE);
+        Ok(ome)
+    }
+}
+
+/// decode UTF-16 when the text starts with a byte order mark or, without one, when the first "<"
+/// is accompanied by a zero byte, and UTF-8 otherwise
+fn decode(bytes: &[u8]) -> Result<Cow<'_, str>, Error> {
+    let utf16 = |bytes: &[u8], from_bytes: fn([u8; 2]) -> u16| {
+        if bytes.len() % 2 != 0 {
+            return Err(Error::Encoding("UTF-16"));
+        }
+        let units = bytes
+            .chunks_exact(2)
+            .map(|unit| from_bytes([unit[0], unit[1]]))
+            .collect::<Vec<_>>();
+        let text = String::from_utf16(&units).map_err(|_| Error::Encoding("UTF-16"))?;
+        Ok(Cow::Owned(text.trim_start_matches('\u{feff}').to_string()))
+    };
+    match bytes {
+        [0xff, 0xfe, ..] | [b'<', 0, ..] => utf16(bytes, u16::from_le_bytes),
+        [0xfe, 0xff, ..] | [0, b'<', ..] => utf16(bytes, u16::from_be_bytes),
+        _ => {
+            let bytes = bytes.strip_prefix(b"\xef\xbb\xbf").unwrap_or(bytes);
+            Ok(Cow::Borrowed(
+                std::str::from_utf8(bytes).map_err(|_| Error::Encoding("UTF-8"))?,
+            ))
+        }
+    }
+}
+
+impl Ome {
+    /// parse UTF-8 or UTF-16 encoded text, with or without a byte order mark
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        decode(bytes)?.parse()
+    }
+
+    /// read and parse a file, errors include the path
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        std::fs::read(path)
+            .map_err(Error::from)
+            .and_then(|bytes| Self::from_bytes(&bytes))
+            .map_err(|err| Error::Path {
+                path: path.to_path_buf(),
+                source: Box::new(err),
+            })
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ome::Convert;
     use std::fs::read_to_string;
 
+    /// compare with the file at path, or write it if the environment variable UPDATE_SNAPSHOTS is
+    /// set
+    #[cfg(feature = "json")]
+    pub(crate) fn assert_snapshot(path: &str, actual: &str) {
+        if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+            std::fs::write(path, actual).unwrap();
+        } else {
+            assert!(
+                read_to_string(path).is_ok_and(|expected| expected == actual),
+                "{path} is out of date, run the tests with UPDATE_SNAPSHOTS=1 to update it"
+            );
+        }
+    }
+
     macro_rules! test_read {
         ($($name:ident: $file:expr $(,)?)*) => {
             $(
                 #[test]
                 fn $name() -> Result<(), Error> {
-                    let file = read_to_string(format!("tests/{}.xml", $file))?;
-                    let _ome: Ome = file.parse()?;
+                    let _ome = Ome::from_path(format!("tests/{}.xml", $file))?;
                     Ok(())
                 }
             )*
@@ -49,5 +324,1293 @@ mod tests {
         j: "20230511-p53-4x-CMV-1min-4h-01-Airyscan Processing-01"
         k: "YTL1849A131_2023_05_04__13_36_36"
         l: "multi-channel.ome"
+        m: "zct.ome"
     );
+
+    #[test]
+    fn summary() -> Result<(), Error> {
+        for entry in std::fs::read_dir("tests")? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|e| e == "xml") {
+                let ome: Ome = read_to_string(&path)?.parse()?;
+                let summary = ome.summary();
+                assert_eq!(summary.image_count, ome.image.len());
+                assert!(summary.to_string().contains(&ome.image[0].id));
+            }
+        }
+        let ome: Ome = read_to_string("tests/test.xml")?.parse()?;
+        let summary = ome.summary();
+        assert_eq!(summary.images[0].size_xyzct, [2, 2, 1, 1, 1]);
+        assert_eq!(summary.images[0].channel_names, vec![None]);
+        #[cfg(feature = "json")]
+        assert!(summary.to_json()?.contains(r#""size_xyzct":[2,2,1,1,1]"#));
+        Ok(())
+    }
+
+    #[test]
+    fn structured_annotations() -> Result<(), Error> {
+        let ome: Ome = read_to_string("tests/annotations.xml")?.parse()?;
+        let annotations = ome.annotations().collect::<Vec<_>>();
+        assert_eq!(annotations.len(), 8);
+        assert!(matches!(
+            annotations[0],
+            ome::StructuredAnnotationsContent::MapAnnotation(map) if map.value.m.len() == 2
+        ));
+        assert!(matches!(
+            annotations[4],
+            ome::StructuredAnnotationsContent::BooleanAnnotation(boolean) if boolean.value
+        ));
+        assert!(matches!(
+            annotations[5],
+            ome::StructuredAnnotationsContent::TagAnnotation(tag) if tag.value == "mitosis"
+        ));
+        assert!(matches!(
+            annotations[6],
+            ome::StructuredAnnotationsContent::TermAnnotation(term) if term.value == "GO:0000279"
+        ));
+        let ome::StructuredAnnotationsContent::TimestampAnnotation(timestamp) = annotations[7]
+        else {
+            panic!("not a TimestampAnnotation");
+        };
+        assert_eq!(timestamp.value, "2025-01-29T15:03:10.5+01:00");
+        #[cfg(feature = "chrono")]
+        assert_eq!(
+            timestamp.date_time()?.to_utc().to_rfc3339(),
+            "2025-01-29T14:03:10.500+00:00"
+        );
+        let xml = quick_xml::se::to_string_with_root("OME", &ome)?;
+        assert!(xml.contains("<TagAnnotation "));
+        assert!(xml.contains("<TermAnnotation "));
+        assert!(xml.contains("<TimestampAnnotation "));
+        let round_trip: Ome = xml.parse()?;
+        assert_eq!(round_trip.annotations().count(), 8);
+        assert!(ome.semantically_eq(&round_trip));
+        Ok(())
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn acquisition_date() -> Result<(), Error> {
+        use chrono::{FixedOffset, TimeZone, Utc};
+
+        let mut ome: Ome = read_to_string("tests/test.xml")?.parse()?;
+        let image = &mut ome.image[0];
+        let utc = Utc.with_ymd_and_hms(2025, 1, 29, 14, 42, 42).unwrap();
+        assert_eq!(image.acquisition_date_parsed().unwrap()?, utc);
+        image.acquisition_date = Some("2025-01-29T15:42:42+01:00".to_string());
+        let date = image.acquisition_date_parsed().unwrap()?;
+        assert_eq!(date, utc);
+        assert_eq!(date.offset(), &FixedOffset::east_opt(3600).unwrap());
+        image.acquisition_date = Some("29 January 2025".to_string());
+        assert!(image.acquisition_date_parsed().unwrap().is_err());
+        image.acquisition_date = None;
+        assert!(image.acquisition_date_parsed().is_none());
+        image.set_acquisition_date(utc);
+        assert_eq!(
+            image.acquisition_date.as_deref(),
+            Some("2025-01-29T14:42:42+00:00")
+        );
+        assert_eq!(image.acquisition_date_parsed().unwrap()?, utc);
+        Ok(())
+    }
+
+    #[test]
+    fn tiff_data_per_plane() -> Result<(), Error> {
+        let ome: Ome = read_to_string("tests/zct.ome.xml")?.parse()?;
+        let pixels = &ome.image[0].pixels;
+        assert_eq!(
+            pixels.tiff_data.len() as i32,
+            pixels.size_z * pixels.size_c * pixels.size_t
+        );
+        let xml = quick_xml::se::to_string_with_root("OME", &ome)?;
+        let round_trip: Ome = xml.parse()?;
+        let ifds = round_trip.image[0]
+            .pixels
+            .tiff_data
+            .iter()
+            .map(|tiff_data| tiff_data.ifd)
+            .collect::<Vec<_>>();
+        assert_eq!(ifds, (0..12).collect::<Vec<_>>());
+        #[allow(deprecated)]
+        {
+            assert!(pixels.single_tiff_data().is_none());
+            let mut pixels = pixels.clone();
+            pixels.tiff_data.truncate(1);
+            assert_eq!(pixels.single_tiff_data().map(|t| t.ifd), Some(0));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn tiff_data_uuid() -> Result<(), Error> {
+        let mut ome: Ome = read_to_string("tests/zct.ome.xml")?.parse()?;
+        let uuid = ome.image[0].pixels.tiff_data[0].uuid.as_ref().unwrap();
+        assert!(uuid.is_valid_urn());
+        #[cfg(feature = "uuid")]
+        assert_eq!(
+            uuid.uuid_value().map(|uuid| uuid.to_string()).as_deref(),
+            Some("5f4a4b5e-7c1d-4d8e-9a0b-2f6c3d1e8a97")
+        );
+        assert!(ome.validate().is_empty());
+
+        let malformed = "urn:uuid:5f4a4b5e-7c1d-4d8e-9a0b-2f6c3d1e8a9";
+        ome.image[0].pixels.tiff_data[3]
+            .uuid
+            .as_mut()
+            .unwrap()
+            .content = malformed.to_string();
+        let uuid = ome.image[0].pixels.tiff_data[3].uuid.as_ref().unwrap();
+        assert!(!uuid.is_valid_urn());
+        #[cfg(feature = "uuid")]
+        assert!(uuid.uuid_value().is_none());
+        let errors = ome.validate();
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(
+            &errors[0],
+            validation::ValidationError::InvalidUuid { element_id, value }
+                if element_id == "Pixels:0" && value == malformed
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn multiple_annotation_refs() -> Result<(), Error> {
+        let ome: Ome = read_to_string("tests/roi_annotation_refs.xml")?.parse()?;
+        let ids =
+            |refs: &[ome::AnnotationRef]| refs.iter().map(|r| r.id.clone()).collect::<Vec<_>>();
+        let expected = ["Annotation:0", "Annotation:1"];
+        assert_eq!(ids(&ome.roi[0].annotation_ref), expected);
+        assert_eq!(ids(&ome.image[0].pixels.plane[0].annotation_ref), expected);
+        let xml = quick_xml::se::to_string_with_root("OME", &ome)?;
+        let round_trip: Ome = xml.parse()?;
+        assert_eq!(ids(&round_trip.roi[0].annotation_ref), expected);
+        assert_eq!(
+            ids(&round_trip.image[0].pixels.plane[0].annotation_ref),
+            expected
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn data_source() -> Result<(), Error> {
+        use ome::DataSource;
+
+        let ome: Ome = read_to_string("tests/test.xml")?.parse()?;
+        let mut pixels = ome.image[0].pixels.clone();
+        assert!(pixels.is_metadata_only());
+        assert!(!pixels.has_bin_data() && !pixels.has_tiff_data());
+        assert_eq!(pixels.data_source(), DataSource::MetadataOnly);
+
+        pixels.metadata_only = None;
+        assert_eq!(pixels.data_source(), DataSource::Missing);
+
+        pixels.bin_data.push(from_str(
+            r#"<BinData BigEndian="false" Length="4">AAAA</BinData>"#,
+        )?);
+        assert!(pixels.has_bin_data());
+        assert_eq!(pixels.data_source(), DataSource::BinData);
+
+        let ome: Ome = read_to_string("tests/zct.ome.xml")?.parse()?;
+        let pixels = &ome.image[0].pixels;
+        assert!(pixels.has_tiff_data() && !pixels.is_metadata_only());
+        assert_eq!(pixels.data_source(), DataSource::TiffData);
+        Ok(())
+    }
+
+    #[test]
+    fn xml_annotation_value() -> Result<(), Error> {
+        let original = read_to_string("tests/annotations.xml")?.replace(
+            "<Settings ",
+            "<!-- written by the camera -->\n        <Settings ",
+        );
+        let ome: Ome = original.parse()?;
+        let xml_annotation = |ome: &Ome| match ome.annotations().nth(1) {
+            Some(ome::StructuredAnnotationsContent::XmlAnnotation(xml)) => xml.clone(),
+            _ => panic!("not an XMLAnnotation"),
+        };
+        let compact = concat!(
+            "<OriginalMetadata><Key>Camera &amp; lens</Key><Value>Andor iXon Ultra 888</Value></OriginalMetadata>",
+            "<OriginalMetadata><Key>Binning</Key><Value/></OriginalMetadata>",
+            r#"<Settings Version="1.2" Mode="fast"/>"#
+        );
+        // the value as it is in the document, with the comment and the indentation
+        let start = original.find("<OriginalMetadata>").unwrap();
+        let end = original.find("</Value>\n    </XMLAnnotation>").unwrap();
+        let verbatim = &original[start - "\n        ".len()..end];
+        assert!(verbatim.contains("<!-- written by the camera -->\n        <Settings "));
+        assert_eq!(xml_annotation(&ome).value_text(), verbatim);
+        for xml in [ome.to_xml()?, ome.to_xml_preserving()?] {
+            assert!(xml.contains(&format!("<Value>{verbatim}</Value>")));
+            let round_trip: Ome = xml.parse()?;
+            assert_eq!(xml_annotation(&round_trip).value_text(), verbatim);
+        }
+        // serde writes the children
+        let xml = quick_xml::se::to_string_with_root("OME", &ome)?;
+        assert!(xml.contains(&format!("<Value>{compact}</Value>")));
+        let round_trip: Ome = xml.parse()?;
+        assert_eq!(
+            xml_annotation(&round_trip).value,
+            xml_annotation(&ome).value
+        );
+        // and so does a changed value
+        let mut changed = xml_annotation(&ome);
+        changed.value.children.pop();
+        assert!(changed.value_text().ends_with("</OriginalMetadata>"));
+        Ok(())
+    }
+
+    #[test]
+    fn typed_refs() -> Result<(), Error> {
+        let ome: Ome = read_to_string("tests/roi_annotation_refs.xml")?.parse()?;
+        let roi_ref: &ome::RoiRef = &ome.image[0].roi_ref[0];
+        assert_eq!(roi_ref.id, "ROI:0");
+        let xml = quick_xml::se::to_string_with_root("OME", &ome)?;
+        assert!(xml.contains(r#"<ROIRef ID="ROI:0"/>"#));
+        let folder: ome::Folder =
+            from_str(r#"<Folder ID="Folder:0"><ImageRef ID="Image:0"/></Folder>"#)?;
+        assert_eq!(folder.image_ref[0].id, ome::ImageRef::new("Image:0").id);
+        Ok(())
+    }
+
+    #[test]
+    fn roi_shapes() -> Result<(), Error> {
+        use ome::ShapeGroup;
+
+        let ome: Ome = read_to_string("tests/roi_shapes.xml")?.parse()?;
+        let shapes = &ome.roi[0].union.as_ref().unwrap().shape_group;
+        assert_eq!(shapes.len(), 3);
+        assert!(matches!(&shapes[0], ShapeGroup::Rectangle(r) if r.width == 64.0));
+        assert!(matches!(&shapes[1], ShapeGroup::Ellipse(e) if e.radius_x == 18.25));
+        assert!(matches!(&shapes[2], ShapeGroup::Polygon(p) if p.points.starts_with("130,90")));
+
+        let xml = quick_xml::se::to_string_with_root("OME", &ome)?;
+        assert!(xml.contains(r#"<Union><Rectangle "#));
+        let round_trip: Ome = xml.parse()?;
+        assert_eq!(
+            round_trip.roi[0].union.as_ref().unwrap().shape_group.len(),
+            3
+        );
+
+        let wrapped: ome::RoiUnion = from_str(
+            r#"<Union><ShapeGroup><Rectangle ID="Shape:0" X="0" Y="0" Width="1" Height="1"/></ShapeGroup></Union>"#,
+        )?;
+        assert!(matches!(
+            wrapped.shape_group[..],
+            [ShapeGroup::Rectangle(_)]
+        ));
+
+        let unknown: ome::RoiUnion = from_str(
+            r#"<Union><Acme:Spline Knots="3"><Acme:Knot/></Acme:Spline><Point ID="Shape:1" X="1" Y="2"/></Union>"#,
+        )?;
+        assert!(matches!(unknown.shape_group[..], [ShapeGroup::Point(_)]));
+        Ok(())
+    }
+
+    #[test]
+    fn sort_planes() -> Result<(), Error> {
+        let ome: Ome = read_to_string("tests/zct.ome.xml")?.parse()?;
+        let mut pixels = ome.image[0].pixels.clone();
+        pixels.dimension_order = ome::PixelsDimensionOrderType::Xyczt;
+        pixels.plane.reverse();
+        pixels.plane.swap(3, 7);
+        pixels.sort_planes();
+        let zct = pixels
+            .plane
+            .iter()
+            .map(|p| (p.the_z, p.the_c, p.the_t))
+            .collect::<Vec<_>>();
+        let mut expected = Vec::new();
+        for t in 0..3 {
+            for z in 0..2 {
+                for c in 0..2 {
+                    expected.push((z, c, t));
+                }
+            }
+        }
+        assert_eq!(zct, expected);
+        assert_eq!(pixels.zct_to_index(1, 1, 2), Some(11));
+        assert_eq!(pixels.zct_to_index(1, 0, 0), Some(2));
+        assert_eq!(pixels.zct_to_index(-1, 0, 0), None);
+        assert_eq!(pixels.zct_to_index(0, 2, 0), None);
+
+        // a negative and an out of range plane go last
+        pixels.plane[0].the_z = -1;
+        pixels.plane[5].the_t = 3;
+        let zct = |p: &ome::Plane| (p.the_z, p.the_c, p.the_t);
+        let outside = [zct(&pixels.plane[0]), zct(&pixels.plane[5])];
+        pixels.sort_planes();
+        let last = pixels.plane[pixels.plane.len() - 2..].iter().map(zct);
+        assert_eq!(last.collect::<Vec<_>>(), outside);
+        Ok(())
+    }
+
+    #[test]
+    fn map_access() -> Result<(), Error> {
+        let environment: ome::ImagingEnvironment = from_str(
+            r#"<ImagingEnvironment Temperature="37"><Map><M K="incubator">Okolab</M><M K="humidity">95%</M><M>no key</M></Map></ImagingEnvironment>"#,
+        )?;
+        assert_eq!(environment.map_get("humidity"), Some("95%"));
+        assert_eq!(environment.map_get("CO2"), None);
+        assert_eq!(
+            environment.map_keys().collect::<Vec<_>>(),
+            ["incubator", "humidity"]
+        );
+        assert_eq!(
+            environment.map_pairs().next(),
+            Some(("incubator", "Okolab"))
+        );
+
+        let source: ome::GenericExcitationSource =
+            from_str(r#"<GenericExcitationSource ID="LightSource:0"/>"#)?;
+        assert_eq!(source.map_get("anything"), None);
+        assert_eq!(source.map_keys().count(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn map_annotation_from_pairs() -> Result<(), Error> {
+        let pairs = [
+            ("cell line", "HeLa"),
+            ("treatment", "DMSO"),
+            ("cell line", "U2OS"),
+        ]
+        .map(|(k, v)| (k.to_string(), v.to_string()));
+        let annotation = ome::MapAnnotation::from_pairs(
+            "Annotation:0",
+            Some("openmicroscopy.org/omero/client/mapAnnotation"),
+            pairs,
+        );
+        let mut ome = Ome::default();
+        ome.add_annotation(ome::StructuredAnnotationsContent::MapAnnotation(annotation));
+        let ome: Ome = ome.to_xml()?.parse()?;
+        let Some(ome::StructuredAnnotationsContent::MapAnnotation(annotation)) =
+            ome.annotations().next()
+        else {
+            panic!("not a MapAnnotation");
+        };
+        assert_eq!(annotation.id, "Annotation:0");
+        assert_eq!(
+            annotation.namespace.as_deref(),
+            Some("openmicroscopy.org/omero/client/mapAnnotation")
+        );
+        assert_eq!(
+            annotation.value.pairs().collect::<Vec<_>>(),
+            [
+                ("cell line", "HeLa"),
+                ("treatment", "DMSO"),
+                ("cell line", "U2OS")
+            ]
+        );
+        let map = annotation.to_hashmap();
+        assert_eq!(map.len(), 2);
+        assert_eq!(map["cell line"], "HeLa");
+        assert_eq!(map["treatment"], "DMSO");
+        Ok(())
+    }
+
+    #[test]
+    fn empty_elements() -> Result<(), Error> {
+        // empty and self-closed elements parse as empty strings
+        let ome: Ome = read_to_string("tests/empty_elements.xml")?.parse()?;
+        let image = &ome.image[0];
+        assert_eq!(image.acquisition_date.as_deref(), Some(""));
+        assert_eq!(image.description.as_deref(), Some(""));
+        #[cfg(feature = "chrono")]
+        assert!(image.acquisition_date_parsed().is_none());
+        let plate = &ome.plate[0];
+        assert_eq!(plate.description.as_deref(), Some(""));
+        assert_eq!(plate.well[0].well_sample[0].timepoint.as_deref(), Some(""));
+        let uuid = image.pixels.tiff_data[0].uuid.as_ref().unwrap();
+        assert_eq!(uuid.file_name.as_deref(), Some("empty.ome.tif"));
+        assert_eq!(uuid.content, "");
+        assert_eq!(image.pixels.plane[0].hash_sha1.as_deref(), Some(""));
+        let mut annotations = ome.annotations();
+        let Some(ome::StructuredAnnotationsContent::MapAnnotation(map)) = annotations.next() else {
+            panic!("not a MapAnnotation");
+        };
+        let pairs = map.value.pairs().collect::<Vec<_>>();
+        assert_eq!(
+            pairs,
+            [("self-closed", ""), ("empty", ""), ("filled", "value")]
+        );
+        let Some(ome::StructuredAnnotationsContent::CommentAnnotation(comment)) =
+            annotations.next()
+        else {
+            panic!("not a CommentAnnotation");
+        };
+        assert_eq!(comment.description.as_deref(), Some(""));
+        assert_eq!(comment.value, "");
+        Ok(())
+    }
+
+    #[test]
+    fn transmittance_range() -> Result<(), Error> {
+        let range: ome::TransmittanceRange =
+            from_str(r#"<TransmittanceRange CutIn="475" CutOut="525"/>"#)?;
+        assert!((range.center_nm().unwrap() - 500.0).abs() < 1e-3);
+        assert!((range.bandwidth_nm().unwrap() - 50.0).abs() < 1e-3);
+        let range: ome::TransmittanceRange = from_str(
+            r#"<TransmittanceRange CutIn="475" CutInUnit="nm" CutOut="0.525" CutOutUnit="µm"/>"#,
+        )?;
+        assert!((range.center_nm().unwrap() - 500.0).abs() < 1e-3);
+        assert!((range.bandwidth_nm().unwrap() - 50.0).abs() < 1e-3);
+        let range: ome::TransmittanceRange =
+            from_str(r#"<TransmittanceRange CutIn="475" CutInUnit="nm"/>"#)?;
+        assert_eq!(range.cut_in_nm(), Some(475.0));
+        assert!(range.center_nm().is_none() && range.bandwidth_nm().is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn transmittance_overlap() -> Result<(), Error> {
+        let range = |xml| -> Result<ome::TransmittanceRange, Error> { from_str(xml) };
+        let gfp = range(
+            r#"<TransmittanceRange CutIn="500" CutInUnit="nm" CutOut="550" CutOutUnit="nm"/>"#,
+        )?;
+        let yfp = range(
+            r#"<TransmittanceRange CutIn="0.52" CutInUnit="µm" CutOut="0.56" CutOutUnit="µm"/>"#,
+        )?;
+        let rfp = range(
+            r#"<TransmittanceRange CutIn="5.8e-7" CutInUnit="m" CutOut="6.3e-7" CutOutUnit="m"/>"#,
+        )?;
+        let open = range(r#"<TransmittanceRange CutIn="500" CutInUnit="nm"/>"#)?;
+        assert!(gfp.contains_wavelength(500.0));
+        assert!(gfp.contains_wavelength(525.0));
+        assert!(gfp.contains_wavelength(550.0));
+        assert!(!gfp.contains_wavelength(560.0));
+        assert!(!open.contains_wavelength(600.0));
+        let (cut_in, cut_out) = gfp.overlap(&yfp).unwrap();
+        assert!((cut_in - 520.0).abs() < 1e-3 && (cut_out - 550.0).abs() < 1e-3);
+        assert_eq!(gfp.overlap(&yfp), yfp.overlap(&gfp));
+        assert_eq!(gfp.overlap(&rfp), None);
+        assert_eq!(gfp.overlap(&open), None);
+        Ok(())
+    }
+
+    #[test]
+    fn legacy_light_source() -> Result<(), Error> {
+        let ome: Ome = read_to_string("tests/legacy_light_source.xml")?.parse()?;
+        let instrument = &ome.instrument[0];
+        assert_eq!(instrument.light_source_group.len(), 2);
+        assert_eq!(instrument.detector.len(), 1);
+        let ome::LightSourceGroup::Laser(laser) = &instrument.light_source_group[0] else {
+            panic!("not a Laser");
+        };
+        assert_eq!(laser.wavelength, Some(488.0));
+        assert_eq!(laser.id, "LightSource:0");
+        assert_eq!(laser.manufacturer.as_deref(), Some("Coherent"));
+        assert_eq!(laser.model.as_deref(), Some("Sapphire"));
+        assert_eq!(laser.power, Some(20.0));
+        let ome::LightSourceGroup::Arc(arc) = &instrument.light_source_group[1] else {
+            panic!("not an Arc");
+        };
+        assert_eq!(arc.id, "LightSource:2");
+
+        let xml = quick_xml::se::to_string_with_root("OME", &ome)?;
+        assert!(!xml.contains("<LightSource "));
+        assert!(
+            xml.contains(r#"<Laser Manufacturer="Coherent" Model="Sapphire" ID="LightSource:0""#)
+        );
+        let again: Ome = xml.parse()?;
+        assert!(ome.semantically_eq(&again));
+        Ok(())
+    }
+
+    #[test]
+    fn xsi_type() -> Result<(), Error> {
+        let ome: Ome = read_to_string("tests/xsi_type.xml")?.parse()?;
+        let light_sources = &ome.instrument[0].light_source_group;
+        assert_eq!(light_sources.len(), 2);
+        let ome::LightSourceGroup::Laser(laser) = &light_sources[0] else {
+            panic!("not a Laser");
+        };
+        assert_eq!(laser.wavelength, Some(561.0));
+        assert!(matches!(light_sources[1], ome::LightSourceGroup::Arc(_)));
+        let shapes = &ome.roi[0].union.as_ref().unwrap().shape_group;
+        assert_eq!(shapes.len(), 3);
+        let ome::ShapeGroup::Rectangle(rectangle) = &shapes[0] else {
+            panic!("not a Rectangle");
+        };
+        assert_eq!(rectangle.width, 64.0);
+        let ome::ShapeGroup::Ellipse(ellipse) = &shapes[1] else {
+            panic!("not an Ellipse");
+        };
+        assert!(ellipse.transform.is_some());
+        assert!(matches!(shapes[2], ome::ShapeGroup::Polygon(_)));
+
+        // written with element names, which parse to the same again
+        let xml = quick_xml::se::to_string_with_root("OME", &ome)?;
+        assert!(!xml.contains("xsi:type"));
+        assert!(xml.contains("<Rectangle "));
+        let again: Ome = xml.parse()?;
+        assert!(ome.semantically_eq(&again));
+        Ok(())
+    }
+
+    #[test]
+    fn enum_equality() -> Result<(), Error> {
+        let ome: Ome = read_to_string("tests/YTL1849A131_2023_05_04__13_36_36.xml")?.parse()?;
+        let pixels = &ome.image[0].pixels;
+        assert_eq!(pixels.dimension_order, ome::PixelsDimensionOrderType::Xyczt);
+        assert_ne!(pixels.dimension_order, ome::PixelsDimensionOrderType::Xyzct);
+        let instrument = &ome.instrument[0];
+        assert!(
+            instrument
+                .detector
+                .iter()
+                .all(|detector| detector.r#type == Some(ome::DetectorType::Other))
+        );
+        assert_eq!(
+            instrument.objective[0].immersion,
+            Some(ome::ObjectiveImmersionType::Other)
+        );
+        assert_ne!(ome::LaserType::Excimer, ome::LaserType::Gas);
+        assert_eq!(
+            ome::ShapeFillRuleType::EvenOdd.clone(),
+            ome::ShapeFillRuleType::EvenOdd
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn timestamp_annotation() -> Result<(), Error> {
+        let timestamp = |value: &str| {
+            format!(
+                "<OME><StructuredAnnotations><TimestampAnnotation ID=\"Annotation:0\">\
+                <Value>{value}</Value></TimestampAnnotation></StructuredAnnotations></OME>"
+            )
+            .parse::<Ome>()
+        };
+        for valid in [
+            "2025-01-29T14:42:42",
+            "2025-01-29T14:42:42Z",
+            "2025-01-29T14:42:42.123-05:00",
+            "-0044-03-15T12:00:00",
+        ] {
+            assert!(timestamp(valid)?.validate().is_empty(), "{valid}");
+        }
+        for invalid in [
+            "",
+            "yesterday",
+            "2025-01-29",
+            "2025-13-29T14:42:42",
+            "2025-01-29 14:42:42",
+            "2025-01-29T14:42:42.",
+            "2025-01-29T14:42:42+0100",
+        ] {
+            // an invalid timestamp does not prevent parsing, but is reported by validate
+            let ome = timestamp(invalid)?;
+            assert!(
+                matches!(
+                    &ome.validate()[..],
+                    [validation::ValidationError::InvalidTimestamp { annotation_id, value }]
+                        if annotation_id == "Annotation:0" && value == invalid
+                ),
+                "{invalid}"
+            );
+        }
+        #[cfg(feature = "chrono")]
+        for lenient in ["2025-01-29 14:42:42", "2025-01-29T15:42:42+0100"] {
+            let ome = timestamp(lenient)?;
+            let Some(ome::StructuredAnnotationsContent::TimestampAnnotation(timestamp)) =
+                ome.annotations().next()
+            else {
+                panic!("not a TimestampAnnotation");
+            };
+            assert_eq!(
+                timestamp.date_time()?.to_utc().to_rfc3339(),
+                "2025-01-29T14:42:42+00:00"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn si_factors() -> Result<(), Error> {
+        assert_eq!(ome::si_prefix_factor("k"), 1e3);
+        assert_eq!(ome::si_prefix_factor("da"), 1e1);
+        assert_eq!(ome::si_prefix_factor(""), 1.0);
+        assert_eq!(ome::si_prefix_factor("µ"), ome::si_prefix_factor("u"));
+        assert!(ome::si_prefix_factor("x").is_nan());
+        macro_rules! assert_factors {
+            ($t:ident { $($variant:ident: $factor:literal),* }) => {
+                $(assert_eq!(ome::$t::$variant.as_si()?, $factor, "{:?}", ome::$t::$variant);)*
+            };
+        }
+        assert_factors!(UnitsElectricPotential {
+            YV: 1e24,
+            ZV: 1e21,
+            EV: 1e18,
+            PV: 1e15,
+            TV: 1e12,
+            GV: 1e9,
+            MV: 1e6,
+            kV: 1e3,
+            hV: 1e2,
+            daV: 1e1,
+            V: 1e0,
+            dV: 1e-1,
+            cV: 1e-2,
+            mV: 1e-3,
+            uV: 1e-6,
+            nV: 1e-9,
+            pV: 1e-12,
+            fV: 1e-15,
+            aV: 1e-18,
+            zV: 1e-21,
+            yV: 1e-24
+        });
+        assert_factors!(UnitsFrequency {
+            YHz: 1e24,
+            ZHz: 1e21,
+            EHz: 1e18,
+            PHz: 1e15,
+            THz: 1e12,
+            GHz: 1e9,
+            MHz: 1e6,
+            kHz: 1e3,
+            hHz: 1e2,
+            daHz: 1e1,
+            Hz: 1e0,
+            dHz: 1e-1,
+            cHz: 1e-2,
+            mHz: 1e-3,
+            uHz: 1e-6,
+            nHz: 1e-9,
+            pHz: 1e-12,
+            fHz: 1e-15,
+            aHz: 1e-18,
+            zHz: 1e-21,
+            yHz: 1e-24
+        });
+        assert_factors!(UnitsLength {
+            Ym: 1e24,
+            Zm: 1e21,
+            Em: 1e18,
+            Pm: 1e15,
+            Tm: 1e12,
+            Gm: 1e9,
+            Mm: 1e6,
+            km: 1e3,
+            hm: 1e2,
+            dam: 1e1,
+            m: 1e0,
+            dm: 1e-1,
+            cm: 1e-2,
+            mm: 1e-3,
+            um: 1e-6,
+            nm: 1e-9,
+            pm: 1e-12,
+            fm: 1e-15,
+            am: 1e-18,
+            zm: 1e-21,
+            ym: 1e-24,
+            A: 1e-10,
+            Thou: 2.54e-5,
+            Li: 5e2,
+            In: 2.54e-2,
+            Ft: 3.05e-1,
+            Yd: 9.14e-1,
+            Mi: 1.609344e3,
+            Ua: 1.496e11,
+            Ly: 9.461e15,
+            Pc: 3.086e16,
+            Pt: 3.52778e-4
+        });
+        assert_factors!(UnitsPower {
+            YW: 1e24,
+            ZW: 1e21,
+            EW: 1e18,
+            PW: 1e15,
+            TW: 1e12,
+            GW: 1e9,
+            MW: 1e6,
+            kW: 1e3,
+            hW: 1e2,
+            daW: 1e1,
+            W: 1e0,
+            dW: 1e-1,
+            cW: 1e-2,
+            mW: 1e-3,
+            uW: 1e-6,
+            nW: 1e-9,
+            pW: 1e-12,
+            fW: 1e-15,
+            aW: 1e-18,
+            zW: 1e-21,
+            yW: 1e-24
+        });
+        assert_factors!(UnitsPressure {
+            YPa: 1e24,
+            ZPa: 1e21,
+            EPa: 1e18,
+            PPa: 1e15,
+            TPa: 1e12,
+            GPa: 1e9,
+            MPa: 1e6,
+            kPa: 1e3,
+            hPa: 1e2,
+            daPa: 1e1,
+            Pa: 1e0,
+            dPa: 1e-1,
+            cPa: 1e-2,
+            mPa: 1e-3,
+            uPa: 1e-6,
+            nPa: 1e-9,
+            pPa: 1e-12,
+            fPa: 1e-15,
+            aPa: 1e-18,
+            zPa: 1e-21,
+            yPa: 1e-24,
+            bar: 1e5,
+            Mbar: 1e11,
+            kbar: 1e8,
+            dbar: 1e4,
+            cbar: 1e3,
+            mbar: 1e2,
+            atm: 1.01325e5,
+            psi: 6.89476e3,
+            Torr: 1.33322e3,
+            mTorr: 1.33322e0,
+            mmHg: 1.33322e2
+        });
+        assert_factors!(UnitsTime {
+            Ys: 1e24,
+            Zs: 1e21,
+            Es: 1e18,
+            Ps: 1e15,
+            Ts: 1e12,
+            Gs: 1e9,
+            Ms: 1e6,
+            ks: 1e3,
+            hs: 1e2,
+            das: 1e1,
+            s: 1e0,
+            ds: 1e-1,
+            cs: 1e-2,
+            ms: 1e-3,
+            us: 1e-6,
+            ns: 1e-9,
+            ps: 1e-12,
+            fs: 1e-15,
+            r#as: 1e-18,
+            zs: 1e-21,
+            ys: 1e-24,
+            min: 6e1,
+            h: 3.6e3,
+            d: 8.64e4
+        });
+        assert!(ome::UnitsLength::Pixel.as_si().is_err());
+        assert!(ome::UnitsLength::ReferenceFrame.as_si().is_err());
+        macro_rules! assert_all_factors {
+            ($($t:ident $(- $without:ident)*),*) => {
+                $(for unit in ome::$t::variants() {
+                    if ![$(ome::$t::$without),*].contains(&unit) {
+                        assert!(unit.as_si()?.is_finite(), "{unit:?}");
+                    }
+                })*
+            };
+        }
+        assert_all_factors!(
+            UnitsElectricPotential,
+            UnitsFrequency,
+            UnitsLength - Pixel - ReferenceFrame,
+            UnitsPower,
+            UnitsPressure,
+            UnitsTime
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn unit_order() {
+        use ome::{UnitsLength, UnitsPressure, UnitsTime};
+        assert!(UnitsLength::nm < UnitsLength::um && UnitsLength::um < UnitsLength::mm);
+        assert!(UnitsLength::mm < UnitsLength::m);
+        assert!(UnitsLength::In > UnitsLength::cm);
+        assert!(UnitsTime::ms < UnitsTime::min);
+        assert!(UnitsPressure::atm > UnitsPressure::kPa);
+        assert_eq!(
+            UnitsLength::um.partial_cmp(&UnitsLength::um),
+            Some(std::cmp::Ordering::Equal)
+        );
+        assert_eq!(UnitsLength::Pixel.partial_cmp(&UnitsLength::m), None);
+    }
+
+    #[test]
+    fn humanize() {
+        use ome::{UnitsLength, UnitsTime};
+        assert_eq!(UnitsLength::humanize(5e-7), (500.0, UnitsLength::nm));
+        assert_eq!(UnitsLength::humanize(-0.25), (-250.0, UnitsLength::mm));
+        assert_eq!(UnitsLength::humanize(1.0), (1.0, UnitsLength::m));
+        assert_eq!(UnitsLength::humanize(0.0), (0.0, UnitsLength::m));
+        let (value, unit) = UnitsLength::humanize(1e-30);
+        assert!((value - 1e-6).abs() < 1e-15 && unit == UnitsLength::ym);
+        let (value, unit) = UnitsLength::humanize(6.5e-7);
+        assert!((value - 650.0).abs() < 1e-9 && unit == UnitsLength::nm);
+        assert_eq!(UnitsTime::humanize(1500.0), (25.0, UnitsTime::min));
+        assert_eq!(UnitsTime::humanize(30.0), (30.0, UnitsTime::s));
+        assert_eq!(UnitsTime::humanize(0.02), (20.0, UnitsTime::ms));
+        assert_eq!(UnitsTime::humanize(7200.0), (2.0, UnitsTime::h));
+        assert_eq!(UnitsTime::humanize(172800.0), (2.0, UnitsTime::d));
+        assert!(UnitsTime::humanize(f64::NAN).0.is_nan());
+    }
+
+    #[test]
+    fn error_position() {
+        let xml =
+            "<OME>\n  <Image ID=\"Image:0\">\n    <Pixels ID=\"Pixels:0\">\n  </Image>\n</OME>";
+        let err = xml.parse::<Ome>().unwrap_err();
+        assert!(matches!(
+            &err,
+            Error::Parse { line: 4, column: 3, snippet, source: DeError::InvalidXml(_) }
+                if snippet == "</Image>"
+        ));
+        assert!(err.to_string().ends_with("at line 4, column 3: </Image>"));
+        let xml = "<OME>\n<Image ID=\"Image:0\">\n<Pixels ID=\"Pixels:0\" DimensionOrder=\"XYZCT\" \
+            Type=\"uint8\" SizeX=\"a\" SizeY=\"1\" SizeZ=\"1\" SizeC=\"1\" SizeT=\"1\"/>\n</Image>\n</OME>";
+        let err = xml.parse::<Ome>().unwrap_err();
+        assert!(matches!(err, Error::Parse { line: 3, .. }), "{err}");
+        assert!(err.to_string().starts_with("invalid type: string \"a\""));
+    }
+
+    #[test]
+    fn from_bytes() -> Result<(), Error> {
+        let text = read_to_string("tests/test.xml")?;
+        let ome: Ome = text.parse()?;
+        let mut bom = b"\xef\xbb\xbf".to_vec();
+        bom.extend(text.as_bytes());
+        assert!(Ome::from_bytes(&bom)?.semantically_eq(&ome));
+        let utf16le = text
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes)
+            .collect::<Vec<_>>();
+        assert!(Ome::from_bytes(&utf16le)?.semantically_eq(&ome));
+        let utf16be = "\u{feff}"
+            .encode_utf16()
+            .chain(text.encode_utf16())
+            .flat_map(u16::to_be_bytes)
+            .collect::<Vec<_>>();
+        assert!(Ome::from_bytes(&utf16be)?.semantically_eq(&ome));
+        assert!(matches!(
+            Ome::from_bytes(b"<OME>\xff</OME>"),
+            Err(Error::Encoding("UTF-8"))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn from_path() -> Result<(), Error> {
+        let ome = Ome::from_path("tests/test.xml")?;
+        assert_eq!(ome.image[0].id, "Image:0");
+        let err = Ome::from_path("tests/missing.xml").unwrap_err();
+        assert!(matches!(err, Error::Path { .. }));
+        assert!(err.to_string().starts_with("tests/missing.xml: "));
+        Ok(())
+    }
+
+    #[test]
+    fn model_magnification() -> Result<(), Error> {
+        let magnification = |model: &str| -> Result<Option<f32>, Error> {
+            let objective: ome::Objective =
+                from_str(&format!(r#"<Objective ID="Objective:0" Model="{model}"/>"#))?;
+            Ok(objective.parse_model_magnification())
+        };
+        assert_eq!(
+            magnification("Plan-Apochromat 63x/1.4 Oil DIC M27")?,
+            Some(63.0)
+        );
+        assert_eq!(
+            magnification("HC PL APO 20x/0.75 IMM CORR CS2")?,
+            Some(20.0)
+        );
+        assert_eq!(magnification("CFI Plan Apo Lambda 60X Oil")?, Some(60.0));
+        assert_eq!(magnification("EC Plan-Neofluar 2.5x/0.075")?, Some(2.5));
+        assert_eq!(magnification("UPLSAPO100XO")?, None);
+        assert_eq!(magnification("2x2 binning")?, None);
+        assert_eq!(magnification("Objective 0x1F")?, None);
+        let objective: ome::Objective = from_str(
+            r#"<Objective ID="Objective:0" Model="Plan-Apochromat 63x/1.4" NominalMagnification="40"/>"#,
+        )?;
+        assert_eq!(objective.parse_model_magnification(), Some(40.0));
+        Ok(())
+    }
+
+    #[test]
+    fn effective_na() -> Result<(), Error> {
+        let objective: ome::Objective =
+            from_str(r#"<Objective ID="Objective:0" LensNA="1.4" Immersion="Oil"/>"#)?;
+        let settings = |medium: &str| -> Result<ome::ObjectiveSettings, Error> {
+            from_str(&format!(
+                r#"<ObjectiveSettings ID="Objective:0" {medium}/>"#
+            ))
+        };
+        let effective_na = |medium| -> Result<Option<f32>, Error> {
+            Ok(settings(medium)?.effective_na(&objective))
+        };
+        assert_eq!(effective_na(r#"Medium="Oil""#)?, Some(1.4));
+        let na = effective_na(r#"Medium="Water""#)?.unwrap();
+        assert!((na - 1.4 * 1.333 / 1.515).abs() < 1e-6);
+        let na = effective_na(r#"Medium="Air" RefractiveIndex="1.45""#)?.unwrap();
+        assert!((na - 1.4 * 1.45 / 1.515).abs() < 1e-6);
+        assert_eq!(effective_na("")?, None);
+        assert_eq!(effective_na(r#"Medium="Other""#)?, None);
+
+        let dry: ome::Objective =
+            from_str(r#"<Objective ID="Objective:1" LensNA="0.95" Immersion="Air"/>"#)?;
+        assert_eq!(settings(r#"Medium="Oil""#)?.effective_na(&dry), Some(0.95));
+        let unknown: ome::Objective = from_str(r#"<Objective ID="Objective:2" LensNA="0.95"/>"#)?;
+        assert_eq!(settings(r#"Medium="Oil""#)?.effective_na(&unknown), None);
+        let no_na: ome::Objective = from_str(r#"<Objective ID="Objective:3" Immersion="Oil"/>"#)?;
+        assert_eq!(settings(r#"Medium="Oil""#)?.effective_na(&no_na), None);
+        Ok(())
+    }
+
+    #[test]
+    fn numpy_dtype() -> Result<(), Error> {
+        assert_eq!(ome::PixelType::Uint16.numpy_dtype(false)?, "uint16");
+        assert_eq!(ome::PixelType::Uint16.numpy_dtype(true)?, ">u2");
+        assert_eq!(ome::PixelType::Uint8.numpy_dtype(true)?, "uint8");
+        assert_eq!(ome::PixelType::Double.numpy_dtype(false)?, "float64");
+        assert!(matches!(
+            ome::PixelType::Bit.numpy_dtype(false),
+            Err(Error::NoNumpyDtype(t)) if t == "bit"
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn dimensionality() -> Result<(), Error> {
+        for (file, z_stack, time_series, multichannel, dimensionality) in [
+            ("test.xml", false, false, false, 2),
+            ("YTL378_JF552.xml", false, false, true, 3),
+            ("beads_2023_05_04__19_00_22.xml", true, false, true, 4),
+            ("Experiment-2029.xml", false, true, true, 4),
+            ("1xp53-01-AP1.xml", true, true, false, 4),
+            ("zct.ome.xml", true, true, true, 5),
+        ] {
+            let ome = Ome::from_path(format!("tests/{file}"))?;
+            let pixels = &ome.image[0].pixels;
+            assert_eq!(pixels.has_z_stack(), z_stack, "{file}");
+            assert_eq!(pixels.has_time_series(), time_series, "{file}");
+            assert_eq!(pixels.has_multichannel(), multichannel, "{file}");
+            assert_eq!(pixels.is_2d(), !z_stack && !time_series, "{file}");
+            assert_eq!(pixels.dimensionality(), dimensionality, "{file}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn max_value() -> Result<(), Error> {
+        let mut ome: Ome = read_to_string("tests/test.xml")?.parse()?;
+        let pixels = &mut ome.image[0].pixels;
+        pixels.r#type = ome::PixelType::Uint16;
+        pixels.significant_bits = None;
+        assert_eq!(pixels.max_value(), Some(65535));
+        pixels.significant_bits = Some(12);
+        assert_eq!(pixels.max_value(), Some(4095));
+        pixels.significant_bits = Some(40);
+        assert_eq!(pixels.max_value(), Some(65535));
+        pixels.significant_bits = None;
+        pixels.r#type = ome::PixelType::Int8;
+        assert_eq!(pixels.max_value(), Some(127));
+        pixels.r#type = ome::PixelType::Uint32;
+        assert_eq!(pixels.max_value(), Some(u32::MAX as u64));
+        pixels.r#type = ome::PixelType::Bit;
+        assert_eq!(pixels.max_value(), Some(1));
+        pixels.r#type = ome::PixelType::Float;
+        assert_eq!(pixels.max_value(), None);
+        Ok(())
+    }
+
+    #[test]
+    fn shape() -> Result<(), Error> {
+        let ome: Ome = read_to_string("tests/zct.ome.xml")?.parse()?;
+        let pixels = &ome.image[0].pixels;
+        let [x, y, z, c, t] = [
+            pixels.size_x,
+            pixels.size_y,
+            pixels.size_z,
+            pixels.size_c,
+            pixels.size_t,
+        ]
+        .map(|size| size as usize);
+        assert_eq!(pixels.shape("TCZYX")?, [t, c, z, y, x]);
+        assert_eq!(pixels.shape("yx")?, [y, x]);
+        assert!(matches!(
+            pixels.shape("TCZYXT"),
+            Err(Error::InvalidAxisOrder(_))
+        ));
+        assert!(matches!(
+            pixels.shape("TCQ"),
+            Err(Error::InvalidAxisOrder(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn unknown_instrument_children() -> Result<(), Error> {
+        let xml = r#"<OME xmlns:Acme="http://example.com/acme">
+            <Instrument ID="Instrument:0">
+                <Acme:Stage Speed="fast"/>
+                <Laser ID="LightSource:0" Wavelength="488"/>
+                <Acme:Shutter/>
+                <Arc ID="LightSource:1"/>
+                <Detector ID="Detector:0"/>
+                <Acme:Camera><Acme:Mode>fast</Acme:Mode></Acme:Camera>
+            </Instrument>
+        </OME>"#;
+        let ome: Ome = xml.parse()?;
+        let instrument = &ome.instrument[0];
+        assert_eq!(instrument.light_source_group.len(), 2);
+        assert_eq!(instrument.detector.len(), 1);
+        let preserved = Ome::from_str_preserving(xml)?;
+        let elements = preserved
+            .unknown_xml
+            .iter()
+            .filter(|unknown| matches!(unknown, preserve::UnknownXml::Element { .. }));
+        assert_eq!(elements.count(), 3);
+        let written = preserved.to_xml_preserving()?;
+        assert!(written.contains(r#"<Acme:Stage Speed="fast"/>"#));
+        assert!(written.contains("<Acme:Camera><Acme:Mode>fast</Acme:Mode></Acme:Camera>"));
+
+        let missing_id = r#"<OME><Instrument ID="Instrument:0"><Laser/></Instrument></OME>"#;
+        assert!(missing_id.parse::<Ome>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn experiment_type() -> Result<(), Error> {
+        let experiment: ome::Experiment =
+            from_str(r#"<Experiment ID="Experiment:0" Type="FRET TimeLapse"/>"#)?;
+        let experiment_type = experiment.r#type.unwrap();
+        assert_eq!(experiment_type.len(), 2);
+        let mut n = 0;
+        for item in &experiment_type {
+            assert!(matches!(
+                item,
+                ome::ExperimentItemType::Fret | ome::ExperimentItemType::TimeLapse
+            ));
+            n += 1;
+        }
+        assert_eq!(n, 2);
+        Ok(())
+    }
+
+    #[test]
+    fn semantically_eq() -> Result<(), Error> {
+        let ome: Ome = read_to_string("tests/YTL378_JF552.xml")?.parse()?;
+        let mut shuffled = ome.clone();
+        shuffled.image.reverse();
+        shuffled.instrument.swap(0, 2);
+        assert!(ome.semantically_eq(&shuffled));
+
+        let mut a = ome.clone();
+        let mut b = ome.clone();
+        for id in ["Annotation:0", "Annotation:1"] {
+            a.image[0]
+                .annotation_ref
+                .push(ome::AnnotationRef { id: id.to_string() });
+        }
+        b.image[0].annotation_ref = a.image[0].annotation_ref.clone();
+        b.image[0].annotation_ref.reverse();
+        assert!(a.semantically_eq(&b));
+
+        shuffled.image[0].name = Some("renamed".to_string());
+        assert!(!ome.semantically_eq(&shuffled));
+
+        let ome: Ome = read_to_string("tests/annotations.xml")?.parse()?;
+        let mut shuffled = ome.clone();
+        shuffled
+            .structured_annotations
+            .as_mut()
+            .unwrap()
+            .content
+            .reverse();
+        assert!(ome.semantically_eq(&shuffled));
+
+        let xml = |light_sources: &str, refs: &str, samples: &str| {
+            format!(
+                r#"<OME>
+                    <Instrument ID="Instrument:0">{light_sources}</Instrument>
+                    <Image ID="Image:0">
+                        <Pixels ID="Pixels:0" DimensionOrder="XYZCT" Type="uint8" SizeX="1" SizeY="1" SizeZ="1" SizeC="1" SizeT="1">
+                            <Channel ID="Channel:0:0">{refs}</Channel>
+                            <MetadataOnly/>
+                            <Plane TheZ="0" TheC="0" TheT="0">{refs}</Plane>
+                        </Pixels>
+                    </Image>
+                    <Plate ID="Plate:0"><Well ID="Well:0" Row="0" Column="0">{samples}</Well></Plate>
+                </OME>"#
+            )
+        };
+        let laser = r#"<Laser ID="LightSource:0"/>"#;
+        let arc = r#"<Arc ID="LightSource:1"/>"#;
+        let refs = [0, 1].map(|i| format!(r#"<AnnotationRef ID="Annotation:{i}"/>"#));
+        let samples = [0, 1].map(|i| format!(r#"<WellSample ID="WellSample:{i}" Index="{i}"/>"#));
+        let a: Ome = xml(
+            &(laser.to_string() + arc),
+            &refs.concat(),
+            &samples.concat(),
+        )
+        .parse()?;
+        let b: Ome = xml(
+            &(arc.to_string() + laser),
+            &(refs[1].clone() + &refs[0]),
+            &(samples[1].clone() + &samples[0]),
+        )
+        .parse()?;
+        assert!(a.semantically_eq(&b));
+        Ok(())
+    }
+
+    #[test]
+    fn deprecated_namespace() -> Result<(), Error> {
+        let ome: Ome = read_to_string("tests/test.xml")?.parse()?;
+        let old: Ome = read_to_string("tests/test_2013-06.xml")?.parse()?;
+        assert!(ome.original_namespace.is_none());
+        assert_eq!(
+            old.original_namespace.as_deref(),
+            Some("http://www.openmicroscopy.org/Schemas/OME/2013-06")
+        );
+        assert!(ome.semantically_eq(&old));
+        assert!(ome.validate().is_empty());
+        assert!(matches!(
+            old.validate()[..],
+            [validation::ValidationError::DeprecatedSchema(_)]
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn microscope_type() -> Result<(), Error> {
+        let ome: Ome = read_to_string("tests/Experiment-2029.xml")?.parse()?;
+        let instrument = &ome.instrument[0];
+        let microscope = instrument.microscope.as_ref().unwrap();
+        assert!(microscope.is_inverted());
+        assert!(!microscope.is_upright());
+        assert_eq!(microscope.microscope_type_str(), "Inverted");
+        assert_eq!(ome::MicroscopeType::Upright.to_string(), "Upright");
+        assert!(instrument.primary_detector_type().is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn lenient_bools() -> Result<(), Error> {
+        let pixels = |interleaved: &str| {
+            from_str::<ome::Pixels>(&format!(
+                r#"<Pixels ID="Pixels:0" DimensionOrder="XYCZT" Type="uint8" SizeX="1" SizeY="1"
+                    SizeZ="1" SizeC="1" SizeT="1" Interleaved="{interleaved}"/>"#
+            ))
+        };
+        for (text, value) in [
+            ("true", true),
+            ("false", false),
+            ("1", true),
+            ("0", false),
+            ("True", true),
+            ("False", false),
+        ] {
+            assert_eq!(pixels(text)?.interleaved, Some(value), "{text}");
+        }
+        assert!(pixels("yes").is_err());
+        assert!(pixels("2").is_err());
+        let ome: Ome = r#"<OME><StructuredAnnotations>
+            <BooleanAnnotation ID="Annotation:0"><Value>1</Value></BooleanAnnotation>
+        </StructuredAnnotations></OME>"#
+            .parse()?;
+        assert!(matches!(
+            &ome.structured_annotations.unwrap().content[0],
+            ome::StructuredAnnotationsContent::BooleanAnnotation(boolean) if boolean.value
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn map_value_whitespace() -> Result<(), Error> {
+        let xml = r#"<OME>
+            <StructuredAnnotations>
+                <MapAnnotation ID="Annotation:0">
+                    <Value>
+                        <M K="padded">  two  words </M>
+                        <M K="lines">first
+second
+</M>
+                        <M K="blank">  </M>
+                    </Value>
+                </MapAnnotation>
+            </StructuredAnnotations>
+        </OME>"#;
+        let values = |ome: &Ome| {
+            let Some(ome::StructuredAnnotationsContent::MapAnnotation(annotation)) =
+                ome.annotations().next()
+            else {
+                panic!("no map annotation");
+            };
+            annotation
+                .value
+                .pairs()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect::<Vec<_>>()
+        };
+        let ome: Ome = xml.parse()?;
+        let parsed = values(&ome);
+        assert_eq!(parsed[0].1, "  two  words ");
+        assert_eq!(parsed[1].1, "first\nsecond\n");
+        assert_eq!(parsed[2].1, "");
+        let pretty = XmlOptions {
+            pretty: true,
+            write_default_units: false,
+        };
+        for xml in [
+            ome.to_xml()?,
+            ome.to_xml_with(pretty)?,
+            ome.to_compact_xml()?,
+        ] {
+            assert_eq!(values(&xml.parse()?), parsed);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn stage_drift() -> Result<(), Error> {
+        // five time points, given out of order, drifting 0.5 µm in x per time point
+        let planes = [3, 0, 4, 1, 2]
+            .map(|t| {
+                from_str::<ome::Plane>(&format!(
+                    r#"<Plane TheZ="0" TheC="0" TheT="{t}" PositionX="{}" PositionY="10"
+                        PositionZ="1" PositionZUnit="mm"/>"#,
+                    100.0 + 0.5 * t as f32
+                ))
+            })
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()?;
+        let trajectory = ome::position_trajectory_m(&planes)?;
+        assert_eq!(trajectory.len(), 5);
+        for (t, [x, y, z]) in trajectory.into_iter().enumerate() {
+            assert!((x - (100.0 + 0.5 * t as f64) * 1e-6).abs() < 1e-12);
+            assert!((y - 10e-6).abs() < 1e-12);
+            assert!((z - 1e-3).abs() < 1e-12);
+        }
+        assert!((ome::total_drift_m(&planes)? - 2e-6).abs() < 1e-12);
+        let mut planes = planes;
+        // the plane at t 2 has no PositionX and is skipped
+        planes[4].position_x = None;
+        let trajectory = ome::position_trajectory_m(&planes)?;
+        assert_eq!(trajectory.len(), 4);
+        assert!((trajectory[2][0] - 101.5e-6).abs() < 1e-12);
+        assert!((ome::total_drift_m(&planes)? - 2e-6).abs() < 1e-12);
+        planes.iter_mut().for_each(|plane| plane.position_y = None);
+        assert!(
+            ome::position_trajectory_m(&planes)?
+                .iter()
+                .all(|[_, y, _]| *y == 0.0)
+        );
+        planes[2].position_z_unit = ome::UnitsLength::Pixel;
+        assert!(matches!(
+            ome::total_drift_m(&planes),
+            Err(Error::SizeOfUnknown(_))
+        ));
+        Ok(())
+    }
 }
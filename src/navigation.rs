@@ -0,0 +1,1202 @@
+use crate::error::Error;
+use crate::ids::IdSite;
+use crate::ome::{
+    AnnotationRef, Arc, Channel, ChannelAcquisitionModeType, ChannelContrastMethodType,
+    ChannelIlluminationType, Convert, Dataset, DatasetRef, Dichroic, DichroicRef, Experiment,
+    ExperimentRef, Experimenter, ExperimenterGroup, ExperimenterGroupRef, ExperimenterRef,
+    Filament, Filter, FilterRef, FilterSet, FilterSetRef, Folder, FolderRef,
+    GenericExcitationSource, Image, ImageRef, Instrument, InstrumentRef, Laser, LightEmittingDiode,
+    LightSourceGroup, MicrobeamManipulation, NamingConventionType, Ome, Pixels, Plane, Plate,
+    PlateRef, Ref, Roi, RoiRef, Screen, StructuredAnnotationsContent, UnitsLength, Well,
+    WellSample,
+};
+use crate::validation::{IdKind, ValidationError};
+use std::collections::{HashMap, HashSet};
+
+/// a folder with its resolved subfolders, see [Ome::folder_tree]
+#[derive(Clone, Debug)]
+pub struct FolderNode<'a> {
+    /// None for the root, which holds all folders not contained in another folder
+    pub folder: Option<&'a Folder>,
+    pub children: Vec<FolderNode<'a>>,
+}
+
+impl FolderNode<'_> {
+    /// the number of folders in this tree, not counting the root
+    pub fn len(&self) -> usize {
+        self.children.iter().map(|child| 1 + child.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.children.is_empty()
+    }
+}
+
+/// typed lookups of referenced elements, so that a reference can only be resolved to the kind of
+/// element it refers to
+macro_rules! impl_resolve {
+    ($t:ty { $($name:ident($r:ty) -> $target:ty: $field:ident $(,)?)* }) => {
+        impl $t {
+            $(
+                pub fn $name(&self, reference: &$r) -> Option<&$target> {
+                    reference.resolve(&self.$field)
+                }
+            )*
+        }
+    };
+}
+
+impl_resolve!(Ome {
+    resolve_dataset(DatasetRef) -> Dataset: dataset,
+    resolve_experiment(ExperimentRef) -> Experiment: experiment,
+    resolve_experimenter(ExperimenterRef) -> Experimenter: experimenter,
+    resolve_experimenter_group(ExperimenterGroupRef) -> ExperimenterGroup: experimenter_group,
+    resolve_folder(FolderRef) -> Folder: folder,
+    resolve_image(ImageRef) -> Image: image,
+    resolve_instrument(InstrumentRef) -> Instrument: instrument,
+    resolve_plate(PlateRef) -> Plate: plate,
+    resolve_roi(RoiRef) -> Roi: roi,
+});
+
+impl_resolve!(Instrument {
+    resolve_dichroic(DichroicRef) -> Dichroic: dichroic,
+    resolve_filter(FilterRef) -> Filter: filter,
+    resolve_filter_set(FilterSetRef) -> FilterSet: filter_set,
+});
+
+/// the light sources of an instrument of one kind
+macro_rules! impl_light_sources {
+    ($($name:ident -> $variant:ident $(,)?)*) => {
+        impl Instrument {
+            $(
+                pub fn $name(&self) -> Vec<&$variant> {
+                    self.light_source_group
+                        .iter()
+                        .filter_map(|light_source| match light_source {
+                            LightSourceGroup::$variant(light_source) => Some(light_source),
+                            _ => None,
+                        })
+                        .collect()
+                }
+            )*
+        }
+    };
+}
+
+impl_light_sources!(
+    lasers -> Laser,
+    arcs -> Arc,
+    filaments -> Filament,
+    leds -> LightEmittingDiode,
+    generic_excitation_sources -> GenericExcitationSource,
+);
+
+impl Instrument {
+    /// the light sources of which the kind is kind, like "Laser" or "LightEmittingDiode", see
+    /// [LightSourceGroup::type_name]
+    pub fn light_sources_of_kind(&self, kind: &str) -> Vec<&LightSourceGroup> {
+        self.light_source_group
+            .iter()
+            .filter(|light_source| light_source.type_name() == kind)
+            .collect()
+    }
+}
+
+impl Pixels {
+    /// the Plane with the metadata of the plane at z, c, t
+    pub fn plane(&self, z: i32, c: i32, t: i32) -> Option<&Plane> {
+        self.plane
+            .iter()
+            .find(|plane| (plane.the_z, plane.the_c, plane.the_t) == (z, c, t))
+    }
+
+    /// the IFD in the TIFF file which holds the plane at z, c, t, a TiffData without PlaneCount
+    /// holds one plane, unless it is the only one and starts at IFD 0 and the first plane, as
+    /// when it has no attributes, then it holds all planes, a TiffData which starts outside the
+    /// sizes, or of which the IFD would be too large, holds no planes
+    pub fn ifd(&self, z: i32, c: i32, t: i32) -> Option<i32> {
+        let index = self.zct_to_index(z, c, t)?;
+        let single = self.tiff_data.len() == 1;
+        self.tiff_data.iter().find_map(|tiff_data| {
+            let first =
+                self.zct_to_index(tiff_data.first_z, tiff_data.first_c, tiff_data.first_t)?;
+            let count = match tiff_data.plane_count {
+                Some(count) => count.max(0) as usize,
+                None if single && tiff_data.ifd == 0 && first == 0 => usize::MAX,
+                None => 1,
+            };
+            if !(first..first.saturating_add(count)).contains(&index) {
+                return None;
+            }
+            tiff_data
+                .ifd
+                .checked_add(i32::try_from(index - first).ok()?)
+        })
+    }
+}
+
+/// a row or column index in a naming convention, from 0: A, B, ..., Z, AA, AB, ... or 1, 2, ...
+fn well_index_name(index: i32, convention: &NamingConventionType) -> String {
+    match convention {
+        NamingConventionType::Number => (index + 1).to_string(),
+        NamingConventionType::Letter => {
+            let mut name = Vec::new();
+            let mut index = index.max(0) as u32 + 1;
+            while index > 0 {
+                index -= 1;
+                name.push(char::from(b'A' + (index % 26) as u8));
+                index /= 26;
+            }
+            name.iter().rev().collect()
+        }
+    }
+}
+
+impl Plate {
+    /// the name of the well at row, column, like "B7", rows are named by letter and columns by
+    /// number unless the naming conventions of the plate say otherwise
+    pub fn well_name(&self, row: i32, column: i32) -> String {
+        let row_convention = self
+            .row_naming_convention
+            .clone()
+            .unwrap_or(NamingConventionType::Letter);
+        let column_convention = self
+            .column_naming_convention
+            .clone()
+            .unwrap_or(NamingConventionType::Number);
+        format!(
+            "{}{}",
+            well_index_name(row, &row_convention),
+            well_index_name(column, &column_convention)
+        )
+    }
+
+    /// the well with this name, see [Plate::well_name], ignoring case
+    pub fn well_by_name(&self, name: &str) -> Option<&Well> {
+        self.well.iter().find(|well| {
+            self.well_name(well.row, well.column)
+                .eq_ignore_ascii_case(name)
+        })
+    }
+
+    /// the x and y of the origin of the wells in m, None if either is not known or its unit has
+    /// no size in m
+    pub fn physical_dimensions_m(&self) -> Option<(f64, f64)> {
+        let m = |value: Option<f32>, unit: &UnitsLength| Some(value? as f64 * unit.as_si().ok()?);
+        Some((
+            m(self.well_origin_x, &self.well_origin_x_unit)?,
+            m(self.well_origin_y, &self.well_origin_y_unit)?,
+        ))
+    }
+
+    /// the distance between the centres of neighbouring wells in x and y in m, the centre of a
+    /// well is estimated as the mean position of its well samples, and the pitch as the change of
+    /// the centres per column or row, fitted by least squares, None if the positions of wells in
+    /// fewer than two columns or rows are known
+    pub fn well_pitch_m(&self) -> Option<(f64, f64)> {
+        type Position = fn(&WellSample) -> (Option<f32>, &UnitsLength);
+        let pitch = |index: fn(&Well) -> i32, position: Position| {
+            let centres = self
+                .well
+                .iter()
+                .filter_map(|well| {
+                    let positions = well
+                        .well_sample
+                        .iter()
+                        .filter_map(|sample| {
+                            let (value, unit) = position(sample);
+                            Some(value? as f64 * unit.as_si().ok()?)
+                        })
+                        .collect::<Vec<_>>();
+                    (!positions.is_empty()).then(|| {
+                        let centre = positions.iter().sum::<f64>() / positions.len() as f64;
+                        (index(well) as f64, centre)
+                    })
+                })
+                .collect::<Vec<_>>();
+            let n = centres.len() as f64;
+            let mean_index = centres.iter().map(|(index, _)| index).sum::<f64>() / n;
+            let mean_centre = centres.iter().map(|(_, centre)| centre).sum::<f64>() / n;
+            let (covariance, variance) =
+                centres
+                    .iter()
+                    .fold((0.0, 0.0), |(covariance, variance), (index, centre)| {
+                        let d = index - mean_index;
+                        (covariance + d * (centre - mean_centre), variance + d * d)
+                    });
+            (variance > 0.0).then(|| (covariance / variance).abs())
+        };
+        Some((
+            pitch(
+                |well| well.column,
+                |sample| (sample.position_x, &sample.position_x_unit),
+            )?,
+            pitch(
+                |well| well.row,
+                |sample| (sample.position_y, &sample.position_y_unit),
+            )?,
+        ))
+    }
+}
+
+impl Screen {
+    /// the plates in this screen, references to plates which do not exist are skipped
+    pub fn plates<'a>(&self, ome: &'a Ome) -> Vec<&'a Plate> {
+        self.plate_ref
+            .iter()
+            .filter_map(|plate_ref| ome.resolve_plate(plate_ref))
+            .collect()
+    }
+}
+
+impl Channel {
+    /// the filter set of this channel, in the instrument of the image with this channel, or in
+    /// any instrument if that image has no InstrumentRef
+    pub fn filter_set<'a>(&self, ome: &'a Ome) -> Option<&'a FilterSet> {
+        let filter_set_ref = self.filter_set_ref.as_ref()?;
+        let image = ome.image.iter().find(|image| {
+            image
+                .pixels
+                .channel
+                .iter()
+                .any(|channel| std::ptr::eq(channel, self) || channel.id == self.id)
+        })?;
+        match &image.instrument_ref {
+            Some(instrument_ref) => ome
+                .resolve_instrument(instrument_ref)?
+                .resolve_filter_set(filter_set_ref),
+            None => ome
+                .instrument
+                .iter()
+                .find_map(|instrument| instrument.resolve_filter_set(filter_set_ref)),
+        }
+    }
+}
+
+impl Image {
+    /// the experiment of this image, None if it has no ExperimentRef or the experiment does not
+    /// exist
+    pub fn experiment<'a>(&self, ome: &'a Ome) -> Option<&'a Experiment> {
+        ome.resolve_experiment(self.experiment_ref.as_ref()?)
+    }
+
+    /// the microbeam manipulations of this image, in any of the experiments, references to
+    /// manipulations which do not exist are skipped
+    pub fn microbeam_manipulations<'a>(&self, ome: &'a Ome) -> Vec<&'a MicrobeamManipulation> {
+        self.microbeam_manipulation_ref
+            .iter()
+            .filter_map(|manipulation_ref| {
+                ome.experiment.iter().find_map(|experiment| {
+                    manipulation_ref.resolve(&experiment.microbeam_manipulation)
+                })
+            })
+            .collect()
+    }
+}
+
+impl MicrobeamManipulation {
+    /// the ROIs which were manipulated, references to ROIs which do not exist are skipped
+    pub fn rois<'a>(&self, ome: &'a Ome) -> Vec<&'a Roi> {
+        self.roi_ref
+            .iter()
+            .filter_map(|roi_ref| ome.resolve_roi(roi_ref))
+            .collect()
+    }
+}
+
+impl WellSample {
+    /// the image of this sample, None if it has no ImageRef or the image does not exist
+    pub fn image<'a>(&self, ome: &'a Ome) -> Option<&'a Image> {
+        ome.resolve_image(self.image_ref.as_ref()?)
+    }
+}
+
+impl Well {
+    /// the samples of the well with their images, samples without an image are skipped
+    pub fn images_for_well<'a>(well: &'a Well, ome: &'a Ome) -> Vec<(&'a WellSample, &'a Image)> {
+        well.well_sample
+            .iter()
+            .filter_map(|sample| Some((sample, sample.image(ome)?)))
+            .collect()
+    }
+}
+
+struct FolderTreeBuilder<'a> {
+    folders: HashMap<&'a str, &'a Folder>,
+    path: Vec<&'a str>,
+    visited: HashSet<&'a str>,
+}
+
+impl<'a> FolderTreeBuilder<'a> {
+    fn build(&mut self, folder: &'a Folder) -> Result<FolderNode<'a>, Error> {
+        if self.path.contains(&folder.id.as_str()) {
+            return Err(Error::FolderCycle(folder.id.clone()));
+        }
+        self.path.push(&folder.id);
+        self.visited.insert(&folder.id);
+        let children = folder
+            .folder_ref
+            .iter()
+            .map(|folder_ref| {
+                let child = *self
+                    .folders
+                    .get(folder_ref.id())
+                    .ok_or_else(|| Error::UnresolvedReference(folder_ref.id().to_string()))?;
+                self.build(child)
+            })
+            .collect::<Result<_, _>>()?;
+        self.path.pop();
+        Ok(FolderNode {
+            folder: Some(folder),
+            children,
+        })
+    }
+}
+
+impl Ome {
+    /// the structured annotation which reference refers to, in any of the StructuredAnnotations
+    pub fn resolve_annotation(
+        &self,
+        reference: &AnnotationRef,
+    ) -> Option<&StructuredAnnotationsContent> {
+        self.structured_annotations
+            .iter()
+            .find_map(|structured_annotations| reference.resolve(&structured_annotations.content))
+    }
+
+    /// the images which refer to the experiment with this ID, in document order
+    pub fn images_in_experiment(&self, experiment_id: &str) -> Vec<&Image> {
+        self.image
+            .iter()
+            .filter(|image| {
+                image
+                    .experiment_ref
+                    .as_ref()
+                    .is_some_and(|experiment_ref| experiment_ref.id() == experiment_id)
+            })
+            .collect()
+    }
+
+    /// all planes of all images, each together with its image, in the order of the images, a
+    /// plane with the same TheZ, TheC and TheT as an earlier plane of its image is left out, see
+    /// [Ome::validate] for the planes that are missing or occur more than once
+    pub fn iter_planes(&self) -> impl Iterator<Item = (&Image, &Plane)> {
+        self.image.iter().flat_map(|image| {
+            let mut seen = HashSet::new();
+            image
+                .pixels
+                .plane
+                .iter()
+                .filter(move |plane| seen.insert((plane.the_z, plane.the_c, plane.the_t)))
+                .map(move |plane| (image, plane))
+        })
+    }
+
+    /// the IDs which references of this kind refer to, anywhere in the document
+    fn referenced_ids(&self, kind: IdKind) -> HashSet<String> {
+        let mut ids = HashSet::new();
+        self.visit_ids(|site, id| {
+            if site == IdSite::Reference(kind) {
+                ids.insert(id.clone());
+            }
+        });
+        ids
+    }
+
+    /// the IDs of all ExperimenterRefs, including group leaders, anywhere in the document
+    pub fn all_experimenter_ids(&self) -> HashSet<String> {
+        self.referenced_ids(IdKind::Experimenter)
+    }
+
+    /// the IDs of all InstrumentRefs in the document
+    pub fn all_instrument_ids(&self) -> HashSet<String> {
+        self.referenced_ids(IdKind::Instrument)
+    }
+
+    /// the IDs that ExperimenterRefs refer to but for which there is no Experimenter, sorted,
+    /// see [Ome::validate_references] for the references of all kinds
+    pub fn undefined_experimenter_ids(&self) -> Vec<String> {
+        let mut undefined: Vec<String> = self
+            .validate_references()
+            .into_iter()
+            .filter_map(|error| match error {
+                ValidationError::UnresolvedReference {
+                    kind: IdKind::Experimenter,
+                    id,
+                    ..
+                } => Some(id),
+                _ => None,
+            })
+            .collect();
+        undefined.sort();
+        undefined.dedup();
+        undefined
+    }
+
+    /// all channels, with the image they are in, for which predicate is true
+    fn find_channels(&self, predicate: impl Fn(&Channel) -> bool) -> Vec<(&Image, &Channel)> {
+        self.image
+            .iter()
+            .flat_map(|image| {
+                image
+                    .pixels
+                    .channel
+                    .iter()
+                    .map(move |channel| (image, channel))
+            })
+            .filter(|(_, channel)| predicate(channel))
+            .collect()
+    }
+
+    /// all channels acquired with this mode, with the image they are in
+    pub fn find_channels_by_acquisition_mode(
+        &self,
+        mode: ChannelAcquisitionModeType,
+    ) -> Vec<(&Image, &Channel)> {
+        self.find_channels(|channel| channel.acquisition_mode.as_ref() == Some(&mode))
+    }
+
+    /// all channels with this illumination type, with the image they are in
+    pub fn find_channels_by_illumination_type(
+        &self,
+        illumination_type: ChannelIlluminationType,
+    ) -> Vec<(&Image, &Channel)> {
+        self.find_channels(|channel| channel.illumination_type.as_ref() == Some(&illumination_type))
+    }
+
+    /// all channels with this contrast method, with the image they are in
+    pub fn find_channels_by_contrast_method(
+        &self,
+        method: ChannelContrastMethodType,
+    ) -> Vec<(&Image, &Channel)> {
+        self.find_channels(|channel| channel.contrast_method.as_ref() == Some(&method))
+    }
+
+    /// the wells of the plate, none if the plate does not exist
+    pub fn wells_of_plate(&self, plate: &PlateRef) -> &[Well] {
+        self.resolve_plate(plate)
+            .map_or(&[], |plate| plate.well.as_slice())
+    }
+
+    /// the images in the dataset with this ID, none if the dataset does not exist, references to
+    /// images which do not exist are skipped
+    pub fn images_for_dataset<'a>(&'a self, dataset_id: &str) -> Vec<&'a Image> {
+        self.dataset
+            .iter()
+            .filter(|dataset| dataset.id == dataset_id)
+            .flat_map(|dataset| &dataset.image_ref)
+            .filter_map(|image_ref| self.resolve_image(image_ref))
+            .collect()
+    }
+
+    /// the datasets in the project with this ID, none if the project does not exist, references
+    /// to datasets which do not exist are skipped
+    pub fn datasets_for_project<'a>(&'a self, project_id: &str) -> Vec<&'a Dataset> {
+        self.project
+            .iter()
+            .filter(|project| project.id == project_id)
+            .flat_map(|project| &project.dataset_ref)
+            .filter_map(|dataset_ref| self.resolve_dataset(dataset_ref))
+            .collect()
+    }
+
+    /// all samples in the wells of all plates, with their image if it exists
+    pub fn all_hcs_images(
+        &self,
+    ) -> impl Iterator<Item = (&Plate, &Well, &WellSample, Option<&Image>)> {
+        self.plate.iter().flat_map(move |plate| {
+            plate.well.iter().flat_map(move |well| {
+                well.well_sample
+                    .iter()
+                    .map(move |sample| (plate, well, sample, sample.image(self)))
+            })
+        })
+    }
+
+    /// the image of the first sample of the well with this name in the plate, see
+    /// [Plate::well_by_name]
+    pub fn image_for_well(&self, plate: &Plate, name: &str) -> Option<&Image> {
+        plate
+            .well_by_name(name)?
+            .well_sample
+            .iter()
+            .filter_map(|sample| Some((sample.index, sample.image_ref.as_ref()?)))
+            .min_by_key(|(index, _)| *index)
+            .and_then(|(_, image_ref)| self.resolve_image(image_ref))
+    }
+
+    /// the hierarchy of folders, resolved through their FolderRefs, fails when a folder is
+    /// (indirectly) contained in itself or when a FolderRef refers to a folder that does not exist
+    pub fn folder_tree(&self) -> Result<FolderNode<'_>, Error> {
+        let contained: HashSet<&str> = self
+            .folder
+            .iter()
+            .flat_map(|folder| &folder.folder_ref)
+            .map(Ref::id)
+            .collect();
+        let mut builder = FolderTreeBuilder {
+            folders: self
+                .folder
+                .iter()
+                .map(|folder| (folder.id.as_str(), folder))
+                .collect(),
+            path: Vec::new(),
+            visited: HashSet::new(),
+        };
+        let children = self
+            .folder
+            .iter()
+            .filter(|folder| !contained.contains(folder.id.as_str()))
+            .map(|folder| builder.build(folder))
+            .collect::<Result<_, _>>()?;
+        // folders in a cycle that is not reachable from any root
+        if let Some(folder) = self
+            .folder
+            .iter()
+            .find(|folder| !builder.visited.contains(folder.id.as_str()))
+        {
+            return Err(Error::FolderCycle(folder.id.clone()));
+        }
+        Ok(FolderNode {
+            folder: None,
+            children,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ome(folders: &str) -> Ome {
+        format!("<OME>{folders}</OME>").parse().unwrap()
+    }
+
+    #[test]
+    fn folder_tree() -> Result<(), Error> {
+        let ome = ome(r#"
+            <Folder ID="Folder:0" Name="experiment">
+                <FolderRef ID="Folder:1"/>
+                <FolderRef ID="Folder:2"/>
+            </Folder>
+            <Folder ID="Folder:1" Name="day 1"><ImageRef ID="Image:0"/></Folder>
+            <Folder ID="Folder:2" Name="day 2"><ImageRef ID="Image:1"/></Folder>
+            <Folder ID="Folder:3" Name="unsorted"/>
+        "#);
+        let tree = ome.folder_tree()?;
+        assert!(tree.folder.is_none());
+        assert_eq!(tree.len(), 4);
+        assert_eq!(tree.children.len(), 2);
+        let experiment = &tree.children[0];
+        assert_eq!(experiment.folder.unwrap().id, "Folder:0");
+        let days = experiment
+            .children
+            .iter()
+            .map(|child| child.folder.unwrap().name.as_deref().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(days, ["day 1", "day 2"]);
+        assert_eq!(
+            experiment.children[1].folder.unwrap().image_ref[0].id,
+            "Image:1"
+        );
+        assert!(tree.children[1].is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn resolve() -> Result<(), Error> {
+        let ome: Ome = std::fs::read_to_string("tests/roi_annotation_refs.xml")?.parse()?;
+        let roi = ome.resolve_roi(&ome.image[0].roi_ref[0]).unwrap();
+        assert_eq!(roi.name.as_deref(), Some("cell"));
+        assert!(ome.resolve_image(&ImageRef::new("Image:1")).is_none());
+        let plane = &ome.image[0].pixels.plane[0];
+        let tag = ome.resolve_annotation(&plane.annotation_ref[0]).unwrap();
+        assert_eq!(tag.type_name(), "TagAnnotation");
+        let comment = ome.resolve_annotation(&roi.annotation_ref[1]).unwrap();
+        assert_eq!(comment.id(), "Annotation:1");
+        let missing = AnnotationRef {
+            id: "Annotation:2".to_string(),
+        };
+        assert!(ome.resolve_annotation(&missing).is_none());
+
+        let ome: Ome = std::fs::read_to_string("tests/YTL378_JF552.xml")?.parse()?;
+        let image = &ome.image[0];
+        let instrument = ome
+            .resolve_instrument(image.instrument_ref.as_ref().unwrap())
+            .unwrap();
+        assert_eq!(instrument.id, image.instrument_ref.as_ref().unwrap().id());
+        Ok(())
+    }
+
+    #[test]
+    fn experiment_images() -> Result<(), Error> {
+        let image = |id: usize, experiment: Option<&str>| {
+            format!(
+                r#"<Image ID="Image:{id}">{}
+                    <Pixels ID="Pixels:{id}" DimensionOrder="XYCZT" Type="uint8" SizeX="1" SizeY="1" SizeZ="1" SizeC="1" SizeT="1">
+                        <MetadataOnly/>
+                    </Pixels>
+                </Image>"#,
+                experiment.map_or(String::new(), |id| format!(r#"<ExperimentRef ID="{id}"/>"#))
+            )
+        };
+        let ome: Ome = format!(
+            r#"<OME>
+                <Experiment ID="Experiment:0" Type="Photobleaching"/>
+                <Experiment ID="Experiment:1" Type="TimeLapse"/>
+                {}{}{}{}
+            </OME>"#,
+            image(0, Some("Experiment:0")),
+            image(1, None),
+            image(2, Some("Experiment:0")),
+            image(3, Some("Experiment:2")),
+        )
+        .parse()?;
+        let ids = |images: Vec<&Image>| images.iter().map(|i| i.id.clone()).collect::<Vec<_>>();
+        assert_eq!(
+            ids(ome.images_in_experiment("Experiment:0")),
+            ["Image:0", "Image:2"]
+        );
+        assert!(ome.images_in_experiment("Experiment:1").is_empty());
+        let experiment = ome.image[2].experiment(&ome).unwrap();
+        assert_eq!(experiment.id, "Experiment:0");
+        assert!(ome.image[1].experiment(&ome).is_none());
+        assert!(ome.image[3].experiment(&ome).is_none());
+        for image in ome.images_in_experiment("Experiment:0") {
+            assert!(std::ptr::eq(image.experiment(&ome).unwrap(), experiment));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn plate_dimensions() -> Result<(), Error> {
+        // a 96 well plate with the first well 14.38 mm from the left and 11.24 mm from the top
+        let mut wells = String::new();
+        for row in 0..8 {
+            for column in 0..12 {
+                wells.push_str(&format!(
+                    r#"<Well ID="Well:{row}:{column}" Row="{row}" Column="{column}">
+                        <WellSample ID="WellSample:{row}:{column}:0" Index="{}" PositionX="{}" PositionXUnit="mm" PositionY="{}" PositionYUnit="mm"/>
+                    </Well>"#,
+                    12 * row + column,
+                    14.38 + 9.0 * column as f32,
+                    11.24 + 9.0 * row as f32,
+                ));
+            }
+        }
+        let ome: Ome = format!(
+            r#"<OME>
+                <Plate ID="Plate:0" Rows="8" Columns="12" WellOriginX="14.38" WellOriginXUnit="mm" WellOriginY="11240">
+                    {wells}
+                </Plate>
+            </OME>"#
+        )
+        .parse()?;
+        let plate = &ome.plate[0];
+        let (x, y) = plate.physical_dimensions_m().unwrap();
+        assert!((x - 14.38e-3).abs() < 1e-9);
+        assert!((y - 11.24e-3).abs() < 1e-9);
+        let (x, y) = plate.well_pitch_m().unwrap();
+        assert!((x - 9e-3).abs() < 1e-6, "{x}");
+        assert!((y - 9e-3).abs() < 1e-6, "{y}");
+
+        // two fields per well, 1 mm apart, in the wells A1, A6 and C1 only
+        let well = |row: i32, column: i32| {
+            let x = 14.38 + 9.0 * column as f32;
+            let y = 11.24 + 9.0 * row as f32;
+            format!(
+                r#"<Well ID="Well:{row}:{column}" Row="{row}" Column="{column}">
+                    <WellSample ID="WellSample:{row}:{column}:0" Index="{}" PositionX="{}" PositionXUnit="mm" PositionY="{y}" PositionYUnit="mm"/>
+                    <WellSample ID="WellSample:{row}:{column}:1" Index="{}" PositionX="{}" PositionXUnit="mm" PositionY="{}" PositionYUnit="mm"/>
+                </Well>"#,
+                2 * (12 * row + column),
+                x - 0.5,
+                2 * (12 * row + column) + 1,
+                x + 0.5,
+                y + 1.0,
+            )
+        };
+        let ome: Ome = format!(
+            r#"<OME><Plate ID="Plate:0">{}{}{}</Plate></OME>"#,
+            well(0, 0),
+            well(0, 5),
+            well(2, 0)
+        )
+        .parse()?;
+        let (x, y) = ome.plate[0].well_pitch_m().unwrap();
+        assert!((x - 9e-3).abs() < 1e-6, "{x}");
+        assert!((y - 9e-3).abs() < 1e-6, "{y}");
+
+        let ome: Ome = std::fs::read_to_string("tests/plate.xml")?.parse()?;
+        assert!(ome.plate[0].physical_dimensions_m().is_none());
+        assert!(ome.plate[0].well_pitch_m().is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn microbeam_manipulations() -> Result<(), Error> {
+        let ome: Ome = r#"<OME>
+            <Experiment ID="Experiment:0" Type="Photobleaching">
+                <MicrobeamManipulation ID="MicrobeamManipulation:0" Type="FRAP">
+                    <ROIRef ID="ROI:1"/>
+                    <ROIRef ID="ROI:2"/>
+                    <ExperimenterRef ID="Experimenter:0"/>
+                </MicrobeamManipulation>
+            </Experiment>
+            <Experimenter ID="Experimenter:0"/>
+            <Image ID="Image:0">
+                <ExperimentRef ID="Experiment:0"/>
+                <MicrobeamManipulationRef ID="MicrobeamManipulation:0"/>
+                <MicrobeamManipulationRef ID="MicrobeamManipulation:1"/>
+                <Pixels ID="Pixels:0" DimensionOrder="XYCZT" Type="uint8" SizeX="8" SizeY="8" SizeZ="1" SizeC="1" SizeT="1">
+                    <MetadataOnly/>
+                </Pixels>
+            </Image>
+            <ROI ID="ROI:0" Name="cell">
+                <Union><Rectangle ID="Shape:0:0" X="0" Y="0" Width="8" Height="8"/></Union>
+            </ROI>
+            <ROI ID="ROI:1" Name="bleached">
+                <Union><Ellipse ID="Shape:1:0" X="4" Y="4" RadiusX="1" RadiusY="1"/></Union>
+            </ROI>
+        </OME>"#
+            .parse()?;
+        let manipulations = ome.image[0].microbeam_manipulations(&ome);
+        assert_eq!(manipulations.len(), 1);
+        assert_eq!(manipulations[0].id, "MicrobeamManipulation:0");
+        let rois = manipulations[0].rois(&ome);
+        assert_eq!(rois.len(), 1);
+        assert_eq!(rois[0].name.as_deref(), Some("bleached"));
+        Ok(())
+    }
+
+    #[test]
+    fn iter_planes() -> Result<(), Error> {
+        let ome: Ome = std::fs::read_to_string("tests/4-Pos_001_002.xml")?.parse()?;
+        let pixels = &ome.image[0].pixels;
+        assert_eq!(
+            ome.iter_planes().count(),
+            (pixels.size_z * pixels.size_c * pixels.size_t) as usize
+        );
+
+        let ome: Ome = std::fs::read_to_string("tests/YTL378_JF552.xml")?.parse()?;
+        let planes = ome.iter_planes().collect::<Vec<_>>();
+        assert_eq!(
+            planes.len(),
+            ome.image
+                .iter()
+                .map(|image| image.pixels.plane.len())
+                .sum::<usize>()
+        );
+        for (image, plane) in planes {
+            assert!(image.pixels.plane.iter().any(|p| std::ptr::eq(p, plane)));
+        }
+
+        let ome: Ome = r#"<OME><Image ID="Image:0">
+            <Pixels ID="Pixels:0" DimensionOrder="XYZCT" Type="uint8" SizeX="1" SizeY="1" SizeZ="2" SizeC="1" SizeT="1">
+                <MetadataOnly/>
+                <Plane TheZ="0" TheC="0" TheT="0"/><Plane TheZ="0" TheC="0" TheT="0"/>
+                <Plane TheZ="1" TheC="0" TheT="0"/><Plane TheZ="1" TheC="0" TheT="0"/>
+            </Pixels>
+        </Image></OME>"#
+            .parse()?;
+        let planes = ome
+            .iter_planes()
+            .map(|(_, plane)| plane)
+            .collect::<Vec<_>>();
+        assert_eq!(planes.len(), 2);
+        assert!(std::ptr::eq(planes[0], &ome.image[0].pixels.plane[0]));
+        assert!(std::ptr::eq(planes[1], &ome.image[0].pixels.plane[2]));
+        Ok(())
+    }
+
+    #[test]
+    fn find_channels() -> Result<(), Error> {
+        let mut ome: Ome =
+            std::fs::read_to_string("tests/YTL1849A131_2023_05_04__13_36_36.xml")?.parse()?;
+        let channels = &mut ome.image[0].pixels.channel;
+        channels[1].acquisition_mode = Some(ChannelAcquisitionModeType::WideField);
+        channels[4].acquisition_mode = None;
+        channels[4].contrast_method = Some(ChannelContrastMethodType::Fluorescence);
+        let tirf = ome.find_channels_by_acquisition_mode(ChannelAcquisitionModeType::Tirf);
+        assert_eq!(tirf.len(), 4);
+        assert!(tirf.iter().all(|(image, _)| image.id == "Image:0"));
+        assert_eq!(
+            tirf.iter().map(|(_, c)| c.id.as_str()).collect::<Vec<_>>(),
+            ["Channel:0:0", "Channel:0:2", "Channel:0:3", "Channel:0:5"]
+        );
+        assert_eq!(
+            ome.find_channels_by_acquisition_mode(ChannelAcquisitionModeType::WideField)
+                .len(),
+            1
+        );
+        assert_eq!(
+            ome.find_channels_by_illumination_type(ChannelIlluminationType::Epifluorescence)
+                .len(),
+            6
+        );
+        assert_eq!(
+            ome.find_channels_by_contrast_method(ChannelContrastMethodType::Fluorescence)
+                .len(),
+            1
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn experimenter_and_instrument_ids() {
+        let ome = ome(r#"
+            <Project ID="Project:0"><ExperimenterRef ID="Experimenter:0"/></Project>
+            <Experiment ID="Experiment:0">
+                <ExperimenterRef ID="Experimenter:1"/>
+                <MicrobeamManipulation ID="MicrobeamManipulation:0">
+                    <ROIRef ID="ROI:0"/>
+                    <ExperimenterRef ID="Experimenter:2"/>
+                </MicrobeamManipulation>
+            </Experiment>
+            <Experimenter ID="Experimenter:0"/>
+            <Experimenter ID="Experimenter:1"/>
+            <ExperimenterGroup ID="ExperimenterGroup:0">
+                <ExperimenterRef ID="Experimenter:0"/>
+                <Leader ID="Experimenter:3"/>
+            </ExperimenterGroup>
+            <Image ID="Image:0">
+                <ExperimenterRef ID="Experimenter:1"/>
+                <InstrumentRef ID="Instrument:0"/>
+                <Pixels ID="Pixels:0" DimensionOrder="XYCZT" Type="uint8" SizeX="1" SizeY="1" SizeZ="1" SizeC="1" SizeT="1"/>
+            </Image>
+        "#);
+        let experimenters = ome.all_experimenter_ids();
+        assert_eq!(experimenters.len(), 4);
+        assert!(experimenters.contains("Experimenter:2"));
+        assert_eq!(
+            ome.all_instrument_ids(),
+            HashSet::from(["Instrument:0".to_string()])
+        );
+        assert_eq!(
+            ome.undefined_experimenter_ids(),
+            ["Experimenter:2", "Experimenter:3"]
+        );
+    }
+
+    #[test]
+    fn folder_tree_cycle() {
+        let cyclic = ome(r#"
+            <Folder ID="Folder:0"><FolderRef ID="Folder:1"/></Folder>
+            <Folder ID="Folder:1"><FolderRef ID="Folder:2"/></Folder>
+            <Folder ID="Folder:2"><FolderRef ID="Folder:1"/></Folder>
+        "#);
+        assert!(matches!(cyclic.folder_tree(), Err(Error::FolderCycle(id)) if id == "Folder:1"));
+
+        let unreachable = ome(r#"
+            <Folder ID="Folder:0"><FolderRef ID="Folder:1"/></Folder>
+            <Folder ID="Folder:1"><FolderRef ID="Folder:0"/></Folder>
+        "#);
+        assert!(matches!(
+            unreachable.folder_tree(),
+            Err(Error::FolderCycle(_))
+        ));
+
+        let dangling = ome(r#"<Folder ID="Folder:0"><FolderRef ID="Folder:9"/></Folder>"#);
+        assert!(matches!(
+            dangling.folder_tree(),
+            Err(Error::UnresolvedReference(id)) if id == "Folder:9"
+        ));
+    }
+
+    #[test]
+    fn light_sources() -> Result<(), Error> {
+        let ome: Ome = r#"<OME>
+            <Instrument ID="Instrument:0">
+                <Laser ID="LightSource:0" Wavelength="488"/>
+                <LightEmittingDiode ID="LightSource:1"/>
+                <Laser ID="LightSource:2" Wavelength="561"/>
+            </Instrument>
+        </OME>"#
+            .parse()?;
+        let instrument = &ome.instrument[0];
+        let wavelengths = instrument
+            .lasers()
+            .iter()
+            .map(|laser| laser.wavelength)
+            .collect::<Vec<_>>();
+        assert_eq!(wavelengths, [Some(488.0), Some(561.0)]);
+        assert_eq!(instrument.leds().len(), 1);
+        assert_eq!(instrument.leds()[0].id, "LightSource:1");
+        assert!(instrument.arcs().is_empty());
+        assert!(instrument.filaments().is_empty());
+        assert!(instrument.generic_excitation_sources().is_empty());
+        assert_eq!(instrument.light_sources_of_kind("Laser").len(), 2);
+        assert_eq!(
+            instrument.light_sources_of_kind("LightEmittingDiode").len(),
+            1
+        );
+        assert!(instrument.light_sources_of_kind("Arc").is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn planes_and_ifds() -> Result<(), Error> {
+        let ome: Ome = r#"<OME><Image ID="Image:0">
+            <Pixels ID="Pixels:0" DimensionOrder="XYCZT" Type="uint8" SizeX="1" SizeY="1" SizeZ="2" SizeC="2" SizeT="2">
+                <TiffData IFD="0" PlaneCount="4"/>
+                <TiffData IFD="10" FirstT="1" PlaneCount="2"/>
+                <TiffData IFD="20" FirstZ="1" FirstT="1"/>
+                <Plane TheZ="1" TheC="0" TheT="1" ExposureTime="5"/>
+            </Pixels>
+        </Image></OME>"#
+            .parse()?;
+        let pixels = &ome.image[0].pixels;
+        assert_eq!(
+            pixels.plane(1, 0, 1).and_then(|p| p.exposure_time),
+            Some(5.0)
+        );
+        assert!(pixels.plane(0, 0, 0).is_none());
+        // XYCZT: the index is c + 2 * (z + 2 * t)
+        assert_eq!(pixels.ifd(0, 0, 0), Some(0));
+        assert_eq!(pixels.ifd(1, 1, 0), Some(3));
+        assert_eq!(pixels.ifd(0, 1, 1), Some(11));
+        assert_eq!(pixels.ifd(1, 0, 1), Some(20));
+        assert_eq!(pixels.ifd(1, 1, 1), None);
+        assert_eq!(pixels.ifd(2, 0, 0), None);
+
+        let ome: Ome = r#"<OME><Image ID="Image:0">
+            <Pixels ID="Pixels:0" DimensionOrder="XYCZT" Type="uint8" SizeX="1" SizeY="1" SizeZ="2" SizeC="1" SizeT="1">
+                <TiffData IFD="3"/>
+            </Pixels>
+        </Image></OME>"#
+            .parse()?;
+        let pixels = &ome.image[0].pixels;
+        assert_eq!(pixels.ifd(0, 0, 0), Some(3));
+        assert_eq!(pixels.ifd(1, 0, 0), None);
+
+        let ome: Ome = r#"<OME><Image ID="Image:0">
+            <Pixels ID="Pixels:0" DimensionOrder="XYZCT" Type="uint8" SizeX="1" SizeY="1" SizeZ="2" SizeC="1" SizeT="2">
+                <TiffData IFD="0" FirstZ="-1" PlaneCount="4"/>
+                <TiffData IFD="2147483647" FirstT="1" PlaneCount="2"/>
+            </Pixels>
+        </Image></OME>"#
+            .parse()?;
+        let pixels = &ome.image[0].pixels;
+        assert_eq!(pixels.ifd(0, 0, 0), None);
+        assert_eq!(pixels.ifd(0, 0, 1), Some(i32::MAX));
+        assert_eq!(pixels.ifd(1, 0, 1), None);
+
+        let ome: Ome = std::fs::read_to_string("tests/zct.ome.xml")?.parse()?;
+        let pixels = &ome.image[0].pixels;
+        for (z, c, t) in
+            (0..2).flat_map(|z| (0..2).flat_map(move |c| (0..3).map(move |t| (z, c, t))))
+        {
+            assert_eq!(
+                pixels.ifd(z, c, t),
+                pixels.zct_to_index(z, c, t).map(|index| index as i32)
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn wells() -> Result<(), Error> {
+        let mut ome: Ome = std::fs::read_to_string("tests/plate.xml")?.parse()?;
+        let plate = &ome.plate[0];
+        assert_eq!(plate.well_name(1, 6), "B7");
+        assert_eq!(plate.well_name(26, 0), "AA1");
+        assert_eq!(
+            plate.well_by_name("a2").map(|w| w.id.as_str()),
+            Some("Well:0:1")
+        );
+        assert!(plate.well_by_name("B1").is_none());
+        assert_eq!(
+            ome.image_for_well(plate, "A2").map(|i| i.id.as_str()),
+            Some("Image:1")
+        );
+        assert!(ome.image_for_well(plate, "C3").is_none());
+        let plate = &mut ome.plate[0];
+        plate.row_naming_convention = Some(NamingConventionType::Number);
+        assert_eq!(plate.well_name(1, 6), "27");
+        plate.column_naming_convention = Some(NamingConventionType::Letter);
+        assert_eq!(plate.well_name(1, 6), "2G");
+        Ok(())
+    }
+
+    #[test]
+    fn screen() -> Result<(), Error> {
+        let ome: Ome = r#"<OME>
+            <Plate ID="Plate:0">
+                <Well ID="Well:0" Row="0" Column="0">
+                    <WellSample ID="WellSample:0" Index="0"><ImageRef ID="Image:0"/></WellSample>
+                </Well>
+                <Well ID="Well:1" Row="0" Column="1">
+                    <WellSample ID="WellSample:1" Index="1"><ImageRef ID="Image:1"/></WellSample>
+                </Well>
+            </Plate>
+            <Screen ID="Screen:0"><PlateRef ID="Plate:0"/><PlateRef ID="Plate:1"/></Screen>
+            <Image ID="Image:0"><Pixels ID="Pixels:0" DimensionOrder="XYZCT" Type="uint8" SizeX="1" SizeY="1" SizeZ="1" SizeC="1" SizeT="1"/></Image>
+            <Image ID="Image:1"><Pixels ID="Pixels:1" DimensionOrder="XYZCT" Type="uint8" SizeX="1" SizeY="1" SizeZ="1" SizeC="1" SizeT="1"/></Image>
+        </OME>"#
+            .parse()?;
+        let screen = &ome.screen[0];
+        let plates = screen.plates(&ome);
+        assert_eq!(plates.len(), 1);
+        assert_eq!(plates[0].id, "Plate:0");
+        let wells = ome.wells_of_plate(&screen.plate_ref[0]);
+        assert_eq!(
+            wells
+                .iter()
+                .map(|well| well.id.as_str())
+                .collect::<Vec<_>>(),
+            ["Well:0", "Well:1"]
+        );
+        assert!(ome.wells_of_plate(&screen.plate_ref[1]).is_empty());
+        let images = plates
+            .iter()
+            .flat_map(|plate| {
+                plate.well.iter().filter_map(|well| {
+                    ome.image_for_well(plate, &plate.well_name(well.row, well.column))
+                })
+            })
+            .map(|image| image.id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(images, ["Image:0", "Image:1"]);
+        Ok(())
+    }
+
+    #[test]
+    fn project_datasets() -> Result<(), Error> {
+        let pixels = r#"<Pixels ID="Pixels:0" DimensionOrder="XYZCT" Type="uint8" SizeX="1" SizeY="1" SizeZ="1" SizeC="1" SizeT="1"/>"#;
+        let ome: Ome = format!(
+            r#"<OME>
+            <Project ID="Project:0"><DatasetRef ID="Dataset:0"/><DatasetRef ID="Dataset:1"/></Project>
+            <Dataset ID="Dataset:0"><ImageRef ID="Image:0"/><ImageRef ID="Image:1"/></Dataset>
+            <Dataset ID="Dataset:1"><ImageRef ID="Image:2"/><ImageRef ID="Image:3"/></Dataset>
+            <Dataset ID="Dataset:2"/>
+            <Image ID="Image:0">{pixels}</Image>
+            <Image ID="Image:1">{pixels}</Image>
+            <Image ID="Image:2">{pixels}</Image>
+            <Image ID="Image:3">{pixels}</Image>
+        </OME>"#
+        )
+        .parse()?;
+        let datasets = ome.datasets_for_project("Project:0");
+        assert_eq!(
+            datasets.iter().map(|d| d.id.as_str()).collect::<Vec<_>>(),
+            ["Dataset:0", "Dataset:1"]
+        );
+        let images = datasets
+            .iter()
+            .map(|dataset| {
+                ome.images_for_dataset(&dataset.id)
+                    .iter()
+                    .map(|image| image.id.as_str())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(images, [["Image:0", "Image:1"], ["Image:2", "Image:3"]]);
+        assert!(ome.images_for_dataset("Dataset:2").is_empty());
+        assert!(ome.images_for_dataset("Dataset:3").is_empty());
+        assert!(ome.datasets_for_project("Project:1").is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn hcs_images() -> Result<(), Error> {
+        let pixels = r#"<Pixels ID="Pixels:0" DimensionOrder="XYZCT" Type="uint8" SizeX="1" SizeY="1" SizeZ="1" SizeC="1" SizeT="1"/>"#;
+        let ome: Ome = format!(
+            r#"<OME>
+            <Plate ID="Plate:0">
+                <Well ID="Well:0" Row="0" Column="0">
+                    <WellSample ID="WellSample:0" Index="0"><ImageRef ID="Image:0"/></WellSample>
+                </Well>
+                <Well ID="Well:1" Row="0" Column="1">
+                    <WellSample ID="WellSample:1" Index="1"><ImageRef ID="Image:1"/></WellSample>
+                </Well>
+                <Well ID="Well:2" Row="1" Column="0">
+                    <WellSample ID="WellSample:2" Index="2"><ImageRef ID="Image:2"/></WellSample>
+                </Well>
+            </Plate>
+            <Image ID="Image:0">{pixels}</Image>
+            <Image ID="Image:1">{pixels}</Image>
+        </OME>"#
+        )
+        .parse()?;
+        let plate = &ome.plate[0];
+        let images = plate
+            .well
+            .iter()
+            .map(|well| {
+                Well::images_for_well(well, &ome)
+                    .into_iter()
+                    .map(|(sample, image)| (sample.id.as_str(), image.id.as_str()))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            images,
+            [
+                vec![("WellSample:0", "Image:0")],
+                vec![("WellSample:1", "Image:1")],
+                vec![]
+            ]
+        );
+        assert!(plate.well[2].well_sample[0].image(&ome).is_none());
+        let all = ome
+            .all_hcs_images()
+            .map(|(plate, well, sample, image)| {
+                (
+                    plate.id.as_str(),
+                    well.id.as_str(),
+                    sample.id.as_str(),
+                    image.map(|image| image.id.as_str()),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            all,
+            [
+                ("Plate:0", "Well:0", "WellSample:0", Some("Image:0")),
+                ("Plate:0", "Well:1", "WellSample:1", Some("Image:1")),
+                ("Plate:0", "Well:2", "WellSample:2", None),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn channel_filter_set() -> Result<(), Error> {
+        let ome: Ome = r#"<OME>
+            <Instrument ID="Instrument:0">
+                <FilterSet ID="FilterSet:0"><EmissionFilterRef ID="Filter:0"/></FilterSet>
+                <Filter ID="Filter:0"/>
+            </Instrument>
+            <Image ID="Image:0">
+                <InstrumentRef ID="Instrument:0"/>
+                <Pixels ID="Pixels:0" DimensionOrder="XYZCT" Type="uint8" SizeX="1" SizeY="1" SizeZ="1" SizeC="2" SizeT="1">
+                    <Channel ID="Channel:0:0"><FilterSetRef ID="FilterSet:0"/></Channel>
+                    <Channel ID="Channel:0:1"/>
+                </Pixels>
+            </Image>
+            <Image ID="Image:1">
+                <Pixels ID="Pixels:1" DimensionOrder="XYZCT" Type="uint8" SizeX="1" SizeY="1" SizeZ="1" SizeC="1" SizeT="1">
+                    <Channel ID="Channel:1:0"><FilterSetRef ID="FilterSet:0"/></Channel>
+                </Pixels>
+            </Image>
+            <Image ID="Image:2">
+                <InstrumentRef ID="Instrument:1"/>
+                <Pixels ID="Pixels:2" DimensionOrder="XYZCT" Type="uint8" SizeX="1" SizeY="1" SizeZ="1" SizeC="1" SizeT="1">
+                    <Channel ID="Channel:2:0"><FilterSetRef ID="FilterSet:0"/></Channel>
+                </Pixels>
+            </Image>
+        </OME>"#
+            .parse()?;
+        let channels = &ome.image[0].pixels.channel;
+        let filter_set = channels[0].filter_set(&ome).unwrap();
+        assert_eq!(filter_set.id, "FilterSet:0");
+        assert_eq!(filter_set.emission_filter_ref[0].id, "Filter:0");
+        assert!(channels[1].filter_set(&ome).is_none());
+        // without an InstrumentRef, the filter set is looked up in all instruments
+        let channel = &ome.image[1].pixels.channel[0];
+        assert_eq!(channel.filter_set(&ome).unwrap().id, "FilterSet:0");
+        // the instrument of the image does not exist
+        assert!(ome.image[2].pixels.channel[0].filter_set(&ome).is_none());
+        Ok(())
+    }
+}
@@ -0,0 +1,350 @@
+//! the multiscales and omero metadata of OME-NGFF 0.4, which go into the .zattrs of an OME-Zarr
+//! image
+
+use crate::error::Error;
+use crate::ome::{Convert, Image, Ome, PixelType, UnitsLength, UnitsTime};
+use serde::Serialize;
+
+const VERSION: &str = "0.4";
+
+#[derive(Serialize)]
+struct NgffAttrs {
+    multiscales: Vec<Multiscale>,
+    omero: Omero,
+}
+
+#[derive(Serialize)]
+struct Multiscale {
+    version: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    axes: Vec<Axis>,
+    datasets: Vec<Dataset>,
+}
+
+#[derive(Serialize)]
+struct Axis {
+    name: &'static str,
+    r#type: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    unit: Option<&'static str>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Dataset {
+    path: &'static str,
+    coordinate_transformations: Vec<Transformation>,
+}
+
+#[derive(Serialize)]
+struct Transformation {
+    r#type: &'static str,
+    scale: Vec<f64>,
+}
+
+#[derive(Serialize)]
+struct Omero {
+    id: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    version: &'static str,
+    channels: Vec<OmeroChannel>,
+    rdefs: Rdefs,
+}
+
+#[derive(Serialize)]
+struct OmeroChannel {
+    active: bool,
+    coefficient: f64,
+    color: String,
+    family: &'static str,
+    inverted: bool,
+    label: String,
+    window: Window,
+}
+
+#[derive(Serialize)]
+struct Window {
+    min: f64,
+    max: f64,
+    start: f64,
+    end: f64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Rdefs {
+    default_t: i32,
+    default_z: i32,
+    model: &'static str,
+}
+
+/// the name of a unit of length in UDUNITS-2, as used by NGFF, None for units without a name
+/// there
+fn length_unit_name(unit: &UnitsLength) -> Option<&'static str> {
+    Some(match unit {
+        UnitsLength::Ym => "yottameter",
+        UnitsLength::Zm => "zettameter",
+        UnitsLength::Em => "exameter",
+        UnitsLength::Pm => "petameter",
+        UnitsLength::Tm => "terameter",
+        UnitsLength::Gm => "gigameter",
+        UnitsLength::Mm => "megameter",
+        UnitsLength::km => "kilometer",
+        UnitsLength::hm => "hectometer",
+        UnitsLength::m => "meter",
+        UnitsLength::dm => "decimeter",
+        UnitsLength::cm => "centimeter",
+        UnitsLength::mm => "millimeter",
+        UnitsLength::um => "micrometer",
+        UnitsLength::nm => "nanometer",
+        UnitsLength::pm => "picometer",
+        UnitsLength::fm => "femtometer",
+        UnitsLength::am => "attometer",
+        UnitsLength::zm => "zeptometer",
+        UnitsLength::ym => "yoctometer",
+        UnitsLength::A => "angstrom",
+        UnitsLength::In => "inch",
+        UnitsLength::Ft => "foot",
+        UnitsLength::Yd => "yard",
+        UnitsLength::Mi => "mile",
+        UnitsLength::Pc => "parsec",
+        _ => return None,
+    })
+}
+
+/// like [length_unit_name], for units of time
+fn time_unit_name(unit: &UnitsTime) -> Option<&'static str> {
+    Some(match unit {
+        UnitsTime::Ys => "yottasecond",
+        UnitsTime::Zs => "zettasecond",
+        UnitsTime::Es => "exasecond",
+        UnitsTime::Ps => "petasecond",
+        UnitsTime::Ts => "terasecond",
+        UnitsTime::Gs => "gigasecond",
+        UnitsTime::Ms => "megasecond",
+        UnitsTime::ks => "kilosecond",
+        UnitsTime::hs => "hectosecond",
+        UnitsTime::s => "second",
+        UnitsTime::ds => "decisecond",
+        UnitsTime::cs => "centisecond",
+        UnitsTime::ms => "millisecond",
+        UnitsTime::us => "microsecond",
+        UnitsTime::ns => "nanosecond",
+        UnitsTime::ps => "picosecond",
+        UnitsTime::fs => "femtosecond",
+        UnitsTime::r#as => "attosecond",
+        UnitsTime::zs => "zeptosecond",
+        UnitsTime::ys => "yoctosecond",
+        UnitsTime::min => "minute",
+        UnitsTime::h => "hour",
+        UnitsTime::d => "day",
+        _ => return None,
+    })
+}
+
+/// the value as it is written in the xml, so that 0.1 does not become 0.10000000149011612
+fn to_f64(value: f32) -> f64 {
+    value.to_string().parse().unwrap_or(value as f64)
+}
+
+/// the size and the NGFF name of its unit, a unit without a name in NGFF is converted into
+/// fallback, None if the size is not known or it has a unit without a size, like pixel
+fn scale<U: Convert>(
+    size: Option<f32>,
+    unit: &U,
+    name: fn(&U) -> Option<&'static str>,
+    fallback: U,
+) -> Option<(f64, &'static str)> {
+    let size = size?;
+    match name(unit) {
+        Some(unit_name) => Some((to_f64(size), unit_name)),
+        None => Some((unit.convert(&fallback, size as f64).ok()?, name(&fallback)?)),
+    }
+}
+
+/// the window of the intensities of a pixel type, 0 - 1 for float and complex types
+fn window(pixel_type_max: Option<u64>, pixel_type: &PixelType) -> Window {
+    let (min, max) = match (pixel_type_max, pixel_type) {
+        (Some(max), PixelType::Int8 | PixelType::Int16 | PixelType::Int32) => {
+            (-(max as f64) - 1.0, max as f64)
+        }
+        (Some(max), _) => (0.0, max as f64),
+        (None, _) => (0.0, 1.0),
+    };
+    Window {
+        min,
+        max,
+        start: min,
+        end: max,
+    }
+}
+
+impl Image {
+    /// the multiscales and omero blocks of the .zattrs of this image in OME-NGFF 0.4, with the
+    /// axes t, c, z, y and x and a single dataset at path "0", the scale of an axis of which the
+    /// physical size or time increment is not known is 1 and the axis has no unit, the id in
+    /// omero is the index of the image in ome
+    pub fn to_ngff_attrs(&self, ome: &Ome) -> Result<serde_json::Value, Error> {
+        let id = ome
+            .image
+            .iter()
+            .position(|image| std::ptr::eq(image, self) || image.id == self.id)
+            .ok_or_else(|| Error::UnresolvedReference(self.id.clone()))?;
+        let pixels = &self.pixels;
+        let space = |size, unit| scale(size, unit, length_unit_name, UnitsLength::um);
+        let scales = [
+            (
+                "t",
+                "time",
+                scale(
+                    pixels.time_increment,
+                    &pixels.time_increment_unit,
+                    time_unit_name,
+                    UnitsTime::s,
+                ),
+            ),
+            ("c", "channel", None),
+            (
+                "z",
+                "space",
+                space(pixels.physical_size_z, &pixels.physical_size_z_unit),
+            ),
+            (
+                "y",
+                "space",
+                space(pixels.physical_size_y, &pixels.physical_size_y_unit),
+            ),
+            (
+                "x",
+                "space",
+                space(pixels.physical_size_x, &pixels.physical_size_x_unit),
+            ),
+        ];
+        let axes = scales
+            .iter()
+            .map(|(name, r#type, scale)| Axis {
+                name,
+                r#type,
+                unit: scale.map(|(_, unit)| unit),
+            })
+            .collect();
+        let scale = scales
+            .iter()
+            .map(|(_, _, scale)| scale.map_or(1.0, |(size, _)| size))
+            .collect();
+        let multiscale = Multiscale {
+            version: VERSION,
+            name: self.name.clone(),
+            axes,
+            datasets: vec![Dataset {
+                path: "0",
+                coordinate_transformations: vec![Transformation {
+                    r#type: "scale",
+                    scale,
+                }],
+            }],
+        };
+        let channels = pixels
+            .channel
+            .iter()
+            .map(|channel| OmeroChannel {
+                active: true,
+                coefficient: 1.0,
+                color: channel.rgba().to_hex_rgb(),
+                family: "linear",
+                inverted: false,
+                label: channel.name.clone().unwrap_or_else(|| channel.id.clone()),
+                window: window(pixels.max_value(), &pixels.r#type),
+            })
+            .collect();
+        let omero = Omero {
+            id,
+            name: self.name.clone(),
+            version: VERSION,
+            channels,
+            rdefs: Rdefs {
+                default_t: 0,
+                default_z: pixels.size_z / 2,
+                model: if pixels.size_c > 1 {
+                    "color"
+                } else {
+                    "greyscale"
+                },
+            },
+        };
+        Ok(serde_json::to_value(NgffAttrs {
+            multiscales: vec![multiscale],
+            omero,
+        })?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshot() -> Result<(), Error> {
+        let ome: Ome = std::fs::read_to_string("tests/YTL378_JF552.xml")?.parse()?;
+        let attrs = ome.image[0].to_ngff_attrs(&ome)?;
+        let json = serde_json::to_string_pretty(&attrs)? + "\n";
+        crate::tests::assert_snapshot("tests/YTL378_JF552.zattrs.json", &json);
+        let channels = &attrs["omero"]["channels"];
+        assert_eq!(channels.as_array().unwrap().len(), 2);
+        // the packed colors 16711935 and -16776961 are opaque green and red
+        assert_eq!(channels[0]["color"], "00FF00");
+        assert_eq!(channels[1]["color"], "FF0000");
+        assert_eq!(channels[0]["window"]["end"], 65535.0);
+        let multiscale = &attrs["multiscales"][0];
+        assert_eq!(multiscale["axes"][4]["unit"], "micrometer");
+        assert_eq!(
+            multiscale["datasets"][0]["coordinateTransformations"][0]["scale"][4],
+            0.1
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn missing_sizes() -> Result<(), Error> {
+        let mut ome: Ome = std::fs::read_to_string("tests/test.xml")?.parse()?;
+        let pixels = &mut ome.image[0].pixels;
+        pixels.physical_size_x = Some(2.0);
+        pixels.physical_size_x_unit = UnitsLength::Thou;
+        pixels.physical_size_y = Some(1.0);
+        pixels.physical_size_y_unit = UnitsLength::Pixel;
+        pixels.physical_size_z = None;
+        pixels.time_increment = Some(0.5);
+        pixels.time_increment_unit = UnitsTime::das;
+        let attrs = ome.image[0].to_ngff_attrs(&ome)?;
+        let multiscale = &attrs["multiscales"][0];
+        let units = multiscale["axes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|axis| axis.get("unit").and_then(|unit| unit.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            units,
+            [Some("second"), None, None, None, Some("micrometer")]
+        );
+        let scale = multiscale["datasets"][0]["coordinateTransformations"][0]["scale"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|scale| scale.as_f64().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(scale[..4], [5.0, 1.0, 1.0, 1.0]);
+        assert!((scale[4] - 50.8).abs() < 1e-9);
+        assert!(!attrs.to_string().contains("null"));
+
+        let mut other = ome.image[0].clone();
+        other.id = "Image:1".to_string();
+        assert!(matches!(
+            other.to_ngff_attrs(&ome),
+            Err(Error::UnresolvedReference(_))
+        ));
+        Ok(())
+    }
+}
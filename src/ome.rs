@@ -1,11 +1,14 @@
 use crate::error::Error;
 use enum_utils::{FromStr, IterVariants};
 #[cfg(feature = "python")]
-use pyo3::types::{PyDict, PyInt, PyString};
+use pyo3::types::{PyDict, PyDictMethods, PyInt, PyString};
 #[cfg(feature = "python")]
 use pyo3::{Bound, IntoPyObject, PyErr, PyResult, Python};
 use serde::{Deserialize, Serialize};
 use std::cmp::PartialEq;
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Deref;
 
 #[cfg(feature = "python")]
 macro_rules! impl_enum_into_py_object {
@@ -58,6 +61,28 @@ macro_rules! impl_boxed_struct_into_py_object {
     };
 }
 
+/// enums of elements become the dict of the element with the name of the variant under "kind"
+#[cfg(feature = "python")]
+macro_rules! impl_kind_enum_into_py_object {
+    ($($t:ident { $($variant:ident),* $(,)? })*) => {
+        $(
+            impl<'py> IntoPyObject<'py> for $t {
+                type Target = PyDict;
+                type Output = Bound<'py, Self::Target>;
+                type Error = PyErr;
+
+                fn into_pyobject(self, py: Python<'py>) -> PyResult<Self::Output> {
+                    let (kind, dict) = match self {
+                        $($t::$variant(value) => (stringify!($variant), value.into_pyobject(py)?),)*
+                    };
+                    dict.set_item("kind", kind)?;
+                    Ok(dict)
+                }
+            }
+        )*
+    };
+}
+
 #[cfg(feature = "python")]
 impl_enum_into_py_object!(
     ArcType,
@@ -88,9 +113,28 @@ impl_enum_into_py_object!(
 );
 
 #[cfg(feature = "python")]
-impl_empty_struct_into_py_object!(MetadataOnly, XmlAnnotationValue);
+impl_empty_struct_into_py_object!(MetadataOnly);
 #[cfg(feature = "python")]
 impl_boxed_struct_into_py_object!(Channel, Image);
+#[cfg(feature = "python")]
+impl_kind_enum_into_py_object! {
+    BinaryFileContent { External, BinData }
+    LightSourceGroup { Laser, Arc, Filament, LightEmittingDiode, GenericExcitationSource }
+    ShapeGroup { Rectangle, Mask, Point, Ellipse, Line, Polyline, Polygon, Label }
+    StructuredAnnotationsContent {
+        XmlAnnotation,
+        FileAnnotation,
+        ListAnnotation,
+        LongAnnotation,
+        DoubleAnnotation,
+        CommentAnnotation,
+        BooleanAnnotation,
+        TimestampAnnotation,
+        TagAnnotation,
+        TermAnnotation,
+        MapAnnotation,
+    }
+}
 
 #[cfg_attr(feature = "python", derive(IntoPyObject))]
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -113,11 +157,23 @@ pub struct AffineTransform {
 pub struct Annotation {
     #[serde(rename = "@ID")]
     pub id: String,
-    #[serde(default, rename = "@Namespace")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@Namespace"
+    )]
     pub namespace: Option<String>,
-    #[serde(default, rename = "@Annotator")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@Annotator"
+    )]
     pub annotator: Option<String>,
-    #[serde(default, rename = "Description")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "Description"
+    )]
     pub description: Option<String>,
     #[serde(default, rename = "AnnotationRef")]
     pub annotation_ref: Vec<AnnotationRef>,
@@ -128,34 +184,171 @@ pub struct AnnotationRef {
     #[serde(rename = "@ID")]
     pub id: String,
 }
+/// a reference to another element by its ID
+pub trait Ref {
+    fn id(&self) -> &str;
+}
+impl Ref for AnnotationRef {
+    fn id(&self) -> &str {
+        &self.id
+    }
+}
+impl AnnotationRef {
+    /// the element in collection which this refers to, the typed references like [ImageRef] get
+    /// this through Deref
+    pub fn resolve<'a, T: HasId>(&self, collection: &'a [T]) -> Option<&'a T> {
+        collection.iter().find(|item| item.id() == self.id)
+    }
+}
+/// elements with an ID, which references can be resolved to with [AnnotationRef::resolve]
+pub trait HasId {
+    fn id(&self) -> &str;
+}
+macro_rules! impl_has_id {
+    ($($t:ty),* $(,)?) => {
+        $(
+            impl HasId for $t {
+                fn id(&self) -> &str {
+                    &self.id
+                }
+            }
+        )*
+    };
+}
+impl_has_id!(
+    Image,
+    Instrument,
+    Roi,
+    Detector,
+    Objective,
+    Filter,
+    FilterSet,
+    Dichroic,
+    Experimenter,
+    ExperimenterGroup,
+    Dataset,
+    Project,
+    Plate,
+    Screen,
+    Folder,
+    Experiment,
+    MicrobeamManipulation,
+    Annotation,
+    BooleanAnnotation,
+    CommentAnnotation,
+    DoubleAnnotation,
+    FileAnnotation,
+    LongAnnotation,
+    MapAnnotation,
+    TagAnnotation,
+    TermAnnotation,
+    TimestampAnnotation,
+    XmlAnnotation,
+);
+impl HasId for StructuredAnnotationsContent {
+    fn id(&self) -> &str {
+        StructuredAnnotationsContent::id(self)
+    }
+}
+/// typed wrappers around [AnnotationRef], so that the type tells what kind of element is referred to
+macro_rules! ref_types {
+    ($($(#[$meta:meta])* $name:ident $(,)?)*) => {
+        $(
+            $(#[$meta])*
+            #[cfg_attr(feature = "python", derive(IntoPyObject))]
+            #[derive(Clone, Debug, Serialize, Deserialize)]
+            #[serde(transparent)]
+            pub struct $name(pub AnnotationRef);
+
+            impl $name {
+                pub fn new(id: impl Into<String>) -> Self {
+                    $name(AnnotationRef { id: id.into() })
+                }
+            }
+
+            impl Deref for $name {
+                type Target = AnnotationRef;
+
+                fn deref(&self) -> &Self::Target {
+                    &self.0
+                }
+            }
+
+            impl Ref for $name {
+                fn id(&self) -> &str {
+                    &self.0.id
+                }
+            }
+
+            impl From<AnnotationRef> for $name {
+                fn from(annotation_ref: AnnotationRef) -> Self {
+                    $name(annotation_ref)
+                }
+            }
+        )*
+    };
+}
+ref_types!(
+    DatasetRef,
+    DichroicRef,
+    ExperimentRef,
+    ExperimenterGroupRef,
+    /// also used for the leaders of an ExperimenterGroup
+    ExperimenterRef,
+    /// an excitation or emission filter
+    FilterRef,
+    FilterSetRef,
+    FolderRef,
+    ImageRef,
+    InstrumentRef,
+    /// a laser pump or other light source
+    LightSourceRef,
+    MicrobeamManipulationRef,
+    PlateRef,
+    ReagentRef,
+    RoiRef,
+    WellSampleRef,
+);
 #[cfg_attr(feature = "python", derive(IntoPyObject))]
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Arc {
-    #[serde(default, rename = "@Manufacturer")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@Manufacturer"
+    )]
     pub manufacturer: Option<String>,
-    #[serde(default, rename = "@Model")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Model")]
     pub model: Option<String>,
-    #[serde(default, rename = "@SerialNumber")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@SerialNumber"
+    )]
     pub serial_number: Option<String>,
-    #[serde(default, rename = "@LotNumber")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@LotNumber"
+    )]
     pub lot_number: Option<String>,
     #[serde(rename = "@ID")]
     pub id: String,
-    #[serde(default, rename = "@Power")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Power")]
     pub power: Option<f32>,
     #[serde(default = "Arc::default_power_unit", rename = "@PowerUnit")]
     pub power_unit: UnitsPower,
-    #[serde(default, rename = "@Type")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Type")]
     pub r#type: Option<ArcType>,
     #[serde(default, rename = "AnnotationRef")]
     pub annotation_ref: Vec<AnnotationRef>,
 }
 impl Arc {
     pub fn default_power_unit() -> UnitsPower {
-        UnitsPower::W
+        UnitsPower::mW
     }
 }
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum ArcType {
     #[serde(rename = "Hg")]
     Hg,
@@ -171,11 +364,13 @@ pub enum ArcType {
 pub struct BinData {
     #[serde(default = "BinData::default_compression", rename = "@Compression")]
     pub compression: BinDataCompressionType,
-    #[serde(rename = "@BigEndian")]
+    #[serde(deserialize_with = "deserialize_bool", rename = "@BigEndian")]
     pub big_endian: bool,
     #[serde(rename = "@Length")]
     pub length: i64,
-    #[serde(rename = "$text")]
+    /// the base64 text as it is in the xml, including line breaks and indentation, which
+    /// [BinData::decode] and [BinData::refresh_length] ignore
+    #[serde(default, rename = "$text")]
     pub content: String,
 }
 impl BinData {
@@ -183,7 +378,7 @@ impl BinData {
         BinDataCompressionType::None
     }
 }
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum BinDataCompressionType {
     #[serde(rename = "zlib")]
     Zlib,
@@ -199,12 +394,11 @@ pub struct BinaryFile {
     pub file_name: String,
     #[serde(rename = "@Size")]
     pub size: i64,
-    #[serde(default, rename = "@MIMEType")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@MIMEType")]
     pub mime_type: Option<String>,
     #[serde(rename = "$value")]
     pub content: BinaryFileContent,
 }
-#[cfg_attr(feature = "python", derive(IntoPyObject))]
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum BinaryFileContent {
     #[serde(rename = "External")]
@@ -212,7 +406,7 @@ pub enum BinaryFileContent {
     #[serde(rename = "BinData")]
     BinData(BinData),
 }
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum BinningType {
     #[serde(rename = "1x1")]
     _1X1,
@@ -230,15 +424,27 @@ pub enum BinningType {
 pub struct BooleanAnnotation {
     #[serde(rename = "@ID")]
     pub id: String,
-    #[serde(default, rename = "@Namespace")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@Namespace"
+    )]
     pub namespace: Option<String>,
-    #[serde(default, rename = "@Annotator")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@Annotator"
+    )]
     pub annotator: Option<String>,
-    #[serde(default, rename = "Description")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "Description"
+    )]
     pub description: Option<String>,
     #[serde(default, rename = "AnnotationRef")]
     pub annotation_ref: Vec<AnnotationRef>,
-    #[serde(rename = "Value")]
+    #[serde(deserialize_with = "deserialize_bool", rename = "Value")]
     pub value: bool,
 }
 #[cfg_attr(feature = "python", derive(IntoPyObject))]
@@ -246,54 +452,98 @@ pub struct BooleanAnnotation {
 pub struct Channel {
     #[serde(rename = "@ID")]
     pub id: String,
-    #[serde(default, rename = "@Name")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Name")]
     pub name: Option<String>,
-    #[serde(default, rename = "@SamplesPerPixel")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@SamplesPerPixel"
+    )]
     pub samples_per_pixel: Option<i32>,
-    #[serde(default, rename = "@IlluminationType")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@IlluminationType"
+    )]
     pub illumination_type: Option<ChannelIlluminationType>,
-    #[serde(default, rename = "@PinholeSize")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@PinholeSize"
+    )]
     pub pinhole_size: Option<f32>,
     #[serde(
         default = "Channel::default_pinhole_size_unit",
         rename = "@PinholeSizeUnit"
     )]
     pub pinhole_size_unit: UnitsLength,
-    #[serde(default, rename = "@AcquisitionMode")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@AcquisitionMode"
+    )]
     pub acquisition_mode: Option<ChannelAcquisitionModeType>,
-    #[serde(default, rename = "@ContrastMethod")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@ContrastMethod"
+    )]
     pub contrast_method: Option<ChannelContrastMethodType>,
-    #[serde(default, rename = "@ExcitationWavelength")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@ExcitationWavelength"
+    )]
     pub excitation_wavelength: Option<f32>,
     #[serde(
         default = "Channel::default_excitation_wavelength_unit",
         rename = "@ExcitationWavelengthUnit"
     )]
     pub excitation_wavelength_unit: UnitsLength,
-    #[serde(default, rename = "@EmissionWavelength")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@EmissionWavelength"
+    )]
     pub emission_wavelength: Option<f32>,
     #[serde(
         default = "Channel::default_emission_wavelength_unit",
         rename = "@EmissionWavelengthUnit"
     )]
     pub emission_wavelength_unit: UnitsLength,
-    #[serde(default, rename = "@Fluor")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Fluor")]
     pub fluor: Option<String>,
-    #[serde(default, rename = "@NDFilter")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@NDFilter")]
     pub nd_filter: Option<f32>,
-    #[serde(default, rename = "@PockelCellSetting")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@PockelCellSetting"
+    )]
     pub pockel_cell_setting: Option<i32>,
     #[serde(default = "Channel::default_color", rename = "@Color")]
     pub color: i32,
-    #[serde(default, rename = "LightSourceSettings")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "LightSourceSettings"
+    )]
     pub light_source_settings: Option<LightSourceSettings>,
-    #[serde(default, rename = "DetectorSettings")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "DetectorSettings"
+    )]
     pub detector_settings: Option<DetectorSettings>,
-    #[serde(default, rename = "FilterSetRef")]
-    pub filter_set_ref: Option<AnnotationRef>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "FilterSetRef"
+    )]
+    pub filter_set_ref: Option<FilterSetRef>,
     #[serde(default, rename = "AnnotationRef")]
     pub annotation_ref: Vec<AnnotationRef>,
-    #[serde(default, rename = "LightPath")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "LightPath")]
     pub light_path: Option<LightPath>,
 }
 impl Channel {
@@ -301,7 +551,7 @@ impl Channel {
         UnitsLength::um
     }
     pub fn default_color() -> i32 {
-        0
+        -1
     }
     pub fn default_excitation_wavelength_unit() -> UnitsLength {
         UnitsLength::nm
@@ -310,7 +560,7 @@ impl Channel {
         UnitsLength::nm
     }
 }
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum ChannelAcquisitionModeType {
     #[serde(rename = "WideField")]
     WideField,
@@ -359,7 +609,7 @@ pub enum ChannelAcquisitionModeType {
     #[serde(rename = "SPIM")]
     Spim,
 }
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum ChannelContrastMethodType {
     #[serde(rename = "Brightfield")]
     Brightfield,
@@ -380,7 +630,7 @@ pub enum ChannelContrastMethodType {
     #[serde(rename = "Other")]
     Other,
 }
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum ChannelIlluminationType {
     #[serde(rename = "Transmitted")]
     Transmitted,
@@ -398,11 +648,23 @@ pub enum ChannelIlluminationType {
 pub struct CommentAnnotation {
     #[serde(rename = "@ID")]
     pub id: String,
-    #[serde(default, rename = "@Namespace")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@Namespace"
+    )]
     pub namespace: Option<String>,
-    #[serde(default, rename = "@Annotator")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@Annotator"
+    )]
     pub annotator: Option<String>,
-    #[serde(default, rename = "Description")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "Description"
+    )]
     pub description: Option<String>,
     #[serde(default, rename = "AnnotationRef")]
     pub annotation_ref: Vec<AnnotationRef>,
@@ -412,47 +674,75 @@ pub struct CommentAnnotation {
 #[cfg_attr(feature = "python", derive(IntoPyObject))]
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Dataset {
-    #[serde(default, rename = "@Name")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Name")]
     pub name: Option<String>,
     #[serde(rename = "@ID")]
     pub id: String,
-    #[serde(default, rename = "Description")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "Description"
+    )]
     pub description: Option<String>,
-    #[serde(default, rename = "ExperimenterRef")]
-    pub experimenter_ref: Option<AnnotationRef>,
-    #[serde(default, rename = "ExperimenterGroupRef")]
-    pub experimenter_group_ref: Option<AnnotationRef>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "ExperimenterRef"
+    )]
+    pub experimenter_ref: Option<ExperimenterRef>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "ExperimenterGroupRef"
+    )]
+    pub experimenter_group_ref: Option<ExperimenterGroupRef>,
     #[serde(default, rename = "ImageRef")]
-    pub image_ref: Vec<AnnotationRef>,
+    pub image_ref: Vec<ImageRef>,
     #[serde(default, rename = "AnnotationRef")]
     pub annotation_ref: Vec<AnnotationRef>,
 }
 #[cfg_attr(feature = "python", derive(IntoPyObject))]
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Detector {
-    #[serde(default, rename = "@Manufacturer")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@Manufacturer"
+    )]
     pub manufacturer: Option<String>,
-    #[serde(default, rename = "@Model")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Model")]
     pub model: Option<String>,
-    #[serde(default, rename = "@SerialNumber")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@SerialNumber"
+    )]
     pub serial_number: Option<String>,
-    #[serde(default, rename = "@LotNumber")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@LotNumber"
+    )]
     pub lot_number: Option<String>,
-    #[serde(default, rename = "@Gain")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Gain")]
     pub gain: Option<f32>,
-    #[serde(default, rename = "@Voltage")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Voltage")]
     pub voltage: Option<f32>,
     #[serde(default = "Detector::default_voltage_unit", rename = "@VoltageUnit")]
     pub voltage_unit: UnitsElectricPotential,
-    #[serde(default, rename = "@Offset")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Offset")]
     pub offset: Option<f32>,
-    #[serde(default, rename = "@Zoom")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Zoom")]
     pub zoom: Option<f32>,
-    #[serde(default, rename = "@AmplificationGain")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@AmplificationGain"
+    )]
     pub amplification_gain: Option<f32>,
     #[serde(rename = "@ID")]
     pub id: String,
-    #[serde(default, rename = "@Type")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Type")]
     pub r#type: Option<DetectorType>,
     #[serde(default, rename = "AnnotationRef")]
     pub annotation_ref: Vec<AnnotationRef>,
@@ -467,29 +757,37 @@ impl Detector {
 pub struct DetectorSettings {
     #[serde(rename = "@ID")]
     pub id: String,
-    #[serde(default, rename = "@Offset")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Offset")]
     pub offset: Option<f32>,
-    #[serde(default, rename = "@Gain")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Gain")]
     pub gain: Option<f32>,
-    #[serde(default, rename = "@Voltage")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Voltage")]
     pub voltage: Option<f32>,
     #[serde(
         default = "DetectorSettings::default_voltage_unit",
         rename = "@VoltageUnit"
     )]
     pub voltage_unit: UnitsElectricPotential,
-    #[serde(default, rename = "@Zoom")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Zoom")]
     pub zoom: Option<f32>,
-    #[serde(default, rename = "@ReadOutRate")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@ReadOutRate"
+    )]
     pub read_out_rate: Option<f32>,
     #[serde(
         default = "DetectorSettings::default_read_out_rate_unit",
         rename = "@ReadOutRateUnit"
     )]
     pub read_out_rate_unit: UnitsFrequency,
-    #[serde(default, rename = "@Binning")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Binning")]
     pub binning: Option<BinningType>,
-    #[serde(default, rename = "@Integration")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@Integration"
+    )]
     pub integration: Option<i32>,
 }
 impl DetectorSettings {
@@ -500,7 +798,7 @@ impl DetectorSettings {
         UnitsFrequency::Hz
     }
 }
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum DetectorType {
     #[serde(rename = "CCD")]
     Ccd,
@@ -534,13 +832,25 @@ pub enum DetectorType {
 #[cfg_attr(feature = "python", derive(IntoPyObject))]
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Dichroic {
-    #[serde(default, rename = "@Manufacturer")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@Manufacturer"
+    )]
     pub manufacturer: Option<String>,
-    #[serde(default, rename = "@Model")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Model")]
     pub model: Option<String>,
-    #[serde(default, rename = "@SerialNumber")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@SerialNumber"
+    )]
     pub serial_number: Option<String>,
-    #[serde(default, rename = "@LotNumber")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@LotNumber"
+    )]
     pub lot_number: Option<String>,
     #[serde(rename = "@ID")]
     pub id: String,
@@ -552,11 +862,23 @@ pub struct Dichroic {
 pub struct DoubleAnnotation {
     #[serde(rename = "@ID")]
     pub id: String,
-    #[serde(default, rename = "@Namespace")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@Namespace"
+    )]
     pub namespace: Option<String>,
-    #[serde(default, rename = "@Annotator")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@Annotator"
+    )]
     pub annotator: Option<String>,
-    #[serde(default, rename = "Description")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "Description"
+    )]
     pub description: Option<String>,
     #[serde(default, rename = "AnnotationRef")]
     pub annotation_ref: Vec<AnnotationRef>,
@@ -566,40 +888,69 @@ pub struct DoubleAnnotation {
 #[cfg_attr(feature = "python", derive(IntoPyObject))]
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Ellipse {
-    #[serde(default, rename = "@FillColor")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@FillColor"
+    )]
     pub fill_color: Option<i32>,
-    #[serde(default, rename = "@FillRule")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@FillRule")]
     pub fill_rule: Option<ShapeFillRuleType>,
-    #[serde(default, rename = "@StrokeColor")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@StrokeColor"
+    )]
     pub stroke_color: Option<i32>,
-    #[serde(default, rename = "@StrokeWidth")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@StrokeWidth"
+    )]
     pub stroke_width: Option<f32>,
     #[serde(
         default = "Ellipse::default_stroke_width_unit",
         rename = "@StrokeWidthUnit"
     )]
     pub stroke_width_unit: UnitsLength,
-    #[serde(default, rename = "@StrokeDashArray")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@StrokeDashArray"
+    )]
     pub stroke_dash_array: Option<String>,
-    #[serde(default, rename = "@Text")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Text")]
     pub text: Option<String>,
-    #[serde(default, rename = "@FontFamily")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@FontFamily"
+    )]
     pub font_family: Option<FontFamilyType>,
-    #[serde(default, rename = "@FontSize")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@FontSize")]
     pub font_size: Option<i32>,
     #[serde(default = "Ellipse::default_font_size_unit", rename = "@FontSizeUnit")]
     pub font_size_unit: UnitsLength,
-    #[serde(default, rename = "@FontStyle")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@FontStyle"
+    )]
     pub font_style: Option<ShapeFontStyleType>,
-    #[serde(default, rename = "@Locked")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_optional_bool",
+        rename = "@Locked"
+    )]
     pub locked: Option<bool>,
     #[serde(rename = "@ID")]
     pub id: String,
-    #[serde(default, rename = "@TheZ")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@TheZ")]
     pub the_z: Option<i32>,
-    #[serde(default, rename = "@TheT")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@TheT")]
     pub the_t: Option<i32>,
-    #[serde(default, rename = "@TheC")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@TheC")]
     pub the_c: Option<i32>,
     #[serde(rename = "@X")]
     pub x: f32,
@@ -609,7 +960,7 @@ pub struct Ellipse {
     pub radius_x: f32,
     #[serde(rename = "@RadiusY")]
     pub radius_y: f32,
-    #[serde(default, rename = "Transform")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "Transform")]
     pub transform: Option<AffineTransform>,
     #[serde(default, rename = "AnnotationRef")]
     pub annotation_ref: Vec<AnnotationRef>,
@@ -625,18 +976,26 @@ impl Ellipse {
 #[cfg_attr(feature = "python", derive(IntoPyObject))]
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Experiment {
-    #[serde(default, rename = "@Type")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Type")]
     pub r#type: Option<ExperimentType>,
     #[serde(rename = "@ID")]
     pub id: String,
-    #[serde(default, rename = "Description")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "Description"
+    )]
     pub description: Option<String>,
-    #[serde(default, rename = "ExperimenterRef")]
-    pub experimenter_ref: Option<AnnotationRef>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "ExperimenterRef"
+    )]
+    pub experimenter_ref: Option<ExperimenterRef>,
     #[serde(default, rename = "MicrobeamManipulation")]
     pub microbeam_manipulation: Vec<MicrobeamManipulation>,
 }
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum ExperimentItemType {
     #[serde(rename = "FP")]
     Fp,
@@ -681,17 +1040,29 @@ pub struct ExperimentType(pub Vec<ExperimentItemType>);
 pub struct Experimenter {
     #[serde(rename = "@ID")]
     pub id: String,
-    #[serde(default, rename = "@FirstName")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@FirstName"
+    )]
     pub first_name: Option<String>,
-    #[serde(default, rename = "@MiddleName")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@MiddleName"
+    )]
     pub middle_name: Option<String>,
-    #[serde(default, rename = "@LastName")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@LastName")]
     pub last_name: Option<String>,
-    #[serde(default, rename = "@Email")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Email")]
     pub email: Option<String>,
-    #[serde(default, rename = "@Institution")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@Institution"
+    )]
     pub institution: Option<String>,
-    #[serde(default, rename = "@UserName")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@UserName")]
     pub user_name: Option<String>,
     #[serde(default, rename = "AnnotationRef")]
     pub annotation_ref: Vec<AnnotationRef>,
@@ -699,16 +1070,20 @@ pub struct Experimenter {
 #[cfg_attr(feature = "python", derive(IntoPyObject))]
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ExperimenterGroup {
-    #[serde(default, rename = "@Name")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Name")]
     pub name: Option<String>,
     #[serde(rename = "@ID")]
     pub id: String,
-    #[serde(default, rename = "Description")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "Description"
+    )]
     pub description: Option<String>,
     #[serde(default, rename = "ExperimenterRef")]
-    pub experimenter_ref: Vec<AnnotationRef>,
+    pub experimenter_ref: Vec<ExperimenterRef>,
     #[serde(default, rename = "Leader")]
-    pub leader: Vec<AnnotationRef>,
+    pub leader: Vec<ExperimenterRef>,
     #[serde(default, rename = "AnnotationRef")]
     pub annotation_ref: Vec<AnnotationRef>,
 }
@@ -730,31 +1105,43 @@ impl External {
 #[cfg_attr(feature = "python", derive(IntoPyObject))]
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Filament {
-    #[serde(default, rename = "@Manufacturer")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@Manufacturer"
+    )]
     pub manufacturer: Option<String>,
-    #[serde(default, rename = "@Model")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Model")]
     pub model: Option<String>,
-    #[serde(default, rename = "@SerialNumber")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@SerialNumber"
+    )]
     pub serial_number: Option<String>,
-    #[serde(default, rename = "@LotNumber")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@LotNumber"
+    )]
     pub lot_number: Option<String>,
     #[serde(rename = "@ID")]
     pub id: String,
-    #[serde(default, rename = "@Power")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Power")]
     pub power: Option<f32>,
     #[serde(default = "Filament::default_power_unit", rename = "@PowerUnit")]
     pub power_unit: UnitsPower,
-    #[serde(default, rename = "@Type")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Type")]
     pub r#type: Option<FilamentType>,
     #[serde(default, rename = "AnnotationRef")]
     pub annotation_ref: Vec<AnnotationRef>,
 }
 impl Filament {
     pub fn default_power_unit() -> UnitsPower {
-        UnitsPower::W
+        UnitsPower::mW
     }
 }
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum FilamentType {
     #[serde(rename = "Incandescent")]
     Incandescent,
@@ -768,11 +1155,23 @@ pub enum FilamentType {
 pub struct FileAnnotation {
     #[serde(rename = "@ID")]
     pub id: String,
-    #[serde(default, rename = "@Namespace")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@Namespace"
+    )]
     pub namespace: Option<String>,
-    #[serde(default, rename = "@Annotator")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@Annotator"
+    )]
     pub annotator: Option<String>,
-    #[serde(default, rename = "Description")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "Description"
+    )]
     pub description: Option<String>,
     #[serde(default, rename = "AnnotationRef")]
     pub annotation_ref: Vec<AnnotationRef>,
@@ -782,21 +1181,41 @@ pub struct FileAnnotation {
 #[cfg_attr(feature = "python", derive(IntoPyObject))]
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Filter {
-    #[serde(default, rename = "@Manufacturer")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@Manufacturer"
+    )]
     pub manufacturer: Option<String>,
-    #[serde(default, rename = "@Model")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Model")]
     pub model: Option<String>,
-    #[serde(default, rename = "@SerialNumber")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@SerialNumber"
+    )]
     pub serial_number: Option<String>,
-    #[serde(default, rename = "@LotNumber")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@LotNumber"
+    )]
     pub lot_number: Option<String>,
-    #[serde(default, rename = "@Type")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Type")]
     pub r#type: Option<FilterType>,
-    #[serde(default, rename = "@FilterWheel")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@FilterWheel"
+    )]
     pub filter_wheel: Option<String>,
     #[serde(rename = "@ID")]
     pub id: String,
-    #[serde(default, rename = "TransmittanceRange")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "TransmittanceRange"
+    )]
     pub transmittance_range: Option<TransmittanceRange>,
     #[serde(default, rename = "AnnotationRef")]
     pub annotation_ref: Vec<AnnotationRef>,
@@ -804,24 +1223,40 @@ pub struct Filter {
 #[cfg_attr(feature = "python", derive(IntoPyObject))]
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct FilterSet {
-    #[serde(default, rename = "@Manufacturer")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@Manufacturer"
+    )]
     pub manufacturer: Option<String>,
-    #[serde(default, rename = "@Model")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Model")]
     pub model: Option<String>,
-    #[serde(default, rename = "@SerialNumber")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@SerialNumber"
+    )]
     pub serial_number: Option<String>,
-    #[serde(default, rename = "@LotNumber")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@LotNumber"
+    )]
     pub lot_number: Option<String>,
     #[serde(rename = "@ID")]
     pub id: String,
     #[serde(default, rename = "ExcitationFilterRef")]
-    pub excitation_filter_ref: Vec<AnnotationRef>,
-    #[serde(default, rename = "DichroicRef")]
-    pub dichroic_ref: Option<AnnotationRef>,
+    pub excitation_filter_ref: Vec<FilterRef>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "DichroicRef"
+    )]
+    pub dichroic_ref: Option<DichroicRef>,
     #[serde(default, rename = "EmissionFilterRef")]
-    pub emission_filter_ref: Vec<AnnotationRef>,
+    pub emission_filter_ref: Vec<FilterRef>,
 }
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum FilterType {
     #[serde(rename = "Dichroic")]
     Dichroic,
@@ -840,25 +1275,49 @@ pub enum FilterType {
     #[serde(rename = "Other")]
     Other,
 }
+impl FilterType {
+    /// the string used for this type in the OME schema
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FilterType::Dichroic => "Dichroic",
+            FilterType::LongPass => "LongPass",
+            FilterType::ShortPass => "ShortPass",
+            FilterType::BandPass => "BandPass",
+            FilterType::MultiPass => "MultiPass",
+            FilterType::NeutralDensity => "NeutralDensity",
+            FilterType::Tuneable => "Tuneable",
+            FilterType::Other => "Other",
+        }
+    }
+}
+impl fmt::Display for FilterType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
 #[cfg_attr(feature = "python", derive(IntoPyObject))]
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Folder {
     #[serde(rename = "@ID")]
     pub id: String,
-    #[serde(default, rename = "@Name")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Name")]
     pub name: Option<String>,
-    #[serde(default, rename = "Description")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "Description"
+    )]
     pub description: Option<String>,
     #[serde(default, rename = "FolderRef")]
-    pub folder_ref: Vec<AnnotationRef>,
+    pub folder_ref: Vec<FolderRef>,
     #[serde(default, rename = "ImageRef")]
-    pub image_ref: Vec<AnnotationRef>,
+    pub image_ref: Vec<ImageRef>,
     #[serde(default, rename = "ROIRef")]
-    pub roi_ref: Vec<AnnotationRef>,
+    pub roi_ref: Vec<RoiRef>,
     #[serde(default, rename = "AnnotationRef")]
     pub annotation_ref: Vec<AnnotationRef>,
 }
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum FontFamilyType {
     #[serde(rename = "serif")]
     Serif,
@@ -874,17 +1333,29 @@ pub enum FontFamilyType {
 #[cfg_attr(feature = "python", derive(IntoPyObject))]
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct GenericExcitationSource {
-    #[serde(default, rename = "@Manufacturer")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@Manufacturer"
+    )]
     pub manufacturer: Option<String>,
-    #[serde(default, rename = "@Model")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Model")]
     pub model: Option<String>,
-    #[serde(default, rename = "@SerialNumber")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@SerialNumber"
+    )]
     pub serial_number: Option<String>,
-    #[serde(default, rename = "@LotNumber")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@LotNumber"
+    )]
     pub lot_number: Option<String>,
     #[serde(rename = "@ID")]
     pub id: String,
-    #[serde(default, rename = "@Power")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Power")]
     pub power: Option<f32>,
     #[serde(
         default = "GenericExcitationSource::default_power_unit",
@@ -893,12 +1364,12 @@ pub struct GenericExcitationSource {
     pub power_unit: UnitsPower,
     #[serde(default, rename = "AnnotationRef")]
     pub annotation_ref: Vec<AnnotationRef>,
-    #[serde(default, rename = "Map")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "Map")]
     pub map: Option<MapType>,
 }
 impl GenericExcitationSource {
     pub fn default_power_unit() -> UnitsPower {
-        UnitsPower::W
+        UnitsPower::mW
     }
 }
 #[cfg_attr(feature = "python", derive(IntoPyObject))]
@@ -906,57 +1377,140 @@ impl GenericExcitationSource {
 pub struct Image {
     #[serde(rename = "@ID")]
     pub id: String,
-    #[serde(default, rename = "@Name")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Name")]
     pub name: Option<String>,
-    #[serde(default, rename = "AcquisitionDate")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "AcquisitionDate"
+    )]
     pub acquisition_date: Option<String>,
-    #[serde(default, rename = "ExperimenterRef")]
-    pub experimenter_ref: Option<AnnotationRef>,
-    #[serde(default, rename = "Description")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "ExperimenterRef"
+    )]
+    pub experimenter_ref: Option<ExperimenterRef>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "Description"
+    )]
     pub description: Option<String>,
-    #[serde(default, rename = "ExperimentRef")]
-    pub experiment_ref: Option<AnnotationRef>,
-    #[serde(default, rename = "ExperimenterGroupRef")]
-    pub experimenter_group_ref: Option<AnnotationRef>,
-    #[serde(default, rename = "InstrumentRef")]
-    pub instrument_ref: Option<AnnotationRef>,
-    #[serde(default, rename = "ObjectiveSettings")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "ExperimentRef"
+    )]
+    pub experiment_ref: Option<ExperimentRef>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "ExperimenterGroupRef"
+    )]
+    pub experimenter_group_ref: Option<ExperimenterGroupRef>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "InstrumentRef"
+    )]
+    pub instrument_ref: Option<InstrumentRef>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "ObjectiveSettings"
+    )]
     pub objective_settings: Option<ObjectiveSettings>,
-    #[serde(default, rename = "ImagingEnvironment")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "ImagingEnvironment"
+    )]
     pub imaging_environment: Option<ImagingEnvironment>,
-    #[serde(default, rename = "StageLabel")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "StageLabel"
+    )]
     pub stage_label: Option<StageLabel>,
     #[serde(rename = "Pixels")]
     pub pixels: Pixels,
     #[serde(default, rename = "ROIRef")]
-    pub roi_ref: Vec<AnnotationRef>,
+    pub roi_ref: Vec<RoiRef>,
     #[serde(default, rename = "MicrobeamManipulationRef")]
-    pub microbeam_manipulation_ref: Vec<AnnotationRef>,
+    pub microbeam_manipulation_ref: Vec<MicrobeamManipulationRef>,
     #[serde(default, rename = "AnnotationRef")]
     pub annotation_ref: Vec<AnnotationRef>,
 }
+/// an xsd:dateTime, taken to be in UTC when it has no timezone offset, a space instead of the T,
+/// surrounding whitespace and an offset without a colon, like +0100, are accepted as well
+#[cfg(feature = "chrono")]
+fn parse_date_time(date: &str) -> Result<chrono::DateTime<chrono::FixedOffset>, Error> {
+    let date = date.trim().replacen(' ', "T", 1);
+    chrono::DateTime::parse_from_rfc3339(&date).or_else(|error| {
+        chrono::DateTime::parse_from_str(&date, "%Y-%m-%dT%H:%M:%S%.f%z")
+            .or_else(|_| {
+                chrono::NaiveDateTime::parse_from_str(&date, "%Y-%m-%dT%H:%M:%S%.f")
+                    .map(|date| date.and_utc().fixed_offset())
+            })
+            .map_err(|_| error.into())
+    })
+}
+#[cfg(feature = "chrono")]
+impl Image {
+    /// the acquisition date, dates without a timezone offset are taken to be in UTC, an empty
+    /// AcquisitionDate element counts as no date
+    pub fn acquisition_date_parsed(
+        &self,
+    ) -> Option<Result<chrono::DateTime<chrono::FixedOffset>, Error>> {
+        let date = self
+            .acquisition_date
+            .as_deref()
+            .filter(|date| !date.is_empty())?;
+        Some(parse_date_time(date))
+    }
+
+    pub fn set_acquisition_date(
+        &mut self,
+        dt: chrono::DateTime<impl chrono::TimeZone<Offset: fmt::Display>>,
+    ) {
+        self.acquisition_date = Some(dt.to_rfc3339());
+    }
+}
 #[cfg_attr(feature = "python", derive(IntoPyObject))]
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ImagingEnvironment {
-    #[serde(default, rename = "@Temperature")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@Temperature"
+    )]
     pub temperature: Option<f32>,
     #[serde(
         default = "ImagingEnvironment::default_temperature_unit",
         rename = "@TemperatureUnit"
     )]
     pub temperature_unit: UnitsTemperature,
-    #[serde(default, rename = "@AirPressure")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@AirPressure"
+    )]
     pub air_pressure: Option<f32>,
     #[serde(
         default = "ImagingEnvironment::default_air_pressure_unit",
         rename = "@AirPressureUnit"
     )]
     pub air_pressure_unit: UnitsPressure,
-    #[serde(default, rename = "@Humidity")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Humidity")]
     pub humidity: Option<f32>,
-    #[serde(default, rename = "@CO2Percent")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@CO2Percent"
+    )]
     pub co_2_percent: Option<f32>,
-    #[serde(default, rename = "Map")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "Map")]
     pub map: Option<MapType>,
 }
 impl ImagingEnvironment {
@@ -964,7 +1518,7 @@ impl ImagingEnvironment {
         UnitsTemperature::C
     }
     pub fn default_air_pressure_unit() -> UnitsPressure {
-        UnitsPressure::atm
+        UnitsPressure::mbar
     }
 }
 #[cfg_attr(feature = "python", derive(IntoPyObject))]
@@ -972,9 +1526,17 @@ impl ImagingEnvironment {
 pub struct Instrument {
     #[serde(rename = "@ID")]
     pub id: String,
-    #[serde(default, rename = "Microscope")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "Microscope"
+    )]
     pub microscope: Option<Microscope>,
-    #[serde(default, rename = "LightSourceGroup")]
+    #[serde(
+        default,
+        rename = "$value",
+        deserialize_with = "deserialize_light_sources"
+    )]
     pub light_source_group: Vec<LightSourceGroup>,
     #[serde(default, rename = "Detector")]
     pub detector: Vec<Detector>,
@@ -989,49 +1551,84 @@ pub struct Instrument {
     #[serde(default, rename = "AnnotationRef")]
     pub annotation_ref: Vec<AnnotationRef>,
 }
+impl Instrument {
+    /// the type of the first detector listed in this instrument
+    pub fn primary_detector_type(&self) -> Option<DetectorType> {
+        self.detector.first()?.r#type.clone()
+    }
+}
 #[cfg_attr(feature = "python", derive(IntoPyObject))]
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Label {
-    #[serde(default, rename = "@FillColor")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@FillColor"
+    )]
     pub fill_color: Option<i32>,
-    #[serde(default, rename = "@FillRule")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@FillRule")]
     pub fill_rule: Option<ShapeFillRuleType>,
-    #[serde(default, rename = "@StrokeColor")]
-    pub stroke_color: Option<i32>,
-    #[serde(default, rename = "@StrokeWidth")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@StrokeColor"
+    )]
+    pub stroke_color: Option<i32>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@StrokeWidth"
+    )]
     pub stroke_width: Option<f32>,
     #[serde(
         default = "Label::default_stroke_width_unit",
         rename = "@StrokeWidthUnit"
     )]
     pub stroke_width_unit: UnitsLength,
-    #[serde(default, rename = "@StrokeDashArray")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@StrokeDashArray"
+    )]
     pub stroke_dash_array: Option<String>,
-    #[serde(default, rename = "@Text")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Text")]
     pub text: Option<String>,
-    #[serde(default, rename = "@FontFamily")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@FontFamily"
+    )]
     pub font_family: Option<FontFamilyType>,
-    #[serde(default, rename = "@FontSize")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@FontSize")]
     pub font_size: Option<i32>,
     #[serde(default = "Label::default_font_size_unit", rename = "@FontSizeUnit")]
     pub font_size_unit: UnitsLength,
-    #[serde(default, rename = "@FontStyle")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@FontStyle"
+    )]
     pub font_style: Option<ShapeFontStyleType>,
-    #[serde(default, rename = "@Locked")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_optional_bool",
+        rename = "@Locked"
+    )]
     pub locked: Option<bool>,
     #[serde(rename = "@ID")]
     pub id: String,
-    #[serde(default, rename = "@TheZ")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@TheZ")]
     pub the_z: Option<i32>,
-    #[serde(default, rename = "@TheT")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@TheT")]
     pub the_t: Option<i32>,
-    #[serde(default, rename = "@TheC")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@TheC")]
     pub the_c: Option<i32>,
     #[serde(rename = "@X")]
     pub x: f32,
     #[serde(rename = "@Y")]
     pub y: f32,
-    #[serde(default, rename = "Transform")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "Transform")]
     pub transform: Option<AffineTransform>,
     #[serde(default, rename = "AnnotationRef")]
     pub annotation_ref: Vec<AnnotationRef>,
@@ -1047,37 +1644,75 @@ impl Label {
 #[cfg_attr(feature = "python", derive(IntoPyObject))]
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Laser {
-    #[serde(default, rename = "@Manufacturer")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@Manufacturer"
+    )]
     pub manufacturer: Option<String>,
-    #[serde(default, rename = "@Model")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Model")]
     pub model: Option<String>,
-    #[serde(default, rename = "@SerialNumber")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@SerialNumber"
+    )]
     pub serial_number: Option<String>,
-    #[serde(default, rename = "@LotNumber")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@LotNumber"
+    )]
     pub lot_number: Option<String>,
     #[serde(rename = "@ID")]
     pub id: String,
-    #[serde(default, rename = "@Power")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Power")]
     pub power: Option<f32>,
     #[serde(default = "Laser::default_power_unit", rename = "@PowerUnit")]
     pub power_unit: UnitsPower,
-    #[serde(default, rename = "@Type")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Type")]
     pub r#type: Option<LaserType>,
-    #[serde(default, rename = "@LaserMedium")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@LaserMedium"
+    )]
     pub laser_medium: Option<LaserLaserMediumType>,
-    #[serde(default, rename = "@Wavelength")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@Wavelength"
+    )]
     pub wavelength: Option<f32>,
     #[serde(default = "Laser::default_wavelength_unit", rename = "@WavelengthUnit")]
     pub wavelength_unit: UnitsLength,
-    #[serde(default, rename = "@FrequencyMultiplication")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@FrequencyMultiplication"
+    )]
     pub frequency_multiplication: Option<i32>,
-    #[serde(default, rename = "@Tuneable")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_optional_bool",
+        rename = "@Tuneable"
+    )]
     pub tuneable: Option<bool>,
-    #[serde(default, rename = "@Pulse")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Pulse")]
     pub pulse: Option<LaserPulseType>,
-    #[serde(default, rename = "@PockelCell")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_optional_bool",
+        rename = "@PockelCell"
+    )]
     pub pockel_cell: Option<bool>,
-    #[serde(default, rename = "@RepetitionRate")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@RepetitionRate"
+    )]
     pub repetition_rate: Option<f32>,
     #[serde(
         default = "Laser::default_repetition_rate_unit",
@@ -1086,8 +1721,8 @@ pub struct Laser {
     pub repetition_rate_unit: UnitsFrequency,
     #[serde(default, rename = "AnnotationRef")]
     pub annotation_ref: Vec<AnnotationRef>,
-    #[serde(default, rename = "Pump")]
-    pub pump: Option<AnnotationRef>,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "Pump")]
+    pub pump: Option<LightSourceRef>,
 }
 impl Laser {
     pub fn default_power_unit() -> UnitsPower {
@@ -1100,7 +1735,7 @@ impl Laser {
         UnitsFrequency::Hz
     }
 }
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum LaserLaserMediumType {
     #[serde(rename = "Cu")]
     Cu,
@@ -1171,7 +1806,7 @@ pub enum LaserLaserMediumType {
     #[serde(rename = "Other")]
     Other,
 }
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum LaserPulseType {
     #[serde(rename = "CW")]
     Cw,
@@ -1186,7 +1821,7 @@ pub enum LaserPulseType {
     #[serde(rename = "Other")]
     Other,
 }
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum LaserType {
     #[serde(rename = "Excimer")]
     Excimer,
@@ -1208,17 +1843,29 @@ pub enum LaserType {
 #[cfg_attr(feature = "python", derive(IntoPyObject))]
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct LightEmittingDiode {
-    #[serde(default, rename = "@Manufacturer")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@Manufacturer"
+    )]
     pub manufacturer: Option<String>,
-    #[serde(default, rename = "@Model")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Model")]
     pub model: Option<String>,
-    #[serde(default, rename = "@SerialNumber")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@SerialNumber"
+    )]
     pub serial_number: Option<String>,
-    #[serde(default, rename = "@LotNumber")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@LotNumber"
+    )]
     pub lot_number: Option<String>,
     #[serde(rename = "@ID")]
     pub id: String,
-    #[serde(default, rename = "@Power")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Power")]
     pub power: Option<f32>,
     #[serde(
         default = "LightEmittingDiode::default_power_unit",
@@ -1237,28 +1884,44 @@ impl LightEmittingDiode {
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct LightPath {
     #[serde(default, rename = "ExcitationFilterRef")]
-    pub excitation_filter_ref: Vec<AnnotationRef>,
-    #[serde(default, rename = "DichroicRef")]
-    pub dichroic_ref: Option<AnnotationRef>,
+    pub excitation_filter_ref: Vec<FilterRef>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "DichroicRef"
+    )]
+    pub dichroic_ref: Option<DichroicRef>,
     #[serde(default, rename = "EmissionFilterRef")]
-    pub emission_filter_ref: Vec<AnnotationRef>,
+    pub emission_filter_ref: Vec<FilterRef>,
     #[serde(default, rename = "AnnotationRef")]
     pub annotation_ref: Vec<AnnotationRef>,
 }
 #[cfg_attr(feature = "python", derive(IntoPyObject))]
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct LightSourceType {
-    #[serde(default, rename = "@Manufacturer")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@Manufacturer"
+    )]
     pub manufacturer: Option<String>,
-    #[serde(default, rename = "@Model")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Model")]
     pub model: Option<String>,
-    #[serde(default, rename = "@SerialNumber")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@SerialNumber"
+    )]
     pub serial_number: Option<String>,
-    #[serde(default, rename = "@LotNumber")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@LotNumber"
+    )]
     pub lot_number: Option<String>,
     #[serde(rename = "@ID")]
     pub id: String,
-    #[serde(default, rename = "@Power")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Power")]
     pub power: Option<f32>,
     #[serde(default = "LightSourceType::default_power_unit", rename = "@PowerUnit")]
     pub power_unit: UnitsPower,
@@ -1270,7 +1933,6 @@ impl LightSourceType {
         UnitsPower::mW
     }
 }
-#[cfg_attr(feature = "python", derive(IntoPyObject))]
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum LightSourceGroup {
     #[serde(rename = "Laser")]
@@ -1284,14 +1946,75 @@ pub enum LightSourceGroup {
     #[serde(rename = "GenericExcitationSource")]
     GenericExcitationSource(GenericExcitationSource),
 }
+/// a light source, or any other element in an Instrument which is not in the schema
+#[derive(Deserialize)]
+enum LightSourceElement {
+    Laser(Laser),
+    Arc(Arc),
+    Filament(Filament),
+    LightEmittingDiode(LightEmittingDiode),
+    GenericExcitationSource(GenericExcitationSource),
+    #[serde(other)]
+    Other,
+}
+/// the light sources of an instrument, ignoring the elements which are not in the schema
+fn deserialize_light_sources<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<LightSourceGroup>, D::Error> {
+    Ok(Vec::<LightSourceElement>::deserialize(deserializer)?
+        .into_iter()
+        .filter_map(|element| match element {
+            LightSourceElement::Laser(laser) => Some(LightSourceGroup::Laser(laser)),
+            LightSourceElement::Arc(arc) => Some(LightSourceGroup::Arc(arc)),
+            LightSourceElement::Filament(filament) => Some(LightSourceGroup::Filament(filament)),
+            LightSourceElement::LightEmittingDiode(led) => {
+                Some(LightSourceGroup::LightEmittingDiode(led))
+            }
+            LightSourceElement::GenericExcitationSource(source) => {
+                Some(LightSourceGroup::GenericExcitationSource(source))
+            }
+            LightSourceElement::Other => None,
+        })
+        .collect())
+}
+impl LightSourceGroup {
+    pub fn id(&self) -> &str {
+        match self {
+            LightSourceGroup::Laser(l) => &l.id,
+            LightSourceGroup::Arc(l) => &l.id,
+            LightSourceGroup::Filament(l) => &l.id,
+            LightSourceGroup::LightEmittingDiode(l) => &l.id,
+            LightSourceGroup::GenericExcitationSource(l) => &l.id,
+        }
+    }
+
+    /// the name of the variant, like "Laser"
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            LightSourceGroup::Laser(_) => "Laser",
+            LightSourceGroup::Arc(_) => "Arc",
+            LightSourceGroup::Filament(_) => "Filament",
+            LightSourceGroup::LightEmittingDiode(_) => "LightEmittingDiode",
+            LightSourceGroup::GenericExcitationSource(_) => "GenericExcitationSource",
+        }
+    }
+}
 #[cfg_attr(feature = "python", derive(IntoPyObject))]
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct LightSourceSettings {
     #[serde(rename = "@ID")]
     pub id: String,
-    #[serde(default, rename = "@Attenuation")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@Attenuation"
+    )]
     pub attenuation: Option<f32>,
-    #[serde(default, rename = "@Wavelength")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@Wavelength"
+    )]
     pub wavelength: Option<f32>,
     #[serde(
         default = "LightSourceSettings::default_wavelength_unit",
@@ -1307,40 +2030,69 @@ impl LightSourceSettings {
 #[cfg_attr(feature = "python", derive(IntoPyObject))]
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Line {
-    #[serde(default, rename = "@FillColor")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@FillColor"
+    )]
     pub fill_color: Option<i32>,
-    #[serde(default, rename = "@FillRule")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@FillRule")]
     pub fill_rule: Option<ShapeFillRuleType>,
-    #[serde(default, rename = "@StrokeColor")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@StrokeColor"
+    )]
     pub stroke_color: Option<i32>,
-    #[serde(default, rename = "@StrokeWidth")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@StrokeWidth"
+    )]
     pub stroke_width: Option<f32>,
     #[serde(
         default = "Line::default_stroke_width_unit",
         rename = "@StrokeWidthUnit"
     )]
     pub stroke_width_unit: UnitsLength,
-    #[serde(default, rename = "@StrokeDashArray")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@StrokeDashArray"
+    )]
     pub stroke_dash_array: Option<String>,
-    #[serde(default, rename = "@Text")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Text")]
     pub text: Option<String>,
-    #[serde(default, rename = "@FontFamily")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@FontFamily"
+    )]
     pub font_family: Option<FontFamilyType>,
-    #[serde(default, rename = "@FontSize")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@FontSize")]
     pub font_size: Option<i32>,
     #[serde(default = "Line::default_font_size_unit", rename = "@FontSizeUnit")]
     pub font_size_unit: UnitsLength,
-    #[serde(default, rename = "@FontStyle")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@FontStyle"
+    )]
     pub font_style: Option<ShapeFontStyleType>,
-    #[serde(default, rename = "@Locked")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_optional_bool",
+        rename = "@Locked"
+    )]
     pub locked: Option<bool>,
     #[serde(rename = "@ID")]
     pub id: String,
-    #[serde(default, rename = "@TheZ")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@TheZ")]
     pub the_z: Option<i32>,
-    #[serde(default, rename = "@TheT")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@TheT")]
     pub the_t: Option<i32>,
-    #[serde(default, rename = "@TheC")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@TheC")]
     pub the_c: Option<i32>,
     #[serde(rename = "@X1")]
     pub x1: f32,
@@ -1350,11 +2102,19 @@ pub struct Line {
     pub x2: f32,
     #[serde(rename = "@Y2")]
     pub y2: f32,
-    #[serde(default, rename = "@MarkerStart")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@MarkerStart"
+    )]
     pub marker_start: Option<MarkerType>,
-    #[serde(default, rename = "@MarkerEnd")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@MarkerEnd"
+    )]
     pub marker_end: Option<MarkerType>,
-    #[serde(default, rename = "Transform")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "Transform")]
     pub transform: Option<AffineTransform>,
     #[serde(default, rename = "AnnotationRef")]
     pub annotation_ref: Vec<AnnotationRef>,
@@ -1372,11 +2132,23 @@ impl Line {
 pub struct LongAnnotation {
     #[serde(rename = "@ID")]
     pub id: String,
-    #[serde(default, rename = "@Namespace")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@Namespace"
+    )]
     pub namespace: Option<String>,
-    #[serde(default, rename = "@Annotator")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@Annotator"
+    )]
     pub annotator: Option<String>,
-    #[serde(default, rename = "Description")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "Description"
+    )]
     pub description: Option<String>,
     #[serde(default, rename = "AnnotationRef")]
     pub annotation_ref: Vec<AnnotationRef>,
@@ -1389,31 +2161,131 @@ pub struct MapType {
     #[serde(default, rename = "M")]
     pub m: Vec<MapM>,
 }
+impl MapType {
+    /// the value of the first entry with this key
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.m
+            .iter()
+            .find(|m| m.k.as_deref() == Some(key))
+            .map(|m| m.content.as_str())
+    }
+
+    /// the keys in document order, entries without a key are skipped
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.pairs().map(|(k, _)| k)
+    }
+
+    /// the (key, value) pairs in document order, entries without a key are skipped
+    pub fn pairs(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.m
+            .iter()
+            .filter_map(|m| Some((m.k.as_deref()?, m.content.as_str())))
+    }
+
+    /// the pairs keyed by key, with the value of the first entry for keys which occur more than
+    /// once, like [MapType::get]
+    pub fn to_hashmap(&self) -> HashMap<String, String> {
+        let mut map = HashMap::new();
+        for (k, v) in self.pairs() {
+            map.entry(k.to_string()).or_insert_with(|| v.to_string());
+        }
+        map
+    }
+}
+impl FromIterator<(String, String)> for MapType {
+    fn from_iter<I: IntoIterator<Item = (String, String)>>(pairs: I) -> Self {
+        MapType {
+            m: pairs
+                .into_iter()
+                .map(|(k, content)| MapM {
+                    k: Some(k),
+                    content,
+                })
+                .collect(),
+        }
+    }
+}
+macro_rules! impl_map_access {
+    ($($t:ty $(,)?)*) => {
+        $(
+            impl $t {
+                pub fn map_get(&self, key: &str) -> Option<&str> {
+                    self.map.as_ref()?.get(key)
+                }
+
+                pub fn map_keys(&self) -> impl Iterator<Item = &str> {
+                    self.map.iter().flat_map(MapType::keys)
+                }
+
+                pub fn map_pairs(&self) -> impl Iterator<Item = (&str, &str)> {
+                    self.map.iter().flat_map(MapType::pairs)
+                }
+            }
+        )*
+    };
+}
+impl_map_access!(GenericExcitationSource, ImagingEnvironment);
 #[cfg_attr(feature = "python", derive(IntoPyObject))]
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct MapAnnotation {
     #[serde(rename = "@ID")]
     pub id: String,
-    #[serde(default, rename = "@Namespace")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@Namespace"
+    )]
     pub namespace: Option<String>,
-    #[serde(default, rename = "@Annotator")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@Annotator"
+    )]
     pub annotator: Option<String>,
-    #[serde(default, rename = "Description")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "Description"
+    )]
     pub description: Option<String>,
     #[serde(default, rename = "AnnotationRef")]
     pub annotation_ref: Vec<AnnotationRef>,
     #[serde(rename = "Value")]
     pub value: MapType,
 }
+impl MapAnnotation {
+    /// an annotation with the pairs as entries, in order
+    pub fn from_pairs(
+        id: &str,
+        namespace: Option<&str>,
+        pairs: impl IntoIterator<Item = (String, String)>,
+    ) -> MapAnnotation {
+        MapAnnotation {
+            id: id.to_string(),
+            namespace: namespace.map(str::to_string),
+            annotator: None,
+            description: None,
+            annotation_ref: Vec::new(),
+            value: pairs.into_iter().collect(),
+        }
+    }
+
+    /// see [MapType::to_hashmap]
+    pub fn to_hashmap(&self) -> HashMap<String, String> {
+        self.value.to_hashmap()
+    }
+}
 #[cfg_attr(feature = "python", derive(IntoPyObject))]
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct MapM {
-    #[serde(default, rename = "@K")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@K")]
     pub k: Option<String>,
-    #[serde(rename = "$text")]
+    /// the value as it is in the xml, leading and trailing whitespace is kept, except that a value
+    /// of only whitespace becomes empty
+    #[serde(default, rename = "$text")]
     pub content: String,
 }
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum MarkerType {
     #[serde(rename = "Arrow")]
     Arrow,
@@ -1421,40 +2293,69 @@ pub enum MarkerType {
 #[cfg_attr(feature = "python", derive(IntoPyObject))]
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Mask {
-    #[serde(default, rename = "@FillColor")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@FillColor"
+    )]
     pub fill_color: Option<i32>,
-    #[serde(default, rename = "@FillRule")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@FillRule")]
     pub fill_rule: Option<ShapeFillRuleType>,
-    #[serde(default, rename = "@StrokeColor")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@StrokeColor"
+    )]
     pub stroke_color: Option<i32>,
-    #[serde(default, rename = "@StrokeWidth")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@StrokeWidth"
+    )]
     pub stroke_width: Option<f32>,
     #[serde(
         default = "Mask::default_stroke_width_unit",
         rename = "@StrokeWidthUnit"
     )]
     pub stroke_width_unit: UnitsLength,
-    #[serde(default, rename = "@StrokeDashArray")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@StrokeDashArray"
+    )]
     pub stroke_dash_array: Option<String>,
-    #[serde(default, rename = "@Text")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Text")]
     pub text: Option<String>,
-    #[serde(default, rename = "@FontFamily")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@FontFamily"
+    )]
     pub font_family: Option<FontFamilyType>,
-    #[serde(default, rename = "@FontSize")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@FontSize")]
     pub font_size: Option<i32>,
     #[serde(default = "Mask::default_font_size_unit", rename = "@FontSizeUnit")]
     pub font_size_unit: UnitsLength,
-    #[serde(default, rename = "@FontStyle")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@FontStyle"
+    )]
     pub font_style: Option<ShapeFontStyleType>,
-    #[serde(default, rename = "@Locked")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_optional_bool",
+        rename = "@Locked"
+    )]
     pub locked: Option<bool>,
     #[serde(rename = "@ID")]
     pub id: String,
-    #[serde(default, rename = "@TheZ")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@TheZ")]
     pub the_z: Option<i32>,
-    #[serde(default, rename = "@TheT")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@TheT")]
     pub the_t: Option<i32>,
-    #[serde(default, rename = "@TheC")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@TheC")]
     pub the_c: Option<i32>,
     #[serde(rename = "@X")]
     pub x: f32,
@@ -1464,7 +2365,7 @@ pub struct Mask {
     pub width: f32,
     #[serde(rename = "@Height")]
     pub height: f32,
-    #[serde(default, rename = "Transform")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "Transform")]
     pub transform: Option<AffineTransform>,
     #[serde(default, rename = "AnnotationRef")]
     pub annotation_ref: Vec<AnnotationRef>,
@@ -1479,25 +2380,80 @@ impl Mask {
         UnitsLength::Pixel
     }
 }
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug)]
 pub struct MetadataOnly;
+/// an empty struct rather than a unit, which would be null in formats like JSON and so be
+/// indistinguishable from a missing MetadataOnly
+impl Serialize for MetadataOnly {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serde::ser::SerializeStruct::end(serializer.serialize_struct("MetadataOnly", 0)?)
+    }
+}
+/// anything without content: an empty element, struct or str, a unit, or the 0 which stands for
+/// empty elements in Python
+impl<'de> Deserialize<'de> for MetadataOnly {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct EmptyVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for EmptyVisitor {
+            type Value = MetadataOnly;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "an empty element")
+            }
+
+            fn visit_unit<E: serde::de::Error>(self) -> Result<MetadataOnly, E> {
+                Ok(MetadataOnly)
+            }
+
+            fn visit_str<E: serde::de::Error>(self, _: &str) -> Result<MetadataOnly, E> {
+                Ok(MetadataOnly)
+            }
+
+            fn visit_i64<E: serde::de::Error>(self, _: i64) -> Result<MetadataOnly, E> {
+                Ok(MetadataOnly)
+            }
+
+            fn visit_u64<E: serde::de::Error>(self, _: u64) -> Result<MetadataOnly, E> {
+                Ok(MetadataOnly)
+            }
+
+            fn visit_map<A: serde::de::MapAccess<'de>>(
+                self,
+                mut map: A,
+            ) -> Result<MetadataOnly, A::Error> {
+                while map
+                    .next_entry::<serde::de::IgnoredAny, serde::de::IgnoredAny>()?
+                    .is_some()
+                {}
+                Ok(MetadataOnly)
+            }
+        }
+
+        deserializer.deserialize_any(EmptyVisitor)
+    }
+}
 #[cfg_attr(feature = "python", derive(IntoPyObject))]
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct MicrobeamManipulation {
     #[serde(rename = "@ID")]
     pub id: String,
-    #[serde(default, rename = "@Type")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Type")]
     pub r#type: Option<MicrobeamManipulationType>,
-    #[serde(default, rename = "Description")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "Description"
+    )]
     pub description: Option<String>,
     #[serde(default, rename = "ROIRef")]
-    pub roi_ref: Vec<AnnotationRef>,
+    pub roi_ref: Vec<RoiRef>,
     #[serde(rename = "ExperimenterRef")]
-    pub experimenter_ref: AnnotationRef,
+    pub experimenter_ref: ExperimenterRef,
     #[serde(default, rename = "LightSourceSettings")]
     pub light_source_settings: Vec<LightSourceSettings>,
 }
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum MicrobeamManipulationItemType {
     #[serde(rename = "FRAP")]
     Frap,
@@ -1522,18 +2478,44 @@ pub struct MicrobeamManipulationType(pub Vec<MicrobeamManipulationItemType>);
 #[cfg_attr(feature = "python", derive(IntoPyObject))]
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Microscope {
-    #[serde(default, rename = "@Manufacturer")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@Manufacturer"
+    )]
     pub manufacturer: Option<String>,
-    #[serde(default, rename = "@Model")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Model")]
     pub model: Option<String>,
-    #[serde(default, rename = "@SerialNumber")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@SerialNumber"
+    )]
     pub serial_number: Option<String>,
-    #[serde(default, rename = "@LotNumber")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@LotNumber"
+    )]
     pub lot_number: Option<String>,
-    #[serde(default, rename = "@Type")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Type")]
     pub r#type: Option<MicroscopeType>,
 }
-#[derive(Clone, Debug, Serialize, Deserialize)]
+impl Microscope {
+    pub fn is_inverted(&self) -> bool {
+        matches!(self.r#type, Some(MicroscopeType::Inverted))
+    }
+    pub fn is_upright(&self) -> bool {
+        matches!(self.r#type, Some(MicroscopeType::Upright))
+    }
+    /// the microscope type as a string, "Unknown" if not set
+    pub fn microscope_type_str(&self) -> &str {
+        self.r#type
+            .as_ref()
+            .map_or("Unknown", MicroscopeType::as_str)
+    }
+}
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum MicroscopeType {
     #[serde(rename = "Upright")]
     Upright,
@@ -1546,7 +2528,24 @@ pub enum MicroscopeType {
     #[serde(rename = "Other")]
     Other,
 }
-#[derive(Clone, Debug, Serialize, Deserialize)]
+impl MicroscopeType {
+    /// the string used for this type in the OME schema
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MicroscopeType::Upright => "Upright",
+            MicroscopeType::Inverted => "Inverted",
+            MicroscopeType::Dissection => "Dissection",
+            MicroscopeType::Electrophysiology => "Electrophysiology",
+            MicroscopeType::Other => "Other",
+        }
+    }
+}
+impl fmt::Display for MicroscopeType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum NamingConventionType {
     #[serde(rename = "letter")]
     Letter,
@@ -1554,6 +2553,15 @@ pub enum NamingConventionType {
     Number,
 }
 
+/// the namespace of the schema implemented by this crate
+pub const NAMESPACE: &str = "http://www.openmicroscopy.org/Schemas/OME/2016-06";
+/// namespaces of older schemas which are still parsed
+pub const DEPRECATED_NAMESPACES: [&str; 3] = [
+    "http://www.openmicroscopy.org/Schemas/OME/2013-06",
+    "http://www.openmicroscopy.org/Schemas/OME/2012-06",
+    "http://www.openmicroscopy.org/Schemas/OME/2010-06",
+];
+
 /// The root of the metadata, create this by parsing an XML string.
 /// ```
 /// use ome_metadata::Ome;
@@ -1572,17 +2580,17 @@ pub enum NamingConventionType {
 /// </OME>"#;
 ///
 /// let ome: Ome = xml.parse().unwrap();
-/// let image = &ome.image.unwrap()[0];
+/// let image = &ome.image[0];
 /// println!("acquisition date: {:#?}", image.acquisition_date);
 /// ```
 #[cfg_attr(feature = "python", derive(IntoPyObject))]
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct Ome {
-    #[serde(default, rename = "@UUID")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@UUID")]
     pub uuid: Option<String>,
-    #[serde(default, rename = "@Creator")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Creator")]
     pub creator: Option<String>,
-    #[serde(rename = "Rights")]
+    #[serde(skip_serializing_if = "Option::is_none", rename = "Rights")]
     pub rights: Option<Rights>,
     #[serde(default, rename = "Project")]
     pub project: Vec<Project>,
@@ -1604,54 +2612,137 @@ pub struct Ome {
     pub instrument: Vec<Instrument>,
     #[serde(default, rename = "Image")]
     pub image: Vec<Image>,
-    #[serde(rename = "StructuredAnnotations")]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        rename = "StructuredAnnotations"
+    )]
     pub structured_annotations: Option<StructuredAnnotations>,
     #[serde(default, rename = "ROI")]
     pub roi: Vec<Roi>,
-    #[serde(rename = "BinaryOnly")]
+    #[serde(skip_serializing_if = "Option::is_none", rename = "BinaryOnly")]
     pub binary_only: Option<OmeBinaryOnly>,
+    /// the namespace of the parsed document if it was not the 2016-06 namespace
+    #[serde(skip)]
+    pub original_namespace: Option<String>,
+    /// elements and attributes not in the schema, only filled by [Ome::from_str_preserving]
+    #[serde(skip)]
+    pub unknown_xml: Vec<crate::preserve::UnknownXml>,
+}
+impl Ome {
+    /// all annotations in StructuredAnnotations
+    pub fn annotations(&self) -> impl Iterator<Item = &StructuredAnnotationsContent> {
+        self.structured_annotations
+            .iter()
+            .flat_map(|structured_annotations| &structured_annotations.content)
+    }
+
+    /// sort the planes of all images, see [Pixels::sort_planes]
+    pub fn sort_planes(&mut self) {
+        for image in &mut self.image {
+            image.pixels.sort_planes();
+        }
+    }
 }
 #[cfg_attr(feature = "python", derive(IntoPyObject))]
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Objective {
-    #[serde(default, rename = "@Manufacturer")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@Manufacturer"
+    )]
     pub manufacturer: Option<String>,
-    #[serde(default, rename = "@Model")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Model")]
     pub model: Option<String>,
-    #[serde(default, rename = "@SerialNumber")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@SerialNumber"
+    )]
     pub serial_number: Option<String>,
-    #[serde(default, rename = "@LotNumber")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@LotNumber"
+    )]
     pub lot_number: Option<String>,
     #[serde(rename = "@ID")]
     pub id: String,
-    #[serde(default, rename = "@Correction")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@Correction"
+    )]
     pub correction: Option<ObjectiveCorrectionType>,
-    #[serde(default, rename = "@Immersion")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@Immersion"
+    )]
     pub immersion: Option<ObjectiveImmersionType>,
-    #[serde(default, rename = "@LensNA")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@LensNA")]
     pub lens_na: Option<f32>,
-    #[serde(default, rename = "@NominalMagnification")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@NominalMagnification"
+    )]
     pub nominal_magnification: Option<f32>,
-    #[serde(default, rename = "@CalibratedMagnification")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@CalibratedMagnification"
+    )]
     pub calibrated_magnification: Option<f32>,
-    #[serde(default, rename = "@WorkingDistance")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@WorkingDistance"
+    )]
     pub working_distance: Option<f32>,
     #[serde(
         default = "Objective::default_working_distance_unit",
         rename = "@WorkingDistanceUnit"
     )]
     pub working_distance_unit: UnitsLength,
-    #[serde(default, rename = "@Iris")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_optional_bool",
+        rename = "@Iris"
+    )]
     pub iris: Option<bool>,
     #[serde(default, rename = "AnnotationRef")]
     pub annotation_ref: Vec<AnnotationRef>,
 }
 impl Objective {
+    /// the nominal magnification, or if that is missing the magnification in the model, like 63
+    /// from "Plan-Apochromat 63x/1.4 Oil", only whole words like "63x" or "63X" are recognized
+    pub fn parse_model_magnification(&self) -> Option<f32> {
+        self.nominal_magnification.or_else(|| {
+            self.model
+                .as_deref()?
+                .split(|c: char| c.is_whitespace() || matches!(c, '/' | ',' | ';' | '(' | ')'))
+                .find_map(|word| {
+                    let number = word.strip_suffix(['x', 'X', '×'])?;
+                    if !number.starts_with(|c: char| c.is_ascii_digit())
+                        || !number.chars().all(|c| c.is_ascii_digit() || c == '.')
+                    {
+                        return None;
+                    }
+                    number
+                        .parse()
+                        .ok()
+                        .filter(|&magnification: &f32| magnification > 0.0)
+                })
+        })
+    }
+
     pub fn default_working_distance_unit() -> UnitsLength {
         UnitsLength::um
     }
 }
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum ObjectiveCorrectionType {
     #[serde(rename = "UV")]
     Uv,
@@ -1684,7 +2775,7 @@ pub enum ObjectiveCorrectionType {
     #[serde(rename = "Other")]
     Other,
 }
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum ObjectiveImmersionType {
     #[serde(rename = "Oil")]
     Oil,
@@ -1701,19 +2792,39 @@ pub enum ObjectiveImmersionType {
     #[serde(rename = "Other")]
     Other,
 }
+impl ObjectiveImmersionType {
+    /// the typical refractive index of the medium, None for Multi and Other
+    pub fn refractive_index(&self) -> Option<f32> {
+        match self {
+            ObjectiveImmersionType::Oil => Some(1.515),
+            ObjectiveImmersionType::Water | ObjectiveImmersionType::WaterDipping => Some(1.333),
+            ObjectiveImmersionType::Air => Some(1.0),
+            ObjectiveImmersionType::Glycerol => Some(1.47),
+            ObjectiveImmersionType::Multi | ObjectiveImmersionType::Other => None,
+        }
+    }
+}
 #[cfg_attr(feature = "python", derive(IntoPyObject))]
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ObjectiveSettings {
     #[serde(rename = "@ID")]
     pub id: String,
-    #[serde(default, rename = "@CorrectionCollar")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@CorrectionCollar"
+    )]
     pub correction_collar: Option<f32>,
-    #[serde(default, rename = "@Medium")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Medium")]
     pub medium: Option<ObjectiveSettingsMediumType>,
-    #[serde(default, rename = "@RefractiveIndex")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@RefractiveIndex"
+    )]
     pub refractive_index: Option<f32>,
 }
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum ObjectiveSettingsMediumType {
     #[serde(rename = "Air")]
     Air,
@@ -1726,6 +2837,32 @@ pub enum ObjectiveSettingsMediumType {
     #[serde(rename = "Other")]
     Other,
 }
+impl ObjectiveSettingsMediumType {
+    /// the typical refractive index of the medium, None for Other
+    pub fn refractive_index(&self) -> Option<f32> {
+        match self {
+            ObjectiveSettingsMediumType::Air => Some(1.0),
+            ObjectiveSettingsMediumType::Oil => Some(1.515),
+            ObjectiveSettingsMediumType::Water => Some(1.333),
+            ObjectiveSettingsMediumType::Glycerol => Some(1.47),
+            ObjectiveSettingsMediumType::Other => None,
+        }
+    }
+}
+impl ObjectiveSettings {
+    /// the numerical aperture of the objective when used with the medium in these settings, the
+    /// light is collected under the same angle as in the medium the objective was designed for,
+    /// so the NA scales with the refractive index, but it never exceeds the NA of the objective,
+    /// refractive_index is used for the medium if set
+    pub fn effective_na(&self, objective: &Objective) -> Option<f32> {
+        let na = objective.lens_na?;
+        let designed = objective.immersion.as_ref()?.refractive_index()?;
+        let actual = self
+            .refractive_index
+            .or_else(|| self.medium.as_ref()?.refractive_index())?;
+        Some(na.min(na * actual / designed))
+    }
+}
 #[cfg_attr(feature = "python", derive(IntoPyObject))]
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct OmeBinaryOnly {
@@ -1734,7 +2871,7 @@ pub struct OmeBinaryOnly {
     #[serde(rename = "@UUID")]
     pub uuid: String,
 }
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum PixelType {
     #[serde(rename = "int8")]
     Int8,
@@ -1759,6 +2896,51 @@ pub enum PixelType {
     #[serde(rename = "bit")]
     Bit,
 }
+impl PixelType {
+    /// the string used for this type in the OME schema
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PixelType::Int8 => "int8",
+            PixelType::Int16 => "int16",
+            PixelType::Int32 => "int32",
+            PixelType::Uint8 => "uint8",
+            PixelType::Uint16 => "uint16",
+            PixelType::Uint32 => "uint32",
+            PixelType::Float => "float",
+            PixelType::Double => "double",
+            PixelType::Complex => "complex",
+            PixelType::DoubleComplex => "double-complex",
+            PixelType::Bit => "bit",
+        }
+    }
+
+    /// the numpy dtype, like "uint16", or with the byte order like ">u2" if big_endian, there is
+    /// none for the complex and bit types
+    pub fn numpy_dtype(&self, big_endian: bool) -> Result<String, Error> {
+        let (name, code) = match self {
+            PixelType::Int8 | PixelType::Uint8 => return Ok(self.as_str().to_string()),
+            PixelType::Int16 => ("int16", "i2"),
+            PixelType::Int32 => ("int32", "i4"),
+            PixelType::Uint16 => ("uint16", "u2"),
+            PixelType::Uint32 => ("uint32", "u4"),
+            PixelType::Float => ("float32", "f4"),
+            PixelType::Double => ("float64", "f8"),
+            PixelType::Complex | PixelType::DoubleComplex | PixelType::Bit => {
+                return Err(Error::NoNumpyDtype(self.to_string()));
+            }
+        };
+        Ok(if big_endian {
+            format!(">{code}")
+        } else {
+            name.to_string()
+        })
+    }
+}
+impl fmt::Display for PixelType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
 #[cfg_attr(feature = "python", derive(IntoPyObject))]
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Pixels {
@@ -1768,11 +2950,25 @@ pub struct Pixels {
     pub dimension_order: PixelsDimensionOrderType,
     #[serde(rename = "@Type")]
     pub r#type: PixelType,
-    #[serde(default, rename = "@SignificantBits")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@SignificantBits"
+    )]
     pub significant_bits: Option<i32>,
-    #[serde(default, rename = "@Interleaved")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_optional_bool",
+        rename = "@Interleaved"
+    )]
     pub interleaved: Option<bool>,
-    #[serde(default, rename = "@BigEndian")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_optional_bool",
+        rename = "@BigEndian"
+    )]
     pub big_endian: Option<bool>,
     #[serde(rename = "@SizeX")]
     pub size_x: i32,
@@ -1784,28 +2980,44 @@ pub struct Pixels {
     pub size_c: i32,
     #[serde(rename = "@SizeT")]
     pub size_t: i32,
-    #[serde(default, rename = "@PhysicalSizeX")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@PhysicalSizeX"
+    )]
     pub physical_size_x: Option<f32>,
     #[serde(
         default = "Pixels::default_physical_size_x_unit",
         rename = "@PhysicalSizeXUnit"
     )]
     pub physical_size_x_unit: UnitsLength,
-    #[serde(default, rename = "@PhysicalSizeY")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@PhysicalSizeY"
+    )]
     pub physical_size_y: Option<f32>,
     #[serde(
         default = "Pixels::default_physical_size_y_unit",
         rename = "@PhysicalSizeYUnit"
     )]
     pub physical_size_y_unit: UnitsLength,
-    #[serde(default, rename = "@PhysicalSizeZ")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@PhysicalSizeZ"
+    )]
     pub physical_size_z: Option<f32>,
     #[serde(
         default = "Pixels::default_physical_size_z_unit",
         rename = "@PhysicalSizeZUnit"
     )]
     pub physical_size_z_unit: UnitsLength,
-    #[serde(default, rename = "@TimeIncrement")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@TimeIncrement"
+    )]
     pub time_increment: Option<f32>,
     #[serde(
         default = "Pixels::default_time_increment_unit",
@@ -1818,12 +3030,157 @@ pub struct Pixels {
     pub bin_data: Vec<BinData>,
     #[serde(default, rename = "TiffData")]
     pub tiff_data: Vec<TiffData>,
-    #[serde(rename = "MetadataOnly")]
+    #[serde(skip_serializing_if = "Option::is_none", rename = "MetadataOnly")]
     pub metadata_only: Option<MetadataOnly>,
     #[serde(default, rename = "Plane")]
     pub plane: Vec<Plane>,
 }
+/// where the pixel data of [Pixels] can be found
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DataSource {
+    BinData,
+    TiffData,
+    MetadataOnly,
+    /// none of BinData, TiffData or MetadataOnly is present
+    Missing,
+}
 impl Pixels {
+    pub fn is_metadata_only(&self) -> bool {
+        self.metadata_only.is_some()
+    }
+
+    pub fn has_bin_data(&self) -> bool {
+        !self.bin_data.is_empty()
+    }
+
+    pub fn has_tiff_data(&self) -> bool {
+        !self.tiff_data.is_empty()
+    }
+
+    pub fn data_source(&self) -> DataSource {
+        if self.has_bin_data() {
+            DataSource::BinData
+        } else if self.has_tiff_data() {
+            DataSource::TiffData
+        } else if self.is_metadata_only() {
+            DataSource::MetadataOnly
+        } else {
+            DataSource::Missing
+        }
+    }
+
+    /// the index of a plane in storage order, following dimension_order, None if z, c or t is
+    /// negative or not less than the size of its axis
+    pub fn zct_to_index(&self, z: i32, c: i32, t: i32) -> Option<usize> {
+        let axis = |i: i32, size: i32| {
+            (0..size)
+                .contains(&i)
+                .then_some((i as usize, size as usize))
+        };
+        let (z, size_z) = axis(z, self.size_z)?;
+        let (c, size_c) = axis(c, self.size_c)?;
+        let (t, size_t) = axis(t, self.size_t)?;
+        // i + size * outer, checked because the sizes come from the document
+        let at = |i: usize, size: usize, outer: usize| size.checked_mul(outer)?.checked_add(i);
+        match self.dimension_order {
+            PixelsDimensionOrderType::Xyzct => at(z, size_z, at(c, size_c, t)?),
+            PixelsDimensionOrderType::Xyztc => at(z, size_z, at(t, size_t, c)?),
+            PixelsDimensionOrderType::Xyczt => at(c, size_c, at(z, size_z, t)?),
+            PixelsDimensionOrderType::Xyctz => at(c, size_c, at(t, size_t, z)?),
+            PixelsDimensionOrderType::Xytcz => at(t, size_t, at(c, size_c, z)?),
+            PixelsDimensionOrderType::Xytzc => at(t, size_t, at(z, size_z, c)?),
+        }
+    }
+
+    /// the sizes of the axes in order, which is made of distinct letters from XYZCT, like "TCZYX"
+    pub fn shape(&self, order: &str) -> Result<Vec<usize>, Error> {
+        let mut shape = Vec::new();
+        for (i, axis) in order.char_indices() {
+            if order[..i].contains(axis) {
+                return Err(Error::InvalidAxisOrder(order.to_string()));
+            }
+            shape.push(match axis.to_ascii_uppercase() {
+                'X' => self.size_x,
+                'Y' => self.size_y,
+                'Z' => self.size_z,
+                'C' => self.size_c,
+                'T' => self.size_t,
+                _ => return Err(Error::InvalidAxisOrder(order.to_string())),
+            } as usize);
+        }
+        Ok(shape)
+    }
+
+    pub fn has_z_stack(&self) -> bool {
+        self.size_z > 1
+    }
+
+    pub fn has_time_series(&self) -> bool {
+        self.size_t > 1
+    }
+
+    pub fn has_multichannel(&self) -> bool {
+        self.size_c > 1
+    }
+
+    /// a single plane per channel: no z-stack and no time series
+    pub fn is_2d(&self) -> bool {
+        self.size_z == 1 && self.size_t == 1
+    }
+
+    /// the number of axes longer than 1, with X and Y always counted, so at least 2
+    pub fn dimensionality(&self) -> u8 {
+        2 + [self.size_z, self.size_c, self.size_t]
+            .iter()
+            .filter(|&&size| size > 1)
+            .count() as u8
+    }
+
+    /// the largest intensity of an integer type, 2^significant_bits - 1, significant_bits is the
+    /// full width of the type if not set, not counting the sign bit of signed types, None for float
+    /// and complex types
+    pub fn max_value(&self) -> Option<u64> {
+        let (width, signed) = match self.r#type {
+            PixelType::Bit => (1, false),
+            PixelType::Int8 => (8, true),
+            PixelType::Int16 => (16, true),
+            PixelType::Int32 => (32, true),
+            PixelType::Uint8 => (8, false),
+            PixelType::Uint16 => (16, false),
+            PixelType::Uint32 => (32, false),
+            PixelType::Float
+            | PixelType::Double
+            | PixelType::Complex
+            | PixelType::DoubleComplex => return None,
+        };
+        let width = if signed { width - 1 } else { width };
+        let bits = self
+            .significant_bits
+            .filter(|&bits| bits > 0)
+            .map_or(width, |bits| (bits as u32).min(width));
+        Some((1 << bits) - 1)
+    }
+
+    /// sort the planes in the order in which they are stored, following dimension_order, planes
+    /// outside the sizes go last, in the order they were in
+    pub fn sort_planes(&mut self) {
+        let mut plane = std::mem::take(&mut self.plane);
+        plane.sort_by_key(|p| {
+            let index = self.zct_to_index(p.the_z, p.the_c, p.the_t);
+            (index.is_none(), index)
+        });
+        self.plane = plane;
+    }
+
+    /// the TiffData if there is exactly one
+    #[deprecated(note = "Pixels can have multiple TiffData, use Pixels::tiff_data instead")]
+    pub fn single_tiff_data(&self) -> Option<&TiffData> {
+        match self.tiff_data.as_slice() {
+            [tiff_data] => Some(tiff_data),
+            _ => None,
+        }
+    }
+
     pub fn default_physical_size_x_unit() -> UnitsLength {
         UnitsLength::um
     }
@@ -1837,7 +3194,7 @@ impl Pixels {
         UnitsTime::s
     }
 }
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum PixelsDimensionOrderType {
     #[serde(rename = "XYZCT")]
     Xyzct,
@@ -1861,33 +3218,49 @@ pub struct Plane {
     pub the_t: i32,
     #[serde(rename = "@TheC")]
     pub the_c: i32,
-    #[serde(default, rename = "@DeltaT")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@DeltaT")]
     pub delta_t: Option<f32>,
     #[serde(default = "Plane::default_delta_t_unit", rename = "@DeltaTUnit")]
     pub delta_t_unit: UnitsTime,
-    #[serde(default, rename = "@ExposureTime")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@ExposureTime"
+    )]
     pub exposure_time: Option<f32>,
     #[serde(
         default = "Plane::default_exposure_time_unit",
         rename = "@ExposureTimeUnit"
     )]
     pub exposure_time_unit: UnitsTime,
-    #[serde(default, rename = "@PositionX")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@PositionX"
+    )]
     pub position_x: Option<f32>,
     #[serde(default = "Plane::default_position_x_unit", rename = "@PositionXUnit")]
     pub position_x_unit: UnitsLength,
-    #[serde(default, rename = "@PositionY")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@PositionY"
+    )]
     pub position_y: Option<f32>,
     #[serde(default = "Plane::default_position_y_unit", rename = "@PositionYUnit")]
     pub position_y_unit: UnitsLength,
-    #[serde(default, rename = "@PositionZ")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@PositionZ"
+    )]
     pub position_z: Option<f32>,
     #[serde(default = "Plane::default_position_z_unit", rename = "@PositionZUnit")]
     pub position_z_unit: UnitsLength,
-    #[serde(rename = "HashSHA1")]
+    #[serde(skip_serializing_if = "Option::is_none", rename = "HashSHA1")]
     pub hash_sha1: Option<String>,
-    #[serde(rename = "AnnotationRef")]
-    pub annotation_ref: Option<AnnotationRef>,
+    #[serde(default, rename = "AnnotationRef")]
+    pub annotation_ref: Vec<AnnotationRef>,
 }
 impl Plane {
     pub fn default_delta_t_unit() -> UnitsTime {
@@ -1906,42 +3279,118 @@ impl Plane {
         UnitsLength::um
     }
 }
+/// the positions of the planes in m, ordered by the_t, then the_z and then the_c, a coordinate
+/// which none of the planes has counts as 0, planes without a coordinate that other planes have
+/// are skipped, the units pixel and reference frame have no size in m and give an error
+pub fn position_trajectory_m(planes: &[Plane]) -> Result<Vec<[f64; 3]>, Error> {
+    fn position(plane: &Plane) -> [(Option<f32>, &UnitsLength); 3] {
+        [
+            (plane.position_x, &plane.position_x_unit),
+            (plane.position_y, &plane.position_y_unit),
+            (plane.position_z, &plane.position_z_unit),
+        ]
+    }
+    let known = [0, 1, 2].map(|i| planes.iter().any(|plane| position(plane)[i].0.is_some()));
+    let mut planes = planes
+        .iter()
+        .filter(|plane| {
+            position(plane)
+                .iter()
+                .zip(known)
+                .all(|((value, _), known)| value.is_some() || !known)
+        })
+        .collect::<Vec<_>>();
+    planes.sort_by_key(|plane| (plane.the_t, plane.the_z, plane.the_c));
+    planes
+        .into_iter()
+        .map(|plane| {
+            let [x, y, z] = position(plane).map(|(value, unit)| {
+                value.map_or(Ok(0.0), |value| {
+                    Ok::<_, Error>(value as f64 * unit.as_si()?)
+                })
+            });
+            Ok([x?, y?, z?])
+        })
+        .collect()
+}
+/// the length in m of the path along the positions of the planes, see [position_trajectory_m]
+pub fn total_drift_m(planes: &[Plane]) -> Result<f64, Error> {
+    Ok(position_trajectory_m(planes)?
+        .windows(2)
+        .map(|pair| {
+            pair[0]
+                .iter()
+                .zip(&pair[1])
+                .map(|(a, b)| (b - a).powi(2))
+                .sum::<f64>()
+                .sqrt()
+        })
+        .sum())
+}
 #[cfg_attr(feature = "python", derive(IntoPyObject))]
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Plate {
     #[serde(rename = "@ID")]
     pub id: String,
-    #[serde(default, rename = "@Name")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Name")]
     pub name: Option<String>,
-    #[serde(default, rename = "@Status")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Status")]
     pub status: Option<String>,
-    #[serde(default, rename = "@ExternalIdentifier")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@ExternalIdentifier"
+    )]
     pub external_identifier: Option<String>,
-    #[serde(default, rename = "@ColumnNamingConvention")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@ColumnNamingConvention"
+    )]
     pub column_naming_convention: Option<NamingConventionType>,
-    #[serde(default, rename = "@RowNamingConvention")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@RowNamingConvention"
+    )]
     pub row_naming_convention: Option<NamingConventionType>,
-    #[serde(default, rename = "@WellOriginX")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@WellOriginX"
+    )]
     pub well_origin_x: Option<f32>,
     #[serde(
         default = "Plate::default_well_origin_x_unit",
         rename = "@WellOriginXUnit"
     )]
     pub well_origin_x_unit: UnitsLength,
-    #[serde(default, rename = "@WellOriginY")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@WellOriginY"
+    )]
     pub well_origin_y: Option<f32>,
     #[serde(
         default = "Plate::default_well_origin_y_unit",
         rename = "@WellOriginYUnit"
     )]
     pub well_origin_y_unit: UnitsLength,
-    #[serde(default, rename = "@Rows")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Rows")]
     pub rows: Option<i32>,
-    #[serde(default, rename = "@Columns")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Columns")]
     pub columns: Option<i32>,
-    #[serde(default, rename = "@FieldIndex")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@FieldIndex"
+    )]
     pub field_index: Option<i32>,
-    #[serde(default, rename = "Description")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "Description"
+    )]
     pub description: Option<String>,
     #[serde(default, rename = "Well")]
     pub well: Vec<Well>,
@@ -1963,62 +3412,103 @@ impl Plate {
 pub struct PlateAcquisition {
     #[serde(rename = "@ID")]
     pub id: String,
-    #[serde(default, rename = "@Name")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Name")]
     pub name: Option<String>,
-    #[serde(default, rename = "@EndTime")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@EndTime")]
     pub end_time: Option<String>,
-    #[serde(default, rename = "@StartTime")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@StartTime"
+    )]
     pub start_time: Option<String>,
-    #[serde(default, rename = "@MaximumFieldCount")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@MaximumFieldCount"
+    )]
     pub maximum_field_count: Option<i32>,
-    #[serde(default, rename = "Description")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "Description"
+    )]
     pub description: Option<String>,
     #[serde(default, rename = "WellSampleRef")]
-    pub well_sample_ref: Vec<AnnotationRef>,
+    pub well_sample_ref: Vec<WellSampleRef>,
     #[serde(default, rename = "AnnotationRef")]
     pub annotation_ref: Vec<AnnotationRef>,
 }
 #[cfg_attr(feature = "python", derive(IntoPyObject))]
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Polygon {
-    #[serde(default, rename = "@FillColor")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@FillColor"
+    )]
     pub fill_color: Option<i32>,
-    #[serde(default, rename = "@FillRule")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@FillRule")]
     pub fill_rule: Option<ShapeFillRuleType>,
-    #[serde(default, rename = "@StrokeColor")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@StrokeColor"
+    )]
     pub stroke_color: Option<i32>,
-    #[serde(default, rename = "@StrokeWidth")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@StrokeWidth"
+    )]
     pub stroke_width: Option<f32>,
     #[serde(
         default = "Polygon::default_stroke_width_unit",
         rename = "@StrokeWidthUnit"
     )]
     pub stroke_width_unit: UnitsLength,
-    #[serde(default, rename = "@StrokeDashArray")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@StrokeDashArray"
+    )]
     pub stroke_dash_array: Option<String>,
-    #[serde(default, rename = "@Text")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Text")]
     pub text: Option<String>,
-    #[serde(default, rename = "@FontFamily")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@FontFamily"
+    )]
     pub font_family: Option<FontFamilyType>,
-    #[serde(default, rename = "@FontSize")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@FontSize")]
     pub font_size: Option<i32>,
     #[serde(default = "Polygon::default_font_size_unit", rename = "@FontSizeUnit")]
     pub font_size_unit: UnitsLength,
-    #[serde(default, rename = "@FontStyle")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@FontStyle"
+    )]
     pub font_style: Option<ShapeFontStyleType>,
-    #[serde(default, rename = "@Locked")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_optional_bool",
+        rename = "@Locked"
+    )]
     pub locked: Option<bool>,
     #[serde(rename = "@ID")]
     pub id: String,
-    #[serde(default, rename = "@TheZ")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@TheZ")]
     pub the_z: Option<i32>,
-    #[serde(default, rename = "@TheT")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@TheT")]
     pub the_t: Option<i32>,
-    #[serde(default, rename = "@TheC")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@TheC")]
     pub the_c: Option<i32>,
     #[serde(rename = "@Points")]
     pub points: String,
-    #[serde(default, rename = "Transform")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "Transform")]
     pub transform: Option<AffineTransform>,
     #[serde(default, rename = "AnnotationRef")]
     pub annotation_ref: Vec<AnnotationRef>,
@@ -2034,48 +3524,85 @@ impl Polygon {
 #[cfg_attr(feature = "python", derive(IntoPyObject))]
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Polyline {
-    #[serde(default, rename = "@FillColor")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@FillColor"
+    )]
     pub fill_color: Option<i32>,
-    #[serde(default, rename = "@FillRule")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@FillRule")]
     pub fill_rule: Option<ShapeFillRuleType>,
-    #[serde(default, rename = "@StrokeColor")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@StrokeColor"
+    )]
     pub stroke_color: Option<i32>,
-    #[serde(default, rename = "@StrokeWidth")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@StrokeWidth"
+    )]
     pub stroke_width: Option<f32>,
     #[serde(
         default = "Polyline::default_stroke_width_unit",
         rename = "@StrokeWidthUnit"
     )]
     pub stroke_width_unit: UnitsLength,
-    #[serde(default, rename = "@StrokeDashArray")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@StrokeDashArray"
+    )]
     pub stroke_dash_array: Option<String>,
-    #[serde(default, rename = "@Text")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Text")]
     pub text: Option<String>,
-    #[serde(default, rename = "@FontFamily")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@FontFamily"
+    )]
     pub font_family: Option<FontFamilyType>,
-    #[serde(default, rename = "@FontSize")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@FontSize")]
     pub font_size: Option<i32>,
     #[serde(default = "Polyline::default_font_size_unit", rename = "@FontSizeUnit")]
     pub font_size_unit: UnitsLength,
-    #[serde(default, rename = "@FontStyle")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@FontStyle"
+    )]
     pub font_style: Option<ShapeFontStyleType>,
-    #[serde(default, rename = "@Locked")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_optional_bool",
+        rename = "@Locked"
+    )]
     pub locked: Option<bool>,
     #[serde(rename = "@ID")]
     pub id: String,
-    #[serde(default, rename = "@TheZ")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@TheZ")]
     pub the_z: Option<i32>,
-    #[serde(default, rename = "@TheT")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@TheT")]
     pub the_t: Option<i32>,
-    #[serde(default, rename = "@TheC")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@TheC")]
     pub the_c: Option<i32>,
     #[serde(rename = "@Points")]
     pub points: String,
-    #[serde(default, rename = "@MarkerStart")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@MarkerStart"
+    )]
     pub marker_start: Option<MarkerType>,
-    #[serde(default, rename = "@MarkerEnd")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@MarkerEnd"
+    )]
     pub marker_end: Option<MarkerType>,
-    #[serde(default, rename = "Transform")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "Transform")]
     pub transform: Option<AffineTransform>,
     #[serde(default, rename = "AnnotationRef")]
     pub annotation_ref: Vec<AnnotationRef>,
@@ -2091,18 +3618,30 @@ impl Polyline {
 #[cfg_attr(feature = "python", derive(IntoPyObject))]
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Project {
-    #[serde(default, rename = "@Name")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Name")]
     pub name: Option<String>,
     #[serde(rename = "@ID")]
     pub id: String,
-    #[serde(default, rename = "Description")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "Description"
+    )]
     pub description: Option<String>,
-    #[serde(default, rename = "ExperimenterRef")]
-    pub experimenter_ref: Option<AnnotationRef>,
-    #[serde(default, rename = "ExperimenterGroupRef")]
-    pub experimenter_group_ref: Option<AnnotationRef>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "ExperimenterRef"
+    )]
+    pub experimenter_ref: Option<ExperimenterRef>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "ExperimenterGroupRef"
+    )]
+    pub experimenter_group_ref: Option<ExperimenterGroupRef>,
     #[serde(default, rename = "DatasetRef")]
-    pub dataset_ref: Vec<AnnotationRef>,
+    pub dataset_ref: Vec<DatasetRef>,
     #[serde(default, rename = "AnnotationRef")]
     pub annotation_ref: Vec<AnnotationRef>,
 }
@@ -2111,13 +3650,13 @@ pub struct Project {
 pub struct Roi {
     #[serde(rename = "@ID")]
     pub id: String,
-    #[serde(default, rename = "@Name")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Name")]
     pub name: Option<String>,
-    #[serde(rename = "Union")]
+    #[serde(skip_serializing_if = "Option::is_none", rename = "Union")]
     pub union: Option<RoiUnion>,
-    #[serde(rename = "AnnotationRef")]
-    pub annotation_ref: Option<AnnotationRef>,
-    #[serde(rename = "Description")]
+    #[serde(default, rename = "AnnotationRef")]
+    pub annotation_ref: Vec<AnnotationRef>,
+    #[serde(skip_serializing_if = "Option::is_none", rename = "Description")]
     pub description: Option<String>,
 }
 #[cfg_attr(feature = "python", derive(IntoPyObject))]
@@ -2125,11 +3664,19 @@ pub struct Roi {
 pub struct Reagent {
     #[serde(rename = "@ID")]
     pub id: String,
-    #[serde(default, rename = "@Name")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Name")]
     pub name: Option<String>,
-    #[serde(default, rename = "@ReagentIdentifier")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@ReagentIdentifier"
+    )]
     pub reagent_identifier: Option<String>,
-    #[serde(default, rename = "Description")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "Description"
+    )]
     pub description: Option<String>,
     #[serde(default, rename = "AnnotationRef")]
     pub annotation_ref: Vec<AnnotationRef>,
@@ -2137,43 +3684,72 @@ pub struct Reagent {
 #[cfg_attr(feature = "python", derive(IntoPyObject))]
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Rectangle {
-    #[serde(default, rename = "@FillColor")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@FillColor"
+    )]
     pub fill_color: Option<i32>,
-    #[serde(default, rename = "@FillRule")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@FillRule")]
     pub fill_rule: Option<ShapeFillRuleType>,
-    #[serde(default, rename = "@StrokeColor")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@StrokeColor"
+    )]
     pub stroke_color: Option<i32>,
-    #[serde(default, rename = "@StrokeWidth")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@StrokeWidth"
+    )]
     pub stroke_width: Option<f32>,
     #[serde(
         default = "Rectangle::default_stroke_width_unit",
         rename = "@StrokeWidthUnit"
     )]
     pub stroke_width_unit: UnitsLength,
-    #[serde(default, rename = "@StrokeDashArray")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@StrokeDashArray"
+    )]
     pub stroke_dash_array: Option<String>,
-    #[serde(default, rename = "@Text")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Text")]
     pub text: Option<String>,
-    #[serde(default, rename = "@FontFamily")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@FontFamily"
+    )]
     pub font_family: Option<FontFamilyType>,
-    #[serde(default, rename = "@FontSize")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@FontSize")]
     pub font_size: Option<i32>,
     #[serde(
         default = "Rectangle::default_font_size_unit",
         rename = "@FontSizeUnit"
     )]
     pub font_size_unit: UnitsLength,
-    #[serde(default, rename = "@FontStyle")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@FontStyle"
+    )]
     pub font_style: Option<ShapeFontStyleType>,
-    #[serde(default, rename = "@Locked")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_optional_bool",
+        rename = "@Locked"
+    )]
     pub locked: Option<bool>,
     #[serde(rename = "@ID")]
     pub id: String,
-    #[serde(default, rename = "@TheZ")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@TheZ")]
     pub the_z: Option<i32>,
-    #[serde(default, rename = "@TheT")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@TheT")]
     pub the_t: Option<i32>,
-    #[serde(default, rename = "@TheC")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@TheC")]
     pub the_c: Option<i32>,
     #[serde(rename = "@X")]
     pub x: f32,
@@ -2183,7 +3759,7 @@ pub struct Rectangle {
     pub width: f32,
     #[serde(rename = "@Height")]
     pub height: f32,
-    #[serde(default, rename = "Transform")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "Transform")]
     pub transform: Option<AffineTransform>,
     #[serde(default, rename = "AnnotationRef")]
     pub annotation_ref: Vec<AnnotationRef>,
@@ -2199,85 +3775,196 @@ impl Rectangle {
 #[cfg_attr(feature = "python", derive(IntoPyObject))]
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Rights {
-    #[serde(default, rename = "RightsHolder")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "RightsHolder"
+    )]
     pub rights_holder: Option<String>,
-    #[serde(default, rename = "RightsHeld")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "RightsHeld"
+    )]
     pub rights_held: Option<String>,
 }
 #[cfg_attr(feature = "python", derive(IntoPyObject))]
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(from = "RoiUnionShapes")]
 pub struct RoiUnion {
-    #[serde(default, rename = "ShapeGroup")]
+    #[serde(default, rename = "$value")]
     pub shape_group: Vec<ShapeGroup>,
 }
+/// the shapes in a Union, also accepting shapes wrapped in a ShapeGroup element as this crate
+/// used to expect, elements which are not shapes are ignored
+#[derive(Deserialize)]
+struct RoiUnionShapes {
+    #[serde(default, rename = "$value")]
+    shapes: Vec<RoiUnionShape>,
+}
+#[derive(Deserialize)]
+enum RoiUnionShape {
+    Rectangle(Rectangle),
+    Mask(Mask),
+    Point(Label),
+    Ellipse(Ellipse),
+    Line(Line),
+    Polyline(Polyline),
+    Polygon(Polygon),
+    Label(Label),
+    ShapeGroup(WrappedShape),
+    #[serde(other)]
+    Other,
+}
+#[derive(Deserialize)]
+struct WrappedShape {
+    #[serde(rename = "$value")]
+    shape: Box<RoiUnionShape>,
+}
+impl RoiUnionShape {
+    fn into_shape(self) -> Option<ShapeGroup> {
+        Some(match self {
+            RoiUnionShape::Rectangle(rectangle) => ShapeGroup::Rectangle(rectangle),
+            RoiUnionShape::Mask(mask) => ShapeGroup::Mask(mask),
+            RoiUnionShape::Point(point) => ShapeGroup::Point(point),
+            RoiUnionShape::Ellipse(ellipse) => ShapeGroup::Ellipse(ellipse),
+            RoiUnionShape::Line(line) => ShapeGroup::Line(line),
+            RoiUnionShape::Polyline(polyline) => ShapeGroup::Polyline(polyline),
+            RoiUnionShape::Polygon(polygon) => ShapeGroup::Polygon(polygon),
+            RoiUnionShape::Label(label) => ShapeGroup::Label(label),
+            RoiUnionShape::ShapeGroup(wrapped) => return wrapped.shape.into_shape(),
+            RoiUnionShape::Other => return None,
+        })
+    }
+}
+impl From<RoiUnionShapes> for RoiUnion {
+    fn from(union: RoiUnionShapes) -> Self {
+        RoiUnion {
+            shape_group: union
+                .shapes
+                .into_iter()
+                .filter_map(RoiUnionShape::into_shape)
+                .collect(),
+        }
+    }
+}
 #[cfg_attr(feature = "python", derive(IntoPyObject))]
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Screen {
     #[serde(rename = "@ID")]
     pub id: String,
-    #[serde(default, rename = "@Name")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Name")]
     pub name: Option<String>,
-    #[serde(default, rename = "@ProtocolIdentifier")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@ProtocolIdentifier"
+    )]
     pub protocol_identifier: Option<String>,
-    #[serde(default, rename = "@ProtocolDescription")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@ProtocolDescription"
+    )]
     pub protocol_description: Option<String>,
-    #[serde(default, rename = "@ReagentSetDescription")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@ReagentSetDescription"
+    )]
     pub reagent_set_description: Option<String>,
-    #[serde(default, rename = "@ReagentSetIdentifier")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@ReagentSetIdentifier"
+    )]
     pub reagent_set_identifier: Option<String>,
-    #[serde(default, rename = "@Type")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Type")]
     pub r#type: Option<String>,
-    #[serde(default, rename = "Description")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "Description"
+    )]
     pub description: Option<String>,
     #[serde(default, rename = "Reagent")]
     pub reagent: Vec<Reagent>,
     #[serde(default, rename = "PlateRef")]
-    pub plate_ref: Vec<AnnotationRef>,
+    pub plate_ref: Vec<PlateRef>,
     #[serde(default, rename = "AnnotationRef")]
     pub annotation_ref: Vec<AnnotationRef>,
 }
 #[cfg_attr(feature = "python", derive(IntoPyObject))]
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ShapeType {
-    #[serde(default, rename = "@FillColor")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@FillColor"
+    )]
     pub fill_color: Option<i32>,
-    #[serde(default, rename = "@FillRule")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@FillRule")]
     pub fill_rule: Option<ShapeFillRuleType>,
-    #[serde(default, rename = "@StrokeColor")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@StrokeColor"
+    )]
     pub stroke_color: Option<i32>,
-    #[serde(default, rename = "@StrokeWidth")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@StrokeWidth"
+    )]
     pub stroke_width: Option<f32>,
     #[serde(
         default = "ShapeType::default_stroke_width_unit",
         rename = "@StrokeWidthUnit"
     )]
     pub stroke_width_unit: UnitsLength,
-    #[serde(default, rename = "@StrokeDashArray")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@StrokeDashArray"
+    )]
     pub stroke_dash_array: Option<String>,
-    #[serde(default, rename = "@Text")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Text")]
     pub text: Option<String>,
-    #[serde(default, rename = "@FontFamily")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@FontFamily"
+    )]
     pub font_family: Option<FontFamilyType>,
-    #[serde(default, rename = "@FontSize")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@FontSize")]
     pub font_size: Option<i32>,
     #[serde(
         default = "ShapeType::default_font_size_unit",
         rename = "@FontSizeUnit"
     )]
     pub font_size_unit: UnitsLength,
-    #[serde(default, rename = "@FontStyle")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@FontStyle"
+    )]
     pub font_style: Option<ShapeFontStyleType>,
-    #[serde(default, rename = "@Locked")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_optional_bool",
+        rename = "@Locked"
+    )]
     pub locked: Option<bool>,
     #[serde(rename = "@ID")]
     pub id: String,
-    #[serde(default, rename = "@TheZ")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@TheZ")]
     pub the_z: Option<i32>,
-    #[serde(default, rename = "@TheT")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@TheT")]
     pub the_t: Option<i32>,
-    #[serde(default, rename = "@TheC")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@TheC")]
     pub the_c: Option<i32>,
-    #[serde(default, rename = "Transform")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "Transform")]
     pub transform: Option<AffineTransform>,
     #[serde(default, rename = "AnnotationRef")]
     pub annotation_ref: Vec<AnnotationRef>,
@@ -2290,14 +3977,14 @@ impl ShapeType {
         UnitsLength::Pixel
     }
 }
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum ShapeFillRuleType {
     #[serde(rename = "EvenOdd")]
     EvenOdd,
     #[serde(rename = "NonZero")]
     NonZero,
 }
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum ShapeFontStyleType {
     #[serde(rename = "Bold")]
     Bold,
@@ -2308,7 +3995,6 @@ pub enum ShapeFontStyleType {
     #[serde(rename = "Normal")]
     Normal,
 }
-#[cfg_attr(feature = "python", derive(IntoPyObject))]
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum ShapeGroup {
     #[serde(rename = "Rectangle")]
@@ -2333,15 +4019,15 @@ pub enum ShapeGroup {
 pub struct StageLabel {
     #[serde(rename = "@Name")]
     pub name: String,
-    #[serde(default, rename = "@X")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@X")]
     pub x: Option<f32>,
     #[serde(default = "StageLabel::default_x_unit", rename = "@XUnit")]
     pub x_unit: UnitsLength,
-    #[serde(default, rename = "@Y")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Y")]
     pub y: Option<f32>,
     #[serde(default = "StageLabel::default_y_unit", rename = "@YUnit")]
     pub y_unit: UnitsLength,
-    #[serde(default, rename = "@Z")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Z")]
     pub z: Option<f32>,
     #[serde(default = "StageLabel::default_z_unit", rename = "@ZUnit")]
     pub z_unit: UnitsLength,
@@ -2361,10 +4047,9 @@ impl StageLabel {
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct StructuredAnnotations {
     #[serde(default, rename = "$value")]
-    pub content: Option<StructuredAnnotationsContent>,
+    pub content: Vec<StructuredAnnotationsContent>,
 }
 #[allow(clippy::enum_variant_names)]
-#[cfg_attr(feature = "python", derive(IntoPyObject))]
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum StructuredAnnotationsContent {
     #[serde(rename = "XMLAnnotation")]
@@ -2382,14 +4067,120 @@ pub enum StructuredAnnotationsContent {
     #[serde(rename = "BooleanAnnotation")]
     BooleanAnnotation(BooleanAnnotation),
     #[serde(rename = "TimestampAnnotation")]
-    TimestampAnnotation(CommentAnnotation),
+    TimestampAnnotation(TimestampAnnotation),
     #[serde(rename = "TagAnnotation")]
-    TagAnnotation(CommentAnnotation),
+    TagAnnotation(TagAnnotation),
     #[serde(rename = "TermAnnotation")]
-    TermAnnotation(CommentAnnotation),
+    TermAnnotation(TermAnnotation),
     #[serde(rename = "MapAnnotation")]
     MapAnnotation(MapAnnotation),
 }
+impl StructuredAnnotationsContent {
+    pub fn id(&self) -> &str {
+        match self {
+            StructuredAnnotationsContent::XmlAnnotation(a) => &a.id,
+            StructuredAnnotationsContent::FileAnnotation(a) => &a.id,
+            StructuredAnnotationsContent::ListAnnotation(a) => &a.id,
+            StructuredAnnotationsContent::LongAnnotation(a) => &a.id,
+            StructuredAnnotationsContent::DoubleAnnotation(a) => &a.id,
+            StructuredAnnotationsContent::CommentAnnotation(a) => &a.id,
+            StructuredAnnotationsContent::BooleanAnnotation(a) => &a.id,
+            StructuredAnnotationsContent::TimestampAnnotation(a) => &a.id,
+            StructuredAnnotationsContent::TagAnnotation(a) => &a.id,
+            StructuredAnnotationsContent::TermAnnotation(a) => &a.id,
+            StructuredAnnotationsContent::MapAnnotation(a) => &a.id,
+        }
+    }
+
+    pub fn id_mut(&mut self) -> &mut String {
+        match self {
+            StructuredAnnotationsContent::XmlAnnotation(a) => &mut a.id,
+            StructuredAnnotationsContent::FileAnnotation(a) => &mut a.id,
+            StructuredAnnotationsContent::ListAnnotation(a) => &mut a.id,
+            StructuredAnnotationsContent::LongAnnotation(a) => &mut a.id,
+            StructuredAnnotationsContent::DoubleAnnotation(a) => &mut a.id,
+            StructuredAnnotationsContent::CommentAnnotation(a) => &mut a.id,
+            StructuredAnnotationsContent::BooleanAnnotation(a) => &mut a.id,
+            StructuredAnnotationsContent::TimestampAnnotation(a) => &mut a.id,
+            StructuredAnnotationsContent::TagAnnotation(a) => &mut a.id,
+            StructuredAnnotationsContent::TermAnnotation(a) => &mut a.id,
+            StructuredAnnotationsContent::MapAnnotation(a) => &mut a.id,
+        }
+    }
+
+    /// the name of the variant, like "MapAnnotation"
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            StructuredAnnotationsContent::XmlAnnotation(_) => "XmlAnnotation",
+            StructuredAnnotationsContent::FileAnnotation(_) => "FileAnnotation",
+            StructuredAnnotationsContent::ListAnnotation(_) => "ListAnnotation",
+            StructuredAnnotationsContent::LongAnnotation(_) => "LongAnnotation",
+            StructuredAnnotationsContent::DoubleAnnotation(_) => "DoubleAnnotation",
+            StructuredAnnotationsContent::CommentAnnotation(_) => "CommentAnnotation",
+            StructuredAnnotationsContent::BooleanAnnotation(_) => "BooleanAnnotation",
+            StructuredAnnotationsContent::TimestampAnnotation(_) => "TimestampAnnotation",
+            StructuredAnnotationsContent::TagAnnotation(_) => "TagAnnotation",
+            StructuredAnnotationsContent::TermAnnotation(_) => "TermAnnotation",
+            StructuredAnnotationsContent::MapAnnotation(_) => "MapAnnotation",
+        }
+    }
+}
+#[cfg_attr(feature = "python", derive(IntoPyObject))]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TagAnnotation {
+    #[serde(rename = "@ID")]
+    pub id: String,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@Namespace"
+    )]
+    pub namespace: Option<String>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@Annotator"
+    )]
+    pub annotator: Option<String>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "Description"
+    )]
+    pub description: Option<String>,
+    #[serde(default, rename = "AnnotationRef")]
+    pub annotation_ref: Vec<AnnotationRef>,
+    #[serde(rename = "Value")]
+    pub value: String,
+}
+#[cfg_attr(feature = "python", derive(IntoPyObject))]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TermAnnotation {
+    #[serde(rename = "@ID")]
+    pub id: String,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@Namespace"
+    )]
+    pub namespace: Option<String>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@Annotator"
+    )]
+    pub annotator: Option<String>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "Description"
+    )]
+    pub description: Option<String>,
+    #[serde(default, rename = "AnnotationRef")]
+    pub annotation_ref: Vec<AnnotationRef>,
+    #[serde(rename = "Value")]
+    pub value: String,
+}
 #[cfg_attr(feature = "python", derive(IntoPyObject))]
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct TiffData {
@@ -2401,9 +4192,13 @@ pub struct TiffData {
     pub first_t: i32,
     #[serde(default = "TiffData::default_first_c", rename = "@FirstC")]
     pub first_c: i32,
-    #[serde(default, rename = "@PlaneCount")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@PlaneCount"
+    )]
     pub plane_count: Option<i32>,
-    #[serde(default, rename = "UUID")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "UUID")]
     pub uuid: Option<TiffDataUuid>,
 }
 impl TiffData {
@@ -2423,57 +4218,265 @@ impl TiffData {
 #[cfg_attr(feature = "python", derive(IntoPyObject))]
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct TiffDataUuid {
-    #[serde(default, rename = "@FileName")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@FileName")]
     pub file_name: Option<String>,
-    #[serde(rename = "$text")]
+    #[serde(default, rename = "$text")]
     pub content: String,
 }
+/// whether the value has the form urn:uuid:xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx, like the
+/// UniversallyUniqueIdentifier of the schema
+pub(crate) fn is_valid_uuid_urn(value: &str) -> bool {
+    value.strip_prefix("urn:uuid:").is_some_and(|uuid| {
+        uuid.len() == 36
+            && uuid.char_indices().all(|(i, c)| match i {
+                8 | 13 | 18 | 23 => c == '-',
+                _ => c.is_ascii_hexdigit(),
+            })
+    })
+}
+
+impl TiffDataUuid {
+    /// whether the content has the form urn:uuid:xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx
+    pub fn is_valid_urn(&self) -> bool {
+        is_valid_uuid_urn(&self.content)
+    }
+
+    /// the UUID after the urn:uuid: prefix
+    #[cfg(feature = "uuid")]
+    pub fn uuid_value(&self) -> Option<uuid::Uuid> {
+        uuid::Uuid::try_parse(self.content.strip_prefix("urn:uuid:")?).ok()
+    }
+}
+#[cfg_attr(feature = "python", derive(IntoPyObject))]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TimestampAnnotation {
+    #[serde(rename = "@ID")]
+    pub id: String,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@Namespace"
+    )]
+    pub namespace: Option<String>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@Annotator"
+    )]
+    pub annotator: Option<String>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "Description"
+    )]
+    pub description: Option<String>,
+    #[serde(default, rename = "AnnotationRef")]
+    pub annotation_ref: Vec<AnnotationRef>,
+    /// an xsd:dateTime, as it is in the xml, see [TimestampAnnotation::is_valid]
+    #[serde(rename = "Value")]
+    pub value: String,
+}
+/// whether value has the form of an xsd:dateTime: [-]YYYY-MM-DDThh:mm:ss[.s+][Z|(+|-)hh:mm]
+pub(crate) fn is_xsd_date_time(value: &str) -> bool {
+    fn number(value: &str, digits: usize, range: std::ops::RangeInclusive<u32>) -> bool {
+        value.len() == digits
+            && value.bytes().all(|b| b.is_ascii_digit())
+            && value.parse().is_ok_and(|n| range.contains(&n))
+    }
+    let value = value.strip_prefix('-').unwrap_or(value);
+    let Some((date, time)) = value.split_once('T') else {
+        return false;
+    };
+    let (time, offset) = match time.find(['Z', '+', '-']) {
+        Some(i) => time.split_at(i),
+        None => (time, ""),
+    };
+    let (time, fraction) = time.split_once('.').unwrap_or((time, "0"));
+    let date = date.rsplitn(3, '-').collect::<Vec<_>>();
+    let time = time.split(':').collect::<Vec<_>>();
+    let offset_ok = match offset.strip_prefix(['+', '-']) {
+        Some(offset) => offset
+            .split_once(':')
+            .is_some_and(|(h, m)| number(h, 2, 0..=14) && number(m, 2, 0..=59)),
+        None => offset.is_empty() || offset == "Z",
+    };
+    matches!(date[..], [day, month, year] if year.len() >= 4
+        && year.bytes().all(|b| b.is_ascii_digit())
+        && number(month, 2, 1..=12)
+        && number(day, 2, 1..=31))
+        && matches!(time[..], [hour, minute, second] if number(hour, 2, 0..=24)
+            && number(minute, 2, 0..=59)
+            && number(second, 2, 0..=60))
+        && !fraction.is_empty()
+        && fraction.bytes().all(|b| b.is_ascii_digit())
+        && offset_ok
+}
+impl TimestampAnnotation {
+    /// whether the value has the form of an xsd:dateTime, [Ome::validate] reports the
+    /// annotations of which it has not
+    pub fn is_valid(&self) -> bool {
+        is_xsd_date_time(&self.value)
+    }
+}
+/// a bool which may also be written as 1, 0, True or False, like by some vendors' software
+struct LenientBool(bool);
+impl<'de> Deserialize<'de> for LenientBool {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor;
+        impl serde::de::Visitor<'_> for Visitor {
+            type Value = LenientBool;
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("true, false, 1, 0, True or False")
+            }
+            fn visit_bool<E: serde::de::Error>(self, value: bool) -> Result<LenientBool, E> {
+                Ok(LenientBool(value))
+            }
+            fn visit_u64<E: serde::de::Error>(self, value: u64) -> Result<LenientBool, E> {
+                match value {
+                    0 => Ok(LenientBool(false)),
+                    1 => Ok(LenientBool(true)),
+                    _ => Err(E::invalid_value(
+                        serde::de::Unexpected::Unsigned(value),
+                        &self,
+                    )),
+                }
+            }
+            fn visit_i64<E: serde::de::Error>(self, value: i64) -> Result<LenientBool, E> {
+                match value {
+                    0 => Ok(LenientBool(false)),
+                    1 => Ok(LenientBool(true)),
+                    _ => Err(E::invalid_value(
+                        serde::de::Unexpected::Signed(value),
+                        &self,
+                    )),
+                }
+            }
+            fn visit_str<E: serde::de::Error>(self, value: &str) -> Result<LenientBool, E> {
+                match value.trim() {
+                    "true" | "1" | "True" => Ok(LenientBool(true)),
+                    "false" | "0" | "False" => Ok(LenientBool(false)),
+                    _ => Err(E::invalid_value(serde::de::Unexpected::Str(value), &self)),
+                }
+            }
+        }
+        // quick-xml gives the text to visit_str if it is not true, false, 1 or 0
+        deserializer.deserialize_bool(Visitor)
+    }
+}
+fn deserialize_bool<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<bool, D::Error> {
+    Ok(LenientBool::deserialize(deserializer)?.0)
+}
+fn deserialize_optional_bool<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<bool>, D::Error> {
+    Ok(Option::<LenientBool>::deserialize(deserializer)?.map(|value| value.0))
+}
+#[cfg(feature = "chrono")]
+impl TimestampAnnotation {
+    /// the value, taken to be in UTC when it has no timezone offset, a value which is not quite an
+    /// xsd:dateTime, like 2025-01-29 14:42:42, is accepted as well
+    pub fn date_time(&self) -> Result<chrono::DateTime<chrono::FixedOffset>, Error> {
+        parse_date_time(&self.value)
+    }
+}
 #[cfg_attr(feature = "python", derive(IntoPyObject))]
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct TransmittanceRange {
-    #[serde(default, rename = "@CutIn")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@CutIn")]
     pub cut_in: Option<f32>,
     #[serde(
         default = "TransmittanceRange::default_cut_in_unit",
         rename = "@CutInUnit"
     )]
     pub cut_in_unit: UnitsLength,
-    #[serde(default, rename = "@CutOut")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@CutOut")]
     pub cut_out: Option<f32>,
     #[serde(
         default = "TransmittanceRange::default_cut_out_unit",
         rename = "@CutOutUnit"
     )]
     pub cut_out_unit: UnitsLength,
-    #[serde(default, rename = "@CutInTolerance")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@CutInTolerance"
+    )]
     pub cut_in_tolerance: Option<f32>,
     #[serde(
         default = "TransmittanceRange::default_cut_in_tolerance_unit",
         rename = "@CutInToleranceUnit"
     )]
     pub cut_in_tolerance_unit: UnitsLength,
-    #[serde(default, rename = "@CutOutTolerance")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@CutOutTolerance"
+    )]
     pub cut_out_tolerance: Option<f32>,
     #[serde(
         default = "TransmittanceRange::default_cut_out_tolerance_unit",
         rename = "@CutOutToleranceUnit"
     )]
     pub cut_out_tolerance_unit: UnitsLength,
-    #[serde(default, rename = "@Transmittance")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@Transmittance"
+    )]
     pub transmittance: Option<f32>,
 }
 impl TransmittanceRange {
     pub fn default_cut_in_unit() -> UnitsLength {
-        UnitsLength::m
+        UnitsLength::nm
     }
     pub fn default_cut_out_unit() -> UnitsLength {
-        UnitsLength::m
+        UnitsLength::nm
     }
     pub fn default_cut_in_tolerance_unit() -> UnitsLength {
-        UnitsLength::m
+        UnitsLength::nm
     }
     pub fn default_cut_out_tolerance_unit() -> UnitsLength {
-        UnitsLength::m
+        UnitsLength::nm
+    }
+}
+impl TransmittanceRange {
+    pub fn cut_in_nm(&self) -> Option<f64> {
+        self.cut_in_unit
+            .convert(&UnitsLength::nm, self.cut_in? as f64)
+            .ok()
+    }
+
+    pub fn cut_out_nm(&self) -> Option<f64> {
+        self.cut_out_unit
+            .convert(&UnitsLength::nm, self.cut_out? as f64)
+            .ok()
+    }
+
+    /// the center of the band between cut in and cut out
+    pub fn center_nm(&self) -> Option<f64> {
+        Some((self.cut_in_nm()? + self.cut_out_nm()?) / 2.0)
+    }
+
+    /// the width of the band between cut in and cut out
+    pub fn bandwidth_nm(&self) -> Option<f64> {
+        Some(self.cut_out_nm()? - self.cut_in_nm()?)
+    }
+
+    /// whether the wavelength in nm is between cut in and cut out, inclusive, false if either is
+    /// missing
+    pub fn contains_wavelength(&self, nm: f32) -> bool {
+        match (self.cut_in_nm(), self.cut_out_nm()) {
+            (Some(cut_in), Some(cut_out)) => (cut_in..=cut_out).contains(&(nm as f64)),
+            _ => false,
+        }
+    }
+
+    /// the band in nm in which both ranges transmit, None if they do not overlap or if a cut in
+    /// or cut out is missing
+    pub fn overlap(&self, other: &Self) -> Option<(f32, f32)> {
+        let cut_in = self.cut_in_nm()?.max(other.cut_in_nm()?);
+        let cut_out = self.cut_out_nm()?.min(other.cut_out_nm()?);
+        (cut_in <= cut_out).then_some((cut_in as f32, cut_out as f32))
     }
 }
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, FromStr, IterVariants)]
@@ -2687,24 +4690,36 @@ pub struct Well {
     pub column: i32,
     #[serde(rename = "@Row")]
     pub row: i32,
-    #[serde(default, rename = "@ExternalDescription")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@ExternalDescription"
+    )]
     pub external_description: Option<String>,
-    #[serde(default, rename = "@ExternalIdentifier")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@ExternalIdentifier"
+    )]
     pub external_identifier: Option<String>,
-    #[serde(default, rename = "@Type")]
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "@Type")]
     pub r#type: Option<String>,
     #[serde(default = "Well::default_color", rename = "@Color")]
     pub color: i32,
     #[serde(default, rename = "WellSample")]
     pub well_sample: Vec<WellSample>,
-    #[serde(default, rename = "ReagentRef")]
-    pub reagent_ref: Option<AnnotationRef>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "ReagentRef"
+    )]
+    pub reagent_ref: Option<ReagentRef>,
     #[serde(default, rename = "AnnotationRef")]
     pub annotation_ref: Vec<AnnotationRef>,
 }
 impl Well {
     pub fn default_color() -> i32 {
-        0
+        -1
     }
 }
 #[cfg_attr(feature = "python", derive(IntoPyObject))]
@@ -2712,26 +4727,38 @@ impl Well {
 pub struct WellSample {
     #[serde(rename = "@ID")]
     pub id: String,
-    #[serde(default, rename = "@PositionX")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@PositionX"
+    )]
     pub position_x: Option<f32>,
     #[serde(
         default = "WellSample::default_position_x_unit",
         rename = "@PositionXUnit"
     )]
     pub position_x_unit: UnitsLength,
-    #[serde(default, rename = "@PositionY")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@PositionY"
+    )]
     pub position_y: Option<f32>,
     #[serde(
         default = "WellSample::default_position_y_unit",
         rename = "@PositionYUnit"
     )]
     pub position_y_unit: UnitsLength,
-    #[serde(default, rename = "@Timepoint")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@Timepoint"
+    )]
     pub timepoint: Option<String>,
     #[serde(rename = "@Index")]
     pub index: i32,
-    #[serde(default, rename = "ImageRef")]
-    pub image_ref: Option<AnnotationRef>,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "ImageRef")]
+    pub image_ref: Option<ImageRef>,
 }
 impl WellSample {
     pub fn default_position_x_unit() -> UnitsLength {
@@ -2746,19 +4773,222 @@ impl WellSample {
 pub struct XmlAnnotation {
     #[serde(rename = "@ID")]
     pub id: String,
-    #[serde(default, rename = "@Namespace")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@Namespace"
+    )]
     pub namespace: Option<String>,
-    #[serde(default, rename = "@Annotator")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "@Annotator"
+    )]
     pub annotator: Option<String>,
-    #[serde(default, rename = "Description")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "Description"
+    )]
     pub description: Option<String>,
     #[serde(default, rename = "AnnotationRef")]
     pub annotation_ref: Vec<AnnotationRef>,
     #[serde(rename = "Value")]
     pub value: XmlAnnotationValue,
 }
-#[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct XmlAnnotationValue;
+impl XmlAnnotation {
+    /// the xml inside Value, as it was in the document unless it was changed, see
+    /// [XmlAnnotationValue]
+    pub fn value_text(&self) -> String {
+        self.value.to_string()
+    }
+}
+/// the content of the Value of an XMLAnnotation, which can be any xml, the children are its
+/// elements with their prefixes, attributes and text, without comments, processing instructions
+/// and the whitespace between elements, and with CDATA as text, the inner xml as it was in the
+/// document is kept in verbatim, and written instead of the children as long as they are not
+/// changed
+#[derive(Clone, Debug, Default)]
+pub struct XmlAnnotationValue {
+    pub attributes: Vec<(String, String)>,
+    pub children: Vec<XmlNode>,
+    pub verbatim: Option<String>,
+}
+impl XmlAnnotationValue {
+    /// the inner xml as it was in the document, if the children are still what was read from it
+    pub fn verbatim_text(&self) -> Option<&str> {
+        let verbatim = self.verbatim.as_deref()?;
+        let value = crate::preserve::parse_tree(&format!("<Value>{verbatim}</Value>")).ok()?;
+        (value.children == self.children).then_some(verbatim)
+    }
+}
+/// values are equal when their attributes and children are, however they were written
+impl PartialEq for XmlAnnotationValue {
+    fn eq(&self, other: &Self) -> bool {
+        self.attributes == other.attributes && self.children == other.children
+    }
+}
+#[derive(Clone, Debug, PartialEq)]
+pub enum XmlNode {
+    Text(String),
+    Element(XmlElement),
+}
+#[derive(Clone, Debug, PartialEq)]
+pub struct XmlElement {
+    pub name: String,
+    pub attributes: Vec<(String, String)>,
+    pub children: Vec<XmlNode>,
+}
+/// serde visitor for the attributes and children of an arbitrary element, quick-xml hands them to
+/// us as a map in document order, with attributes prefixed by @ and text under $text
+struct XmlContentVisitor;
+impl<'de> serde::de::Visitor<'de> for XmlContentVisitor {
+    type Value = XmlAnnotationValue;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("xml content")
+    }
+
+    fn visit_unit<E: serde::de::Error>(self) -> Result<Self::Value, E> {
+        Ok(XmlAnnotationValue::default())
+    }
+
+    fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        self.visit_string(v.to_string())
+    }
+
+    fn visit_string<E: serde::de::Error>(self, v: String) -> Result<Self::Value, E> {
+        #[cfg(feature = "json")]
+        if crate::json::xml_as_text() {
+            return crate::json::parse_xml_annotation_value(&v).map_err(E::custom);
+        }
+        Ok(XmlAnnotationValue {
+            attributes: Vec::new(),
+            children: if v.is_empty() {
+                Vec::new()
+            } else {
+                vec![XmlNode::Text(v)]
+            },
+            verbatim: None,
+        })
+    }
+
+    fn visit_map<A: serde::de::MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut value = XmlAnnotationValue::default();
+        while let Some(key) = map.next_key::<String>()? {
+            if let Some(attribute) = key.strip_prefix('@') {
+                value
+                    .attributes
+                    .push((attribute.to_string(), map.next_value()?));
+            } else if key == "$text" {
+                value.children.push(XmlNode::Text(map.next_value()?));
+            } else {
+                let XmlAnnotationValue {
+                    attributes,
+                    children,
+                    ..
+                } = map.next_value()?;
+                value.children.push(XmlNode::Element(XmlElement {
+                    name: key,
+                    attributes,
+                    children,
+                }));
+            }
+        }
+        Ok(value)
+    }
+}
+impl<'de> Deserialize<'de> for XmlAnnotationValue {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(XmlContentVisitor)
+    }
+}
+/// attributes and children as a map in the form quick-xml expects
+struct XmlContent<'a>(&'a [(String, String)], &'a [XmlNode]);
+impl Serialize for XmlContent<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeMap;
+
+        let mut map = serializer.serialize_map(None)?;
+        for (name, value) in self.0 {
+            map.serialize_entry(&format!("@{name}"), value)?;
+        }
+        for child in self.1 {
+            match child {
+                XmlNode::Text(text) => map.serialize_entry("$text", text)?,
+                XmlNode::Element(element) => map.serialize_entry(
+                    &element.name,
+                    &XmlContent(&element.attributes, &element.children),
+                )?,
+            }
+        }
+        map.end()
+    }
+}
+impl Serialize for XmlAnnotationValue {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[cfg(feature = "json")]
+        if crate::json::xml_as_text() {
+            return serializer.serialize_str(&self.to_string());
+        }
+        XmlContent(&self.attributes, &self.children).serialize(serializer)
+    }
+}
+impl fmt::Display for XmlNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            XmlNode::Text(text) => write!(f, "{}", quick_xml::escape::escape(text)),
+            XmlNode::Element(element) => {
+                write!(f, "<{}", element.name)?;
+                for (name, value) in &element.attributes {
+                    write!(f, r#" {name}="{}""#, quick_xml::escape::escape(value))?;
+                }
+                if element.children.is_empty() {
+                    write!(f, "/>")
+                } else {
+                    write!(f, ">")?;
+                    for child in &element.children {
+                        write!(f, "{child}")?;
+                    }
+                    write!(f, "</{}>", element.name)
+                }
+            }
+        }
+    }
+}
+/// the inner xml, as it was in the document if it is not changed
+impl fmt::Display for XmlAnnotationValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(verbatim) = self.verbatim_text() {
+            return f.write_str(verbatim);
+        }
+        for child in &self.children {
+            write!(f, "{child}")?;
+        }
+        Ok(())
+    }
+}
+#[cfg(feature = "python")]
+impl<'py> IntoPyObject<'py> for XmlAnnotationValue {
+    type Target = PyString;
+    type Output = Bound<'py, Self::Target>;
+    type Error = PyErr;
+
+    fn into_pyobject(self, py: Python<'py>) -> PyResult<Self::Output> {
+        Ok(self.to_string().into_pyobject(py)?)
+    }
+}
+
+#[cfg(feature = "python")]
+impl<'py> IntoPyObject<'py> for XmlElement {
+    type Target = PyString;
+    type Output = Bound<'py, Self::Target>;
+    type Error = PyErr;
+
+    fn into_pyobject(self, py: Python<'py>) -> PyResult<Self::Output> {
+        Ok(XmlNode::Element(self).to_string().into_pyobject(py)?)
+    }
+}
 
 pub trait Convert: PartialEq {
     /// conversion factor between this and SI value
@@ -2787,6 +5017,43 @@ macro_rules! impl_enum_variants {
     };
 }
 
+macro_rules! impl_deref_vec {
+    ($($t:ty: $i:ty $(,)?)*) => {
+        $(
+            impl Deref for $t {
+                type Target = [$i];
+
+                fn deref(&self) -> &Self::Target {
+                    &self.0
+                }
+            }
+
+            impl<'a> IntoIterator for &'a $t {
+                type Item = &'a $i;
+                type IntoIter = std::slice::Iter<'a, $i>;
+
+                fn into_iter(self) -> Self::IntoIter {
+                    self.0.iter()
+                }
+            }
+
+            impl IntoIterator for $t {
+                type Item = $i;
+                type IntoIter = std::vec::IntoIter<$i>;
+
+                fn into_iter(self) -> Self::IntoIter {
+                    self.0.into_iter()
+                }
+            }
+        )*
+    };
+}
+
+impl_deref_vec!(
+    ExperimentType: ExperimentItemType,
+    MicrobeamManipulationType: MicrobeamManipulationItemType,
+);
+
 impl_enum_variants!(
     UnitsElectricPotential,
     UnitsFrequency,
@@ -2797,167 +5064,188 @@ impl_enum_variants!(
     UnitsTime,
 );
 
-impl Convert for UnitsElectricPotential {
-    fn as_si(&self) -> Result<f64, Error> {
-        match self {
-            UnitsElectricPotential::YV => Ok(1e24),
-            UnitsElectricPotential::ZV => Ok(1e21),
-            UnitsElectricPotential::EV => Ok(1e18),
-            UnitsElectricPotential::PV => Ok(1e15),
-            UnitsElectricPotential::TV => Ok(1e12),
-            UnitsElectricPotential::GV => Ok(1e9),
-            UnitsElectricPotential::MV => Ok(1e6),
-            UnitsElectricPotential::kV => Ok(1e3),
-            UnitsElectricPotential::hV => Ok(1e2),
-            UnitsElectricPotential::daV => Ok(1e1),
-            UnitsElectricPotential::V => Ok(1e0),
-            UnitsElectricPotential::dV => Ok(1e-1),
-            UnitsElectricPotential::cV => Ok(1e-2),
-            UnitsElectricPotential::mV => Ok(1e-3),
-            UnitsElectricPotential::uV => Ok(1e-6),
-            UnitsElectricPotential::nV => Ok(1e-9),
-            UnitsElectricPotential::pV => Ok(1e-12),
-            UnitsElectricPotential::fV => Ok(1e-15),
-            UnitsElectricPotential::aV => Ok(1e-18),
-            UnitsElectricPotential::zV => Ok(1e-21),
-            UnitsElectricPotential::yV => Ok(1e-24),
-        }
+/// the factor of an SI prefix like "k" or "da", 1 for no prefix, NaN for an unknown prefix
+pub fn si_prefix_factor(prefix: &str) -> f64 {
+    match prefix {
+        "Y" => 1e24,
+        "Z" => 1e21,
+        "E" => 1e18,
+        "P" => 1e15,
+        "T" => 1e12,
+        "G" => 1e9,
+        "M" => 1e6,
+        "k" => 1e3,
+        "h" => 1e2,
+        "da" => 1e1,
+        "" => 1e0,
+        "d" => 1e-1,
+        "c" => 1e-2,
+        "m" => 1e-3,
+        "u" | "µ" => 1e-6,
+        "n" => 1e-9,
+        "p" => 1e-12,
+        "f" => 1e-15,
+        "a" => 1e-18,
+        "z" => 1e-21,
+        "y" => 1e-24,
+        _ => f64::NAN,
     }
 }
 
-impl Convert for UnitsFrequency {
-    fn as_si(&self) -> Result<f64, Error> {
-        match self {
-            UnitsFrequency::YHz => Ok(1e24),
-            UnitsFrequency::ZHz => Ok(1e21),
-            UnitsFrequency::EHz => Ok(1e18),
-            UnitsFrequency::PHz => Ok(1e15),
-            UnitsFrequency::THz => Ok(1e12),
-            UnitsFrequency::GHz => Ok(1e9),
-            UnitsFrequency::MHz => Ok(1e6),
-            UnitsFrequency::kHz => Ok(1e3),
-            UnitsFrequency::hHz => Ok(1e2),
-            UnitsFrequency::daHz => Ok(1e1),
-            UnitsFrequency::Hz => Ok(1e0),
-            UnitsFrequency::dHz => Ok(1e-1),
-            UnitsFrequency::cHz => Ok(1e-2),
-            UnitsFrequency::mHz => Ok(1e-3),
-            UnitsFrequency::uHz => Ok(1e-6),
-            UnitsFrequency::nHz => Ok(1e-9),
-            UnitsFrequency::pHz => Ok(1e-12),
-            UnitsFrequency::fHz => Ok(1e-15),
-            UnitsFrequency::aHz => Ok(1e-18),
-            UnitsFrequency::zHz => Ok(1e-21),
-            UnitsFrequency::yHz => Ok(1e-24),
+/// Convert for units which are SI prefixes on base units, given as the factor of the base unit
+/// followed by the variants with their prefix, and some other units with explicit factors
+macro_rules! impl_si_convert {
+    ($t:ident, $($factor:expr => [$($prefixed:ident: $prefix:literal),* $(,)?]),+ $(,)?
+        $(; $($variant:ident => $value:expr),* $(,)?)?) => {
+        impl Convert for $t {
+            fn as_si(&self) -> Result<f64, Error> {
+                match self {
+                    $($($t::$prefixed => Ok(si_prefix_factor($prefix) * $factor),)*)+
+                    $($($t::$variant => $value,)*)?
+                }
+            }
         }
-    }
+    };
 }
 
-impl Convert for UnitsLength {
-    fn as_si(&self) -> Result<f64, Error> {
-        match self {
-            UnitsLength::Ym => Ok(1e24),
-            UnitsLength::Zm => Ok(1e21),
-            UnitsLength::Em => Ok(1e18),
-            UnitsLength::Pm => Ok(1e15),
-            UnitsLength::Tm => Ok(1e12),
-            UnitsLength::Gm => Ok(1e9),
-            UnitsLength::Mm => Ok(1e6),
-            UnitsLength::km => Ok(1e3),
-            UnitsLength::hm => Ok(1e2),
-            UnitsLength::dam => Ok(1e1),
-            UnitsLength::m => Ok(1e0),
-            UnitsLength::dm => Ok(1e-1),
-            UnitsLength::cm => Ok(1e-2),
-            UnitsLength::mm => Ok(1e-3),
-            UnitsLength::um => Ok(1e-6),
-            UnitsLength::nm => Ok(1e-9),
-            UnitsLength::pm => Ok(1e-12),
-            UnitsLength::fm => Ok(1e-15),
-            UnitsLength::am => Ok(1e-18),
-            UnitsLength::zm => Ok(1e-21),
-            UnitsLength::ym => Ok(1e-24),
-            UnitsLength::A => Ok(1e-10),
-            UnitsLength::Thou => Ok(2.54e-5),
-            UnitsLength::Li => Ok(5e2),
-            UnitsLength::In => Ok(2.54e-2),
-            UnitsLength::Ft => Ok(3.05e-1),
-            UnitsLength::Yd => Ok(9.14e-1),
-            UnitsLength::Mi => Ok(1.609344e3),
-            UnitsLength::Ua => Ok(1.496e11),
-            UnitsLength::Ly => Ok(9.461e15),
-            UnitsLength::Pc => Ok(3.086e16),
-            UnitsLength::Pt => Ok(3.52778e-4),
-            UnitsLength::Pixel => Err(Error::SizeOfUnknown("pixel".to_string())),
-            UnitsLength::ReferenceFrame => Err(Error::SizeOfUnknown("reference frame".to_string())),
-        }
+impl_si_convert!(
+    UnitsElectricPotential,
+    1e0 => [
+        YV: "Y", ZV: "Z", EV: "E", PV: "P", TV: "T", GV: "G", MV: "M", kV: "k", hV: "h", daV: "da",
+        V: "", dV: "d", cV: "c", mV: "m", uV: "u", nV: "n", pV: "p", fV: "f", aV: "a", zV: "z",
+        yV: "y",
+    ],
+);
+
+impl_si_convert!(
+    UnitsFrequency,
+    1e0 => [
+        YHz: "Y", ZHz: "Z", EHz: "E", PHz: "P", THz: "T", GHz: "G", MHz: "M", kHz: "k", hHz: "h",
+        daHz: "da", Hz: "", dHz: "d", cHz: "c", mHz: "m", uHz: "u", nHz: "n", pHz: "p", fHz: "f",
+        aHz: "a", zHz: "z", yHz: "y",
+    ],
+);
+
+impl_si_convert!(
+    UnitsLength,
+    1e0 => [
+        Ym: "Y", Zm: "Z", Em: "E", Pm: "P", Tm: "T", Gm: "G", Mm: "M", km: "k", hm: "h", dam: "da",
+        m: "", dm: "d", cm: "c", mm: "m", um: "u", nm: "n", pm: "p", fm: "f", am: "a", zm: "z",
+        ym: "y",
+    ];
+    A => Ok(1e-10),
+    Thou => Ok(2.54e-5),
+    Li => Ok(5e2),
+    In => Ok(2.54e-2),
+    Ft => Ok(3.05e-1),
+    Yd => Ok(9.14e-1),
+    Mi => Ok(1.609344e3),
+    Ua => Ok(1.496e11),
+    Ly => Ok(9.461e15),
+    Pc => Ok(3.086e16),
+    Pt => Ok(3.52778e-4),
+    Pixel => Err(Error::SizeOfUnknown("pixel".to_string())),
+    ReferenceFrame => Err(Error::SizeOfUnknown("reference frame".to_string())),
+);
+
+impl_si_convert!(
+    UnitsPower,
+    1e0 => [
+        YW: "Y", ZW: "Z", EW: "E", PW: "P", TW: "T", GW: "G", MW: "M", kW: "k", hW: "h", daW: "da",
+        W: "", dW: "d", cW: "c", mW: "m", uW: "u", nW: "n", pW: "p", fW: "f", aW: "a", zW: "z",
+        yW: "y",
+    ],
+);
+
+impl_si_convert!(
+    UnitsPressure,
+    1e0 => [
+        YPa: "Y", ZPa: "Z", EPa: "E", PPa: "P", TPa: "T", GPa: "G", MPa: "M", kPa: "k", hPa: "h",
+        daPa: "da", Pa: "", dPa: "d", cPa: "c", mPa: "m", uPa: "u", nPa: "n", pPa: "p", fPa: "f",
+        aPa: "a", zPa: "z", yPa: "y",
+    ],
+    1e5 => [Mbar: "M", kbar: "k", bar: "", dbar: "d", cbar: "c", mbar: "m"],
+    1.33322e3 => [Torr: "", mTorr: "m"];
+    atm => Ok(1.01325e5),
+    psi => Ok(6.89476e3),
+    mmHg => Ok(1.33322e2),
+);
+
+/// order units by their factor to SI, units without a factor, like pixel, are not ordered,
+/// UnitsTemperature has no order because its conversions are not just a factor
+macro_rules! impl_si_order {
+    ($($t:ty $(,)?)*) => {
+        $(
+            impl PartialOrd for $t {
+                fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+                    if self == other {
+                        return Some(std::cmp::Ordering::Equal);
+                    }
+                    match self.as_si().ok()?.partial_cmp(&other.as_si().ok()?)? {
+                        std::cmp::Ordering::Equal => None,
+                        ordering => Some(ordering),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_si_order!(
+    UnitsElectricPotential,
+    UnitsFrequency,
+    UnitsLength,
+    UnitsPower,
+    UnitsPressure,
+    UnitsTime,
+);
+
+/// a value in SI in the largest of the units, which are sorted from small to large, in which it
+/// is at least 1, or in the smallest unit if there is none, zero, infinite and NaN values are in
+/// the si unit
+fn humanize<U: Convert + Clone>(value_si: f64, units: &[U], si: U) -> (f64, U) {
+    if value_si == 0.0 || !value_si.is_finite() {
+        return (value_si, si);
+    }
+    let unit = units
+        .iter()
+        .rev()
+        .find(|unit| unit.as_si().is_ok_and(|factor| value_si.abs() >= factor))
+        .unwrap_or(&units[0]);
+    match unit.as_si() {
+        // the inverse of a factor like 1e-9 is a whole number, while the factor itself is not
+        // exact, so that 5e-7 m is 500 nm and not 499.99999999999994 nm
+        Ok(factor) if factor < 1.0 => (value_si * (1.0 / factor).round(), unit.clone()),
+        Ok(factor) => (value_si / factor, unit.clone()),
+        Err(_) => (value_si, si),
     }
 }
 
-impl Convert for UnitsPower {
-    fn as_si(&self) -> Result<f64, Error> {
-        match self {
-            UnitsPower::YW => Ok(1e24),
-            UnitsPower::ZW => Ok(1e21),
-            UnitsPower::EW => Ok(1e18),
-            UnitsPower::PW => Ok(1e15),
-            UnitsPower::TW => Ok(1e12),
-            UnitsPower::GW => Ok(1e9),
-            UnitsPower::MW => Ok(1e6),
-            UnitsPower::kW => Ok(1e3),
-            UnitsPower::hW => Ok(1e2),
-            UnitsPower::daW => Ok(1e1),
-            UnitsPower::W => Ok(1e0),
-            UnitsPower::dW => Ok(1e-1),
-            UnitsPower::cW => Ok(1e-2),
-            UnitsPower::mW => Ok(1e-3),
-            UnitsPower::uW => Ok(1e-6),
-            UnitsPower::nW => Ok(1e-9),
-            UnitsPower::pW => Ok(1e-12),
-            UnitsPower::fW => Ok(1e-15),
-            UnitsPower::aW => Ok(1e-18),
-            UnitsPower::zW => Ok(1e-21),
-            UnitsPower::yW => Ok(1e-24),
-        }
+impl UnitsLength {
+    /// a length in m in the metric unit in which it is at least 1 and less than 1000, like
+    /// (500.0, nm) for 5e-7 m
+    pub fn humanize(value_m: f64) -> (f64, UnitsLength) {
+        use UnitsLength::*;
+        humanize(
+            value_m,
+            &[
+                ym, zm, am, fm, pm, nm, um, mm, m, km, Mm, Gm, Tm, Pm, Em, Zm, Ym,
+            ],
+            m,
+        )
     }
 }
 
-impl Convert for UnitsPressure {
-    fn as_si(&self) -> Result<f64, Error> {
-        match self {
-            UnitsPressure::YPa => Ok(1e24),
-            UnitsPressure::ZPa => Ok(1e21),
-            UnitsPressure::EPa => Ok(1e18),
-            UnitsPressure::PPa => Ok(1e15),
-            UnitsPressure::TPa => Ok(1e12),
-            UnitsPressure::GPa => Ok(1e9),
-            UnitsPressure::MPa => Ok(1e6),
-            UnitsPressure::kPa => Ok(1e3),
-            UnitsPressure::hPa => Ok(1e2),
-            UnitsPressure::daPa => Ok(1e1),
-            UnitsPressure::Pa => Ok(1e0),
-            UnitsPressure::dPa => Ok(1e-1),
-            UnitsPressure::cPa => Ok(1e-2),
-            UnitsPressure::mPa => Ok(1e-3),
-            UnitsPressure::uPa => Ok(1e-6),
-            UnitsPressure::nPa => Ok(1e-9),
-            UnitsPressure::pPa => Ok(1e-12),
-            UnitsPressure::fPa => Ok(1e-15),
-            UnitsPressure::aPa => Ok(1e-18),
-            UnitsPressure::zPa => Ok(1e-21),
-            UnitsPressure::yPa => Ok(1e-24),
-            UnitsPressure::bar => Ok(1e5),
-            UnitsPressure::Mbar => Ok(1e11),
-            UnitsPressure::kbar => Ok(1e8),
-            UnitsPressure::dbar => Ok(1e4),
-            UnitsPressure::cbar => Ok(1e3),
-            UnitsPressure::mbar => Ok(1e2),
-            UnitsPressure::atm => Ok(1.01325e5),
-            UnitsPressure::psi => Ok(6.89476e3),
-            UnitsPressure::Torr => Ok(1.33322e3),
-            UnitsPressure::mTorr => Ok(1.33322),
-            UnitsPressure::mmHg => Ok(1.33322e2),
-        }
+impl UnitsTime {
+    /// a time in s in the metric unit in which it is at least 1 and less than 1000 if it is less
+    /// than a minute, and otherwise in min, h or d, like (25.0, min) for 1500 s
+    pub fn humanize(value_s: f64) -> (f64, UnitsTime) {
+        use UnitsTime::*;
+        humanize(
+            value_s,
+            &[ys, zs, r#as, fs, ps, ns, us, ms, s, min, h, d],
+            s,
+        )
     }
 }
 
@@ -2990,33 +5278,14 @@ impl Convert for UnitsTemperature {
     }
 }
 
-impl Convert for UnitsTime {
-    fn as_si(&self) -> Result<f64, Error> {
-        match self {
-            UnitsTime::Ys => Ok(1e24),
-            UnitsTime::Zs => Ok(1e21),
-            UnitsTime::Es => Ok(1e18),
-            UnitsTime::Ps => Ok(1e15),
-            UnitsTime::Ts => Ok(1e12),
-            UnitsTime::Gs => Ok(1e9),
-            UnitsTime::Ms => Ok(1e6),
-            UnitsTime::ks => Ok(1e3),
-            UnitsTime::hs => Ok(1e2),
-            UnitsTime::das => Ok(1e1),
-            UnitsTime::s => Ok(1e0),
-            UnitsTime::ds => Ok(1e-1),
-            UnitsTime::cs => Ok(1e-2),
-            UnitsTime::ms => Ok(1e-3),
-            UnitsTime::us => Ok(1e-6),
-            UnitsTime::ns => Ok(1e-9),
-            UnitsTime::ps => Ok(1e-12),
-            UnitsTime::fs => Ok(1e-15),
-            UnitsTime::r#as => Ok(1e-18),
-            UnitsTime::zs => Ok(1e-21),
-            UnitsTime::ys => Ok(1e-24),
-            UnitsTime::min => Ok(6e1),
-            UnitsTime::h => Ok(3.6e2),
-            UnitsTime::d => Ok(8.64e4),
-        }
-    }
-}
+impl_si_convert!(
+    UnitsTime,
+    1e0 => [
+        Ys: "Y", Zs: "Z", Es: "E", Ps: "P", Ts: "T", Gs: "G", Ms: "M", ks: "k", hs: "h", das: "da",
+        s: "", ds: "d", cs: "c", ms: "m", us: "u", ns: "n", ps: "p", fs: "f", r#as: "a", zs: "z",
+        ys: "y",
+    ];
+    min => Ok(6e1),
+    h => Ok(3.6e3),
+    d => Ok(8.64e4),
+);
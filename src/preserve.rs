@@ -0,0 +1,565 @@
+use crate::error::Error;
+use crate::ome::{Ome, StructuredAnnotationsContent, XmlAnnotationValue, XmlElement, XmlNode};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::reader::Reader;
+use std::collections::HashMap;
+use std::ops::Range;
+
+/// a child element, identified by its name and ID if it has an ID, so that it is found again after
+/// elements were added, removed or reordered, and otherwise by its name and its index among the
+/// siblings with that name
+#[cfg_attr(feature = "python", derive(pyo3::IntoPyObject))]
+#[derive(Clone, Debug, PartialEq)]
+pub enum PathStep {
+    Id(String, String),
+    Index(String, usize),
+}
+
+impl PathStep {
+    fn new(element: &XmlElement, index: usize) -> PathStep {
+        match element.attributes.iter().find(|(name, _)| name == "ID") {
+            Some((_, id)) => PathStep::Id(element.name.clone(), id.clone()),
+            None => PathStep::Index(element.name.clone(), index),
+        }
+    }
+
+    /// the index in children of the element this step points to
+    fn position(&self, children: &[XmlNode]) -> Option<usize> {
+        let mut counts = HashMap::new();
+        children.iter().position(|child| match child {
+            XmlNode::Element(child) => {
+                let count = counts.entry(child.name.as_str()).or_insert(0);
+                *count += 1;
+                PathStep::new(child, *count - 1) == *self
+            }
+            XmlNode::Text(_) => false,
+        })
+    }
+}
+
+/// xml that was not understood while parsing, located by the path of elements from the root
+/// (which is the empty path)
+#[cfg_attr(feature = "python", derive(pyo3::IntoPyObject))]
+#[derive(Clone, Debug, PartialEq)]
+pub enum UnknownXml {
+    Attribute {
+        path: Vec<PathStep>,
+        name: String,
+        value: String,
+    },
+    Element {
+        path: Vec<PathStep>,
+        /// the sibling after which the element occurred, None if it was the first child
+        after: Option<PathStep>,
+        element: XmlElement,
+    },
+}
+
+fn start_element(start: &BytesStart) -> Result<XmlElement, Error> {
+    let mut attributes = Vec::new();
+    for attribute in start.attributes() {
+        let attribute = attribute.map_err(quick_xml::Error::from)?;
+        attributes.push((
+            String::from_utf8_lossy(attribute.key.as_ref()).to_string(),
+            attribute.unescape_value()?.to_string(),
+        ));
+    }
+    Ok(XmlElement {
+        name: String::from_utf8_lossy(start.name().as_ref()).to_string(),
+        attributes,
+        children: Vec::new(),
+    })
+}
+
+/// add text to the element, after the text before it if the last child is text, and otherwise only
+/// if it is not just whitespace, because quick-xml gives the text around a reference like &amp;
+/// separately
+fn push_text(element: &mut XmlElement, text: &str) {
+    match element.children.last_mut() {
+        Some(XmlNode::Text(last)) => last.push_str(text),
+        _ if text.trim().is_empty() => {}
+        _ => element.children.push(XmlNode::Text(text.to_string())),
+    }
+}
+
+/// read a document into a tree preserving element and attribute names including prefixes,
+/// whitespace between elements is dropped
+pub(crate) fn parse_tree(s: &str) -> Result<XmlElement, Error> {
+    let mut reader = Reader::from_str(s);
+    let mut stack: Vec<XmlElement> = Vec::new();
+    loop {
+        match reader.read_event()? {
+            Event::Start(start) => stack.push(start_element(&start)?),
+            Event::Empty(start) => {
+                let element = start_element(&start)?;
+                match stack.last_mut() {
+                    Some(parent) => parent.children.push(XmlNode::Element(element)),
+                    None => return Ok(element),
+                }
+            }
+            Event::End(_) => {
+                let element = stack.pop().expect("quick-xml checks that tags match");
+                match stack.last_mut() {
+                    Some(parent) => parent.children.push(XmlNode::Element(element)),
+                    None => return Ok(element),
+                }
+            }
+            Event::Text(text) => {
+                let text = text.decode().map_err(quick_xml::Error::from)?;
+                if let Some(parent) = stack.last_mut() {
+                    push_text(
+                        parent,
+                        &quick_xml::escape::unescape(&text).map_err(quick_xml::Error::from)?,
+                    );
+                }
+            }
+            Event::GeneralRef(reference) => {
+                let text = match reference.resolve_char_ref()? {
+                    Some(c) => c.to_string(),
+                    None => {
+                        let name = reference.decode().map_err(quick_xml::Error::from)?;
+                        match quick_xml::escape::resolve_predefined_entity(&name) {
+                            Some(text) => text.to_string(),
+                            None => format!("&{name};"),
+                        }
+                    }
+                };
+                if let Some(parent) = stack.last_mut() {
+                    push_text(parent, &text);
+                }
+            }
+            Event::CData(data) => {
+                if let Some(parent) = stack.last_mut() {
+                    parent
+                        .children
+                        .push(XmlNode::Text(String::from_utf8_lossy(&data).to_string()));
+                }
+            }
+            Event::Eof => {
+                return Err(
+                    quick_xml::Error::Syntax(quick_xml::errors::SyntaxError::UnclosedTag).into(),
+                );
+            }
+            _ => {}
+        }
+    }
+}
+
+/// the name without its prefix
+fn local_name(name: &str) -> &str {
+    name.split_once(':').map_or(name, |(_, local)| local)
+}
+
+/// the child elements with this name, ignoring prefixes
+fn children_named<'a>(element: &'a XmlElement, name: &str) -> impl Iterator<Item = &'a XmlElement> {
+    element
+        .children
+        .iter()
+        .filter_map(move |child| match child {
+            XmlNode::Element(child) if local_name(&child.name) == name => Some(child),
+            _ => None,
+        })
+}
+
+/// the prefixes of the names of the elements and attributes in nodes
+fn prefixes<'a>(nodes: &'a [XmlNode], prefixes: &mut Vec<&'a str>) {
+    for node in nodes {
+        if let XmlNode::Element(element) = node {
+            let names = std::iter::once(&element.name)
+                .chain(element.attributes.iter().map(|(name, _)| name));
+            for name in names {
+                if let Some((prefix, _)) = name.split_once(':')
+                    && !matches!(prefix, "xml" | "xmlns")
+                    && !prefixes.contains(&prefix)
+                {
+                    prefixes.push(prefix);
+                }
+            }
+            self::prefixes(&element.children, prefixes);
+        }
+    }
+}
+
+/// where the Value of an XMLAnnotation is in a document
+enum ValueSpan {
+    /// the inner xml
+    Inner(Range<usize>),
+    /// the whole element, which is empty, like <Value/>
+    Empty(Range<usize>),
+}
+
+/// the Value of each XMLAnnotation in the document, with the ID of the XMLAnnotation
+fn value_spans(s: &str) -> Result<Vec<(String, ValueSpan)>, Error> {
+    let mut reader = Reader::from_str(s);
+    // the local names of the elements around the current event
+    let mut path: Vec<String> = Vec::new();
+    let mut id = None;
+    let mut spans = Vec::new();
+    loop {
+        let before = reader.buffer_position() as usize;
+        let (start, empty) = match reader.read_event()? {
+            Event::Start(start) => (start, false),
+            Event::Empty(start) => (start, true),
+            Event::End(_) => {
+                path.pop();
+                continue;
+            }
+            Event::Eof => return Ok(spans),
+            _ => continue,
+        };
+        let name = String::from_utf8_lossy(start.local_name().as_ref()).to_string();
+        let in_annotation =
+            path.len() == 3 && path[1] == "StructuredAnnotations" && path[2] == "XMLAnnotation";
+        if in_annotation && name == "Value" {
+            let span = if empty {
+                ValueSpan::Empty(before..reader.buffer_position() as usize)
+            } else {
+                let inner = reader.read_to_end(start.name())?;
+                ValueSpan::Inner(inner.start as usize..inner.end as usize)
+            };
+            if let Some(id) = id.take() {
+                spans.push((id, span));
+            }
+        } else if !empty {
+            if path.len() == 2 && name == "XMLAnnotation" {
+                id = start
+                    .try_get_attribute("ID")
+                    .map_err(quick_xml::Error::from)?
+                    .map(|id| id.unescape_value().map(|id| id.to_string()))
+                    .transpose()?;
+            }
+            path.push(name);
+        }
+    }
+}
+
+/// write the Value of the XMLAnnotations which have their inner xml as it was read, which serde
+/// cannot write, into xml written by serde
+pub(crate) fn write_verbatim_values(ome: &Ome, xml: String) -> Result<String, Error> {
+    let verbatim = ome
+        .annotations()
+        .filter_map(|annotation| match annotation {
+            StructuredAnnotationsContent::XmlAnnotation(annotation) => {
+                Some((annotation.id.as_str(), annotation.value.verbatim_text()?))
+            }
+            _ => None,
+        })
+        .collect::<HashMap<_, _>>();
+    if verbatim.is_empty() {
+        return Ok(xml);
+    }
+    let mut written = String::with_capacity(xml.len());
+    let mut copied = 0;
+    for (id, span) in value_spans(&xml)? {
+        let Some(text) = verbatim.get(id.as_str()) else {
+            continue;
+        };
+        match span {
+            ValueSpan::Inner(inner) => {
+                written.push_str(&xml[copied..inner.start]);
+                written.push_str(text);
+                copied = inner.end;
+            }
+            ValueSpan::Empty(element) => {
+                written.push_str(&xml[copied..element.start]);
+                written.push_str(xml[element.clone()].trim_end_matches("/>"));
+                written.push_str(&format!(">{text}</Value>"));
+                copied = element.end;
+            }
+        }
+    }
+    written.push_str(&xml[copied..]);
+    Ok(written)
+}
+
+/// the content of the Value of the XMLAnnotations in the document by their ID, read from the
+/// document itself because quick-xml's serde leaves out the prefixes of element names, the
+/// namespaces of the prefixes which are declared outside Value are declared on Value, and the
+/// inner xml is kept as it is so that it can be written again in the same way
+fn xml_annotation_values(s: &str) -> Result<HashMap<String, XmlAnnotationValue>, Error> {
+    let root = parse_tree(s)?;
+    let mut verbatim = value_spans(s)?
+        .into_iter()
+        .filter_map(|(id, span)| match span {
+            ValueSpan::Inner(inner) => Some((id, s[inner].to_string())),
+            ValueSpan::Empty(_) => None,
+        })
+        .collect::<HashMap<_, _>>();
+    let mut values = HashMap::new();
+    for structured_annotations in children_named(&root, "StructuredAnnotations") {
+        for annotation in children_named(structured_annotations, "XMLAnnotation") {
+            let (Some((_, id)), Some(value)) = (
+                annotation.attributes.iter().find(|(name, _)| name == "ID"),
+                children_named(annotation, "Value").next(),
+            ) else {
+                continue;
+            };
+            let mut attributes = value.attributes.clone();
+            let mut used = Vec::new();
+            prefixes(&value.children, &mut used);
+            for prefix in used {
+                let declaration = format!("xmlns:{prefix}");
+                if attributes.iter().any(|(name, _)| *name == declaration) {
+                    continue;
+                }
+                // the closest declaration
+                let namespace = [annotation, structured_annotations, &root]
+                    .into_iter()
+                    .find_map(|element| {
+                        element
+                            .attributes
+                            .iter()
+                            .find(|(name, _)| *name == declaration)
+                    });
+                if let Some((_, namespace)) = namespace {
+                    attributes.push((declaration, namespace.clone()));
+                }
+            }
+            values.insert(
+                id.clone(),
+                XmlAnnotationValue {
+                    attributes,
+                    children: value.children.clone(),
+                    verbatim: verbatim.remove(id),
+                },
+            );
+        }
+    }
+    Ok(values)
+}
+
+/// replace the content of the Value of the XMLAnnotations by what is in the document s, see
+/// [xml_annotation_values]
+pub(crate) fn read_xml_annotation_values(ome: &mut Ome, s: &str) -> Result<(), Error> {
+    let Some(structured_annotations) = &mut ome.structured_annotations else {
+        return Ok(());
+    };
+    let mut annotations = structured_annotations
+        .content
+        .iter_mut()
+        .filter_map(|annotation| match annotation {
+            StructuredAnnotationsContent::XmlAnnotation(annotation) => Some(annotation),
+            _ => None,
+        })
+        .peekable();
+    if annotations.peek().is_none() {
+        return Ok(());
+    }
+    let mut values = xml_annotation_values(s)?;
+    for annotation in annotations {
+        if let Some(value) = values.remove(&annotation.id) {
+            annotation.value = value;
+        }
+    }
+    Ok(())
+}
+
+/// the child elements with their path steps
+fn child_elements(element: &XmlElement) -> Vec<(PathStep, &XmlElement)> {
+    let mut counts = HashMap::new();
+    element
+        .children
+        .iter()
+        .filter_map(|child| match child {
+            XmlNode::Element(child) => {
+                let count = counts.entry(child.name.as_str()).or_insert(0);
+                *count += 1;
+                Some((PathStep::new(child, *count - 1), child))
+            }
+            XmlNode::Text(_) => None,
+        })
+        .collect()
+}
+
+/// everything in original that has no counterpart in known
+fn collect_unknown(
+    original: &XmlElement,
+    known: &XmlElement,
+    path: &mut Vec<PathStep>,
+    unknown: &mut Vec<UnknownXml>,
+) {
+    for (name, value) in &original.attributes {
+        if !known.attributes.iter().any(|(known, _)| known == name) {
+            unknown.push(UnknownXml::Attribute {
+                path: path.clone(),
+                name: name.clone(),
+                value: value.clone(),
+            });
+        }
+    }
+    let known_children = child_elements(known);
+    let mut after = None;
+    for (step, child) in child_elements(original) {
+        match known_children.iter().find(|(known, _)| *known == step) {
+            Some((_, known)) => {
+                path.push(step.clone());
+                collect_unknown(child, known, path, unknown);
+                path.pop();
+            }
+            None => unknown.push(UnknownXml::Element {
+                path: path.clone(),
+                after: after.clone(),
+                element: child.clone(),
+            }),
+        }
+        after = Some(step);
+    }
+}
+
+fn find_mut<'a>(element: &'a mut XmlElement, path: &[PathStep]) -> Option<&'a mut XmlElement> {
+    let Some((step, rest)) = path.split_first() else {
+        return Some(element);
+    };
+    let position = step.position(&element.children)?;
+    match &mut element.children[position] {
+        XmlNode::Element(child) => find_mut(child, rest),
+        XmlNode::Text(_) => None,
+    }
+}
+
+/// put the unknown xml back, unknown xml of which the location no longer exists is dropped
+fn insert_unknown(root: &mut XmlElement, unknown: &[UnknownXml]) {
+    for unknown in unknown {
+        match unknown {
+            UnknownXml::Attribute { path, name, value } => {
+                if let Some(element) = find_mut(root, path) {
+                    if !element.attributes.iter().any(|(n, _)| n == name) {
+                        element.attributes.push((name.clone(), value.clone()));
+                    }
+                }
+            }
+            UnknownXml::Element {
+                path,
+                after,
+                element,
+            } => {
+                if let Some(parent) = find_mut(root, path) {
+                    let position = match after {
+                        None => Some(0),
+                        Some(step) => step.position(&parent.children).map(|i| i + 1),
+                    };
+                    let position = position.unwrap_or(parent.children.len());
+                    parent
+                        .children
+                        .insert(position, XmlNode::Element(element.clone()));
+                }
+            }
+        }
+    }
+}
+
+impl Ome {
+    /// parse, and keep elements and attributes which are not in the schema, like vendor
+    /// extensions, in [Ome::unknown_xml] so that [Ome::to_xml_preserving] can write them again
+    pub fn from_str_preserving(s: &str) -> Result<Ome, Error> {
+        let mut ome: Ome = s.parse()?;
+        let known = parse_tree(&quick_xml::se::to_string_with_root("OME", &ome)?)?;
+        let mut unknown = Vec::new();
+        collect_unknown(
+            &parse_tree(&crate::resolve_abstract_elements(s)?)?,
+            &known,
+            &mut Vec::new(),
+            &mut unknown,
+        );
+        ome.unknown_xml = unknown;
+        Ok(ome)
+    }
+
+    /// serialize like [Ome::to_xml], including the xml in [Ome::unknown_xml]
+    pub fn to_xml_preserving(&self) -> Result<String, Error> {
+        let mut root = parse_tree(&self.to_xml()?)?;
+        insert_unknown(&mut root, &self.unknown_xml);
+        write_verbatim_values(self, XmlNode::Element(root).to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip_vendor_xml() -> Result<(), Error> {
+        let xml = std::fs::read_to_string("tests/vendor.xml")?;
+        let ome = Ome::from_str_preserving(&xml)?;
+        assert!(ome.unknown_xml.contains(&UnknownXml::Attribute {
+            path: vec![PathStep::Id("Image".to_string(), "Image:0".to_string())],
+            name: "Vendor".to_string(),
+            value: "Acme & Co".to_string(),
+        }));
+        let foo = r#"<MyVendor:Foo Mode="fast"><MyVendor:Bar>42</MyVendor:Bar></MyVendor:Foo>"#;
+        let written = ome.to_xml_preserving()?;
+        assert!(
+            written.contains(r#"<Image ID="Image:0" Name="vendor.tif" Vendor="Acme &amp; Co">"#)
+        );
+        assert!(written.contains(&format!("</AcquisitionDate>{foo}<Pixels ")));
+        assert!(written.contains(r#"xmlns:MyVendor="http://example.com/myvendor""#));
+
+        // writing again what was read from the written document gives the same
+        let again = Ome::from_str_preserving(&written)?;
+        assert_eq!(again.to_xml_preserving()?, written);
+        // and without preserving the vendor xml is dropped
+        let plain: Ome = xml.parse()?;
+        assert!(plain.unknown_xml.is_empty());
+        assert!(!plain.to_xml_preserving()?.contains("MyVendor"));
+        Ok(())
+    }
+
+    #[test]
+    fn reattach_by_id() -> Result<(), Error> {
+        let xml = std::fs::read_to_string("tests/vendor.xml")?;
+        let mut ome = Ome::from_str_preserving(&xml)?;
+        let mut other = ome.image[0].clone();
+        other.id = "Image:1".to_string();
+        other.name = Some("other.tif".to_string());
+        ome.image.insert(0, other);
+        let written = ome.to_xml_preserving()?;
+        assert!(written.contains(r#"<Image ID="Image:1" Name="other.tif">"#));
+        assert!(
+            written.contains(r#"<Image ID="Image:0" Name="vendor.tif" Vendor="Acme &amp; Co">"#)
+        );
+        let foo = written.find("<MyVendor:Foo").unwrap();
+        assert!(written.find(r#"ID="Image:0""#).unwrap() < foo);
+        assert_eq!(written.matches("<MyVendor:Foo").count(), 1);
+
+        // the vendor xml of a removed image is dropped
+        ome.image.remove(1);
+        assert!(!ome.to_xml_preserving()?.contains("<MyVendor:Foo"));
+        Ok(())
+    }
+
+    #[test]
+    fn xml_annotation_prefixes() -> Result<(), Error> {
+        let xml = std::fs::read_to_string("tests/annotations.xml")?
+            .replacen("<OME ", r#"<OME xmlns:acme="http://example.com/acme" "#, 1)
+            .replace(
+                r#"<Settings Version="1.2" Mode="fast"/>"#,
+                r#"<!-- set by the camera --><acme:Settings acme:Mode="fast"/>"#,
+            );
+        let ome: Ome = xml.parse()?;
+        let value = |ome: &Ome| match ome.annotations().nth(1) {
+            Some(StructuredAnnotationsContent::XmlAnnotation(xml)) => xml.value.clone(),
+            _ => panic!("not an XMLAnnotation"),
+        };
+        let annotation = value(&ome);
+        let children = XmlAnnotationValue {
+            verbatim: None,
+            ..annotation.clone()
+        };
+        assert!(
+            children
+                .to_string()
+                .ends_with(r#"</OriginalMetadata><acme:Settings acme:Mode="fast"/>"#)
+        );
+        assert_eq!(
+            annotation.attributes,
+            [(
+                "xmlns:acme".to_string(),
+                "http://example.com/acme".to_string()
+            )]
+        );
+        let written = ome.to_xml()?;
+        assert!(written.contains(r#"<Value xmlns:acme="http://example.com/acme">"#));
+        assert_eq!(value(&written.parse()?), annotation);
+        Ok(())
+    }
+}
@@ -0,0 +1,243 @@
+use crate::error::Error;
+use crate::ome::{MapAnnotation, Ome, Pixels, StructuredAnnotationsContent, TiffDataUuid};
+use std::collections::HashSet;
+
+/// the namespace of the MapAnnotation linking a derived document to its original
+pub const PROVENANCE_NAMESPACE: &str = "ome-metadata/provenance";
+
+/// whether the value is a hyphenated UUID, with or without the urn:uuid: prefix
+fn is_valid_uuid(value: &str) -> bool {
+    let uuid = value.strip_prefix("urn:uuid:").unwrap_or(value);
+    uuid.len() == 36 && uuid::Uuid::parse_str(uuid).is_ok()
+}
+
+impl Ome {
+    /// a new random UUID of the form urn:uuid:xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx
+    pub fn generate_uuid() -> String {
+        uuid::Uuid::new_v4().urn().to_string()
+    }
+
+    /// the UUID of the document, a new one is generated if it has none
+    pub fn ensure_uuid(&mut self) -> &str {
+        self.uuid.get_or_insert_with(Ome::generate_uuid)
+    }
+
+    /// give all TiffData without a UUID the UUID of the document, generating it if there is none
+    /// yet, and file_name, the name of the file with this document, so that the TiffData still
+    /// refer to this file when the planes of other files are added, see
+    /// [Pixels::ensure_uuid_for_tiff_data]
+    pub fn ensure_uuid_for_tiff_data(&mut self, file_name: Option<&str>) {
+        let uuid = self.ensure_uuid().to_string();
+        for image in &mut self.image {
+            image.pixels.ensure_uuid_for_tiff_data(&uuid, file_name);
+        }
+    }
+
+    /// check that the UUID of the document and of BinaryOnly, if present, are UUIDs of the form
+    /// urn:uuid:xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx or xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx,
+    /// [Ome::validate] also checks that they have the urn:uuid: form of the schema
+    pub fn validate_uuid(&self) -> Result<(), Error> {
+        let uuids = self
+            .uuid
+            .iter()
+            .chain(self.binary_only.iter().map(|binary_only| &binary_only.uuid));
+        for uuid in uuids {
+            if !is_valid_uuid(uuid) {
+                return Err(Error::InvalidUuid(uuid.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    /// an ID of the form Annotation:n which is not used by any annotation yet
+    fn free_annotation_id(&self) -> String {
+        let used = self.annotations().map(|a| a.id()).collect::<HashSet<_>>();
+        (0..)
+            .map(|n| format!("Annotation:{n}"))
+            .find(|id| !used.contains(id.as_str()))
+            .expect("ran out of annotation IDs")
+    }
+
+    /// a copy of this document with a new random UUID, linked to the original by a MapAnnotation
+    /// in [PROVENANCE_NAMESPACE], see [Ome::clone_with_new_uuid_in_namespace]
+    pub fn clone_with_new_uuid(&self) -> Ome {
+        self.clone_with_new_uuid_in_namespace(PROVENANCE_NAMESPACE)
+    }
+
+    /// a copy of this document with a new random UUID, if this document has a UUID, a
+    /// MapAnnotation in the given namespace with the key OriginalUUID refers to it and the
+    /// TiffData which refer to the original UUID refer to the new one
+    pub fn clone_with_new_uuid_in_namespace(&self, namespace: &str) -> Ome {
+        let mut ome = self.clone();
+        let uuid = Ome::generate_uuid();
+        if let Some(original) = &self.uuid {
+            for tiff_data in ome
+                .image
+                .iter_mut()
+                .flat_map(|image| &mut image.pixels.tiff_data)
+            {
+                if let Some(tiff_data_uuid) = &mut tiff_data.uuid {
+                    if &tiff_data_uuid.content == original {
+                        tiff_data_uuid.content = uuid.clone();
+                    }
+                }
+            }
+        }
+        ome.uuid = Some(uuid);
+        if let Some(original) = &self.uuid {
+            let annotation = MapAnnotation::from_pairs(
+                &ome.free_annotation_id(),
+                Some(namespace),
+                [("OriginalUUID".to_string(), original.clone())],
+            );
+            ome.add_annotation(StructuredAnnotationsContent::MapAnnotation(annotation));
+        }
+        ome
+    }
+}
+
+impl Pixels {
+    /// give all TiffData without a UUID the UUID uuid, normally the UUID of the document, and
+    /// file_name, the name of the file with this document
+    pub fn ensure_uuid_for_tiff_data(&mut self, uuid: &str, file_name: Option<&str>) {
+        for tiff_data in self
+            .tiff_data
+            .iter_mut()
+            .filter(|tiff_data| tiff_data.uuid.is_none())
+        {
+            tiff_data.uuid = Some(TiffDataUuid {
+                file_name: file_name.map(str::to_string),
+                content: uuid.to_string(),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clone_with_new_uuid() -> Result<(), Error> {
+        let ome: Ome = std::fs::read_to_string("tests/zct.ome.xml")?.parse()?;
+        let derived = ome.clone_with_new_uuid();
+        assert_ne!(derived.uuid, ome.uuid);
+        assert!(derived.uuid.as_ref().unwrap().starts_with("urn:uuid:"));
+        let Some(StructuredAnnotationsContent::MapAnnotation(link)) = derived.annotations().last()
+        else {
+            panic!("no provenance annotation");
+        };
+        assert_eq!(link.namespace.as_deref(), Some(PROVENANCE_NAMESPACE));
+        assert_eq!(link.value.get("OriginalUUID"), ome.uuid.as_deref());
+
+        let twice = derived.clone_with_new_uuid_in_namespace("example.org/processing");
+        let ids = twice.annotations().map(|a| a.id()).collect::<Vec<_>>();
+        assert_eq!(ids, ["Annotation:0", "Annotation:1"]);
+
+        let ome: Ome = std::fs::read_to_string("tests/test.xml")?.parse()?;
+        let derived = ome.clone_with_new_uuid();
+        assert!(derived.uuid.is_some());
+        assert_eq!(derived.annotations().count(), 0);
+
+        let original = "urn:uuid:0f8fad5b-d9cb-469f-a165-70867728950e";
+        let other = "urn:uuid:7c9e6679-7425-40de-944b-e07fc1f90ae7";
+        let ome: Ome = format!(
+            r#"<OME UUID="{original}"><Image ID="Image:0">
+            <Pixels ID="Pixels:0" DimensionOrder="XYZCT" Type="uint8" SizeX="1" SizeY="1" SizeZ="1" SizeC="1" SizeT="2">
+                <TiffData IFD="0"><UUID FileName="a.ome.tif">{original}</UUID></TiffData>
+                <TiffData IFD="0" FirstT="1"><UUID FileName="b.ome.tif">{other}</UUID></TiffData>
+            </Pixels>
+        </Image></OME>"#
+        )
+        .parse()?;
+        let derived = ome.clone_with_new_uuid();
+        let uuids = derived.image[0]
+            .pixels
+            .tiff_data
+            .iter()
+            .map(|tiff_data| tiff_data.uuid.as_ref().unwrap().content.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(uuids, [derived.uuid.as_deref().unwrap(), other]);
+        Ok(())
+    }
+
+    #[test]
+    fn generate_uuid() -> Result<(), Error> {
+        let (a, b) = (Ome::generate_uuid(), Ome::generate_uuid());
+        assert_ne!(a, b);
+        for uuid in [&a, &b] {
+            assert!(uuid.starts_with("urn:uuid:"));
+            assert!(is_valid_uuid(uuid));
+        }
+
+        let mut ome = Ome::default();
+        let uuid = ome.ensure_uuid().to_string();
+        assert!(is_valid_uuid(&uuid));
+        assert_eq!(ome.ensure_uuid(), uuid);
+        assert_eq!(ome.uuid.as_deref(), Some(uuid.as_str()));
+
+        let mut ome: Ome = r#"<OME><Image ID="Image:0">
+            <Pixels ID="Pixels:0" DimensionOrder="XYZCT" Type="uint8" SizeX="1" SizeY="1" SizeZ="1" SizeC="1" SizeT="3">
+                <TiffData IFD="0"/>
+                <TiffData IFD="1"><UUID FileName="other.tif">urn:uuid:0f8fad5b-d9cb-469f-a165-70867728950e</UUID></TiffData>
+                <TiffData IFD="2"/>
+            </Pixels>
+        </Image></OME>"#
+            .parse()?;
+        ome.ensure_uuid_for_tiff_data(Some("image.ome.tif"));
+        let uuid = ome.uuid.clone().unwrap();
+        let uuids = ome.image[0]
+            .pixels
+            .tiff_data
+            .iter()
+            .map(|tiff_data| tiff_data.uuid.as_ref().unwrap())
+            .collect::<Vec<_>>();
+        for own in [uuids[0], uuids[2]] {
+            assert_eq!(own.content, uuid);
+            assert_eq!(own.file_name.as_deref(), Some("image.ome.tif"));
+        }
+        assert_eq!(uuids[1].file_name.as_deref(), Some("other.tif"));
+        assert_eq!(
+            uuids[1].content,
+            "urn:uuid:0f8fad5b-d9cb-469f-a165-70867728950e"
+        );
+
+        let pixels = &mut ome.image[0].pixels;
+        pixels.tiff_data[0].uuid = None;
+        pixels.ensure_uuid_for_tiff_data("urn:uuid:44b8b5ac-3b5e-4f6e-9d0c-6fb1a4f6a1a2", None);
+        let own = pixels.tiff_data[0].uuid.as_ref().unwrap();
+        assert_eq!(own.content, "urn:uuid:44b8b5ac-3b5e-4f6e-9d0c-6fb1a4f6a1a2");
+        assert_eq!(own.file_name, None);
+        assert_eq!(pixels.tiff_data[2].uuid.as_ref().unwrap().content, uuid);
+        Ok(())
+    }
+
+    #[test]
+    fn validate_uuid() -> Result<(), Error> {
+        let mut ome = Ome::default();
+        assert!(ome.validate_uuid().is_ok());
+        for valid in [
+            "urn:uuid:0f8fad5b-d9cb-469f-a165-70867728950e",
+            "urn:uuid:0F8FAD5B-D9CB-469F-A165-70867728950E",
+            "0f8fad5b-d9cb-469f-a165-70867728950e",
+        ] {
+            ome.uuid = Some(valid.to_string());
+            assert!(ome.validate_uuid().is_ok(), "{valid}");
+        }
+        for invalid in [
+            "urn:uuid:0f8fad5b-d9cb-469f-a165",
+            "0f8fad5bd9cb469fa16570867728950e",
+            "uuid:0f8fad5b-d9cb-469f-a165-70867728950e",
+            "{0f8fad5b-d9cb-469f-a165-70867728950e}",
+            "urn:uuid:0f8fad5b-d9cb-469f-a165-70867728950g",
+        ] {
+            ome.uuid = Some(invalid.to_string());
+            assert!(
+                matches!(ome.validate_uuid(), Err(Error::InvalidUuid(uuid)) if uuid == invalid)
+            );
+        }
+        let ome: Ome = std::fs::read_to_string("tests/zct.ome.xml")?.parse()?;
+        assert!(ome.validate_uuid().is_ok());
+        Ok(())
+    }
+}
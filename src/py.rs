@@ -1,14 +1,436 @@
-use crate::Ome;
+use crate::error::Error;
 use crate::ome::{
-    Convert, UnitsElectricPotential, UnitsFrequency, UnitsLength, UnitsPower, UnitsPressure,
+    self, Convert, UnitsElectricPotential, UnitsFrequency, UnitsLength, UnitsPower, UnitsPressure,
     UnitsTemperature, UnitsTime,
 };
-use pyo3::exceptions::PyValueError;
+use crate::validation::{Severity, ValidationError};
+use pyo3::IntoPyObjectExt;
+use pyo3::create_exception;
+use pyo3::exceptions::{PyDeprecationWarning, PyIndexError, PyKeyError, PyTypeError, PyValueError};
 use pyo3::prelude::*;
+use pyo3::pyclass::CompareOp;
+use pyo3::types::{PyBool, PyDict, PyFloat, PyInt, PyIterator, PyList, PyString, PyTuple};
+use quick_xml::DeError;
+use serde::Deserialize;
+use serde::de::{
+    DeserializeSeed, Deserializer, EnumAccess, Error as _, IntoDeserializer, MapAccess, SeqAccess,
+    VariantAccess, Visitor,
+};
+use std::any::Any;
+use std::borrow::Cow;
+use std::ffi::CString;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use build::{new_image, new_ome};
+use quantity::{Quantity, exposure_time, pixel_size, stage_position};
+
+mod build;
+mod ome_types;
+mod quantity;
+#[cfg(test)]
+mod stub;
+
+create_exception!(
+    ome_metadata_rs,
+    OmeError,
+    PyValueError,
+    "the base of the errors raised by ome_metadata"
+);
+create_exception!(
+    ome_metadata_rs,
+    OmeParseError,
+    OmeError,
+    "the text is not valid (OME-)XML, or a dict does not match the schema"
+);
+create_exception!(
+    ome_metadata_rs,
+    OmeUnitError,
+    OmeError,
+    "the unit is unknown, or there is no conversion between the units"
+);
+create_exception!(
+    ome_metadata_rs,
+    OmeValidationError,
+    OmeError,
+    "the metadata has validation errors, the entries are in report"
+);
+
+/// the exception for an error, with the message of the outermost error
+fn new_err(err: &Error, message: String) -> PyErr {
+    match err {
+        Error::Path { source, .. } => new_err(source, message),
+        Error::Xml(_) | Error::SerdeXml(_) | Error::Parse { .. } | Error::Encoding(_) => {
+            OmeParseError::new_err(message)
+        }
+        #[cfg(feature = "binary")]
+        Error::Base64(_) => OmeParseError::new_err(message),
+        #[cfg(feature = "chrono")]
+        Error::Chrono(_) => OmeParseError::new_err(message),
+        #[cfg(feature = "json")]
+        Error::Json(_) => OmeParseError::new_err(message),
+        Error::SizeOfUnknown(_) | Error::UnknownUnitFactor(_) | Error::TemparatureConversion => {
+            OmeUnitError::new_err(message)
+        }
+        _ => OmeError::new_err(message),
+    }
+}
+
+impl From<Error> for PyErr {
+    fn from(err: Error) -> PyErr {
+        new_err(&err, err.to_string())
+    }
+}
+
+impl ome::Ome {
+    /// nested dicts and lists with the snake_case field names as keys, ints, floats and strs for
+    /// numbers and text, None for missing optional fields and unit classes like Length for units
+    pub fn into_python_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        py.detach(|| self.clone()).into_pyobject(py)
+    }
+}
+
+impl ome::Ome {
+    /// the inverse of [Ome::into_python_dict](ome::Ome::into_python_dict), missing keys and None
+    /// are taken as missing optional fields, and units and other enums can also be given by name
+    pub fn from_python_dict(dict: &Bound<'_, PyDict>) -> PyResult<Self> {
+        Ok(ome::Ome::deserialize(PyDeserializer(dict.as_any())).map_err(Error::from)?)
+    }
+}
+
+fn py_error(err: PyErr) -> DeError {
+    DeError::custom(err.to_string())
+}
+
+/// lower case letters and digits only, so that "physical_size_x" matches "@PhysicalSizeX"
+fn normalize(name: &str) -> String {
+    name.chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+/// the serde name of a variant given by its serde name or by its rust name, like "µm" for "um" or
+/// "XYCZT" for "Xyczt"
+fn variant_name(name: &str, variants: &'static [&'static str]) -> Result<&'static str, DeError> {
+    let plain = |name: &str| -> String {
+        name.replace('µ', "u")
+            .replace('Å', "A")
+            .chars()
+            .filter(char::is_ascii_alphanumeric)
+            .collect()
+    };
+    let unique = |eq: &dyn Fn(&str, &str) -> bool| {
+        let mut matches = variants
+            .iter()
+            .filter(|variant| eq(&plain(variant), &plain(name)));
+        match (matches.next(), matches.next()) {
+            (Some(variant), None) => Some(*variant),
+            _ => None,
+        }
+    };
+    variants
+        .iter()
+        .find(|variant| **variant == name)
+        .copied()
+        .or_else(|| unique(&|a, b| a == b))
+        .or_else(|| unique(&|a, b| a.eq_ignore_ascii_case(b)))
+        .ok_or_else(|| DeError::unknown_variant(name, variants))
+}
+
+/// the names in rust, and so the keys in the dicts, of the fields which serde knows as $value or
+/// $text
+const CONTENT_FIELDS: &[&str] = &["content", "light_source_group", "shape_group"];
+
+/// the fields of OME in the dicts which are not in the xml
+const SKIPPED_FIELDS: &[&str] = &["original_namespace", "unknown_xml"];
+
+/// serde deserializer for the dicts, lists and values made by into_python_dict, keys are matched
+/// with the fields ignoring case and underscores, the keys in CONTENT_FIELDS go to the $value or
+/// $text field if the struct has one, and other keys are an error, enum variants of elements are
+/// given by "kind"
+struct PyDeserializer<'a, 'py>(&'a Bound<'py, PyAny>);
+
+impl<'de> Deserializer<'de> for PyDeserializer<'_, '_> {
+    type Error = DeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        let value = self.0;
+        if value.is_none() {
+            visitor.visit_unit()
+        } else if let Ok(value) = value.cast::<PyBool>() {
+            visitor.visit_bool(value.is_true())
+        } else if value.is_instance_of::<PyInt>() {
+            visitor.visit_i64(value.extract().map_err(py_error)?)
+        } else if value.is_instance_of::<PyFloat>() {
+            visitor.visit_f64(value.extract().map_err(py_error)?)
+        } else if value.is_instance_of::<PyList>() || value.is_instance_of::<PyTuple>() {
+            let items = value
+                .try_iter()
+                .and_then(|items| items.collect::<PyResult<Vec<_>>>())
+                .map_err(py_error)?;
+            visitor.visit_seq(PySeq(items.into_iter()))
+        } else if let Ok(dict) = value.cast::<PyDict>() {
+            let mut entries = Vec::new();
+            for (key, value) in dict.iter() {
+                entries.push((key.str().map_err(py_error)?.to_string(), value, false));
+            }
+            visitor.visit_map(PyMap::new(entries))
+        } else {
+            visitor.visit_string(value.str().map_err(py_error)?.to_string())
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        if self.0.is_none() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    /// empty elements like MetadataOnly are 0
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        let Ok(dict) = self.0.cast::<PyDict>() else {
+            return Err(DeError::custom(format!("{name} needs a dict")));
+        };
+        let content = fields
+            .iter()
+            .find(|field| matches!(**field, "$value" | "$text"));
+        let mut entries = Vec::new();
+        for (key, value) in dict.iter() {
+            let key = key.str().map_err(py_error)?.to_string();
+            if key == "kind" || (name == "Ome" && SKIPPED_FIELDS.contains(&key.as_str())) {
+                continue;
+            }
+            let field = fields
+                .iter()
+                .find(|field| !field.starts_with('$') && normalize(field) == normalize(&key))
+                .or(content.filter(|_| CONTENT_FIELDS.contains(&key.as_str())));
+            let Some(&field) = field else {
+                return Err(DeError::custom(format!("{name} has no field {key}")));
+            };
+            if value.is_none() {
+                continue;
+            }
+            // the value of an XMLAnnotation is xml in a str
+            let xml =
+                name == "XmlAnnotation" && field == "Value" && value.is_instance_of::<PyString>();
+            entries.push((field.to_string(), value, xml));
+        }
+        visitor.visit_map(PyMap::new(entries))
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        let (variant, value) = match self.0.cast::<PyDict>() {
+            Ok(dict) => {
+                let kind = dict
+                    .get_item("kind")
+                    .map_err(py_error)?
+                    .ok_or_else(|| DeError::custom(format!("the kind of {name} is missing")))?;
+                (kind.str().map_err(py_error)?.to_string(), Some(self.0))
+            }
+            Err(_) => (self.0.str().map_err(py_error)?.to_string(), None),
+        };
+        visitor.visit_enum(PyEnum {
+            variant: variant_name(&variant, variants)?,
+            value,
+        })
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf unit
+        seq tuple tuple_struct map identifier ignored_any
+    }
+}
+
+struct PySeq<'py>(std::vec::IntoIter<Bound<'py, PyAny>>);
+
+impl<'de> SeqAccess<'de> for PySeq<'_> {
+    type Error = DeError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, DeError> {
+        self.0
+            .next()
+            .map(|item| seed.deserialize(PyDeserializer(&item)))
+            .transpose()
+    }
+}
+
+/// keys, values and whether the value is a str with xml
+struct PyMap<'py> {
+    entries: std::vec::IntoIter<(String, Bound<'py, PyAny>, bool)>,
+    value: Option<(Bound<'py, PyAny>, bool)>,
+}
+
+impl<'py> PyMap<'py> {
+    fn new(entries: Vec<(String, Bound<'py, PyAny>, bool)>) -> Self {
+        PyMap {
+            entries: entries.into_iter(),
+            value: None,
+        }
+    }
+}
+
+impl<'de> MapAccess<'de> for PyMap<'_> {
+    type Error = DeError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, DeError> {
+        let Some((key, value, xml)) = self.entries.next() else {
+            return Ok(None);
+        };
+        self.value = Some((value, xml));
+        seed.deserialize(IntoDeserializer::<DeError>::into_deserializer(key))
+            .map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, DeError> {
+        match self.value.take() {
+            Some((value, true)) => {
+                // read like the xml in JSON, so that it is kept as it is
+                let xml = value.str().map_err(py_error)?.to_string();
+                let _guard = crate::json::XmlAsTextGuard::new();
+                seed.deserialize(IntoDeserializer::<DeError>::into_deserializer(xml))
+            }
+            Some((value, false)) => seed.deserialize(PyDeserializer(&value)),
+            None => Err(DeError::custom("value without a key")),
+        }
+    }
+}
+
+struct PyEnum<'a, 'py> {
+    variant: &'static str,
+    /// the dict of an element, None for unit variants
+    value: Option<&'a Bound<'py, PyAny>>,
+}
+
+impl<'de> EnumAccess<'de> for PyEnum<'_, '_> {
+    type Error = DeError;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self), DeError> {
+        let variant =
+            seed.deserialize(IntoDeserializer::<DeError>::into_deserializer(self.variant))?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de> VariantAccess<'de> for PyEnum<'_, '_> {
+    type Error = DeError;
+
+    fn unit_variant(self) -> Result<(), DeError> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, DeError> {
+        match self.value {
+            Some(value) => seed.deserialize(PyDeserializer(value)),
+            None => Err(DeError::custom(format!("{} needs a dict", self.variant))),
+        }
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, _visitor: V) -> Result<V::Value, DeError> {
+        Err(DeError::custom(format!("{} is not a tuple", self.variant)))
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, DeError> {
+        Err(DeError::custom(format!("{} is not a struct", self.variant)))
+    }
+}
+
+/// a problem found by Ome.validate
+#[derive(Clone, IntoPyObject)]
+struct ValidationEntry {
+    severity: String,
+    code: &'static str,
+    path: String,
+    message: String,
+}
+
+impl From<&ValidationError> for ValidationEntry {
+    fn from(error: &ValidationError) -> Self {
+        ValidationEntry {
+            severity: error.severity().to_string(),
+            code: error.code(),
+            path: error.path().to_string(),
+            message: error.to_string(),
+        }
+    }
+}
 
-impl From<crate::error::Error> for PyErr {
-    fn from(err: crate::error::Error) -> PyErr {
-        PyErr::new::<PyValueError, _>(err.to_string())
+/// OME-XML as str, or as UTF-8 or UTF-16 encoded bytes, copied so that it can be parsed without
+/// holding the GIL
+#[derive(FromPyObject)]
+enum Xml {
+    Str(String),
+    Bytes(Vec<u8>),
+}
+
+impl Xml {
+    fn parse(self, py: Python<'_>) -> PyResult<ome::Ome> {
+        Ok(py.detach(|| match self {
+            Xml::Str(text) => text.parse(),
+            Xml::Bytes(bytes) => ome::Ome::from_bytes(&bytes),
+        })?)
+    }
+}
+
+/// read and parse a file without holding the GIL
+fn read_file(py: Python<'_>, path: &Path) -> PyResult<ome::Ome> {
+    Ok(py.detach(|| ome::Ome::from_path(path))?)
+}
+
+/// a unit given by its symbol as in the xml, like "µm", or by its name in rust, like "um"
+fn parse_unit<T: std::str::FromStr + serde::de::DeserializeOwned>(name: &str) -> PyResult<T> {
+    name.parse()
+        .ok()
+        .or_else(|| serde_json::from_value(serde_json::Value::from(name)).ok())
+        .ok_or_else(|| OmeUnitError::new_err(format!("Invalid unit: {}", name)))
+}
+
+/// the symbol of a unit as in the xml, like "µm"
+fn unit_symbol<T: serde::Serialize + std::fmt::Debug>(unit: &T) -> String {
+    match serde_json::to_value(unit) {
+        Ok(serde_json::Value::String(symbol)) => symbol,
+        _ => format!("{:?}", unit),
     }
 }
 
@@ -24,18 +446,17 @@ macro_rules! impl_enum_into_py_object {
             impl $s {
                 #[new]
                 fn new(unit: &str) -> PyResult<Self> {
-                    match unit.parse() {
-                        Ok(unit) => Ok(Self { inner: unit }),
-                        Err(_) => Err(PyErr::new::<PyValueError, _>(format!("Invalid unit: {}", unit)))
-                    }
+                    Ok(Self { inner: parse_unit(unit)? })
                 }
 
-                /// convert a value between units
-                fn convert(&self, unit: &str, value: f64) -> PyResult<f64> {
-                    match unit.parse() {
-                        Ok(unit) => Ok(self.inner.convert(&unit, value)?),
-                        Err(_) => Err(PyErr::new::<PyValueError, _>(format!("Invalid unit: {}", unit)))
-                    }
+                /// convert a value between units, the unit can be given by its name or as a class
+                /// attribute like Length.um
+                fn convert(&self, unit: &Bound<'_, PyAny>, value: f64) -> PyResult<f64> {
+                    let unit = match unit.cast::<$s>() {
+                        Ok(unit) => unit.borrow().inner.clone(),
+                        Err(_) => parse_unit(&unit.extract::<String>()?)?,
+                    };
+                    Ok(self.inner.convert(&unit, value)?)
                 }
 
                 /// all possible variants of this enum that can be constructed or converted into
@@ -45,15 +466,81 @@ macro_rules! impl_enum_into_py_object {
                 }
 
                 fn __repr__(&self) -> String {
-                    format!("{:?}", self.inner)
+                    unit_symbol(&self.inner)
                 }
 
                 fn __str__(&self) -> String {
-                    format!("{:?}", self.inner)
+                    unit_symbol(&self.inner)
                 }
 
                 fn __getnewargs__(&self) -> (String,) {
-                    (format!("{:?}", self.inner),)
+                    (unit_symbol(&self.inner),)
+                }
+
+                /// conversion factor between this unit and the SI unit
+                fn as_si(&self) -> PyResult<f64> {
+                    Ok(self.inner.as_si()?)
+                }
+
+                fn __hash__(&self) -> u64 {
+                    let mut hasher = DefaultHasher::new();
+                    format!("{:?}", self.inner).hash(&mut hasher);
+                    hasher.finish()
+                }
+
+                /// units are equal if they are the same unit, and ordered by their size in SI
+                fn __richcmp__<'py>(
+                    &self,
+                    other: &Bound<'py, PyAny>,
+                    op: CompareOp,
+                ) -> PyResult<Bound<'py, PyAny>> {
+                    let py = other.py();
+                    let Ok(other) = other.cast::<$s>() else {
+                        return Ok(py.NotImplemented().into_bound(py));
+                    };
+                    let other = &other.borrow().inner;
+                    let result = match op {
+                        CompareOp::Eq => self.inner == *other,
+                        CompareOp::Ne => self.inner != *other,
+                        _ => match (self.inner.as_si(), other.as_si()) {
+                            (Ok(a), Ok(b)) => a.partial_cmp(&b).is_some_and(|o| op.matches(o)),
+                            _ => return Ok(py.NotImplemented().into_bound(py)),
+                        },
+                    };
+                    result.into_bound_py_any(py)
+                }
+            }
+
+            #[cfg(test)]
+            impl stub::PyStubTarget for $s {
+                const PY_TYPE: &'static str = stringify!($s);
+            }
+
+            #[cfg(test)]
+            impl $s {
+                fn stub() -> String {
+                    format!(
+                        concat!(
+                            "class {0}:\n",
+                            "    def __init__(self, unit: str) -> None: ...\n",
+                            "    def convert(self, unit: str | {0}, value: float) -> float: ...\n",
+                            "    @staticmethod\n",
+                            "    def variants() -> list[str]: ...\n",
+                            "    def as_si(self) -> float: ...\n",
+                            "    def __getnewargs__(self) -> tuple[str]: ...\n",
+                            "    def __hash__(self) -> int: ...\n",
+                            "    def __eq__(self, other: object) -> bool: ...\n",
+                            "    def __ne__(self, other: object) -> bool: ...\n",
+                            "    def __lt__(self, other: {0}) -> bool: ...\n",
+                            "    def __le__(self, other: {0}) -> bool: ...\n",
+                            "    def __gt__(self, other: {0}) -> bool: ...\n",
+                            "    def __ge__(self, other: {0}) -> bool: ...\n",
+                        ),
+                        stringify!($s)
+                    ) + &stub::class_attributes(
+                        stringify!($s),
+                        <$t>::variants().iter().map(|v| format!("{:?}", v)),
+                    )
                 }
             }
 
@@ -66,6 +553,102 @@ macro_rules! impl_enum_into_py_object {
                 }
             }
         )*
+
+        /// a unit of any of the unit classes
+        #[derive(Clone, Debug, PartialEq)]
+        enum AnyUnit {
+            $($s($t),)*
+        }
+
+        impl AnyUnit {
+            /// a unit from an instance of one of the unit classes
+            fn from_py(unit: &Bound<'_, PyAny>) -> PyResult<Self> {
+                $(
+                    if let Ok(unit) = unit.cast::<$s>() {
+                        return Ok(AnyUnit::$s(unit.borrow().inner.clone()));
+                    }
+                )*
+                Err(PyTypeError::new_err(format!(
+                    "{} is not a unit",
+                    unit.get_type().name()?
+                )))
+            }
+
+            /// a unit from an instance of one of the unit classes, or from the name of a unit of
+            /// the same class as self
+            fn like(&self, unit: UnitLike) -> PyResult<Self> {
+                let name = match unit {
+                    UnitLike::Unit(unit) => return Ok(unit),
+                    UnitLike::Name(name) => name,
+                };
+                Ok(match self {
+                    $(AnyUnit::$s(_) => AnyUnit::$s(parse_unit(&name)?),)*
+                })
+            }
+
+            /// the name of the class and the unit, like "Length.um"
+            fn name(&self) -> String {
+                match self {
+                    $(AnyUnit::$s(unit) => format!("{}.{:?}", stringify!($s), unit),)*
+                }
+            }
+
+            /// the symbol of the unit as in the xml, like "µm"
+            fn symbol(&self) -> String {
+                match self {
+                    $(AnyUnit::$s(unit) => unit_symbol(unit),)*
+                }
+            }
+
+            fn convert(&self, unit: &Self, value: f64) -> PyResult<f64> {
+                match (self, unit) {
+                    $((AnyUnit::$s(a), AnyUnit::$s(b)) => Ok(a.convert(b, value)?),)*
+                    _ => Err(OmeUnitError::new_err(format!(
+                        "cannot convert {} into {}",
+                        self.name(),
+                        unit.name()
+                    ))),
+                }
+            }
+        }
+
+        impl<'a, 'py> FromPyObject<'a, 'py> for AnyUnit {
+            type Error = PyErr;
+
+            fn extract(unit: Borrowed<'a, 'py, PyAny>) -> PyResult<Self> {
+                Self::from_py(&unit)
+            }
+        }
+
+        impl<'py> IntoPyObject<'py> for AnyUnit {
+            type Target = PyAny;
+            type Output = Bound<'py, PyAny>;
+            type Error = PyErr;
+            fn into_pyobject(self, py: Python<'py>) -> PyResult<Self::Output> {
+                match self {
+                    $(AnyUnit::$s(unit) => unit.into_bound_py_any(py),)*
+                }
+            }
+        }
+
+        /// the stubs of the unit classes, followed by _Unit, the type of a unit of any of them
+        #[cfg(test)]
+        fn unit_stubs() -> Vec<String> {
+            let mut stubs = vec![$($s::stub()),*];
+            stubs.push(format!("_Unit = {}\n", [$(stringify!($s)),*].join(" | ")));
+            stubs
+        }
+
+        /// the variants of the units as class attributes, like Length.um
+        fn add_unit_variants(py: Python<'_>) -> PyResult<()> {
+            $(
+                let class = py.get_type::<$s>();
+                for unit in <$t>::variants() {
+                    class.setattr(format!("{:?}", unit), $s { inner: unit })?;
+                }
+            )*
+            Ok(())
+        }
     };
 }
 
@@ -79,9 +662,680 @@ impl_enum_into_py_object! {
     Time: UnitsTime
 }
 
+/// a unit, or the name of a unit
+#[derive(FromPyObject)]
+enum UnitLike {
+    Unit(AnyUnit),
+    Name(String),
+}
+
+/// the metadata of which the classes share parts
+type Owner = dyn Any + Send + Sync;
+
+/// a metadata struct in the metadata it is part of, so that getters share it instead of copying it
+/// with everything in it
+pub(crate) struct Shared<T> {
+    owner: Arc<Owner>,
+    get: Arc<dyn Fn(&Owner) -> &T + Send + Sync>,
+}
+
+/// the function to a part of the owner, which gets its lifetimes from the bound
+fn getter<T, F>(get: F) -> Arc<dyn Fn(&Owner) -> &T + Send + Sync>
+where
+    F: Fn(&Owner) -> &T + Send + Sync + 'static,
+{
+    Arc::new(get)
+}
+
+impl<T: Send + Sync + 'static> Shared<T> {
+    fn new(inner: T) -> Self {
+        Shared {
+            owner: Arc::new(inner),
+            get: getter(|owner| owner.downcast_ref().expect("the owner is a T")),
+        }
+    }
+
+    /// a part of this struct, sharing the same owner
+    fn project<U>(&self, part: impl Fn(&T) -> &U + Send + Sync + 'static) -> Shared<U> {
+        let get = self.get.clone();
+        Shared {
+            owner: self.owner.clone(),
+            get: getter(move |owner| part(get(owner))),
+        }
+    }
+}
+
+impl<T> Deref for Shared<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        (self.get)(&*self.owner)
+    }
+}
+
+/// warn that the metadata classes are no longer dicts
+fn deprecated_dict(py: Python<'_>, class: &str) -> PyResult<()> {
+    let message = format!("{class} is no longer a dict, use its attributes, or to_dict for a dict");
+    PyErr::warn(
+        py,
+        &py.get_type::<PyDeprecationWarning>(),
+        &CString::new(message)?,
+        1,
+    )
+}
+
+/// the name of a field in Python, and in the dict from to_dict
+macro_rules! stub_name {
+    ($field:ident) => {
+        stringify!($field).trim_start_matches("r#")
+    };
+}
+
+/// the value of a field as a Python object, fields that are (lists of) structs with a class of
+/// their own are wrapped in that class, other fields are converted like in to_dict
+macro_rules! py_field {
+    ($py:ident, $value:expr) => {
+        $value.clone().into_bound_py_any($py)
+    };
+    ($py:ident, $inner:expr, $field:ident, one $class:ident) => {
+        $class {
+            inner: $inner.project(|inner| &inner.$field),
+        }
+        .into_bound_py_any($py)
+    };
+    ($py:ident, $inner:expr, $field:ident, list $class:ident) => {
+        (0..$inner.$field.len())
+            .map(|i| $class {
+                inner: $inner.project(move |inner| &inner.$field[i]),
+            })
+            .collect::<Vec<_>>()
+            .into_bound_py_any($py)
+    };
+    ($py:ident, $inner:expr, $field:ident) => {
+        py_field!($py, $inner.$field)
+    };
+}
+
+/// the Python type of a field in the stub, like the object made by py_field
+#[cfg(test)]
+macro_rules! stub_type {
+    ($t:ty, $field:ident) => {
+        stub::field_type(|inner: &$t| &inner.$field)
+    };
+    ($t:ty, $field:ident, one $class:ident) => {
+        stringify!($class).to_string()
+    };
+    ($t:ty, $field:ident, list $class:ident) => {
+        format!("list[{}]", stringify!($class))
+    };
+}
+
+/// read-only Python classes around the metadata structs, with a getter for each field, the
+/// fields in repr are shown by __repr__, and optionally a block of more methods
+macro_rules! impl_py_class {
+    ($(
+        $(#[doc = $doc:literal])*
+        $s:ident: $t:ty, repr($($repr:ident),*) {
+            $($name:ident: $field:ident $($kind:ident $class:ident)?),* $(,)?
+        } $({ $($methods:tt)* })?
+    )*) => {
+        $(
+            $(#[doc = $doc])*
+            #[pyclass(module = "ome_metadata.ome_metadata_rs", frozen)]
+            pub struct $s {
+                inner: Shared<$t>,
+            }
+
+            #[pymethods]
+            impl $s {
+                $(
+                    #[getter]
+                    fn $name<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+                        py_field!(py, self.inner, $field $(, $kind $class)?)
+                    }
+                )*
+
+                /// the same as a conversion without these classes: a dict of dicts and lists
+                fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+                    py.detach(|| self.inner.clone()).into_bound_py_any(py)
+                }
+
+                /// item access like on the dict from to_dict, without converting the other fields
+                fn __getitem__<'py>(
+                    &self,
+                    py: Python<'py>,
+                    key: &str,
+                ) -> PyResult<Bound<'py, PyAny>> {
+                    $(
+                        if key == stub_name!($field) {
+                            return py_field!(py, self.inner.$field);
+                        }
+                    )*
+                    self.to_dict(py)?.get_item(key)
+                }
+
+                /// the keys of the dict from to_dict, deprecated like iterating and in, which are
+                /// there because the metadata used to be dicts
+                fn keys(&self, py: Python<'_>) -> PyResult<Vec<&'static str>> {
+                    deprecated_dict(py, stringify!($s))?;
+                    let mut keys = vec![$(stub_name!($field)),*];
+                    keys.dedup();
+                    Ok(keys)
+                }
+
+                fn __iter__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyIterator>> {
+                    self.keys(py)?.into_pyobject(py)?.try_iter()
+                }
+
+                fn __contains__(&self, py: Python<'_>, key: &str) -> PyResult<bool> {
+                    Ok(self.keys(py)?.contains(&key))
+                }
+
+                fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+                    let fields: Vec<String> = vec![$(
+                        format!(
+                            "{}={}",
+                            stringify!($repr),
+                            py_field!(py, self.inner.$repr)?.repr()?
+                        )
+                    ),*];
+                    Ok(format!("{}({})", stringify!($s), fields.join(", ")))
+                }
+
+                $($($methods)*)?
+            }
+
+            #[cfg(test)]
+            impl $s {
+                fn stub() -> String {
+                    let mut stub = format!("class {}:\n", stringify!($s));
+                    stub.push_str(&stub::docstring(&[$($doc),*], "    "));
+                    $(
+                        stub.push_str(&format!(
+                            "    @property\n    def {}(self) -> {}: ...\n",
+                            stub::py_name(stringify!($name)),
+                            stub_type!($t, $field $(, $kind $class)?),
+                        ));
+                    )*
+                    stub.push_str("    def to_dict(self) -> dict[str, Any]: ...\n");
+                    stub.push_str("    def __getitem__(self, key: str) -> Any: ...\n");
+                    stub.push_str("    def keys(self) -> list[str]: ...\n");
+                    stub.push_str("    def __iter__(self) -> Iterator[str]: ...\n");
+                    stub.push_str("    def __contains__(self, key: str) -> bool: ...\n");
+                    $(stub.push_str(&stub::Sources::new().methods(
+                        stringify!($s),
+                        stringify!($($methods)*),
+                    ));)?
+                    stub
+                }
+            }
+        )*
+    };
+}
+
+impl_py_class! {
+    /// the root of the metadata, get one with Ome.from_xml
+    Ome: ome::Ome, repr(uuid, creator) {
+        uuid: uuid,
+        creator: creator,
+        rights: rights,
+        project: project,
+        dataset: dataset,
+        folder: folder,
+        experiment: experiment,
+        plate: plate,
+        screen: screen,
+        experimenter: experimenter,
+        experimenter_group: experimenter_group,
+        instrument: instrument list Instrument,
+        instruments: instrument list Instrument,
+        image: image list Image,
+        images: image list Image,
+        structured_annotations: structured_annotations,
+        roi: roi,
+        binary_only: binary_only,
+        original_namespace: original_namespace,
+        unknown_xml: unknown_xml,
+    } {
+        /// parse OME-XML given as str or bytes
+        #[staticmethod]
+        fn from_xml(py: Python<'_>, xml: Xml) -> PyResult<Self> {
+            Ok(Ome { inner: Shared::new(xml.parse(py)?) })
+        }
+
+        /// pickle as xml, including the elements and attributes which are not in the schema
+        fn __reduce__<'py>(slf: &Bound<'py, Self>) -> PyResult<(Bound<'py, PyAny>, (String,))> {
+            let inner = &slf.get().inner;
+            let xml = slf.py().detach(|| inner.to_xml_preserving())?;
+            Ok((slf.get_type().getattr("_from_pickle")?, (xml,)))
+        }
+
+        #[staticmethod]
+        fn _from_pickle(py: Python<'_>, xml: &str) -> PyResult<Self> {
+            let inner = py.detach(|| ome::Ome::from_str_preserving(xml))?;
+            Ok(Ome { inner: Shared::new(inner) })
+        }
+
+        /// the problems found in the metadata as dicts with severity ("warning" or "error"), code,
+        /// path (the ID of the element with the problem) and message, raises OmeValidationError
+        /// with the list as report if raise_on_error and there are errors
+        #[pyo3(signature = (*, raise_on_error = false))]
+        fn validate(&self, py: Python<'_>, raise_on_error: bool) -> PyResult<Vec<ValidationEntry>> {
+            let errors = py.detach(|| self.inner.validate());
+            let report = errors.iter().map(ValidationEntry::from).collect::<Vec<_>>();
+            let failed = errors
+                .iter()
+                .filter(|error| error.severity() == Severity::Error)
+                .collect::<Vec<_>>();
+            if raise_on_error && !failed.is_empty() {
+                let messages = failed.iter().map(|error| error.to_string()).collect::<Vec<_>>();
+                let err = OmeValidationError::new_err(messages.join("; "));
+                err.value(py).setattr("report", report.clone())?;
+                return Err(err);
+            }
+            Ok(report)
+        }
+
+        /// the inverse of to_dict
+        #[staticmethod]
+        fn from_dict(dict: &Bound<'_, PyDict>) -> PyResult<Self> {
+            Ok(Ome { inner: Shared::new(ome::Ome::from_python_dict(dict)?) })
+        }
+
+        /// read and parse an OME-XML file
+        #[staticmethod]
+        fn from_file(py: Python<'_>, path: PathBuf) -> PyResult<Self> {
+            Ok(Ome { inner: Shared::new(read_file(py, &path)?) })
+        }
+
+        /// serialize to OME-XML, indented if pretty, and leaving out attributes which have their
+        /// default value, like physical_size_x_unit="µm", if not write_default_units
+        #[pyo3(signature = (*, pretty = false, write_default_units = true))]
+        fn to_xml(&self, py: Python<'_>, pretty: bool, write_default_units: bool) -> PyResult<String> {
+            let options = crate::XmlOptions { pretty, write_default_units };
+            Ok(py.detach(|| self.inner.to_xml_with(options))?)
+        }
+    }
+    Image: ome::Image, repr(id, name) {
+        id: id,
+        name: name,
+        acquisition_date: acquisition_date,
+        experimenter_ref: experimenter_ref,
+        description: description,
+        experiment_ref: experiment_ref,
+        experimenter_group_ref: experimenter_group_ref,
+        instrument_ref: instrument_ref,
+        objective_settings: objective_settings,
+        imaging_environment: imaging_environment,
+        stage_label: stage_label,
+        pixels: pixels one Pixels,
+        roi_ref: roi_ref,
+        microbeam_manipulation_ref: microbeam_manipulation_ref,
+        annotation_ref: annotation_ref,
+    }
+    Pixels: ome::Pixels, repr(id, size_x, size_y, size_z, size_c, size_t) {
+        id: id,
+        dimension_order: dimension_order,
+        r#type: r#type,
+        significant_bits: significant_bits,
+        interleaved: interleaved,
+        big_endian: big_endian,
+        size_x: size_x,
+        size_y: size_y,
+        size_z: size_z,
+        size_c: size_c,
+        size_t: size_t,
+        physical_size_x: physical_size_x,
+        physical_size_x_unit: physical_size_x_unit,
+        physical_size_y: physical_size_y,
+        physical_size_y_unit: physical_size_y_unit,
+        physical_size_z: physical_size_z,
+        physical_size_z_unit: physical_size_z_unit,
+        time_increment: time_increment,
+        time_increment_unit: time_increment_unit,
+        channel: channel list Channel,
+        channels: channel list Channel,
+        bin_data: bin_data,
+        tiff_data: tiff_data,
+        metadata_only: metadata_only,
+        plane: plane list Plane,
+        planes: plane list Plane,
+    }
+    Channel: ome::Channel, repr(id, name) {
+        id: id,
+        name: name,
+        samples_per_pixel: samples_per_pixel,
+        illumination_type: illumination_type,
+        pinhole_size: pinhole_size,
+        pinhole_size_unit: pinhole_size_unit,
+        acquisition_mode: acquisition_mode,
+        contrast_method: contrast_method,
+        excitation_wavelength: excitation_wavelength,
+        excitation_wavelength_unit: excitation_wavelength_unit,
+        emission_wavelength: emission_wavelength,
+        emission_wavelength_unit: emission_wavelength_unit,
+        fluor: fluor,
+        nd_filter: nd_filter,
+        pockel_cell_setting: pockel_cell_setting,
+        color: color,
+        light_source_settings: light_source_settings,
+        detector_settings: detector_settings,
+        filter_set_ref: filter_set_ref,
+        annotation_ref: annotation_ref,
+        light_path: light_path,
+    }
+    Plane: ome::Plane, repr(the_z, the_c, the_t) {
+        the_z: the_z,
+        the_t: the_t,
+        the_c: the_c,
+        delta_t: delta_t,
+        delta_t_unit: delta_t_unit,
+        exposure_time: exposure_time,
+        exposure_time_unit: exposure_time_unit,
+        position_x: position_x,
+        position_x_unit: position_x_unit,
+        position_y: position_y,
+        position_y_unit: position_y_unit,
+        position_z: position_z,
+        position_z_unit: position_z_unit,
+        hash_sha1: hash_sha1,
+        annotation_ref: annotation_ref,
+    }
+    Instrument: ome::Instrument, repr(id) {
+        id: id,
+        microscope: microscope,
+        light_source_group: light_source_group,
+        detector: detector list Detector,
+        detectors: detector list Detector,
+        objective: objective list Objective,
+        objectives: objective list Objective,
+        filter_set: filter_set,
+        filter: filter,
+        dichroic: dichroic,
+        annotation_ref: annotation_ref,
+    }
+    Objective: ome::Objective, repr(id, model) {
+        manufacturer: manufacturer,
+        model: model,
+        serial_number: serial_number,
+        lot_number: lot_number,
+        id: id,
+        correction: correction,
+        immersion: immersion,
+        lens_na: lens_na,
+        nominal_magnification: nominal_magnification,
+        calibrated_magnification: calibrated_magnification,
+        working_distance: working_distance,
+        working_distance_unit: working_distance_unit,
+        iris: iris,
+        annotation_ref: annotation_ref,
+    }
+    Detector: ome::Detector, repr(id, model) {
+        manufacturer: manufacturer,
+        model: model,
+        serial_number: serial_number,
+        lot_number: lot_number,
+        gain: gain,
+        voltage: voltage,
+        voltage_unit: voltage_unit,
+        offset: offset,
+        zoom: zoom,
+        amplification_gain: amplification_gain,
+        id: id,
+        r#type: r#type,
+        annotation_ref: annotation_ref,
+    }
+}
+
+/// the type of the pixels, from a name like "uint16"
+#[pyclass(module = "ome_metadata.ome_metadata_rs", frozen)]
+pub struct PixelType {
+    inner: ome::PixelType,
+}
+
+#[pymethods]
+impl PixelType {
+    #[new]
+    fn new(name: Bound<'_, PyString>) -> PyResult<Self> {
+        Ok(PixelType {
+            inner: ome::PixelType::deserialize(PyDeserializer(name.as_any()))
+                .map_err(Error::from)?,
+        })
+    }
+
+    /// the numpy dtype, like "uint16", or with the byte order like ">u2" if big_endian, raises
+    /// ValueError for the complex and bit types
+    #[pyo3(signature = (big_endian = false))]
+    fn numpy_dtype(&self, big_endian: bool) -> PyResult<String> {
+        Ok(self.inner.numpy_dtype(big_endian)?)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("PixelType('{}')", self.inner)
+    }
+
+    fn __str__(&self) -> String {
+        self.inner.to_string()
+    }
+}
+
+fn pixels(ome: &Ome, image_index: usize) -> PyResult<&ome::Pixels> {
+    ome.inner
+        .image
+        .get(image_index)
+        .map(|image| &image.pixels)
+        .ok_or_else(|| PyIndexError::new_err(format!("there is no image {image_index}")))
+}
+
+/// the numpy dtype of the pixels of an image, like "uint16", or ">u2" if the pixels are big endian
+#[pyfunction]
+#[pyo3(signature = (ome, image_index = 0))]
+fn pixel_numpy_dtype(ome: PyRef<'_, Ome>, image_index: usize) -> PyResult<String> {
+    let pixels = pixels(&ome, image_index)?;
+    Ok(pixels
+        .r#type
+        .numpy_dtype(pixels.big_endian.unwrap_or(false))?)
+}
+
+/// a tuple of ints
+type Shape<'py> = Bound<'py, PyTuple>;
+
+/// the sizes of the axes of an image, in order, which is made of distinct letters from XYZCT
+#[pyfunction]
+#[pyo3(signature = (ome, image_index = 0, order = "TCZYX"))]
+fn image_shape<'py>(
+    py: Python<'py>,
+    ome: PyRef<'_, Ome>,
+    image_index: usize,
+    order: &str,
+) -> PyResult<Shape<'py>> {
+    PyTuple::new(py, pixels(&ome, image_index)?.shape(order)?)
+}
+
+/// an Ome, or a dict of dicts and lists like from Ome.to_dict
+#[derive(FromPyObject)]
+enum OmeLike<'py> {
+    Class(PyRef<'py, Ome>),
+    Dict(Bound<'py, PyDict>),
+}
+
+impl OmeLike<'_> {
+    fn metadata(&self) -> PyResult<Cow<'_, ome::Ome>> {
+        Ok(match self {
+            OmeLike::Class(ome) => Cow::Borrowed(&ome.inner),
+            OmeLike::Dict(dict) => Cow::Owned(ome::Ome::from_python_dict(dict)?),
+        })
+    }
+
+    /// a metadata struct wrapped in its class if self is an Ome, and as a dict otherwise
+    fn wrap<'py, T, C>(
+        &self,
+        py: Python<'py>,
+        inner: &T,
+        class: fn(T) -> C,
+    ) -> PyResult<Wrapped<'py, C>>
+    where
+        T: Clone + IntoPyObject<'py, Output = Bound<'py, PyDict>, Error = PyErr>,
+    {
+        Ok(match self {
+            OmeLike::Class(_) => Wrapped::Class(class(inner.clone())),
+            OmeLike::Dict(_) => Wrapped::Dict(inner.clone().into_pyobject(py)?),
+        })
+    }
+}
+
+/// a metadata struct in its class, or as a dict
+#[derive(IntoPyObject)]
+enum Wrapped<'py, C> {
+    Class(C),
+    Dict(Bound<'py, PyDict>),
+}
+
+/// an element given by its position in a list, or by its ID
+#[derive(FromPyObject)]
+enum Key {
+    Index(usize),
+    Id(String),
+}
+
+impl Key {
+    fn find<'a, T>(&self, items: &'a [T], id: fn(&T) -> &str) -> PyResult<&'a T> {
+        match self {
+            Key::Index(index) => items
+                .get(*index)
+                .ok_or_else(|| PyIndexError::new_err(format!("there is no element {index}"))),
+            Key::Id(key) => items
+                .iter()
+                .find(|item| id(item) == key)
+                .ok_or_else(|| PyKeyError::new_err(key.clone())),
+        }
+    }
+}
+
+/// the plane at z, c, t of an image given by index or ID, as a Plane if ome is an Ome and as a
+/// dict if it is a dict, None if there is no metadata for the plane
+#[pyfunction]
+fn plane<'py>(
+    py: Python<'py>,
+    ome: OmeLike<'py>,
+    image: Key,
+    z: i32,
+    c: i32,
+    t: i32,
+) -> PyResult<Option<Wrapped<'py, Plane>>> {
+    let metadata = ome.metadata()?;
+    let image = image.find(&metadata.image, |image| &image.id)?;
+    image
+        .pixels
+        .plane(z, c, t)
+        .map(|plane| {
+            ome.wrap(py, plane, |inner| Plane {
+                inner: Shared::new(inner),
+            })
+        })
+        .transpose()
+}
+
+/// the name of the well at row, column of a plate given by index or ID, like "B7"
+#[pyfunction]
+fn well_name(ome: OmeLike<'_>, plate: Key, row: i32, column: i32) -> PyResult<String> {
+    let metadata = ome.metadata()?;
+    Ok(plate
+        .find(&metadata.plate, |plate| &plate.id)?
+        .well_name(row, column))
+}
+
+/// the image of the first sample of a well given by name, like "B7", in a plate given by index
+/// or ID, as an Image if ome is an Ome and as a dict if it is a dict
+#[pyfunction]
+fn image_for_well<'py>(
+    py: Python<'py>,
+    ome: OmeLike<'py>,
+    plate: Key,
+    well: &str,
+) -> PyResult<Option<Wrapped<'py, Image>>> {
+    let metadata = ome.metadata()?;
+    let plate = plate.find(&metadata.plate, |plate| &plate.id)?;
+    metadata
+        .image_for_well(plate, well)
+        .map(|image| {
+            ome.wrap(py, image, |inner| Image {
+                inner: Shared::new(inner),
+            })
+        })
+        .transpose()
+}
+
+/// the IFD in the TIFF file with the plane at z, c, t of an image given by index or ID
+#[pyfunction]
+fn ifd_for(ome: OmeLike<'_>, image: Key, z: i32, c: i32, t: i32) -> PyResult<Option<i32>> {
+    let metadata = ome.metadata()?;
+    Ok(image
+        .find(&metadata.image, |image| &image.id)?
+        .pixels
+        .ifd(z, c, t))
+}
+
+/// serialize to JSON like Ome::to_json in Rust, with the names of the XML without the @ of
+/// attributes, like "ID", indented by indent spaces if given
+#[pyfunction]
+#[pyo3(signature = (ome, indent = None))]
+fn to_json(py: Python<'_>, ome: OmeLike<'_>, indent: Option<usize>) -> PyResult<String> {
+    let metadata = ome.metadata()?;
+    Ok(py.detach(|| match indent {
+        Some(indent) => metadata.to_json_indented(indent),
+        None => metadata.to_json(),
+    })?)
+}
+
+/// serialize to OME-XML like Ome.to_xml
+#[pyfunction]
+#[pyo3(signature = (ome, *, pretty = false, write_default_units = true))]
+fn to_xml(
+    py: Python<'_>,
+    ome: OmeLike<'_>,
+    pretty: bool,
+    write_default_units: bool,
+) -> PyResult<String> {
+    let metadata = ome.metadata()?;
+    let options = crate::XmlOptions {
+        pretty,
+        write_default_units,
+    };
+    Ok(py.detach(|| metadata.to_xml_with(options))?)
+}
+
+/// parse JSON made by to_json
+#[pyfunction]
+fn from_json(py: Python<'_>, text: &str) -> PyResult<Ome> {
+    let inner = py.detach(|| crate::Ome::from_json(text))?;
+    Ok(Ome {
+        inner: Shared::new(inner),
+    })
+}
+
+/// a dict which ome_types.OME.model_validate accepts, with the field names of ome-types, like
+/// "images" and "physical_size_x_unit", the values of enums as in the XML, like "µm", and the
+/// value of an XMLAnnotation as the any_elements of ome-types
+#[pyfunction]
+fn to_ome_types_dict<'py>(py: Python<'py>, ome: OmeLike<'py>) -> PyResult<Bound<'py, PyDict>> {
+    let metadata = ome.metadata()?;
+    let value = py
+        .detach(|| ome_types::from_ome(&metadata))
+        .map_err(Error::from)?;
+    Ok(ome_types::into_py(py, value)?.cast_into()?)
+}
+
+/// parse into a dict of dicts and lists, see Ome.from_xml for parsing into classes
+#[pyfunction(name = "ome")]
+fn ome_dict<'py>(py: Python<'py>, text: Xml) -> PyResult<Bound<'py, PyDict>> {
+    text.parse(py)?.into_pyobject(py)
+}
+
+/// read and parse a file into a dict like ome, the path can be a str or os.PathLike
 #[pyfunction]
-fn ome(text: &str) -> PyResult<Ome> {
-    Ok(text.parse()?)
+fn ome_from_file<'py>(py: Python<'py>, path: PathBuf) -> PyResult<Bound<'py, PyDict>> {
+    read_file(py, &path)?.into_pyobject(py)
 }
 
 #[pymodule]
@@ -94,6 +1348,40 @@ fn ome_metadata_rs(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<Pressure>()?;
     m.add_class::<Temperature>()?;
     m.add_class::<Time>()?;
-    m.add_function(wrap_pyfunction!(ome, m)?)?;
+    add_unit_variants(m.py())?;
+    m.add_class::<Ome>()?;
+    m.add_class::<PixelType>()?;
+    m.add_class::<Image>()?;
+    m.add_class::<Pixels>()?;
+    m.add_class::<Channel>()?;
+    m.add_class::<Plane>()?;
+    m.add_class::<Instrument>()?;
+    m.add_class::<Objective>()?;
+    m.add_class::<Detector>()?;
+    m.add_class::<Quantity>()?;
+    m.add("OmeError", m.py().get_type::<OmeError>())?;
+    m.add("OmeParseError", m.py().get_type::<OmeParseError>())?;
+    m.add("OmeUnitError", m.py().get_type::<OmeUnitError>())?;
+    m.add(
+        "OmeValidationError",
+        m.py().get_type::<OmeValidationError>(),
+    )?;
+    m.add_function(wrap_pyfunction!(ome_dict, m)?)?;
+    m.add_function(wrap_pyfunction!(pixel_numpy_dtype, m)?)?;
+    m.add_function(wrap_pyfunction!(image_shape, m)?)?;
+    m.add_function(wrap_pyfunction!(ome_from_file, m)?)?;
+    m.add_function(wrap_pyfunction!(plane, m)?)?;
+    m.add_function(wrap_pyfunction!(well_name, m)?)?;
+    m.add_function(wrap_pyfunction!(image_for_well, m)?)?;
+    m.add_function(wrap_pyfunction!(ifd_for, m)?)?;
+    m.add_function(wrap_pyfunction!(to_json, m)?)?;
+    m.add_function(wrap_pyfunction!(from_json, m)?)?;
+    m.add_function(wrap_pyfunction!(to_ome_types_dict, m)?)?;
+    m.add_function(wrap_pyfunction!(to_xml, m)?)?;
+    m.add_function(wrap_pyfunction!(new_image, m)?)?;
+    m.add_function(wrap_pyfunction!(new_ome, m)?)?;
+    m.add_function(wrap_pyfunction!(pixel_size, m)?)?;
+    m.add_function(wrap_pyfunction!(exposure_time, m)?)?;
+    m.add_function(wrap_pyfunction!(stage_position, m)?)?;
     Ok(())
 }
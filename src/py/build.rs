@@ -0,0 +1,244 @@
+//! keyword argument constructors, so that metadata can be written from Python, all arguments are
+//! checked before anything is made
+
+use super::*;
+
+/// the problems with the arguments of a constructor, raised together as OmeValidationError
+#[derive(Default)]
+struct Problems(Vec<ValidationEntry>);
+
+impl Problems {
+    fn add(&mut self, argument: &str, message: String) {
+        self.0.push(ValidationEntry {
+            severity: Severity::Error.to_string(),
+            code: "invalid-argument",
+            path: argument.to_string(),
+            message: format!("{argument}: {message}"),
+        });
+    }
+
+    /// the variant of an enum with this name, like "uint16" of PixelType
+    fn variant<'de, T: Deserialize<'de>>(
+        &mut self,
+        py: Python<'_>,
+        argument: &str,
+        name: &str,
+    ) -> Option<T> {
+        T::deserialize(PyDeserializer(PyString::new(py, name).as_any()))
+            .map_err(|err| self.add(argument, err.to_string()))
+            .ok()
+    }
+
+    /// check that there are size_c items in a list that is given for each channel
+    fn check_channels<T>(
+        &mut self,
+        argument: &str,
+        what: &str,
+        items: &Option<Vec<T>>,
+        size_c: i32,
+    ) {
+        if let Some(items) = items
+            && items.len() != size_c.max(0) as usize
+        {
+            self.add(
+                argument,
+                format!("{} {what} given for size_c={size_c}", items.len()),
+            );
+        }
+    }
+
+    fn raise(self, py: Python<'_>) -> PyResult<()> {
+        if self.0.is_empty() {
+            return Ok(());
+        }
+        let messages = self
+            .0
+            .iter()
+            .map(|entry| entry.message.clone())
+            .collect::<Vec<_>>();
+        let err = OmeValidationError::new_err(messages.join("; "));
+        err.value(py)
+            .setattr("report", self.0.into_bound_py_any(py)?)?;
+        Err(err)
+    }
+}
+
+/// an image with pixels of this size and type, and a channel for each of size_c, named by
+/// channels and with the packed RGBA colors in colors if given, physical sizes are in µm and
+/// time_increment in s, acquisition_date is an xsd:dateTime like 2025-01-29T14:42:42, the IDs are
+/// set by new_ome
+#[pyfunction]
+#[pyo3(signature = (
+    *,
+    size_x,
+    size_y,
+    size_z = 1,
+    size_c = 1,
+    size_t = 1,
+    pixel_type = "uint16",
+    dimension_order = "XYCZT",
+    name = None,
+    channels = None,
+    colors = None,
+    physical_size_x = None,
+    physical_size_y = None,
+    physical_size_z = None,
+    time_increment = None,
+    description = None,
+    acquisition_date = None,
+))]
+#[allow(clippy::too_many_arguments)]
+pub(super) fn new_image(
+    py: Python<'_>,
+    size_x: i32,
+    size_y: i32,
+    size_z: i32,
+    size_c: i32,
+    size_t: i32,
+    pixel_type: &str,
+    dimension_order: &str,
+    name: Option<String>,
+    channels: Option<Vec<String>>,
+    colors: Option<Vec<i32>>,
+    physical_size_x: Option<f32>,
+    physical_size_y: Option<f32>,
+    physical_size_z: Option<f32>,
+    time_increment: Option<f32>,
+    description: Option<String>,
+    acquisition_date: Option<String>,
+) -> PyResult<Image> {
+    let mut problems = Problems::default();
+    for (argument, size) in [
+        ("size_x", size_x),
+        ("size_y", size_y),
+        ("size_z", size_z),
+        ("size_c", size_c),
+        ("size_t", size_t),
+    ] {
+        if size < 1 {
+            problems.add(argument, format!("must be at least 1, not {size}"));
+        }
+    }
+    for (argument, size) in [
+        ("physical_size_x", physical_size_x),
+        ("physical_size_y", physical_size_y),
+        ("physical_size_z", physical_size_z),
+        ("time_increment", time_increment),
+    ] {
+        if let Some(size) = size.filter(|size| !size.is_finite() || *size <= 0.0) {
+            problems.add(argument, format!("must be positive and finite, not {size}"));
+        }
+    }
+    problems.check_channels("channels", "names", &channels, size_c);
+    problems.check_channels("colors", "colors", &colors, size_c);
+    let pixel_type = problems.variant::<ome::PixelType>(py, "pixel_type", pixel_type);
+    let dimension_order =
+        problems.variant::<ome::PixelsDimensionOrderType>(py, "dimension_order", dimension_order);
+    if let Some(date) = acquisition_date
+        .as_deref()
+        .filter(|date| !ome::is_xsd_date_time(date))
+    {
+        problems.add("acquisition_date", format!("{date} is not an xsd:dateTime"));
+    }
+    problems.raise(py)?;
+    let (Some(pixel_type), Some(dimension_order)) = (pixel_type, dimension_order) else {
+        unreachable!("the problems are raised");
+    };
+
+    let channel = (0..size_c as usize)
+        .map(|c| ome::Channel {
+            id: format!("Channel:0:{c}"),
+            name: channels.as_ref().map(|names| names[c].clone()),
+            samples_per_pixel: Some(1),
+            illumination_type: None,
+            pinhole_size: None,
+            pinhole_size_unit: ome::Channel::default_pinhole_size_unit(),
+            acquisition_mode: None,
+            contrast_method: None,
+            excitation_wavelength: None,
+            excitation_wavelength_unit: ome::Channel::default_excitation_wavelength_unit(),
+            emission_wavelength: None,
+            emission_wavelength_unit: ome::Channel::default_emission_wavelength_unit(),
+            fluor: None,
+            nd_filter: None,
+            pockel_cell_setting: None,
+            color: colors
+                .as_ref()
+                .map_or_else(ome::Channel::default_color, |colors| colors[c]),
+            light_source_settings: None,
+            detector_settings: None,
+            filter_set_ref: None,
+            annotation_ref: Vec::new(),
+            light_path: None,
+        })
+        .collect();
+    let pixels = ome::Pixels {
+        id: "Pixels:0".to_string(),
+        dimension_order,
+        r#type: pixel_type,
+        significant_bits: None,
+        interleaved: None,
+        big_endian: None,
+        size_x,
+        size_y,
+        size_z,
+        size_c,
+        size_t,
+        physical_size_x,
+        physical_size_x_unit: ome::Pixels::default_physical_size_x_unit(),
+        physical_size_y,
+        physical_size_y_unit: ome::Pixels::default_physical_size_y_unit(),
+        physical_size_z,
+        physical_size_z_unit: ome::Pixels::default_physical_size_z_unit(),
+        time_increment,
+        time_increment_unit: ome::Pixels::default_time_increment_unit(),
+        channel,
+        bin_data: Vec::new(),
+        tiff_data: Vec::new(),
+        metadata_only: None,
+        plane: Vec::new(),
+    };
+    let inner = ome::Image {
+        id: "Image:0".to_string(),
+        name,
+        acquisition_date,
+        experimenter_ref: None,
+        description,
+        experiment_ref: None,
+        experimenter_group_ref: None,
+        instrument_ref: None,
+        objective_settings: None,
+        imaging_environment: None,
+        stage_label: None,
+        pixels,
+        roi_ref: Vec::new(),
+        microbeam_manipulation_ref: Vec::new(),
+        annotation_ref: Vec::new(),
+    };
+    Ok(Image {
+        inner: Shared::new(inner),
+    })
+}
+
+/// metadata with these images, which get the IDs Image:0, Image:1, ... in order, with their
+/// pixels and channels numbered the same way
+#[pyfunction]
+#[pyo3(signature = (*, images = Vec::new(), creator = None))]
+pub(super) fn new_ome(images: Vec<PyRef<'_, Image>>, creator: Option<String>) -> Ome {
+    let mut inner = ome::Ome {
+        creator,
+        ..Default::default()
+    };
+    for (i, image) in images.iter().enumerate() {
+        let mut image = image.inner.clone();
+        image.id = format!("Image:{i}");
+        image.pixels.id = format!("Pixels:{i}");
+        for (c, channel) in image.pixels.channel.iter_mut().enumerate() {
+            channel.id = format!("Channel:{i}:{c}");
+        }
+        inner.image.push(image);
+    }
+    Ome {
+        inner: Shared::new(inner),
+    }
+}
@@ -0,0 +1,287 @@
+//! the dicts which ome_types.OME.model_validate accepts, made from the serde representation, which
+//! has the names of the XML elements and attributes and the values of enums as in the XML
+
+use crate::ome::{self, StructuredAnnotationsContent, XmlElement, XmlNode};
+use pyo3::IntoPyObjectExt;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+use serde_json::{Map, Value, json};
+use std::collections::HashMap;
+
+/// the names of lists in ome-types which are not the element name with an s
+const PLURALS: [(&str, &str); 5] = [
+    ("bin_data", "bin_data_blocks"),
+    ("tiff_data", "tiff_data_blocks"),
+    ("excitation_filter_ref", "excitation_filters"),
+    ("emission_filter_ref", "emission_filters"),
+    ("m", "ms"),
+];
+
+/// like PhysicalSizeXUnit -> physical_size_x_unit, ROIRef -> roi_ref and @ID -> id
+fn snake_case(name: &str) -> String {
+    let chars = name.trim_start_matches('@').chars().collect::<Vec<_>>();
+    let mut snake = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if i > 0 && c.is_uppercase() {
+            let previous = chars[i - 1];
+            let next_is_lowercase = chars.get(i + 1).is_some_and(|next| next.is_lowercase());
+            if previous.is_lowercase() || (previous.is_uppercase() && next_is_lowercase) {
+                snake.push('_');
+            }
+        }
+        snake.extend(c.to_lowercase());
+    }
+    snake
+}
+
+fn plural(name: String) -> String {
+    match PLURALS.iter().find(|(singular, _)| *singular == name) {
+        Some((_, plural)) => plural.to_string(),
+        None if name.ends_with('s') => name,
+        None => format!("{name}s"),
+    }
+}
+
+/// rename the keys to the field names of ome-types, lists get a plural name, text content is
+/// value, and the elements of a choice, like the shapes in a Union, are put in a list per kind
+fn convert(value: Value) -> Value {
+    match value {
+        Value::Object(object) => {
+            let mut converted = Map::new();
+            for (key, value) in object {
+                match (key.as_str(), value) {
+                    ("$value", Value::Array(items)) => {
+                        for item in items {
+                            for (kind, value) in choice(item) {
+                                let list = converted
+                                    .entry(plural(snake_case(&kind)))
+                                    .or_insert_with(|| Value::Array(Vec::new()));
+                                if let Value::Array(list) = list {
+                                    list.push(convert(value));
+                                }
+                            }
+                        }
+                    }
+                    ("$value", Value::Object(object)) => {
+                        for (kind, value) in object {
+                            converted.insert(snake_case(&kind), convert(value));
+                        }
+                    }
+                    ("$value" | "$text", value) => {
+                        converted.insert("value".to_string(), convert(value));
+                    }
+                    (key, Value::Array(items)) => {
+                        converted.insert(
+                            plural(snake_case(key)),
+                            Value::Array(items.into_iter().map(convert).collect()),
+                        );
+                    }
+                    (key, value) => {
+                        converted.insert(snake_case(key), convert(value));
+                    }
+                }
+            }
+            Value::Object(converted)
+        }
+        Value::Array(items) => Value::Array(items.into_iter().map(convert).collect()),
+        value => value,
+    }
+}
+
+/// the dict of the metadata, with the value of each XMLAnnotation as the any_elements of ome-types
+pub(super) fn from_ome(metadata: &ome::Ome) -> serde_json::Result<Value> {
+    let mut value = convert(serde_json::to_value(metadata)?);
+    let values = metadata
+        .structured_annotations
+        .iter()
+        .flat_map(|annotations| &annotations.content)
+        .filter_map(|annotation| match annotation {
+            StructuredAnnotationsContent::XmlAnnotation(annotation) => Some(&annotation.value),
+            _ => None,
+        });
+    if let Some(Value::Array(converted)) =
+        value.pointer_mut("/structured_annotations/xml_annotations")
+    {
+        for (converted, xml) in converted.iter_mut().zip(values) {
+            let mut namespaces = Namespaces::from([(String::new(), ome::NAMESPACE.to_string())]);
+            declare(&mut namespaces, &xml.attributes);
+            let (_, any_elements) = any_elements(&xml.children, &namespaces);
+            converted["value"] = json!({ "any_elements": any_elements });
+        }
+    }
+    Ok(value)
+}
+
+/// the namespaces by prefix, "" is the default namespace
+type Namespaces = HashMap<String, String>;
+
+/// add the namespaces declared in attributes
+fn declare(namespaces: &mut Namespaces, attributes: &[(String, String)]) {
+    for (name, value) in attributes {
+        if name == "xmlns" {
+            namespaces.insert(String::new(), value.clone());
+        } else if let Some(prefix) = name.strip_prefix("xmlns:") {
+            namespaces.insert(prefix.to_string(), value.clone());
+        }
+    }
+}
+
+/// the text before the first element, and the elements like the AnyElement of xsdata, which
+/// ome-types uses for xml of any kind, each with the text after it as its tail
+fn any_elements(nodes: &[XmlNode], namespaces: &Namespaces) -> (Option<String>, Vec<Value>) {
+    let mut text: Option<String> = None;
+    let mut elements: Vec<(&XmlElement, Option<String>)> = Vec::new();
+    for node in nodes {
+        match node {
+            XmlNode::Text(node) => {
+                let text = match elements.last_mut() {
+                    Some((_, tail)) => tail,
+                    None => &mut text,
+                };
+                text.get_or_insert_with(String::new).push_str(node);
+            }
+            XmlNode::Element(element) => elements.push((element, None)),
+        }
+    }
+    let elements = elements
+        .into_iter()
+        .map(|(element, tail)| any_element(element, tail, namespaces))
+        .collect();
+    (text, elements)
+}
+
+/// the names are in Clark notation, like {http://example.com}name, names with a prefix that is
+/// not declared are kept as they are, and namespace declarations are not attributes
+fn any_element(element: &XmlElement, tail: Option<String>, namespaces: &Namespaces) -> Value {
+    let mut namespaces = namespaces.clone();
+    declare(&mut namespaces, &element.attributes);
+    let qname = |name: &str, default: bool| {
+        let (prefix, local) = match name.split_once(':') {
+            Some((prefix, local)) => (prefix, local),
+            None if default => ("", name),
+            None => return name.to_string(),
+        };
+        match namespaces.get(prefix) {
+            Some(namespace) => format!("{{{namespace}}}{local}"),
+            None => name.to_string(),
+        }
+    };
+    let attributes = element
+        .attributes
+        .iter()
+        .filter(|(name, _)| name != "xmlns" && !name.starts_with("xmlns:"))
+        .map(|(name, value)| (qname(name, false), Value::from(value.as_str())))
+        .collect::<Map<_, _>>();
+    let (text, children) = any_elements(&element.children, &namespaces);
+    json!({
+        "qname": qname(&element.name, true),
+        "text": text,
+        "tail": tail,
+        "children": children,
+        "attributes": attributes,
+    })
+}
+
+/// the kind and the content of an element of a choice, like {"Rectangle": {...}}
+fn choice(item: Value) -> Map<String, Value> {
+    match item {
+        Value::Object(object) => object,
+        value => Map::from_iter([("value".to_string(), value)]),
+    }
+}
+
+pub(super) fn into_py<'py>(py: Python<'py>, value: Value) -> PyResult<Bound<'py, PyAny>> {
+    match value {
+        Value::Null => Ok(py.None().into_bound(py)),
+        Value::Bool(value) => value.into_bound_py_any(py),
+        Value::Number(number) => {
+            if let Some(number) = number.as_i64() {
+                number.into_bound_py_any(py)
+            } else if let Some(number) = number.as_u64() {
+                number.into_bound_py_any(py)
+            } else {
+                number.as_f64().into_bound_py_any(py)
+            }
+        }
+        Value::String(value) => value.into_bound_py_any(py),
+        Value::Array(items) => {
+            let list = PyList::empty(py);
+            for item in items {
+                list.append(into_py(py, item)?)?;
+            }
+            Ok(list.into_any())
+        }
+        Value::Object(object) => {
+            let dict = PyDict::new(py);
+            for (key, value) in object {
+                dict.set_item(key, into_py(py, value)?)?;
+            }
+            Ok(dict.into_any())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names() {
+        for (xml, python) in [
+            ("@PhysicalSizeXUnit", "physical_size_x_unit"),
+            ("ROIRef", "roi_ref"),
+            ("@ID", "id"),
+            ("HashSHA1", "hash_sha1"),
+            ("@MIMEType", "mime_type"),
+            ("NDFilter", "nd_filter"),
+            ("@TheZ", "the_z"),
+            ("@A00", "a00"),
+        ] {
+            assert_eq!(snake_case(xml), python);
+        }
+        assert_eq!(plural("image".to_string()), "images");
+        assert_eq!(plural("tiff_data".to_string()), "tiff_data_blocks");
+        assert_eq!(
+            plural("light_source_settings".to_string()),
+            "light_source_settings"
+        );
+    }
+
+    #[test]
+    fn xml_annotation() -> Result<(), crate::error::Error> {
+        let metadata: ome::Ome = r#"<OME xmlns="http://www.openmicroscopy.org/Schemas/OME/2016-06">
+            <StructuredAnnotations>
+                <XMLAnnotation ID="Annotation:0"><Value><a x="1">b<c xmlns="urn:v"/>d</a><e/>f</Value></XMLAnnotation>
+            </StructuredAnnotations>
+        </OME>"#
+            .parse()?;
+        let value = from_ome(&metadata)?;
+        let ns = ome::NAMESPACE;
+        assert_eq!(
+            value["structured_annotations"]["xml_annotations"][0]["value"],
+            json!({"any_elements": [
+                {
+                    "qname": format!("{{{ns}}}a"),
+                    "text": "b",
+                    "tail": null,
+                    "children": [{
+                        "qname": "{urn:v}c",
+                        "text": null,
+                        "tail": "d",
+                        "children": [],
+                        "attributes": {},
+                    }],
+                    "attributes": {"x": "1"},
+                },
+                {
+                    "qname": format!("{{{ns}}}e"),
+                    "text": null,
+                    "tail": "f",
+                    "children": [],
+                    "attributes": {},
+                },
+            ]})
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,207 @@
+//! values together with their unit, so that a value and the field with its unit, like
+//! physical_size_x and physical_size_x_unit, cannot get separated
+
+use super::*;
+
+/// a value with a unit of one of the unit classes, like Quantity(0.65, Length.um)
+#[pyclass(module = "ome_metadata.ome_metadata_rs", frozen)]
+pub struct Quantity {
+    value: f64,
+    unit: AnyUnit,
+}
+
+impl Quantity {
+    fn of(value: Option<f32>, unit: AnyUnit) -> Option<Self> {
+        value.map(|value| Quantity {
+            value: value as f64,
+            unit,
+        })
+    }
+
+    /// the value in the unit of self
+    fn value_in_unit_of(&self, other: &Quantity) -> PyResult<f64> {
+        other.unit.convert(&self.unit, other.value)
+    }
+}
+
+#[derive(FromPyObject)]
+enum Divisor<'py> {
+    Quantity(PyRef<'py, Quantity>),
+    Number(f64),
+}
+
+#[derive(IntoPyObject)]
+enum Quotient {
+    Quantity(Quantity),
+    Ratio(f64),
+}
+
+#[pymethods]
+impl Quantity {
+    #[new]
+    fn new(value: f64, unit: AnyUnit) -> Self {
+        Quantity { value, unit }
+    }
+
+    #[getter]
+    fn value(&self) -> f64 {
+        self.value
+    }
+
+    #[getter]
+    fn unit(&self) -> AnyUnit {
+        self.unit.clone()
+    }
+
+    /// this quantity in another unit of the same class, given like Length.um or by name
+    fn to(&self, unit: UnitLike) -> PyResult<Self> {
+        let unit = self.unit.like(unit)?;
+        Ok(Quantity {
+            value: self.unit.convert(&unit, self.value)?,
+            unit,
+        })
+    }
+
+    /// the sum in the unit of self
+    fn __add__(&self, other: PyRef<'_, Quantity>) -> PyResult<Self> {
+        Ok(Quantity {
+            value: self.value + self.value_in_unit_of(&other)?,
+            unit: self.unit.clone(),
+        })
+    }
+
+    /// the difference in the unit of self
+    fn __sub__(&self, other: PyRef<'_, Quantity>) -> PyResult<Self> {
+        Ok(Quantity {
+            value: self.value - self.value_in_unit_of(&other)?,
+            unit: self.unit.clone(),
+        })
+    }
+
+    fn __mul__(&self, factor: f64) -> Self {
+        Quantity {
+            value: self.value * factor,
+            unit: self.unit.clone(),
+        }
+    }
+
+    fn __rmul__(&self, factor: f64) -> Self {
+        self.__mul__(factor)
+    }
+
+    /// divided by a number this is a quantity, divided by a quantity of the same class it is the
+    /// ratio of the two
+    fn __truediv__(&self, other: Divisor<'_>) -> PyResult<Quotient> {
+        Ok(match other {
+            Divisor::Quantity(other) => {
+                Quotient::Ratio(self.value / self.value_in_unit_of(&other)?)
+            }
+            Divisor::Number(divisor) => Quotient::Quantity(Quantity {
+                value: self.value / divisor,
+                unit: self.unit.clone(),
+            }),
+        })
+    }
+
+    fn __neg__(&self) -> Self {
+        self.__mul__(-1.0)
+    }
+
+    fn __abs__(&self) -> Self {
+        Quantity {
+            value: self.value.abs(),
+            unit: self.unit.clone(),
+        }
+    }
+
+    /// quantities are compared after converting them into the same unit
+    fn __richcmp__<'py>(
+        &self,
+        other: &Bound<'py, PyAny>,
+        op: CompareOp,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let py = other.py();
+        let Some(other) = other
+            .cast::<Quantity>()
+            .ok()
+            .and_then(|other| self.value_in_unit_of(other.get()).ok())
+        else {
+            return Ok(py.NotImplemented().into_bound(py));
+        };
+        self.value
+            .partial_cmp(&other)
+            .is_some_and(|ordering| op.matches(ordering))
+            .into_bound_py_any(py)
+    }
+
+    fn __getnewargs__(&self) -> (f64, AnyUnit) {
+        (self.value, self.unit.clone())
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Quantity({:?}, {})", self.value, self.unit.name())
+    }
+
+    fn __str__(&self) -> String {
+        format!("{:?} {}", self.value, self.unit.symbol())
+    }
+}
+
+fn plane_at(pixels: &ome::Pixels, plane_index: usize) -> PyResult<&ome::Plane> {
+    pixels
+        .plane
+        .get(plane_index)
+        .ok_or_else(|| PyIndexError::new_err(format!("there is no plane {plane_index}")))
+}
+
+/// the size of a pixel along the axis x, y or z of an image given by index or ID, None if it is
+/// not known
+#[pyfunction]
+#[pyo3(signature = (ome, image = Key::Index(0), axis = "x"))]
+pub(super) fn pixel_size(ome: OmeLike<'_>, image: Key, axis: &str) -> PyResult<Option<Quantity>> {
+    let metadata = ome.metadata()?;
+    let pixels = &image.find(&metadata.image, |image| &image.id)?.pixels;
+    let (size, unit) = match axis {
+        "x" | "X" => (pixels.physical_size_x, &pixels.physical_size_x_unit),
+        "y" | "Y" => (pixels.physical_size_y, &pixels.physical_size_y_unit),
+        "z" | "Z" => (pixels.physical_size_z, &pixels.physical_size_z_unit),
+        _ => return Err(PyValueError::new_err(format!("there is no axis {axis}"))),
+    };
+    Ok(Quantity::of(size, AnyUnit::Length(unit.clone())))
+}
+
+/// the exposure time of a plane of an image given by index or ID, the plane is given by its
+/// index in the list of planes, None if it is not known
+#[pyfunction]
+pub(super) fn exposure_time(
+    ome: OmeLike<'_>,
+    image: Key,
+    plane_index: usize,
+) -> PyResult<Option<Quantity>> {
+    let metadata = ome.metadata()?;
+    let pixels = &image.find(&metadata.image, |image| &image.id)?.pixels;
+    let plane = plane_at(pixels, plane_index)?;
+    Ok(Quantity::of(
+        plane.exposure_time,
+        AnyUnit::Time(plane.exposure_time_unit.clone()),
+    ))
+}
+
+/// the x, y and z position of the stage at a plane of an image like in exposure_time, each None if
+/// it is not known
+#[pyfunction]
+pub(super) fn stage_position(
+    ome: OmeLike<'_>,
+    image: Key,
+    plane_index: usize,
+) -> PyResult<(Option<Quantity>, Option<Quantity>, Option<Quantity>)> {
+    let metadata = ome.metadata()?;
+    let pixels = &image.find(&metadata.image, |image| &image.id)?.pixels;
+    let plane = plane_at(pixels, plane_index)?;
+    let position = |value, unit: &UnitsLength| Quantity::of(value, AnyUnit::Length(unit.clone()));
+    Ok((
+        position(plane.position_x, &plane.position_x_unit),
+        position(plane.position_y, &plane.position_y_unit),
+        position(plane.position_z, &plane.position_z_unit),
+    ))
+}
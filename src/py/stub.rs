@@ -0,0 +1,619 @@
+//! the type stub ome_metadata_rs.pyi, the classes made by the macros in py.rs write their own
+//! part of it, and the signatures of the other functions and methods are read from the source by
+//! syn, so that it is updated together with the code
+
+use super::*;
+use std::collections::HashMap;
+
+/// the Python type of the object something is converted into
+pub(super) trait PyStub {
+    fn py_type() -> String;
+}
+
+/// the Python type of a converted object, for the types which are the Target of IntoPyObject
+pub(super) trait PyStubTarget {
+    const PY_TYPE: &'static str;
+}
+
+/// the Python type of something converted into Target, Option and Vec are converted into PyAny,
+/// and have their own impl so that they don't overlap with the other targets
+pub(super) trait PyStubOf<Target: ?Sized> {
+    fn py_type() -> String;
+}
+
+impl<T> PyStub for T
+where
+    T: IntoPyObject<'static> + PyStubOf<T::Target>,
+{
+    fn py_type() -> String {
+        <T as PyStubOf<T::Target>>::py_type()
+    }
+}
+
+impl<T, Target: PyStubTarget> PyStubOf<Target> for T {
+    fn py_type() -> String {
+        Target::PY_TYPE.to_string()
+    }
+}
+
+impl<T: PyStub> PyStubOf<PyAny> for Option<T> {
+    fn py_type() -> String {
+        format!("{} | None", T::py_type())
+    }
+}
+
+impl<T: PyStub> PyStubOf<PyAny> for Vec<T> {
+    fn py_type() -> String {
+        format!("list[{}]", T::py_type())
+    }
+}
+
+/// the xml that was not understood while parsing, an enum of which the variants are dicts
+impl PyStubOf<PyAny> for crate::preserve::UnknownXml {
+    fn py_type() -> String {
+        "dict[str, Any]".to_string()
+    }
+}
+
+macro_rules! impl_py_stub_target {
+    ($($t:ty: $name:literal),* $(,)?) => {
+        $(
+            impl PyStubTarget for $t {
+                const PY_TYPE: &'static str = $name;
+            }
+        )*
+    };
+}
+
+impl_py_stub_target! {
+    PyBool: "bool",
+    PyDict: "dict[str, Any]",
+    PyFloat: "float",
+    PyInt: "int",
+    PyString: "str",
+}
+
+/// the Python type of a field, given by a closure which gets it
+pub(super) fn field_type<S, T: PyStub>(_: fn(&S) -> &T) -> String {
+    T::py_type()
+}
+
+/// the name of a getter or class in Python
+pub(super) fn py_name(name: &str) -> &str {
+    name.trim_start_matches("r#")
+}
+
+/// a docstring indented by indent from the lines of a doc comment
+pub(super) fn docstring(lines: &[&str], indent: &str) -> String {
+    if lines.is_empty() {
+        return String::new();
+    }
+    let text = lines
+        .iter()
+        .map(|line| line.strip_prefix(' ').unwrap_or(line))
+        .collect::<Vec<_>>()
+        .join(&format!("\n{indent}"));
+    // a quote just before the closing quotes would end the docstring
+    let text = match text.strip_suffix('"') {
+        Some(text) => format!("{text}\\\""),
+        None => text,
+    };
+    format!("{indent}\"\"\"{text}\"\"\"\n")
+}
+
+/// the keywords of Python, class attributes with these names can only be used with getattr
+const KEYWORDS: [&str; 35] = [
+    "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class", "continue",
+    "def", "del", "elif", "else", "except", "finally", "for", "from", "global", "if", "import",
+    "in", "is", "lambda", "nonlocal", "not", "or", "pass", "raise", "return", "try", "while",
+    "with", "yield",
+];
+
+/// the class attributes of a class with its own type, except those which are a Python keyword,
+/// like the unit as (attoseconds)
+pub(super) fn class_attributes(class: &str, names: impl Iterator<Item = String>) -> String {
+    names
+        .filter(|name| !KEYWORDS.contains(&name.as_str()))
+        .map(|name| format!("    {name}: ClassVar[{class}]\n"))
+        .collect()
+}
+
+const HEADER: &str = r#"# generated by the stub test in src/py/stub.rs, do not edit
+from __future__ import annotations
+
+from collections.abc import Iterator
+from os import PathLike
+from typing import Any, ClassVar
+
+"#;
+
+const EXCEPTIONS: &str = r#"class OmeError(ValueError): ...
+class OmeParseError(OmeError): ...
+class OmeUnitError(OmeError): ...
+class OmeValidationError(OmeError):
+    report: list[dict[str, Any]]
+"#;
+
+/// the Python types of the Rust types which are not a class, or an enum or struct with a derived
+/// conversion
+const TYPES: [(&str, &str); 17] = [
+    ("bool", "bool"),
+    ("f32", "float"),
+    ("f64", "float"),
+    ("i32", "int"),
+    ("i64", "int"),
+    ("u32", "int"),
+    ("u64", "int"),
+    ("usize", "int"),
+    ("str", "str"),
+    ("String", "str"),
+    ("PathBuf", "str | PathLike[str]"),
+    ("PyAny", "Any"),
+    ("PyDict", "dict[str, Any]"),
+    ("PyString", "str"),
+    ("PyTuple", "tuple[Any, ...]"),
+    ("Shape", "tuple[int, ...]"),
+    ("AnyUnit", "_Unit"),
+];
+
+/// the Rust types which are converted like the type in them
+const WRAPPERS: [&str; 5] = ["Borrowed", "Bound", "Py", "PyRef", "PyResult"];
+
+/// an argument in the signature of a pyfunction
+enum Argument {
+    /// the * after which the arguments can only be given by keyword
+    Star,
+    /// the name of an argument with its default in Python
+    Named(String, Option<String>),
+}
+
+/// the name, signature and decorators given by the attributes of a function
+#[derive(Default)]
+struct Attributes {
+    name: Option<String>,
+    signature: Option<Vec<Argument>>,
+    new: bool,
+    staticmethod: bool,
+    getter: bool,
+}
+
+impl Attributes {
+    fn new(attrs: &[syn::Attribute]) -> Self {
+        let mut attributes = Attributes::default();
+        for attr in attrs {
+            let path = attr.path();
+            attributes.new |= path.is_ident("new");
+            attributes.staticmethod |= path.is_ident("staticmethod");
+            attributes.getter |= path.is_ident("getter");
+            if (path.is_ident("pyo3") || path.is_ident("pyfunction"))
+                && matches!(attr.meta, syn::Meta::List(_))
+            {
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("name") {
+                        attributes.name = Some(meta.value()?.parse::<syn::LitStr>()?.value());
+                    } else if meta.path.is_ident("signature") {
+                        attributes.signature = Some(signature(meta.value()?)?);
+                    }
+                    Ok(())
+                })
+                .unwrap();
+            }
+        }
+        attributes
+    }
+}
+
+/// the arguments in signature = (...)
+fn signature(input: syn::parse::ParseStream) -> syn::Result<Vec<Argument>> {
+    let content;
+    syn::parenthesized!(content in input);
+    let mut arguments = Vec::new();
+    while !content.is_empty() {
+        if content.parse::<Option<syn::Token![*]>>()?.is_some() {
+            arguments.push(Argument::Star);
+        } else {
+            let name = content.parse::<syn::Ident>()?.to_string();
+            let default = match content.parse::<Option<syn::Token![=]>>()? {
+                Some(_) => Some(py_default(&content.parse()?)),
+                None => None,
+            };
+            arguments.push(Argument::Named(name, default));
+        }
+        if !content.is_empty() {
+            content.parse::<syn::Token![,]>()?;
+        }
+    }
+    Ok(arguments)
+}
+
+/// a default value in Python, like a literal, ... for values which cannot be written like that
+fn py_default(expr: &syn::Expr) -> String {
+    match expr {
+        syn::Expr::Lit(literal) => match &literal.lit {
+            syn::Lit::Str(literal) => format!("{:?}", literal.value()),
+            syn::Lit::Bool(literal) if literal.value => "True".to_string(),
+            syn::Lit::Bool(_) => "False".to_string(),
+            syn::Lit::Int(literal) => literal.base10_digits().to_string(),
+            syn::Lit::Float(literal) => literal.base10_digits().to_string(),
+            _ => "...".to_string(),
+        },
+        syn::Expr::Path(path) if path.path.is_ident("None") => "None".to_string(),
+        // an enum variant around a value, like Key::Index(0)
+        syn::Expr::Call(call) if call.args.len() == 1 => py_default(&call.args[0]),
+        _ => "...".to_string(),
+    }
+}
+
+/// the Rust source of the functions and classes which are not made by the macros, from which the
+/// signatures in the stub are read, and the enums and structs of the arguments and return values
+pub(super) struct Sources(Vec<syn::Item>);
+
+impl Sources {
+    pub(super) fn new() -> Self {
+        let sources = [
+            include_str!("../py.rs"),
+            include_str!("build.rs"),
+            include_str!("quantity.rs"),
+        ];
+        Sources(
+            sources
+                .iter()
+                .flat_map(|source| syn::parse_file(source).unwrap().items)
+                .collect(),
+        )
+    }
+
+    /// the Python type of a Rust type, class is the type of Self
+    fn py_type(&self, ty: &syn::Type, class: &str, generics: &HashMap<String, String>) -> String {
+        let path = match ty {
+            syn::Type::Reference(reference) => {
+                return self.py_type(&reference.elem, class, generics);
+            }
+            syn::Type::Tuple(tuple) if tuple.elems.is_empty() => return "None".to_string(),
+            syn::Type::Tuple(tuple) => {
+                let types = tuple
+                    .elems
+                    .iter()
+                    .map(|ty| self.py_type(ty, class, generics))
+                    .collect::<Vec<_>>();
+                return format!("tuple[{}]", types.join(", "));
+            }
+            syn::Type::Path(path) => path.path.segments.last().unwrap(),
+            _ => panic!("no Python type for {ty:?}"),
+        };
+        let name = path.ident.to_string();
+        let arguments = match &path.arguments {
+            syn::PathArguments::AngleBracketed(arguments) => arguments
+                .args
+                .iter()
+                .filter_map(|argument| match argument {
+                    syn::GenericArgument::Type(ty) => Some(ty),
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+        let argument = |i: usize| self.py_type(arguments[i], class, generics);
+        if let Some(ty) = generics.get(&name) {
+            return ty.clone();
+        }
+        if let Some((_, ty)) = TYPES.iter().find(|(rust, _)| *rust == name) {
+            return ty.to_string();
+        }
+        match name.as_str() {
+            "Self" => return class.to_string(),
+            "Option" => return format!("{} | None", argument(0)),
+            "Vec"
+                if name == "Vec"
+                    && matches!(arguments[0], syn::Type::Path(path) if path.path.is_ident("u8")) =>
+            {
+                return "bytes".to_string();
+            }
+            "Vec" => return format!("list[{}]", argument(0)),
+            _ if WRAPPERS.contains(&name.as_str()) => return argument(arguments.len() - 1),
+            _ => {}
+        }
+        for item in &self.0 {
+            match item {
+                syn::Item::Enum(item) if item.ident == name && derives_conversion(&item.attrs) => {
+                    let generics = item
+                        .generics
+                        .type_params()
+                        .map(|param| param.ident.to_string())
+                        .zip((0..arguments.len()).map(argument))
+                        .collect();
+                    let types = item
+                        .variants
+                        .iter()
+                        .map(|variant| {
+                            let field = variant.fields.iter().next().unwrap();
+                            self.py_type(&field.ty, class, &generics)
+                        })
+                        .collect::<Vec<_>>();
+                    return types.join(" | ");
+                }
+                syn::Item::Struct(item)
+                    if item.ident == name && derives_conversion(&item.attrs) =>
+                {
+                    return "dict[str, Any]".to_string();
+                }
+                _ => {}
+            }
+        }
+        // a class
+        name
+    }
+
+    /// the stub of a function or method, with its decorators, class is None for a function
+    fn function(
+        &self,
+        attrs: &[syn::Attribute],
+        sig: &syn::Signature,
+        class: Option<&str>,
+    ) -> String {
+        let attributes = Attributes::new(attrs);
+        let indent = if class.is_some() { "    " } else { "" };
+        let class = class.unwrap_or_default();
+        let no_generics = HashMap::new();
+        let mut receiver = false;
+        let mut arguments = Vec::new();
+        for input in &sig.inputs {
+            match input {
+                syn::FnArg::Receiver(_) => receiver = true,
+                syn::FnArg::Typed(typed) => {
+                    let syn::Pat::Ident(name) = &*typed.pat else {
+                        panic!("{} has an argument without a name", sig.ident);
+                    };
+                    let is_python = matches!(&*typed.ty, syn::Type::Path(path) if path.path.segments.last().unwrap().ident == "Python");
+                    if name.ident == "slf" {
+                        receiver = true;
+                    } else if !is_python {
+                        let py_type = self.py_type(&typed.ty, class, &no_generics);
+                        arguments.push((name.ident.to_string(), py_type));
+                    }
+                }
+            }
+        }
+        let mut parameters = Vec::new();
+        if receiver || attributes.new {
+            parameters.push("self".to_string());
+        }
+        match &attributes.signature {
+            Some(signature) => {
+                for argument in signature {
+                    parameters.push(match argument {
+                        Argument::Star => "*".to_string(),
+                        Argument::Named(name, default) => {
+                            let (_, py_type) = arguments
+                                .iter()
+                                .find(|(argument, _)| argument == name)
+                                .unwrap_or_else(|| panic!("{} has no argument {name}", sig.ident));
+                            match default {
+                                Some(default) => format!("{name}: {py_type} = {default}"),
+                                None => format!("{name}: {py_type}"),
+                            }
+                        }
+                    });
+                }
+            }
+            None => {
+                for (name, py_type) in &arguments {
+                    parameters.push(format!("{name}: {py_type}"));
+                }
+            }
+        }
+        let name = attributes
+            .name
+            .clone()
+            .unwrap_or_else(|| py_name(&sig.ident.to_string()).to_string());
+        if name == "__richcmp__" {
+            let self_type = [
+                ("object", ["__eq__", "__ne__"].as_slice()),
+                (class, &["__lt__", "__le__", "__gt__", "__ge__"]),
+            ];
+            return self_type
+                .iter()
+                .flat_map(|(other, names)| {
+                    names.iter().map(move |name| {
+                        format!("{indent}def {name}(self, other: {other}) -> bool: ...\n")
+                    })
+                })
+                .collect();
+        }
+        let (name, returns) = if attributes.new {
+            ("__init__".to_string(), "None".to_string())
+        } else {
+            let returns = match &sig.output {
+                syn::ReturnType::Default => "None".to_string(),
+                syn::ReturnType::Type(_, ty) => self.py_type(ty, class, &no_generics),
+            };
+            (name, returns)
+        };
+        let mut stub = String::new();
+        if attributes.staticmethod {
+            stub.push_str(&format!("{indent}@staticmethod\n"));
+        }
+        if attributes.getter {
+            stub.push_str(&format!("{indent}@property\n"));
+        }
+        let line = format!(
+            "{indent}def {name}({}) -> {returns}: ...\n",
+            parameters.join(", ")
+        );
+        if line.len() <= 101 {
+            stub.push_str(&line);
+        } else {
+            stub.push_str(&format!("{indent}def {name}(\n"));
+            for parameter in parameters {
+                stub.push_str(&format!("{indent}    {parameter},\n"));
+            }
+            stub.push_str(&format!("{indent}) -> {returns}: ...\n"));
+        }
+        stub
+    }
+
+    /// the stubs of the methods in an impl block of class, given as Rust source, __repr__ and
+    /// __str__ are left out because object has them
+    pub(super) fn methods(&self, class: &str, methods: &str) -> String {
+        let block =
+            syn::parse_str::<syn::ItemImpl>(&format!("impl {class} {{ {methods} }}")).unwrap();
+        block
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                syn::ImplItem::Fn(method)
+                    if !matches!(
+                        method.sig.ident.to_string().as_str(),
+                        "__repr__" | "__str__"
+                    ) =>
+                {
+                    Some(self.function(&method.attrs, &method.sig, Some(class)))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// the stub of a pyclass which is not made by a macro, with the methods of its pymethods
+    fn class(&self, class: &str) -> String {
+        let mut stub = format!("class {class}:\n");
+        for item in &self.0 {
+            match item {
+                syn::Item::Struct(item) if item.ident == class => {
+                    let doc = item
+                        .attrs
+                        .iter()
+                        .filter_map(|attr| match &attr.meta {
+                            syn::Meta::NameValue(meta) if meta.path.is_ident("doc") => {
+                                match &meta.value {
+                                    syn::Expr::Lit(syn::ExprLit {
+                                        lit: syn::Lit::Str(doc),
+                                        ..
+                                    }) => Some(doc.value()),
+                                    _ => None,
+                                }
+                            }
+                            _ => None,
+                        })
+                        .collect::<Vec<_>>();
+                    stub.push_str(&docstring(
+                        &doc.iter().map(String::as_str).collect::<Vec<_>>(),
+                        "    ",
+                    ));
+                }
+                _ => {}
+            }
+        }
+        for item in &self.0 {
+            match item {
+                syn::Item::Impl(block)
+                    if block
+                        .attrs
+                        .iter()
+                        .any(|attr| attr.path().is_ident("pymethods"))
+                        && matches!(&*block.self_ty, syn::Type::Path(path) if path.path.is_ident(class)) =>
+                {
+                    for item in &block.items {
+                        if let syn::ImplItem::Fn(method) = item
+                            && !matches!(
+                                method.sig.ident.to_string().as_str(),
+                                "__repr__" | "__str__"
+                            )
+                        {
+                            stub.push_str(&self.function(&method.attrs, &method.sig, Some(class)));
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        stub
+    }
+
+    /// the stubs of the pyfunctions in the order in which they are added to the module
+    fn functions(&self) -> String {
+        let source = include_str!("../py.rs");
+        let module = &source[source.find("#[pymodule]").unwrap()..];
+        names(module, "wrap_pyfunction!(", ',')
+            .into_iter()
+            .map(|name| {
+                self.0
+                    .iter()
+                    .find_map(|item| match item {
+                        syn::Item::Fn(function) if function.sig.ident == name => {
+                            Some(self.function(&function.attrs, &function.sig, None))
+                        }
+                        _ => None,
+                    })
+                    .unwrap_or_else(|| panic!("there is no function {name}"))
+            })
+            .collect()
+    }
+}
+
+/// whether the attributes derive FromPyObject or IntoPyObject
+fn derives_conversion(attrs: &[syn::Attribute]) -> bool {
+    attrs.iter().any(|attr| {
+        let mut derives = false;
+        if attr.path().is_ident("derive") {
+            attr.parse_nested_meta(|meta| {
+                derives |= meta.path.is_ident("FromPyObject") || meta.path.is_ident("IntoPyObject");
+                Ok(())
+            })
+            .unwrap();
+        }
+        derives
+    })
+}
+
+/// the names in the quotes after each occurrence of start in text
+fn names<'a>(text: &'a str, start: &str, end: char) -> Vec<&'a str> {
+    text.split(start)
+        .skip(1)
+        .filter_map(|rest| rest.split(end).next())
+        .collect()
+}
+
+/// the complete stub of the module
+fn stub() -> String {
+    let sources = Sources::new();
+    let mut stubs = vec![HEADER.to_string()];
+    let mut units = unit_stubs();
+    let unit = units.pop().unwrap();
+    stubs.extend(units);
+    stubs.extend([
+        Ome::stub(),
+        Image::stub(),
+        Pixels::stub(),
+        Channel::stub(),
+        Plane::stub(),
+        Instrument::stub(),
+        Objective::stub(),
+        Detector::stub(),
+        sources.class("PixelType"),
+        unit + "\n" + &sources.class("Quantity"),
+        EXCEPTIONS.to_string(),
+        sources.functions(),
+    ]);
+    stubs.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stub_is_complete() {
+        let stub = stub();
+        crate::tests::assert_snapshot("py/ome_metadata/ome_metadata_rs.pyi", &stub);
+        let source = include_str!("../py.rs");
+        let module = &source[source.find("#[pymodule]").unwrap()..];
+        let classes = names(module, "add_class::<", '>');
+        let exceptions = names(module, "get_type::<", '>');
+        assert!(!classes.is_empty() && !exceptions.is_empty());
+        assert!(stub.contains("\ndef ome("));
+        for class in classes.into_iter().chain(exceptions) {
+            assert!(stub.contains(&format!("\nclass {class}")), "{class}");
+        }
+    }
+}
@@ -0,0 +1,139 @@
+use crate::ome::{Roi, ShapeGroup};
+
+/// a field which all shapes have
+macro_rules! shape_field {
+    ($shape:expr, $field:ident) => {
+        match $shape {
+            ShapeGroup::Rectangle(shape) => &shape.$field,
+            ShapeGroup::Mask(shape) => &shape.$field,
+            ShapeGroup::Point(shape) => &shape.$field,
+            ShapeGroup::Ellipse(shape) => &shape.$field,
+            ShapeGroup::Line(shape) => &shape.$field,
+            ShapeGroup::Polyline(shape) => &shape.$field,
+            ShapeGroup::Polygon(shape) => &shape.$field,
+            ShapeGroup::Label(shape) => &shape.$field,
+        }
+    };
+}
+
+impl ShapeGroup {
+    pub fn id(&self) -> &str {
+        shape_field!(self, id)
+    }
+
+    pub fn is_locked(&self) -> Option<bool> {
+        *shape_field!(self, locked)
+    }
+
+    /// the z index of the plane the shape is on, None if it is on all planes
+    pub fn z_index(&self) -> Option<i32> {
+        *shape_field!(self, the_z)
+    }
+
+    /// the channel the shape is on, None if it is on all channels
+    pub fn c_index(&self) -> Option<i32> {
+        *shape_field!(self, the_c)
+    }
+
+    /// the time point the shape is on, None if it is on all time points
+    pub fn t_index(&self) -> Option<i32> {
+        *shape_field!(self, the_t)
+    }
+}
+
+impl Roi {
+    fn shapes(&self) -> impl Iterator<Item = &ShapeGroup> {
+        self.union.iter().flat_map(|union| &union.shape_group)
+    }
+
+    pub fn shape_count(&self) -> usize {
+        self.union
+            .as_ref()
+            .map_or(0, |union| union.shape_group.len())
+    }
+
+    pub fn locked_shape_count(&self) -> usize {
+        self.shapes()
+            .filter(|shape| shape.is_locked() == Some(true))
+            .count()
+    }
+
+    /// the shapes on channel c, including those on all channels
+    ///
+    /// ```
+    /// use ome_metadata::Ome;
+    ///
+    /// let ome: Ome = r#"<OME><ROI ID="ROI:0"><Union>
+    ///     <Point ID="Shape:0" X="1" Y="1" TheC="0" TheT="0"/>
+    ///     <Point ID="Shape:1" X="2" Y="2" TheC="1" TheT="0"/>
+    ///     <Point ID="Shape:2" X="3" Y="3" TheC="0" TheT="1"/>
+    ///     <Rectangle ID="Shape:3" X="0" Y="0" Width="4" Height="4" TheT="1"/>
+    /// </Union></ROI></OME>"#.parse()?;
+    /// let roi = &ome.roi[0];
+    /// let per_channel = (0..2)
+    ///     .map(|c| roi.shapes_for_channel(c).count())
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(per_channel, [3, 2]);
+    /// let per_channel_at_t1 = (0..2)
+    ///     .map(|c| {
+    ///         roi.shapes_for_channel(c)
+    ///             .filter(|shape| shape.t_index().is_none_or(|t| t == 1))
+    ///             .count()
+    ///     })
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(per_channel_at_t1, [2, 1]);
+    /// # Ok::<(), ome_metadata::error::Error>(())
+    /// ```
+    pub fn shapes_for_channel(&self, c: i32) -> impl Iterator<Item = &ShapeGroup> {
+        self.shapes()
+            .filter(move |shape| shape.c_index().is_none_or(|index| index == c))
+    }
+
+    /// the shapes at time point t, including those at all time points
+    pub fn shapes_for_timepoint(&self, t: i32) -> impl Iterator<Item = &ShapeGroup> {
+        self.shapes()
+            .filter(move |shape| shape.t_index().is_none_or(|index| index == t))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+    use crate::ome::Ome;
+
+    #[test]
+    fn shape_counts() -> Result<(), Error> {
+        let ome: Ome = r#"<OME>
+            <ROI ID="ROI:0"><Union>
+                <Rectangle ID="Shape:0" X="0" Y="0" Width="1" Height="1" Locked="true" TheC="0"/>
+                <Ellipse ID="Shape:1" X="0" Y="0" RadiusX="1" RadiusY="1" Locked="false" TheT="2"/>
+                <Line ID="Shape:2" X1="0" Y1="0" X2="1" Y2="1" TheC="1" TheT="2" TheZ="3"/>
+            </Union></ROI>
+            <ROI ID="ROI:1"/>
+        </OME>"#
+            .parse()?;
+        let roi = &ome.roi[0];
+        assert_eq!(roi.shape_count(), 3);
+        assert_eq!(roi.locked_shape_count(), 1);
+        let ids = |shapes: Vec<&ShapeGroup>| {
+            shapes
+                .into_iter()
+                .map(|s| s.id().to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            ids(roi.shapes_for_channel(1).collect()),
+            ["Shape:1", "Shape:2"]
+        );
+        assert_eq!(ids(roi.shapes_for_timepoint(0).collect()), ["Shape:0"]);
+        assert_eq!(roi.shapes_for_timepoint(2).count(), 3);
+        assert_eq!(
+            roi.union.as_ref().unwrap().shape_group[2].z_index(),
+            Some(3)
+        );
+        assert_eq!(ome.roi[1].shape_count(), 0);
+        assert_eq!(ome.roi[1].locked_shape_count(), 0);
+        Ok(())
+    }
+}
@@ -0,0 +1,222 @@
+use crate::error::Error;
+use crate::ome::{Convert, Pixels, Plane, UnitsTime};
+use std::collections::{BTreeMap, HashMap};
+
+/// statistics of the intervals between consecutive time points, in seconds
+#[derive(Clone, Debug, PartialEq)]
+pub struct TimeStat {
+    pub mean_s: f64,
+    pub std_s: f64,
+    pub min_s: f64,
+    pub max_s: f64,
+    /// the time between the first and the last time point
+    pub total_s: f64,
+}
+
+/// statistics of exposure times, in seconds
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExposureStat {
+    pub mean_s: f64,
+    pub std_s: f64,
+    pub min_s: f64,
+    pub max_s: f64,
+}
+
+/// mean, (population) standard deviation, minimum and maximum
+fn describe(values: &[f64]) -> Option<[f64; 4]> {
+    if values.is_empty() {
+        return None;
+    }
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    let std = (values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n).sqrt();
+    let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    Some([mean, std, min, max])
+}
+
+impl Pixels {
+    /// statistics of the intervals between time points, using the earliest DeltaT of the planes
+    /// at each time point, None if no plane has a DeltaT
+    pub fn time_series_stats(&self) -> Option<TimeStat> {
+        let mut times = BTreeMap::new();
+        for plane in &self.plane {
+            if let Some(delta_t) = plane.delta_t {
+                let Ok(s) = plane.delta_t_unit.convert(&UnitsTime::s, delta_t as f64) else {
+                    continue;
+                };
+                times
+                    .entry(plane.the_t)
+                    .and_modify(|t: &mut f64| *t = t.min(s))
+                    .or_insert(s);
+            }
+        }
+        let times = times.into_values().collect::<Vec<_>>();
+        let total_s = times.last()? - times.first()?;
+        let intervals = times.windows(2).map(|w| w[1] - w[0]).collect::<Vec<_>>();
+        let [mean_s, std_s, min_s, max_s] = describe(&intervals).unwrap_or_default();
+        Some(TimeStat {
+            mean_s,
+            std_s,
+            min_s,
+            max_s,
+            total_s,
+        })
+    }
+
+    /// check that for each z and c the DeltaT of the planes increases with t, and fail with the
+    /// earliest plane (by t) of which the DeltaT is not after that of the previous time point,
+    /// planes without DeltaT are skipped
+    pub fn check_timepoints_monotonic(&self) -> Result<(), Error> {
+        let mut series: HashMap<(i32, i32), BTreeMap<i32, f64>> = HashMap::new();
+        for plane in &self.plane {
+            if let Some(delta_t) = plane.delta_t {
+                let s = plane.delta_t_unit.convert(&UnitsTime::s, delta_t as f64)?;
+                series
+                    .entry((plane.the_z, plane.the_c))
+                    .or_default()
+                    .insert(plane.the_t, s);
+            }
+        }
+        let first = series
+            .iter()
+            .filter_map(|(&(z, c), times)| {
+                let times = times.iter().collect::<Vec<_>>();
+                times
+                    .windows(2)
+                    .find(|w| w[1].1 <= w[0].1)
+                    .map(|w| (*w[1].0, c, z))
+            })
+            .min();
+        match first {
+            Some((t, c, z)) => Err(Error::TimepointNotMonotonic(z, c, t)),
+            None => Ok(()),
+        }
+    }
+
+    /// statistics of the exposure times of all planes, None if no plane has an exposure time
+    pub fn exposure_time_stats(&self) -> Option<ExposureStat> {
+        self.exposure_stats_where(|_| true)
+    }
+
+    /// exposure time statistics for each channel index
+    pub fn per_channel_exposure_stats(&self) -> Vec<(i32, Option<ExposureStat>)> {
+        (0..self.size_c)
+            .map(|c| (c, self.exposure_stats_where(|plane| plane.the_c == c)))
+            .collect()
+    }
+
+    fn exposure_stats_where(&self, select: impl Fn(&Plane) -> bool) -> Option<ExposureStat> {
+        let exposure_times = self
+            .plane
+            .iter()
+            .filter(|plane| select(plane))
+            .filter_map(|plane| {
+                plane
+                    .exposure_time_unit
+                    .convert(&UnitsTime::s, plane.exposure_time? as f64)
+                    .ok()
+            })
+            .collect::<Vec<_>>();
+        let [mean_s, std_s, min_s, max_s] = describe(&exposure_times)?;
+        Some(ExposureStat {
+            mean_s,
+            std_s,
+            min_s,
+            max_s,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with_planes(planes: &str) -> Result<Pixels, Error> {
+        Ok(quick_xml::de::from_str(&format!(
+            r#"<Pixels ID="Pixels:0" DimensionOrder="XYCZT" Type="uint8" SizeX="1" SizeY="1" SizeZ="1" SizeC="1" SizeT="5">{planes}</Pixels>"#
+        ))?)
+    }
+
+    #[test]
+    fn time_series_stats() -> Result<(), Error> {
+        let pixels = with_planes(
+            r#"<Plane TheZ="0" TheC="0" TheT="3" DeltaT="6.5"/>
+            <Plane TheZ="0" TheC="0" TheT="0" DeltaT="0" DeltaTUnit="ms"/>
+            <Plane TheZ="0" TheC="0" TheT="1" DeltaT="2000" DeltaTUnit="ms"/>
+            <Plane TheZ="0" TheC="0" TheT="2" DeltaT="4"/>
+            <Plane TheZ="0" TheC="0" TheT="4" DeltaT="8500" DeltaTUnit="ms"/>"#,
+        )?;
+        let stat = pixels.time_series_stats().unwrap();
+        assert!((stat.total_s - 8.5).abs() < 1e-9);
+        assert!((stat.mean_s - 2.125).abs() < 1e-9);
+        assert!((stat.min_s - 2.0).abs() < 1e-9);
+        assert!((stat.max_s - 2.5).abs() < 1e-9);
+        let std = ((3.0 * 0.125f64.powi(2) + 0.375f64.powi(2)) / 4.0).sqrt();
+        assert!((stat.std_s - std).abs() < 1e-9);
+
+        assert!(
+            with_planes(r#"<Plane TheZ="0" TheC="0" TheT="0"/>"#)?
+                .time_series_stats()
+                .is_none()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn check_timepoints_monotonic() -> Result<(), Error> {
+        let mut planes = String::new();
+        for (t, delta_t) in [0.0, 1.5, 3.0, 4.5, 6.0].into_iter().enumerate() {
+            planes.push_str(&format!(
+                r#"<Plane TheZ="0" TheC="0" TheT="{t}" DeltaT="{delta_t}"/>
+                <Plane TheZ="0" TheC="1" TheT="{t}" DeltaT="{}" DeltaTUnit="ms"/>"#,
+                delta_t * 1000.0 + 200.0
+            ));
+        }
+        let mut pixels = with_planes(&planes)?;
+        pixels.check_timepoints_monotonic()?;
+
+        // the clock of the second channel went back at t = 3
+        pixels.plane[7].delta_t = Some(2500.0);
+        assert!(matches!(
+            pixels.check_timepoints_monotonic(),
+            Err(Error::TimepointNotMonotonic(0, 1, 3))
+        ));
+        // and that of the first channel stood still at t = 2, which is reported first
+        pixels.plane[4].delta_t = Some(1.5);
+        assert!(matches!(
+            pixels.check_timepoints_monotonic(),
+            Err(Error::TimepointNotMonotonic(0, 0, 2))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn exposure_time_stats() -> Result<(), Error> {
+        let mut planes = String::new();
+        for t in 0..5 {
+            planes.push_str(&format!(
+                r#"<Plane TheZ="0" TheC="0" TheT="{t}" ExposureTime="50" ExposureTimeUnit="ms"/>
+                <Plane TheZ="0" TheC="1" TheT="{t}" ExposureTime="0.1"/>"#
+            ));
+        }
+        let mut pixels = with_planes(&planes)?;
+        pixels.size_c = 3;
+        let stat = pixels.exposure_time_stats().unwrap();
+        assert!((stat.mean_s - 0.075).abs() < 1e-6);
+        assert!((stat.std_s - 0.025).abs() < 1e-6);
+        assert!((stat.min_s - 0.05).abs() < 1e-6);
+        assert!((stat.max_s - 0.1).abs() < 1e-6);
+
+        let per_channel = pixels.per_channel_exposure_stats();
+        assert_eq!(per_channel.len(), 3);
+        let (c, stat) = &per_channel[0];
+        let stat = stat.as_ref().unwrap();
+        assert_eq!(*c, 0);
+        assert!((stat.mean_s - 0.05).abs() < 1e-6 && stat.std_s < 1e-6);
+        let stat = per_channel[1].1.as_ref().unwrap();
+        assert!((stat.min_s - 0.1).abs() < 1e-6 && (stat.max_s - 0.1).abs() < 1e-6);
+        assert_eq!(per_channel[2], (2, None));
+        Ok(())
+    }
+}
@@ -0,0 +1,540 @@
+use crate::color::Rgba;
+#[cfg(feature = "json")]
+use crate::error::Error;
+use crate::ome::{
+    Convert, Dichroic, Filter, FilterRef, FilterType, Image, Instrument, LightPath, Ome, PixelType,
+    Pixels, Ref, UnitsLength,
+};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+/// a compact overview of the metadata, see [Ome::summary]
+#[derive(Clone, Debug, Serialize)]
+pub struct OmeSummary {
+    pub image_count: usize,
+    pub images: Vec<ImageSummary>,
+    pub annotation_count: usize,
+    pub annotation_type_counts: BTreeMap<String, usize>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ImageSummary {
+    pub id: String,
+    pub name: Option<String>,
+    pub size_xyzct: [i32; 5],
+    pub physical_size_xyz_um: [Option<f64>; 3],
+    pub channel_names: Vec<Option<String>>,
+    pub pixel_type: PixelType,
+}
+
+/// one row per channel, see [Ome::channel_table]
+#[derive(Clone, Debug, Serialize)]
+pub struct ChannelRow {
+    pub image_id: String,
+    pub channel_id: String,
+    pub name: Option<String>,
+    pub excitation_nm: Option<f64>,
+    pub emission_nm: Option<f64>,
+    pub fluor: Option<String>,
+    /// like "#FF0000FF" for opaque red, "#FFFFFFFF" if the channel has no Color
+    pub color: String,
+}
+
+fn to_um(value: Option<f32>, unit: &UnitsLength) -> Option<f64> {
+    unit.convert(&UnitsLength::um, value? as f64).ok()
+}
+
+fn to_nm(value: Option<f32>, unit: &UnitsLength) -> Option<f64> {
+    unit.convert(&UnitsLength::nm, value? as f64).ok()
+}
+
+impl From<&Image> for ImageSummary {
+    fn from(image: &Image) -> Self {
+        let pixels = &image.pixels;
+        ImageSummary {
+            id: image.id.clone(),
+            name: image.name.clone(),
+            size_xyzct: [
+                pixels.size_x,
+                pixels.size_y,
+                pixels.size_z,
+                pixels.size_c,
+                pixels.size_t,
+            ],
+            physical_size_xyz_um: [
+                to_um(pixels.physical_size_x, &pixels.physical_size_x_unit),
+                to_um(pixels.physical_size_y, &pixels.physical_size_y_unit),
+                to_um(pixels.physical_size_z, &pixels.physical_size_z_unit),
+            ],
+            channel_names: pixels.channel.iter().map(|c| c.name.clone()).collect(),
+            pixel_type: pixels.r#type.clone(),
+        }
+    }
+}
+
+/// at most 4 decimals, without trailing zeros
+fn format_size(size: f64) -> String {
+    let size = format!("{size:.4}");
+    size.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+impl Image {
+    /// a one line description like "2048x2048, 1z, 3c, 10t, uint16, 0.065x0.065x0.3 µm"
+    pub fn dimensions_summary(&self) -> String {
+        self.pixels.to_string()
+    }
+}
+
+/// like "2048x2048, 1z, 3c, 10t, uint16, 0.065x0.065x0.3 µm", with ? for an unknown physical
+/// size, the physical size in z is left out for a single z if it is unknown
+impl fmt::Display for Pixels {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}x{}, {}z, {}c, {}t, {}",
+            self.size_x, self.size_y, self.size_z, self.size_c, self.size_t, self.r#type
+        )?;
+        let mut sizes = vec![
+            to_um(self.physical_size_x, &self.physical_size_x_unit),
+            to_um(self.physical_size_y, &self.physical_size_y_unit),
+        ];
+        let size_z = to_um(self.physical_size_z, &self.physical_size_z_unit);
+        if size_z.is_some() || self.size_z > 1 {
+            sizes.push(size_z);
+        }
+        if sizes.iter().any(Option::is_some) {
+            let sizes = sizes
+                .iter()
+                .map(|size| size.map_or_else(|| "?".to_string(), format_size))
+                .collect::<Vec<_>>();
+            write!(f, ", {} µm", sizes.join("x"))?;
+        }
+        Ok(())
+    }
+}
+
+/// the transmittance of a filter in nm, like "525/50" for the center and width of a band pass
+/// filter, and only the cut in or cut out of a long or short pass filter
+fn transmittance(filter: &Filter) -> Option<String> {
+    let range = filter.transmittance_range.as_ref()?;
+    let cut_in = to_nm(range.cut_in, &range.cut_in_unit);
+    let cut_out = to_nm(range.cut_out, &range.cut_out_unit);
+    match (&filter.r#type, cut_in, cut_out) {
+        (Some(FilterType::LongPass), Some(cut_in), _) => Some(format_size(cut_in)),
+        (Some(FilterType::ShortPass), _, Some(cut_out)) => Some(format_size(cut_out)),
+        (_, Some(cut_in), Some(cut_out)) => Some(format!(
+            "{}/{}",
+            format_size((cut_in + cut_out) / 2.0),
+            format_size(cut_out - cut_in)
+        )),
+        _ => None,
+    }
+}
+
+/// the words joined by spaces, or the ID if there are none
+fn label(words: impl IntoIterator<Item = Option<String>>, id: &str) -> String {
+    let words = words.into_iter().flatten().collect::<Vec<_>>();
+    if words.is_empty() {
+        id.to_string()
+    } else {
+        words.join(" ")
+    }
+}
+
+/// like "Chroma ET525/50m BandPass 525/50"
+fn filter_label(filter: &Filter) -> String {
+    label(
+        [
+            filter.manufacturer.clone(),
+            filter.model.clone(),
+            filter.r#type.as_ref().map(FilterType::to_string),
+            transmittance(filter),
+        ],
+        &filter.id,
+    )
+}
+
+fn dichroic_label(dichroic: &Dichroic) -> String {
+    label(
+        [dichroic.manufacturer.clone(), dichroic.model.clone()],
+        &dichroic.id,
+    )
+}
+
+impl LightPath {
+    /// like "Ex: BandPass 488/10; Dich: LP490; Em: BandPass 525/50", with the filters and the
+    /// dichroic resolved in the instrument, the IDs are used for those which cannot be resolved
+    pub fn summary(&self, instrument: &Instrument) -> String {
+        self.describe(Some(instrument))
+    }
+
+    fn describe(&self, instrument: Option<&Instrument>) -> String {
+        let filters = |filter_refs: &[FilterRef]| {
+            filter_refs
+                .iter()
+                .map(|filter_ref| {
+                    instrument
+                        .and_then(|instrument| instrument.resolve_filter(filter_ref))
+                        .map_or_else(|| filter_ref.id().to_string(), filter_label)
+                })
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let mut parts = Vec::new();
+        if !self.excitation_filter_ref.is_empty() {
+            parts.push(format!("Ex: {}", filters(&self.excitation_filter_ref)));
+        }
+        if let Some(dichroic_ref) = &self.dichroic_ref {
+            let dichroic = instrument
+                .and_then(|instrument| instrument.resolve_dichroic(dichroic_ref))
+                .map_or_else(|| dichroic_ref.id().to_string(), dichroic_label);
+            parts.push(format!("Dich: {dichroic}"));
+        }
+        if !self.emission_filter_ref.is_empty() {
+            parts.push(format!("Em: {}", filters(&self.emission_filter_ref)));
+        }
+        parts.join("; ")
+    }
+}
+
+/// like [LightPath::summary], but with the IDs of all filters and the dichroic, like
+/// "Ex: Filter:0; Dich: Dichroic:0; Em: Filter:1"
+impl fmt::Display for LightPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.describe(None))
+    }
+}
+
+/// at most 40 characters, longer names are cut short with an ellipsis
+fn short_name(name: &str) -> String {
+    if name.chars().count() > 40 {
+        format!("{}…", name.chars().take(39).collect::<String>())
+    } else {
+        name.to_string()
+    }
+}
+
+/// like "Image:0 "cells.tif": 2048x2048, 1z, 3c, 10t, uint16, 0.065x0.065x0.3 µm"
+impl fmt::Display for Image {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.id)?;
+        if let Some(name) = &self.name {
+            write!(f, " \"{}\"", short_name(name))?;
+        }
+        write!(f, ": {}", self.pixels)
+    }
+}
+
+/// the images one per line, and the number of ROIs and annotations
+impl fmt::Display for Ome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} image(s)", self.image.len())?;
+        for image in &self.image {
+            writeln!(f, "  {image}")?;
+        }
+        write!(
+            f,
+            "{} ROI(s), {} annotation(s)",
+            self.roi.len(),
+            self.total_annotation_count()
+        )
+    }
+}
+
+impl Ome {
+    /// a compact overview of the images in this metadata
+    pub fn summary(&self) -> OmeSummary {
+        OmeSummary {
+            image_count: self.image.len(),
+            images: self.image.iter().map(ImageSummary::from).collect(),
+            annotation_count: self.total_annotation_count(),
+            annotation_type_counts: self.annotation_type_counts().into_iter().collect(),
+        }
+    }
+
+    /// the number of annotations of each type, keyed by variant name like "MapAnnotation"
+    pub fn annotation_type_counts(&self) -> HashMap<String, usize> {
+        let mut counts = HashMap::new();
+        for annotation in self.annotations() {
+            *counts
+                .entry(annotation.type_name().to_string())
+                .or_default() += 1;
+        }
+        counts
+    }
+
+    pub fn total_annotation_count(&self) -> usize {
+        self.annotations().count()
+    }
+
+    /// the channels of all images, with the wavelengths in nm
+    pub fn channel_table(&self) -> Vec<ChannelRow> {
+        self.image
+            .iter()
+            .flat_map(|image| {
+                image.pixels.channel.iter().map(|channel| {
+                    let Rgba { r, g, b, a } = channel.rgba();
+                    ChannelRow {
+                        image_id: image.id.clone(),
+                        channel_id: channel.id.clone(),
+                        name: channel.name.clone(),
+                        excitation_nm: to_nm(
+                            channel.excitation_wavelength,
+                            &channel.excitation_wavelength_unit,
+                        ),
+                        emission_nm: to_nm(
+                            channel.emission_wavelength,
+                            &channel.emission_wavelength_unit,
+                        ),
+                        fluor: channel.fluor.clone(),
+                        color: format!("#{r:02X}{g:02X}{b:02X}{a:02X}"),
+                    }
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(feature = "json")]
+impl OmeSummary {
+    pub fn to_json(&self) -> Result<String, Error> {
+        Ok(serde_json::to_string(self)?)
+    }
+}
+
+impl fmt::Display for OmeSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let optional = |value: &Option<f64>| value.map_or("-".to_string(), format_size);
+        let mut rows = vec![
+            [
+                "ID",
+                "Name",
+                "X",
+                "Y",
+                "Z",
+                "C",
+                "T",
+                "Type",
+                "Pixel size (µm)",
+                "Channels",
+            ]
+            .map(String::from),
+        ];
+        for image in &self.images {
+            let [x, y, z, c, t] = image.size_xyzct.map(|s| s.to_string());
+            let channels = image
+                .channel_names
+                .iter()
+                .map(|name| name.as_deref().unwrap_or("-"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            rows.push([
+                image.id.clone(),
+                image.name.clone().unwrap_or("-".to_string()),
+                x,
+                y,
+                z,
+                c,
+                t,
+                image.pixel_type.to_string(),
+                image
+                    .physical_size_xyz_um
+                    .iter()
+                    .map(optional)
+                    .collect::<Vec<_>>()
+                    .join(" x "),
+                channels,
+            ]);
+        }
+        let mut widths = [0; 10];
+        for row in &rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+        writeln!(f, "{} image(s)", self.image_count)?;
+        for row in &rows {
+            let line = row
+                .iter()
+                .zip(widths)
+                .map(|(cell, width)| format!("{cell:<width$}"))
+                .collect::<Vec<_>>()
+                .join("  ");
+            writeln!(f, "{}", line.trim_end())?;
+        }
+        if self.annotation_count > 0 {
+            let counts = self
+                .annotation_type_counts
+                .iter()
+                .map(|(name, count)| format!("{count} {name}"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            writeln!(f, "{} annotation(s): {counts}", self.annotation_count)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dimensions_summary() -> Result<(), crate::error::Error> {
+        let mut ome: Ome = std::fs::read_to_string("tests/test.xml")?.parse()?;
+        let image = &mut ome.image[0];
+        assert_eq!(image.dimensions_summary(), "2x2, 1z, 1c, 1t, int8");
+        image.pixels.physical_size_x = Some(65.0);
+        image.pixels.physical_size_x_unit = UnitsLength::nm;
+        image.pixels.physical_size_y = Some(0.065);
+        assert_eq!(
+            image.dimensions_summary(),
+            "2x2, 1z, 1c, 1t, int8, 0.065x0.065 µm"
+        );
+        image.pixels.physical_size_x = None;
+        image.pixels.size_z = 3;
+        assert_eq!(
+            image.dimensions_summary(),
+            "2x2, 3z, 1c, 1t, int8, ?x0.065x? µm"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn light_path_summary() -> Result<(), crate::error::Error> {
+        let ome: Ome = r#"<OME>
+            <Instrument ID="Instrument:0">
+                <Dichroic ID="Dichroic:0" Model="LP490"/>
+                <Filter ID="Filter:0" Type="BandPass">
+                    <TransmittanceRange CutIn="483" CutInUnit="nm" CutOut="493" CutOutUnit="nm"/>
+                </Filter>
+                <Filter ID="Filter:1" Manufacturer="Chroma" Model="ET525/50m" Type="BandPass">
+                    <TransmittanceRange CutIn="0.5" CutInUnit="µm" CutOut="550" CutOutUnit="nm"/>
+                </Filter>
+                <Filter ID="Filter:2" Type="LongPass">
+                    <TransmittanceRange CutIn="6e-7" CutInUnit="m"/>
+                </Filter>
+                <Filter ID="Filter:3"/>
+            </Instrument>
+            <Image ID="Image:0">
+                <Pixels ID="Pixels:0" DimensionOrder="XYCZT" Type="uint8" SizeX="1" SizeY="1" SizeZ="1" SizeC="2" SizeT="1">
+                    <Channel ID="Channel:0:0">
+                        <LightPath>
+                            <ExcitationFilterRef ID="Filter:0"/>
+                            <DichroicRef ID="Dichroic:0"/>
+                            <EmissionFilterRef ID="Filter:1"/>
+                            <EmissionFilterRef ID="Filter:2"/>
+                        </LightPath>
+                    </Channel>
+                    <Channel ID="Channel:0:1">
+                        <LightPath>
+                            <DichroicRef ID="Dichroic:1"/>
+                            <EmissionFilterRef ID="Filter:3"/>
+                        </LightPath>
+                    </Channel>
+                    <MetadataOnly/>
+                </Pixels>
+            </Image>
+        </OME>"#
+            .parse()?;
+        let instrument = &ome.instrument[0];
+        let channels = &ome.image[0].pixels.channel;
+        let light_path = channels[0].light_path.as_ref().unwrap();
+        assert_eq!(
+            light_path.summary(instrument),
+            "Ex: BandPass 488/10; Dich: LP490; Em: Chroma ET525/50m BandPass 525/50, LongPass 600"
+        );
+        assert_eq!(
+            light_path.to_string(),
+            "Ex: Filter:0; Dich: Dichroic:0; Em: Filter:1, Filter:2"
+        );
+        let light_path = channels[1].light_path.as_ref().unwrap();
+        assert_eq!(
+            light_path.summary(instrument),
+            "Dich: Dichroic:1; Em: Filter:3"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn annotation_counts() -> Result<(), crate::error::Error> {
+        let ome: Ome = r#"<OME>
+            <StructuredAnnotations>
+                <CommentAnnotation ID="Annotation:0"><Value>first</Value></CommentAnnotation>
+                <MapAnnotation ID="Annotation:1"><Value><M K="a">1</M></Value></MapAnnotation>
+                <CommentAnnotation ID="Annotation:2"><Value>second</Value></CommentAnnotation>
+            </StructuredAnnotations>
+        </OME>"#
+            .parse()?;
+        let counts = ome.annotation_type_counts();
+        assert_eq!(counts.len(), 2);
+        assert_eq!(counts["CommentAnnotation"], 2);
+        assert_eq!(counts["MapAnnotation"], 1);
+        assert_eq!(ome.total_annotation_count(), 3);
+        let summary = ome.summary();
+        assert_eq!(summary.annotation_count, 3);
+        assert!(
+            summary
+                .to_string()
+                .contains("3 annotation(s): 2 CommentAnnotation, 1 MapAnnotation")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn display() -> Result<(), crate::error::Error> {
+        for entry in std::fs::read_dir("tests")? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|e| e == "xml") {
+                let ome: Ome = std::fs::read_to_string(&path)?.parse()?;
+                let text = ome.to_string();
+                assert!(text.starts_with(&format!("{} image(s)\n", ome.image.len())));
+                assert!(text.ends_with(&format!(
+                    "{} ROI(s), {} annotation(s)",
+                    ome.roi.len(),
+                    ome.total_annotation_count()
+                )));
+                for image in &ome.image {
+                    let pixels = &image.pixels;
+                    let sizes = format!(
+                        "{}x{}, {}z, {}c, {}t",
+                        pixels.size_x, pixels.size_y, pixels.size_z, pixels.size_c, pixels.size_t
+                    );
+                    assert!(text.contains(&format!("  {}", image)), "{path:?}");
+                    assert!(image.to_string().contains(&sizes), "{path:?}");
+                }
+            }
+        }
+        let mut ome: Ome = std::fs::read_to_string("tests/test.xml")?.parse()?;
+        ome.image[0].name = Some("a".repeat(50));
+        assert_eq!(
+            ome.image[0].to_string(),
+            format!("Image:0 \"{}…\": 2x2, 1z, 1c, 1t, int8", "a".repeat(39))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn channel_table() -> Result<(), crate::error::Error> {
+        let mut ome: Ome = std::fs::read_to_string("tests/test.xml")?.parse()?;
+        let rows = ome.channel_table();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].image_id, "Image:0");
+        assert_eq!(rows[0].channel_id, "Channel:0:0");
+        assert_eq!(rows[0].color, "#FFFFFFFF");
+        assert_eq!(rows[0].excitation_nm, None);
+        ome.image[0].pixels.channel[0].color = 0;
+        assert_eq!(ome.channel_table()[0].color, "#00000000");
+        let channel = &mut ome.image[0].pixels.channel[0];
+        channel.excitation_wavelength = Some(0.488);
+        channel.excitation_wavelength_unit = UnitsLength::um;
+        channel.emission_wavelength = Some(510.0);
+        channel.color = -16776961;
+        let rows = ome.channel_table();
+        assert!((rows[0].excitation_nm.unwrap() - 488.0).abs() < 1e-3);
+        assert_eq!(rows[0].emission_nm, Some(510.0));
+        assert_eq!(rows[0].color, "#FF0000FF");
+        Ok(())
+    }
+}
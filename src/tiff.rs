@@ -0,0 +1,421 @@
+//! the OME-XML in the ImageDescription of the first IFD of an OME-TIFF, read and replaced without
+//! a TIFF library and without reading or moving any pixel data
+
+use crate::error::Error;
+use crate::ome::Ome;
+use std::fs::{File, OpenOptions};
+use std::io::{BufReader, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+/// the tag of the ImageDescription
+const IMAGE_DESCRIPTION: u16 = 270;
+
+/// a classic TIFF or BigTIFF in either byte order, only as far as needed to find and replace a
+/// tag in the first IFD
+struct Tiff<F> {
+    file: F,
+    big_endian: bool,
+    big_tiff: bool,
+    first_ifd: u64,
+}
+
+/// an entry of an IFD
+struct Entry {
+    tag: u16,
+    /// where the entry is in the file
+    position: u64,
+    count: u64,
+    /// where the values are in the file, this is in the entry itself when they fit in it
+    values: u64,
+}
+
+impl<F: Read + Seek> Tiff<F> {
+    /// check the header and read the byte order, whether this is a BigTIFF and where the first
+    /// IFD is
+    fn new(mut file: F) -> Result<Self, Error> {
+        let mut header = [0; 4];
+        file.read_exact(&mut header)?;
+        let (big_endian, big_tiff) = match header {
+            [b'I', b'I', 42, 0] => (false, false),
+            [b'I', b'I', 43, 0] => (false, true),
+            [b'M', b'M', 0, 42] => (true, false),
+            [b'M', b'M', 0, 43] => (true, true),
+            _ => return Err(Error::NotTiff),
+        };
+        let mut tiff = Tiff {
+            file,
+            big_endian,
+            big_tiff,
+            first_ifd: 0,
+        };
+        if big_tiff && (tiff.read_u16()?, tiff.read_u16()?) != (8, 0) {
+            return Err(Error::NotTiff);
+        }
+        tiff.first_ifd = tiff.read_offset()?;
+        Ok(tiff)
+    }
+
+    /// the size of a count or an offset, and of the values which fit in an entry
+    fn offset_size(&self) -> u64 {
+        if self.big_tiff { 8 } else { 4 }
+    }
+
+    /// where the values of the entry at position are when they fit in the entry
+    fn inline_values(&self, position: u64) -> u64 {
+        position + 4 + self.offset_size()
+    }
+
+    fn read_u16(&mut self) -> Result<u16, Error> {
+        let mut bytes = [0; 2];
+        self.file.read_exact(&mut bytes)?;
+        Ok(if self.big_endian {
+            u16::from_be_bytes(bytes)
+        } else {
+            u16::from_le_bytes(bytes)
+        })
+    }
+
+    fn read_u32(&mut self) -> Result<u32, Error> {
+        let mut bytes = [0; 4];
+        self.file.read_exact(&mut bytes)?;
+        Ok(if self.big_endian {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        })
+    }
+
+    fn read_u64(&mut self) -> Result<u64, Error> {
+        let mut bytes = [0; 8];
+        self.file.read_exact(&mut bytes)?;
+        Ok(if self.big_endian {
+            u64::from_be_bytes(bytes)
+        } else {
+            u64::from_le_bytes(bytes)
+        })
+    }
+
+    /// a count or an offset, which have 4 bytes in a classic TIFF and 8 in a BigTIFF
+    fn read_offset(&mut self) -> Result<u64, Error> {
+        if self.big_tiff {
+            self.read_u64()
+        } else {
+            self.read_u32().map(u64::from)
+        }
+    }
+
+    /// the entries of the first IFD, assuming one byte per value for finding the values, like
+    /// for ASCII
+    fn first_ifd_entries(&mut self) -> Result<Vec<Entry>, Error> {
+        self.file.seek(SeekFrom::Start(self.first_ifd))?;
+        let (n_entries, entry_size) = if self.big_tiff {
+            (self.read_u64()?, 20)
+        } else {
+            (u64::from(self.read_u16()?), 12)
+        };
+        let first_entry = self.first_ifd + if self.big_tiff { 8 } else { 2 };
+        (0..n_entries)
+            .map(|i| {
+                let position = first_entry + i * entry_size;
+                self.file.seek(SeekFrom::Start(position))?;
+                let tag = self.read_u16()?;
+                self.read_u16()?;
+                let count = self.read_offset()?;
+                let values = if count > self.offset_size() {
+                    self.read_offset()?
+                } else {
+                    self.inline_values(position)
+                };
+                Ok(Entry {
+                    tag,
+                    position,
+                    count,
+                    values,
+                })
+            })
+            .collect()
+    }
+
+    /// the entry of a tag in the first IFD, None if the IFD does not have the tag
+    fn first_ifd_entry(&mut self, tag: u16) -> Result<Option<Entry>, Error> {
+        Ok(self
+            .first_ifd_entries()?
+            .into_iter()
+            .find(|entry| entry.tag == tag))
+    }
+
+    /// the bytes of a tag of one byte per value, like ASCII, in the first IFD, None if the IFD
+    /// does not have the tag
+    fn first_ifd_bytes(&mut self, tag: u16) -> Result<Option<Vec<u8>>, Error> {
+        let Some(entry) = self.first_ifd_entry(tag)? else {
+            return Ok(None);
+        };
+        self.file.seek(SeekFrom::Start(entry.values))?;
+        let mut bytes = Vec::new();
+        (&mut self.file).take(entry.count).read_to_end(&mut bytes)?;
+        if (bytes.len() as u64) < entry.count {
+            return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+        }
+        Ok(Some(bytes))
+    }
+}
+
+impl<F: Read + Write + Seek> Tiff<F> {
+    fn write_offset(&mut self, offset: u64) -> Result<(), Error> {
+        let bytes = match (self.big_tiff, self.big_endian) {
+            (true, true) => offset.to_be_bytes().to_vec(),
+            (true, false) => offset.to_le_bytes().to_vec(),
+            (false, true) => (offset as u32).to_be_bytes().to_vec(),
+            (false, false) => (offset as u32).to_le_bytes().to_vec(),
+        };
+        Ok(self.file.write_all(&bytes)?)
+    }
+
+    /// replace the values of a tag of one byte per value in the first IFD, in place when they
+    /// fit where the old values are, or else at the end of the file, nothing else in the file is
+    /// changed
+    fn replace_first_ifd_bytes(&mut self, tag: u16, bytes: &[u8]) -> Result<(), Error> {
+        let entry = self
+            .first_ifd_entry(tag)?
+            .ok_or(Error::NoImageDescription)?;
+        let count = bytes.len() as u64;
+        let inline = self.inline_values(entry.position);
+        let (values, slot) = if count <= self.offset_size() {
+            (inline, self.offset_size())
+        } else if count <= entry.count && entry.values != inline {
+            (entry.values, entry.count)
+        } else {
+            let end = self.file.seek(SeekFrom::End(0))?;
+            // values start at a word boundary
+            (end + end % 2, count)
+        };
+        if !self.big_tiff && values + slot > u64::from(u32::MAX) {
+            return Err(Error::TiffTooLarge);
+        }
+        // the values are written before the entry refers to them, so that the entry never refers
+        // to values which are not there yet, when appending to a file of odd length, the seek
+        // leaves a gap, which becomes zero
+        self.file.seek(SeekFrom::Start(values))?;
+        self.file.write_all(bytes)?;
+        self.file.write_all(&vec![0; (slot - count) as usize])?;
+        self.file.flush()?;
+        self.file.seek(SeekFrom::Start(entry.position + 4))?;
+        self.write_offset(count)?;
+        if values != inline {
+            self.write_offset(values)?;
+        }
+        Ok(self.file.flush()?)
+    }
+}
+
+/// the ImageDescription of the first IFD, without the terminating zero
+fn image_description(file: impl Read + Seek) -> Result<String, Error> {
+    let mut bytes = Tiff::new(file)?
+        .first_ifd_bytes(IMAGE_DESCRIPTION)?
+        .ok_or(Error::NoImageDescription)?;
+    while bytes.last() == Some(&0) {
+        bytes.pop();
+    }
+    Ok(crate::decode(&bytes)?.into_owned())
+}
+
+impl Ome {
+    /// the OME-XML in the ImageDescription of the first IFD of a classic TIFF or BigTIFF, errors
+    /// include the path
+    pub fn xml_from_tiff<P: AsRef<Path>>(path: P) -> Result<String, Error> {
+        let path = path.as_ref();
+        File::open(path)
+            .map_err(Error::from)
+            .and_then(|file| image_description(BufReader::new(file)))
+            .map_err(|err| Error::Path {
+                path: path.to_path_buf(),
+                source: Box::new(err),
+            })
+    }
+
+    /// replace the ImageDescription of the first IFD of a classic TIFF or BigTIFF with this
+    /// document, including the xml in [Ome::unknown_xml], the pixel data and the other tags are
+    /// left as they are, errors include the path
+    pub fn write_to_tiff<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        let path = path.as_ref();
+        let mut description = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>{}"#,
+            self.to_xml_preserving()?
+        );
+        description.push('\0');
+        OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(path)
+            .map_err(Error::from)
+            .and_then(|file| {
+                Tiff::new(file)?.replace_first_ifd_bytes(IMAGE_DESCRIPTION, description.as_bytes())
+            })
+            .map_err(|err| Error::Path {
+                path: path.to_path_buf(),
+                source: Box::new(err),
+            })
+    }
+
+    /// parse the OME-XML in an OME-TIFF, see [Ome::xml_from_tiff], keeping the xml which is not
+    /// in the schema like [Ome::from_str_preserving], so that [Ome::write_to_tiff] writes it
+    /// again, when the TIFF only has a
+    /// BinaryOnly element referring to the file with the metadata, the error is
+    /// [Error::BinaryOnly] with the name of that file, parse [Ome::xml_from_tiff] to get the
+    /// BinaryOnly element itself
+    pub fn from_tiff_path<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let ome =
+            Ome::from_str_preserving(&Ome::xml_from_tiff(path)?).map_err(|err| Error::Path {
+                path: path.to_path_buf(),
+                source: Box::new(err),
+            })?;
+        match ome.binary_only {
+            Some(binary_only) => Err(Error::BinaryOnly {
+                metadata_file: binary_only.metadata_file,
+                uuid: binary_only.uuid,
+            }),
+            None => Ok(ome),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classic() -> Result<(), Error> {
+        for path in ["tests/tiny.ome.tif", "tests/tiny_big_endian.ome.tif"] {
+            let ome = Ome::from_tiff_path(path)?;
+            let pixels = &ome.image[0].pixels;
+            assert_eq!((pixels.size_x, pixels.size_y), (2, 2));
+            assert_eq!(pixels.channel[0].name.as_deref(), Some("µ-DAPI"));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn big_tiff() -> Result<(), Error> {
+        let xml = Ome::xml_from_tiff("tests/tiny.ome.tif")?;
+        for path in ["tests/tiny.ome.btf", "tests/tiny_big_endian.ome.btf"] {
+            assert_eq!(Ome::xml_from_tiff(path)?, xml);
+            assert_eq!(
+                Ome::from_tiff_path(path)?.image[0].name.as_deref(),
+                Some("tiny")
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn binary_only() -> Result<(), Error> {
+        let path = "tests/tiny_binary_only.ome.tif";
+        let Err(Error::BinaryOnly { metadata_file, .. }) = Ome::from_tiff_path(path) else {
+            panic!("BinaryOnly is not an error");
+        };
+        assert_eq!(metadata_file, "tiny.companion.ome");
+        let ome: Ome = Ome::xml_from_tiff(path)?.parse()?;
+        assert!(ome.binary_only.is_some());
+        Ok(())
+    }
+
+    /// the tag, count and position of the values of the entries of an IFD
+    type Tags = Vec<(u16, u64, u64)>;
+
+    /// the entries of the first IFD except the ImageDescription, and the pixels
+    fn tags_and_pixels(path: &Path) -> Result<(Tags, Vec<u8>), Error> {
+        let mut tiff = Tiff::new(File::open(path)?)?;
+        let entries = tiff.first_ifd_entries()?;
+        let strip = entries.iter().find(|entry| entry.tag == 273).unwrap();
+        tiff.file.seek(SeekFrom::Start(strip.values))?;
+        let offset = tiff.read_u32()?;
+        let mut pixels = vec![0; 4];
+        tiff.file.seek(SeekFrom::Start(offset.into()))?;
+        tiff.file.read_exact(&mut pixels)?;
+        let tags = entries
+            .iter()
+            .filter(|entry| entry.tag != IMAGE_DESCRIPTION)
+            .map(|entry| (entry.tag, entry.count, entry.values))
+            .collect();
+        Ok((tags, pixels))
+    }
+
+    #[test]
+    fn write_to_tiff() -> Result<(), Error> {
+        let dir =
+            std::env::temp_dir().join(format!("ome_metadata_write_to_tiff_{}", std::process::id()));
+        std::fs::create_dir_all(&dir)?;
+        for fixture in [
+            "tiny.ome.tif",
+            "tiny_big_endian.ome.tif",
+            "tiny.ome.btf",
+            "tiny_big_endian.ome.btf",
+        ] {
+            let path = dir.join(fixture);
+            std::fs::copy(format!("tests/{fixture}"), &path)?;
+            let (tags, pixels) = tags_and_pixels(&path)?;
+            assert_eq!(pixels, [0, 64, 128, 255]);
+            let mut ome = Ome::from_tiff_path(&path)?;
+
+            // a longer description is appended
+            ome.image[0].name = Some("a much longer name than the name of the fixture".into());
+            let length = std::fs::metadata(&path)?.len();
+            ome.write_to_tiff(&path)?;
+            assert!(std::fs::metadata(&path)?.len() > length);
+            let written = Ome::from_tiff_path(&path)?;
+            assert_eq!(written.image[0].name, ome.image[0].name);
+            assert_eq!(tags_and_pixels(&path)?, (tags.clone(), pixels.clone()));
+
+            // and a shorter one replaces it in place
+            ome.image[0].name = Some("short".into());
+            let length = std::fs::metadata(&path)?.len();
+            ome.write_to_tiff(&path)?;
+            assert_eq!(std::fs::metadata(&path)?.len(), length);
+            let description = Ome::xml_from_tiff(&path)?;
+            assert!(!description.ends_with('\0'));
+            let written: Ome = description.parse()?;
+            assert_eq!(written.image[0].name.as_deref(), Some("short"));
+            assert!(ome.semantically_eq(&written));
+            assert_eq!(tags_and_pixels(&path)?, (tags, pixels));
+        }
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn write_to_tiff_keeps_unknown_xml() -> Result<(), Error> {
+        let path = std::env::temp_dir().join(format!(
+            "ome_metadata_write_to_tiff_keeps_unknown_xml_{}.ome.tif",
+            std::process::id()
+        ));
+        std::fs::copy("tests/tiny.ome.tif", &path)?;
+        let note = r#"<Acme:Note xmlns:Acme="http://example.com/acme">kept</Acme:Note>"#;
+        let xml = Ome::xml_from_tiff(&path)?.replacen("<Pixels ", &format!("{note}<Pixels "), 1);
+        Tiff::new(OpenOptions::new().read(true).write(true).open(&path)?)?
+            .replace_first_ifd_bytes(IMAGE_DESCRIPTION, format!("{xml}\0").as_bytes())?;
+
+        let mut ome = Ome::from_tiff_path(&path)?;
+        assert!(ome.unknown_xml.iter().any(|unknown| matches!(
+            unknown,
+            crate::preserve::UnknownXml::Element { element, .. } if element.name == "Acme:Note"
+        )));
+        ome.image[0].name = Some("renamed".into());
+        ome.write_to_tiff(&path)?;
+        let description = Ome::xml_from_tiff(&path)?;
+        assert!(description.contains(&format!("{note}<Pixels ")));
+        let written = Ome::from_tiff_path(&path)?;
+        assert_eq!(written.image[0].name.as_deref(), Some("renamed"));
+        assert_eq!(written.unknown_xml, ome.unknown_xml);
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn not_tiff() {
+        let Err(Error::Path { source, .. }) = Ome::xml_from_tiff("tests/test.xml") else {
+            panic!("xml is read as tiff");
+        };
+        assert!(matches!(*source, Error::NotTiff));
+    }
+}
@@ -0,0 +1,365 @@
+pub use crate::ids::IdKind;
+use crate::ids::IdSite;
+use crate::ome::{
+    DEPRECATED_NAMESPACES, Ome, Pixels, StructuredAnnotationsContent, is_valid_uuid_urn,
+};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use thiserror::Error;
+
+#[derive(Clone, Debug, Error)]
+pub enum ValidationError {
+    #[error("the document uses the deprecated schema {0}")]
+    DeprecatedSchema(String),
+    #[error("the document uses the unknown schema {0}")]
+    UnknownSchema(String),
+    #[error("{element_id} has a UUID that is not of the form urn:uuid:...: {value}")]
+    InvalidUuid { element_id: String, value: String },
+    #[error("{pixels_id} has no plane for z: {z}, c: {c}, t: {t}")]
+    MissingPlane {
+        pixels_id: String,
+        z: i32,
+        c: i32,
+        t: i32,
+    },
+    #[error("{pixels_id} has more than one plane for z: {z}, c: {c}, t: {t}")]
+    DuplicatePlane {
+        pixels_id: String,
+        z: i32,
+        c: i32,
+        t: i32,
+    },
+    #[error("{element_id} refers to {image_id}, which does not exist")]
+    UnresolvedImageRef {
+        element_id: String,
+        image_id: String,
+    },
+    #[error("{annotation_id} has a value which is not an xsd:dateTime: {value}")]
+    InvalidTimestamp {
+        annotation_id: String,
+        value: String,
+    },
+    #[error("{element_id} refers to the {kind} {id}, which does not exist")]
+    UnresolvedReference {
+        element_id: String,
+        kind: IdKind,
+        id: String,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// the metadata is incomplete or outdated, but consistent
+    Warning,
+    /// the metadata contradicts itself or the schema
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+impl ValidationError {
+    pub fn severity(&self) -> Severity {
+        match self {
+            ValidationError::DeprecatedSchema(_)
+            | ValidationError::UnknownSchema(_)
+            | ValidationError::MissingPlane { .. } => Severity::Warning,
+            ValidationError::InvalidUuid { .. }
+            | ValidationError::InvalidTimestamp { .. }
+            | ValidationError::DuplicatePlane { .. }
+            | ValidationError::UnresolvedImageRef { .. }
+            | ValidationError::UnresolvedReference { .. } => Severity::Error,
+        }
+    }
+
+    /// the name of the variant, like "MissingPlane"
+    pub fn code(&self) -> &'static str {
+        match self {
+            ValidationError::DeprecatedSchema(_) => "DeprecatedSchema",
+            ValidationError::UnknownSchema(_) => "UnknownSchema",
+            ValidationError::InvalidUuid { .. } => "InvalidUuid",
+            ValidationError::InvalidTimestamp { .. } => "InvalidTimestamp",
+            ValidationError::MissingPlane { .. } => "MissingPlane",
+            ValidationError::DuplicatePlane { .. } => "DuplicatePlane",
+            ValidationError::UnresolvedImageRef { .. } => "UnresolvedImageRef",
+            ValidationError::UnresolvedReference { .. } => "UnresolvedReference",
+        }
+    }
+
+    /// the ID of the element with the problem, "OME" for problems of the document as a whole
+    pub fn path(&self) -> &str {
+        match self {
+            ValidationError::DeprecatedSchema(_) | ValidationError::UnknownSchema(_) => "OME",
+            ValidationError::InvalidUuid { element_id, .. }
+            | ValidationError::UnresolvedImageRef { element_id, .. }
+            | ValidationError::UnresolvedReference { element_id, .. } => element_id,
+            ValidationError::InvalidTimestamp { annotation_id, .. } => annotation_id,
+            ValidationError::MissingPlane { pixels_id, .. }
+            | ValidationError::DuplicatePlane { pixels_id, .. } => pixels_id,
+        }
+    }
+}
+
+/// how often each (z, c, t) within the size of the pixels occurs in its planes
+fn plane_counts(pixels: &Pixels) -> Vec<((i32, i32, i32), usize)> {
+    let mut counts = HashMap::new();
+    for plane in &pixels.plane {
+        *counts
+            .entry((plane.the_z, plane.the_c, plane.the_t))
+            .or_default() += 1;
+    }
+    let mut zct = Vec::new();
+    for t in 0..pixels.size_t {
+        for c in 0..pixels.size_c {
+            for z in 0..pixels.size_z {
+                zct.push(((z, c, t), counts.get(&(z, c, t)).copied().unwrap_or(0)));
+            }
+        }
+    }
+    zct
+}
+
+impl Ome {
+    /// (pixels id, z, c, t) of each plane that is missing from pixels which have Plane elements
+    pub fn detect_missing_planes(&self) -> Vec<(String, i32, i32, i32)> {
+        self.planes_counted(|count| count == 0)
+    }
+
+    /// (pixels id, z, c, t) of each plane that occurs more than once
+    pub fn detect_duplicate_planes(&self) -> Vec<(String, i32, i32, i32)> {
+        self.planes_counted(|count| count > 1)
+    }
+
+    fn planes_counted(&self, select: fn(usize) -> bool) -> Vec<(String, i32, i32, i32)> {
+        self.image
+            .iter()
+            .map(|image| &image.pixels)
+            .filter(|pixels| !pixels.plane.is_empty())
+            .flat_map(|pixels| {
+                plane_counts(pixels)
+                    .into_iter()
+                    .filter(move |(_, count)| select(*count))
+                    .map(|((z, c, t), _)| (pixels.id.clone(), z, c, t))
+            })
+            .collect()
+    }
+
+    /// check that every reference, like an ImageRef or the ID of LightSourceSettings, refers to an
+    /// existing element of its kind
+    pub fn validate_references(&self) -> Vec<ValidationError> {
+        let mut defined = HashSet::new();
+        self.visit_ids(|site, id| {
+            if let IdSite::Definition(kind) = site {
+                defined.insert((kind, id.as_str()));
+            }
+        });
+        let mut errors = Vec::new();
+        let mut element_id = "";
+        self.visit_ids(|site, id| match site {
+            IdSite::Definition(_) => element_id = id,
+            IdSite::Reference(kind) if !defined.contains(&(kind, id.as_str())) => {
+                errors.push(match kind {
+                    IdKind::Image => ValidationError::UnresolvedImageRef {
+                        element_id: element_id.to_string(),
+                        image_id: id.clone(),
+                    },
+                    kind => ValidationError::UnresolvedReference {
+                        element_id: element_id.to_string(),
+                        kind,
+                        id: id.clone(),
+                    },
+                })
+            }
+            IdSite::Reference(_) => {}
+        });
+        errors
+    }
+
+    /// check the metadata for problems that do not prevent parsing
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors = Vec::new();
+        if let Some(namespace) = &self.original_namespace {
+            if DEPRECATED_NAMESPACES.contains(&namespace.as_str()) {
+                errors.push(ValidationError::DeprecatedSchema(namespace.clone()));
+            } else {
+                errors.push(ValidationError::UnknownSchema(namespace.clone()));
+            }
+        }
+        let uuids = self
+            .uuid
+            .iter()
+            .chain(self.binary_only.iter().map(|binary_only| &binary_only.uuid));
+        for uuid in uuids {
+            if !is_valid_uuid_urn(uuid) {
+                errors.push(ValidationError::InvalidUuid {
+                    element_id: "OME".to_string(),
+                    value: uuid.clone(),
+                });
+            }
+        }
+        for image in &self.image {
+            for tiff_data in &image.pixels.tiff_data {
+                if let Some(uuid) = &tiff_data.uuid {
+                    if !uuid.is_valid_urn() {
+                        errors.push(ValidationError::InvalidUuid {
+                            element_id: image.pixels.id.clone(),
+                            value: uuid.content.clone(),
+                        });
+                    }
+                }
+            }
+        }
+        for annotation in self.annotations() {
+            if let StructuredAnnotationsContent::TimestampAnnotation(timestamp) = annotation {
+                if !timestamp.is_valid() {
+                    errors.push(ValidationError::InvalidTimestamp {
+                        annotation_id: timestamp.id.clone(),
+                        value: timestamp.value.clone(),
+                    });
+                }
+            }
+        }
+        for (pixels_id, z, c, t) in self.detect_missing_planes() {
+            errors.push(ValidationError::MissingPlane { pixels_id, z, c, t });
+        }
+        for (pixels_id, z, c, t) in self.detect_duplicate_planes() {
+            errors.push(ValidationError::DuplicatePlane { pixels_id, z, c, t });
+        }
+        errors.extend(self.validate_references());
+        errors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+
+    #[test]
+    fn planes() -> Result<(), Error> {
+        let xml = |planes: &str| {
+            format!(
+                r#"<OME><Image ID="Image:0"><Pixels ID="Pixels:0" DimensionOrder="XYZCT" Type="uint8" SizeX="1" SizeY="1" SizeZ="3" SizeC="1" SizeT="1">
+                    <MetadataOnly/>{planes}
+                </Pixels></Image></OME>"#
+            )
+        };
+        let complete: Ome = xml(r#"<Plane TheZ="0" TheC="0" TheT="0"/><Plane TheZ="1" TheC="0" TheT="0"/><Plane TheZ="2" TheC="0" TheT="0"/>"#).parse()?;
+        assert!(complete.detect_missing_planes().is_empty());
+        assert!(complete.validate().is_empty());
+
+        let ome: Ome = xml(r#"<Plane TheZ="0" TheC="0" TheT="0"/><Plane TheZ="2" TheC="0" TheT="0"/><Plane TheZ="2" TheC="0" TheT="0"/>"#).parse()?;
+        assert_eq!(
+            ome.detect_missing_planes(),
+            [("Pixels:0".to_string(), 1, 0, 0)]
+        );
+        assert_eq!(
+            ome.detect_duplicate_planes(),
+            [("Pixels:0".to_string(), 2, 0, 0)]
+        );
+        let errors = ome.validate();
+        assert_eq!(errors.len(), 2);
+        assert!(matches!(
+            &errors[0],
+            ValidationError::MissingPlane { z: 1, .. }
+        ));
+        assert!(matches!(
+            &errors[1],
+            ValidationError::DuplicatePlane { z: 2, .. }
+        ));
+        assert_eq!(errors[0].severity(), Severity::Warning);
+        assert_eq!(errors[1].severity(), Severity::Error);
+        assert_eq!(errors[1].code(), "DuplicatePlane");
+        assert_eq!(errors[1].path(), "Pixels:0");
+
+        let without_planes: Ome = xml("").parse()?;
+        assert!(without_planes.detect_missing_planes().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn uuids() -> Result<(), Error> {
+        let ome: Ome = r#"<OME UUID="0f8fad5b-d9cb-469f-a165-70867728950e">
+            <BinaryOnly MetadataFile="companion.ome.xml" UUID="urn:uuid:0f8fad5b"/>
+        </OME>"#
+            .parse()?;
+        let errors = ome.validate();
+        let invalid = errors
+            .iter()
+            .map(|error| match error {
+                ValidationError::InvalidUuid { value, .. } => (error.path(), value.as_str()),
+                error => panic!("unexpected {error}"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            invalid,
+            [
+                ("OME", "0f8fad5b-d9cb-469f-a165-70867728950e"),
+                ("OME", "urn:uuid:0f8fad5b")
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn references() -> Result<(), Error> {
+        for entry in std::fs::read_dir("tests")? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|extension| extension == "xml") {
+                let ome: Ome = std::fs::read_to_string(&path)?.parse()?;
+                assert!(ome.validate_references().is_empty(), "{path:?}");
+            }
+        }
+
+        let ome: Ome = r#"<OME>
+            <Instrument ID="Instrument:0"><Laser ID="LightSource:0"/></Instrument>
+            <Image ID="Image:0"><InstrumentRef ID="Instrument:1"/>
+                <Pixels ID="Pixels:0" DimensionOrder="XYZCT" Type="uint8" SizeX="1" SizeY="1" SizeZ="1" SizeC="1" SizeT="1">
+                    <Channel ID="Channel:0:0"><LightSourceSettings ID="LightSource:0"/><AnnotationRef ID="Annotation:0"/></Channel>
+                    <Channel ID="Channel:0:1"><LightSourceSettings ID="LightSource:1"/></Channel>
+                    <MetadataOnly/>
+                </Pixels>
+            </Image>
+            <Dataset ID="Dataset:0"><ImageRef ID="Image:1"/></Dataset>
+        </OME>"#
+            .parse()?;
+        let errors = ome.validate_references();
+        let unresolved = errors
+            .iter()
+            .map(|error| match error {
+                ValidationError::UnresolvedReference {
+                    element_id,
+                    kind,
+                    id,
+                } => (element_id.as_str(), Some(*kind), id.as_str()),
+                ValidationError::UnresolvedImageRef {
+                    element_id,
+                    image_id,
+                } => (element_id.as_str(), None, image_id.as_str()),
+                error => panic!("unexpected {error}"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            unresolved,
+            [
+                ("Dataset:0", None, "Image:1"),
+                ("Image:0", Some(IdKind::Instrument), "Instrument:1"),
+                ("Channel:0:0", Some(IdKind::Annotation), "Annotation:0"),
+                ("Channel:0:1", Some(IdKind::LightSource), "LightSource:1"),
+            ]
+        );
+        assert_eq!(errors[1].code(), "UnresolvedReference");
+        assert_eq!(errors[1].severity(), Severity::Error);
+        assert_eq!(
+            errors[3].to_string(),
+            "Channel:0:1 refers to the LightSource LightSource:1, which does not exist"
+        );
+        Ok(())
+    }
+}
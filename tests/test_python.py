@@ -0,0 +1,670 @@
+import json
+import pickle
+import sys
+import threading
+import warnings
+from math import isclose
+from pathlib import Path
+
+from ome_metadata import (
+    Image,
+    Length,
+    Ome,
+    OmeError,
+    OmeParseError,
+    OmeUnitError,
+    OmeValidationError,
+    PixelType,
+    Quantity,
+    Temperature,
+    Time,
+    exposure_time,
+    from_json,
+    ifd_for,
+    image_for_well,
+    image_shape,
+    new_image,
+    new_ome,
+    pixel_size,
+    pixel_numpy_dtype,
+    plane,
+    stage_position,
+    to_json,
+    to_ome_types_dict,
+    to_xml,
+    well_name,
+    rs,
+)
+
+TESTS = Path(__file__).parent
+
+
+def ome(name: str) -> Ome:
+    return Ome.from_xml((TESTS / name).read_text())
+
+
+def test_attribute_access():
+    metadata = ome("YTL1849A131_2023_05_04__13_36_36.xml")
+    assert isinstance(metadata.images[0], Image)
+    pixels = metadata.images[0].pixels
+    assert pixels.size_x == 256
+    assert pixels.size_c == 6
+    assert isinstance(pixels.physical_size_x_unit, Length)
+    assert metadata.instruments[0].objectives[0].id == "Objective:0"
+
+
+def test_list_fields():
+    metadata = ome("YTL1849A131_2023_05_04__13_36_36.xml")
+    pixels = metadata.image[0].pixels
+    assert len(pixels.channels) == pixels.size_c
+    assert [channel.id for channel in pixels.channel][:2] == ["Channel:0:0", "Channel:0:1"]
+    assert len(pixels.planes) == pixels.size_c * pixels.size_z * pixels.size_t
+    assert pixels.planes[0].the_t == 0
+    # the pixels share the metadata, which is kept for them
+    del metadata
+    assert pixels.planes[-1].the_t == pixels.size_t - 1
+
+
+def test_dict_compatibility():
+    metadata = ome("test.xml")
+    pixels = metadata.images[0].pixels
+    with warnings.catch_warnings(record=True) as caught:
+        warnings.simplefilter("always")
+        assert metadata.keys() == list(metadata.to_dict())
+        assert list(pixels) == list(pixels.to_dict())
+        assert "type" in pixels and "size_x" in pixels and "sizes" not in pixels
+    assert caught and all(issubclass(warning.category, DeprecationWarning) for warning in caught)
+    assert pixels["type"] == pixels.to_dict()["type"]
+
+
+def test_repr_and_dict():
+    metadata = ome("test.xml")
+    assert repr(metadata.images[0]).startswith("Image(id='Image:0'")
+    assert "size_x=2" in repr(metadata.images[0].pixels)
+    as_dict = metadata.to_dict()
+    assert as_dict["image"][0]["pixels"]["size_x"] == 2
+    assert metadata["image"][0]["id"] == "Image:0"
+
+
+def test_from_file():
+    path = TESTS / "test.xml"
+    assert rs.ome_from_file(path)["image"][0]["id"] == "Image:0"
+    assert rs.ome_from_file(str(path))["image"][0]["pixels"]["size_x"] == 2
+    assert Ome.from_file(path).images[0].id == "Image:0"
+    try:
+        rs.ome_from_file(TESTS / "missing.xml")
+    except ValueError as error:
+        assert "missing.xml" in str(error)
+    else:
+        raise AssertionError("no error for a missing file")
+
+
+def test_bytes():
+    text = (TESTS / "test.xml").read_text()
+    for encoding in ("utf-8", "utf-8-sig", "utf-16", "utf-16-le"):
+        assert rs.ome(text.encode(encoding))["image"][0]["name"] == "test.tif"
+
+
+def test_to_xml_round_trip():
+    metadata = ome("YTL1849A131_2023_05_04__13_36_36.xml")
+    for kwargs in ({}, {"pretty": True}, {"write_default_units": False}):
+        xml = metadata.to_xml(**kwargs)
+        assert ("\n  <Image" in xml) == kwargs.get("pretty", False)
+        parsed = Ome.from_xml(xml)
+        assert parsed.uuid == metadata.uuid
+        assert parsed.images[0].id == metadata.images[0].id
+        assert parsed.images[0].pixels.size_c == metadata.images[0].pixels.size_c
+        assert parsed.images[0].pixels.physical_size_x == metadata.images[0].pixels.physical_size_x
+        assert [c.name for c in parsed.images[0].pixels.channels] == [
+            c.name for c in metadata.images[0].pixels.channels
+        ]
+        assert parsed.instruments[0].objectives[0].id == "Objective:0"
+    assert "PhysicalSizeXUnit" not in metadata.to_xml(write_default_units=False)
+
+
+def test_python_dict():
+    def keys(value):
+        if isinstance(value, dict):
+            for key, item in value.items():
+                yield key
+                yield from keys(item)
+        elif isinstance(value, list):
+            for item in value:
+                yield from keys(item)
+
+    as_dict = rs.ome_from_file(TESTS / "YTL1849A131_2023_05_04__13_36_36.xml")
+    for key in keys(as_dict):
+        assert key == key.lower() and not key.startswith(("@", "$")), key
+    pixels = as_dict["image"][0]["pixels"]
+    assert type(pixels["size_x"]) is int
+    assert type(pixels["physical_size_x"]) is float
+    assert isinstance(pixels["physical_size_x_unit"], Length)
+    assert as_dict["image"][0]["description"] is None
+
+
+def test_unit_comparison():
+    assert Length("um") == Length("um")
+    assert Length("um") != Length("nm")
+    assert Length("um") != "um"
+    assert len({Length("um"), Length("um"), Length("nm")}) == 2
+    factors = {Length("um"): Length("um").as_si()}
+    assert factors[Length("um")] == 1e-6
+    assert Length("nm") < Length("um") < Length("mm")
+    assert Length("mm") >= Length("um")
+    assert sorted([Length("mm"), Length("nm"), Length("um")]) == [
+        Length("nm"),
+        Length("um"),
+        Length("mm"),
+    ]
+    for unit in (Length("Pixel"), Length("ReferenceFrame"), Temperature("C")):
+        try:
+            unit.as_si()
+        except ValueError:
+            pass
+        else:
+            raise AssertionError(f"{unit} has an SI factor")
+    try:
+        Length("Pixel") < Length("um")
+    except TypeError:
+        pass
+    else:
+        raise AssertionError("pixels are ordered")
+
+
+def test_unit_variants():
+    assert Length.nm.convert(Length.um, 1500) == 1.5
+    assert Length.nm.convert("um", 1500) == 1.5
+    assert Length.um == Length("um")
+    assert repr(Length.um) == "µm"
+    assert str(Length.A) == "Å"
+    assert Length("µm") == Length.um
+    assert Length("Å") == Length.A
+    assert repr(Temperature.K) == "K"
+    assert all(getattr(Length, name) == Length(name) for name in Length.variants())
+    raises(OmeUnitError, lambda: Length.nm.convert("parsec", 1))
+    raises(TypeError, lambda: Length.nm.convert(Temperature.K, 1))
+
+
+def test_from_dict_round_trip():
+    for path in sorted(TESTS.glob("*.xml")):
+        metadata = Ome.from_file(path)
+        as_dict = metadata.to_dict()
+        assert Ome.from_dict(as_dict).to_xml() == metadata.to_xml(), path.name
+
+
+def test_from_dict():
+    metadata = Ome.from_dict(
+        {
+            "image": [
+                {
+                    "id": "Image:0",
+                    "name": "built",
+                    "pixels": {
+                        "id": "Pixels:0",
+                        "dimension_order": "XYCZT",
+                        "type": "uint16",
+                        "size_x": 512,
+                        "size_y": 512,
+                        "size_z": 1,
+                        "size_c": 1,
+                        "size_t": 1,
+                        "physical_size_x": 1,
+                        "physical_size_x_unit": Length("nm"),
+                        "metadata_only": 0,
+                    },
+                }
+            ],
+            "structured_annotations": {
+                "content": [
+                    {"kind": "TagAnnotation", "id": "Annotation:0", "value": "tag"},
+                    {"kind": "XmlAnnotation", "id": "Annotation:1", "value": "<a x='1'>b</a>"},
+                ]
+            },
+        }
+    )
+    xml = metadata.to_xml()
+    assert 'PhysicalSizeXUnit="nm"' in xml
+    assert '<TagAnnotation ID="Annotation:0"><Value>tag</Value>' in xml
+    # the xml of an XMLAnnotation is written as it is given
+    assert "<Value><a x='1'>b</a></Value>" in xml
+    assert Ome.from_xml(xml).images[0].pixels.size_x == 512
+    try:
+        Ome.from_dict({"structured_annotations": {"content": [{"id": "Annotation:0"}]}})
+    except ValueError as error:
+        assert "kind" in str(error)
+    else:
+        raise AssertionError("an annotation without kind")
+    pixels = {"id": "Pixels:0", "dimension_order": "XYCZT", "type": "uint8", "size_xx": 2}
+    image = {"id": "Image:0", "pixels": pixels}
+    error = raises(OmeParseError, lambda: Ome.from_dict({"image": [image]}))
+    assert "size_xx" in str(error)
+    instrument = {"id": "Instrument:0", "microscop": {}}
+    error = raises(OmeParseError, lambda: Ome.from_dict({"instrument": [instrument]}))
+    assert "microscop" in str(error)
+
+
+def test_pickle():
+    metadata = ome("YTL1849A131_2023_05_04__13_36_36.xml")
+    unpickled = pickle.loads(pickle.dumps(metadata))
+    assert isinstance(unpickled, Ome)
+    assert unpickled.images[0].pixels.size_x == metadata.images[0].pixels.size_x
+    assert unpickled.images[0].pixels.channels[1].name == metadata.images[0].pixels.channels[1].name
+    assert unpickled.to_xml() == metadata.to_xml()
+    unit = metadata.images[0].pixels.physical_size_x_unit
+    assert pickle.loads(pickle.dumps(unit)) == unit
+    as_dict = pickle.loads(pickle.dumps(metadata.to_dict()))
+    assert as_dict["image"][0]["pixels"]["physical_size_x_unit"] == unit
+
+
+def test_validate():
+    broken = Ome.from_xml(
+        """<OME>
+            <Dataset ID="Dataset:0"><ImageRef ID="Image:1"/></Dataset>
+            <Image ID="Image:0">
+                <Pixels ID="Pixels:0" DimensionOrder="XYZCT" Type="uint8" SizeX="1" SizeY="1" SizeZ="2" SizeC="1" SizeT="1">
+                    <MetadataOnly/><Plane TheZ="0" TheC="0" TheT="0"/>
+                </Pixels>
+            </Image>
+        </OME>"""
+    )
+    report = broken.validate()
+    assert [entry["code"] for entry in report] == ["MissingPlane", "UnresolvedImageRef"]
+    assert report[0]["path"] == "Pixels:0"
+    assert report[0]["severity"] == "warning"
+    assert report[1] == {
+        "severity": "error",
+        "code": "UnresolvedImageRef",
+        "path": "Dataset:0",
+        "message": "Dataset:0 refers to Image:1, which does not exist",
+    }
+    try:
+        broken.validate(raise_on_error=True)
+    except OmeValidationError as error:
+        assert isinstance(error, ValueError)
+        assert error.report == report
+        assert "Image:1" in str(error)
+    else:
+        raise AssertionError("no OmeValidationError")
+    assert ome("test.xml").validate(raise_on_error=True) == []
+
+
+def test_numpy_dtype():
+    expected = {
+        "int8": ("int8", "int8"),
+        "int16": ("int16", ">i2"),
+        "int32": ("int32", ">i4"),
+        "uint8": ("uint8", "uint8"),
+        "uint16": ("uint16", ">u2"),
+        "uint32": ("uint32", ">u4"),
+        "float": ("float32", ">f4"),
+        "double": ("float64", ">f8"),
+    }
+    for name, (little, big) in expected.items():
+        assert PixelType(name).numpy_dtype() == little
+        assert PixelType(name).numpy_dtype(big_endian=True) == big
+    try:
+        import numpy
+    except ImportError:
+        pass
+    else:
+        for name, (little, big) in expected.items():
+            assert numpy.dtype(little) == numpy.dtype(big).newbyteorder("=")
+    for name in ("complex", "double-complex", "bit"):
+        try:
+            PixelType(name).numpy_dtype()
+        except ValueError as error:
+            assert name in str(error)
+        else:
+            raise AssertionError(f"{name} has a numpy dtype")
+    raises(TypeError, lambda: PixelType(16))
+    metadata = ome("test.xml")
+    assert str(PixelType(metadata.images[0].pixels.type)) == "int8"
+    assert pixel_numpy_dtype(metadata) == "int8"
+    metadata = ome("zct.ome.xml")
+    pixels = metadata.images[0].pixels
+    assert image_shape(metadata) == (pixels.size_t, pixels.size_c, pixels.size_z, pixels.size_y, pixels.size_x)
+    assert image_shape(metadata, 0, "YX") == (pixels.size_y, pixels.size_x)
+    for call in (lambda: image_shape(metadata, 0, "XX"), lambda: pixel_numpy_dtype(metadata, 5)):
+        try:
+            call()
+        except (ValueError, IndexError):
+            pass
+        else:
+            raise AssertionError("no error")
+
+
+def test_parse_releases_gil():
+    # with a switch interval this long a thread holding the GIL is not interrupted, so the main
+    # thread can only set the event while the other thread parses if parsing releases the GIL,
+    # otherwise the other thread parses the maximum number of times
+    images = "".join(
+        f'<Image ID="Image:{i}"><Pixels ID="Pixels:{i}" DimensionOrder="XYCZT" Type="uint8" SizeX="1" '
+        f'SizeY="1" SizeZ="1" SizeC="1" SizeT="100">'
+        + "".join(f'<Plane TheZ="0" TheC="0" TheT="{t}" DeltaT="{t}"/>' for t in range(100))
+        + "</Pixels></Image>"
+        for i in range(20)
+    )
+    xml = f'<OME xmlns="http://www.openmicroscopy.org/Schemas/OME/2016-06">{images}</OME>'
+    progressed = threading.Event()
+    parses = []
+
+    def parse():
+        while not progressed.is_set() and len(parses) < 100:
+            Ome.from_xml(xml)
+            parses.append(None)
+
+    interval = sys.getswitchinterval()
+    sys.setswitchinterval(1000)
+    try:
+        thread = threading.Thread(target=parse)
+        thread.start()
+        progressed.set()
+        thread.join()
+    finally:
+        sys.setswitchinterval(interval)
+    assert len(parses) < 100
+
+
+def raises(exception, call):
+    try:
+        call()
+    except exception as error:
+        return error
+    raise AssertionError(f"no {exception.__name__}")
+
+
+def test_exceptions():
+    for exception in (OmeParseError, OmeUnitError, OmeValidationError):
+        assert issubclass(exception, OmeError)
+    assert issubclass(OmeError, ValueError)
+    raises(OmeParseError, lambda: Ome.from_xml("<OME><Image></OME>"))
+    raises(OmeParseError, lambda: rs.ome(b"\xff\xfe<\x00O"))
+    raises(OmeParseError, lambda: Ome.from_dict({"image": [{"id": "Image:0"}]}))
+    error = raises(OmeUnitError, lambda: Length("Pixel").convert("um", 1.0))
+    assert "pixel" in str(error)
+    error = raises(OmeUnitError, lambda: Length("um").convert("ReferenceFrame", 1.0))
+    assert "reference frame" in str(error)
+    raises(OmeUnitError, lambda: Length("parsec"))
+    error = raises(OmeError, lambda: Ome.from_file(TESTS / "missing.xml"))
+    assert "missing.xml" in str(error)
+    error = raises(OmeParseError, lambda: Ome.from_file(TESTS / "test_python.py"))
+    assert "test_python.py" in str(error)
+
+
+PLATE = """<OME xmlns="http://www.openmicroscopy.org/Schemas/OME/2016-06">
+    <Plate ID="Plate:0" Rows="2" Columns="12">
+        <Well ID="Well:0" Row="1" Column="6">
+            <WellSample ID="WellSample:0" Index="0"><ImageRef ID="Image:1"/></WellSample>
+        </Well>
+    </Plate>
+    <Image ID="Image:0"><Pixels ID="Pixels:0" DimensionOrder="XYZCT" Type="uint8" SizeX="1" SizeY="1" SizeZ="1" SizeC="1" SizeT="1"><MetadataOnly/></Pixels></Image>
+    <Image ID="Image:1" Name="B7">
+        <Pixels ID="Pixels:1" DimensionOrder="XYZCT" Type="uint8" SizeX="1" SizeY="1" SizeZ="2" SizeC="1" SizeT="2">
+            <TiffData IFD="4" PlaneCount="4"/>
+            <Plane TheZ="1" TheC="0" TheT="1" PositionX="3.5"/>
+        </Pixels>
+    </Image>
+</OME>"""
+
+
+MALFORMED_TIFF_DATA = """<OME xmlns="http://www.openmicroscopy.org/Schemas/OME/2016-06">
+    <Image ID="Image:0">
+        <Pixels ID="Pixels:0" DimensionOrder="XYZCT" Type="uint8" SizeX="1" SizeY="1" SizeZ="2" SizeC="1" SizeT="1">
+            <TiffData IFD="0" FirstZ="-1" FirstC="-3" PlaneCount="2"/>
+        </Pixels>
+    </Image>
+</OME>"""
+
+
+def test_malformed_tiff_data():
+    metadata = Ome.from_xml(MALFORMED_TIFF_DATA)
+    for ome in (metadata, metadata.to_dict()):
+        assert ifd_for(ome, 0, 0, 0, 0) is None
+        assert ifd_for(ome, 0, -1, 0, 0) is None
+
+
+def test_navigation():
+    metadata = Ome.from_xml(PLATE)
+    for ome in (metadata, metadata.to_dict()):
+        assert well_name(ome, 0, 1, 6) == "B7"
+        assert well_name(ome, "Plate:0", 0, 0) == "A1"
+        image = image_for_well(ome, "Plate:0", "B7")
+        assert image_for_well(ome, 0, "A1") is None
+        found = plane(ome, "Image:1", 1, 0, 1)
+        assert plane(ome, 1, 0, 0, 0) is None
+        assert ifd_for(ome, 1, 1, 0, 1) == 7
+        assert ifd_for(ome, "Image:0", 0, 0, 0) is None
+        raises(KeyError, lambda: well_name(ome, "Plate:1", 0, 0))
+        raises(IndexError, lambda: plane(ome, 2, 0, 0, 0))
+        if isinstance(ome, Ome):
+            assert isinstance(image, Image)
+            assert image.name == "B7"
+            assert found.position_x == 3.5
+        else:
+            assert image["name"] == "B7"
+            assert found["position_x"] == 3.5
+
+
+def test_json():
+    for name in ("zct.ome.xml", "YTL1849A131_2023_05_04__13_36_36.xml", "test.xml"):
+        metadata = ome(name)
+        text = to_json(metadata)
+        assert to_json(metadata.to_dict()) == text
+        document = json.loads(text)
+        assert document["Image"][0]["ID"] == metadata.images[0].id
+        assert document["Image"][0]["Pixels"]["PhysicalSizeXUnit"] == "µm"
+        restored = from_json(text)
+        assert restored.to_xml() == metadata.to_xml()
+        for image, original in zip(restored.images, metadata.images):
+            pixels, expected = image.pixels, original.pixels
+            assert (pixels.size_x, pixels.size_y, pixels.size_z, pixels.size_c, pixels.size_t) == (
+                expected.size_x,
+                expected.size_y,
+                expected.size_z,
+                expected.size_c,
+                expected.size_t,
+            )
+            assert [c.name for c in pixels.channels] == [c.name for c in expected.channels]
+        assert to_json(metadata, indent=2).startswith('{\n  "')
+        assert json.loads(to_json(metadata, indent=2)) == document
+    with open(TESTS / "test.json") as file:
+        assert from_json(file.read()).to_xml() == ome("test.xml").to_xml()
+    raises(OmeParseError, lambda: from_json("{"))
+
+
+OME_TYPES = """<OME xmlns="http://www.openmicroscopy.org/Schemas/OME/2016-06">
+  <Instrument ID="Instrument:0">
+    <Laser ID="LightSource:0" Wavelength="488"/>
+    <Objective ID="Objective:0" LensNA="1.4"/>
+  </Instrument>
+  <Image ID="Image:0" Name="cells">
+    <InstrumentRef ID="Instrument:0"/>
+    <Pixels ID="Pixels:0" DimensionOrder="XYZCT" Type="uint16" SizeX="4" SizeY="4" SizeZ="1"
+        SizeC="2" SizeT="1" PhysicalSizeX="0.5" PhysicalSizeXUnit="nm">
+      <Channel ID="Channel:0:0" Name="GFP" Color="-16776961" EmissionWavelength="510"/>
+      <Channel ID="Channel:0:1"/>
+      <TiffData IFD="0" PlaneCount="1"/>
+    </Pixels>
+    <ROIRef ID="ROI:0"/>
+  </Image>
+  <StructuredAnnotations>
+    <MapAnnotation ID="Annotation:0"><Value><M K="a">1</M></Value></MapAnnotation>
+    <XMLAnnotation ID="Annotation:1"><Value><a x="1">b</a></Value></XMLAnnotation>
+  </StructuredAnnotations>
+  <ROI ID="ROI:0"><Union><Point ID="Shape:0" X="1" Y="2"/></Union></ROI>
+</OME>"""
+
+
+def test_ome_types_dict():
+    metadata = Ome.from_xml(OME_TYPES)
+    document = to_ome_types_dict(metadata)
+    assert to_ome_types_dict(metadata.to_dict()) == document
+    instrument = document["instruments"][0]
+    assert instrument["lasers"][0]["wavelength"] == 488
+    assert abs(instrument["objectives"][0]["lens_na"] - 1.4) < 1e-6
+    assert document["structured_annotations"]["map_annotations"][0]["value"] == {
+        "ms": [{"k": "a", "value": "1"}]
+    }
+    assert document["rois"][0]["union"]["points"][0]["x"] == 1
+    image = document["images"][0]
+    assert set(image) == {
+        "id",
+        "name",
+        "instrument_ref",
+        "pixels",
+        "roi_refs",
+        "microbeam_manipulation_refs",
+        "annotation_refs",
+    }
+    assert image["roi_refs"] == [{"id": "ROI:0"}]
+    pixels = image["pixels"]
+    assert pixels["dimension_order"] == "XYZCT"
+    assert pixels["type"] == "uint16"
+    assert pixels["physical_size_x_unit"] == "nm"
+    assert pixels["physical_size_y_unit"] == "µm"
+    assert pixels["tiff_data_blocks"] == [
+        {"ifd": 0, "plane_count": 1, "first_z": 0, "first_c": 0, "first_t": 0}
+    ]
+    assert [channel["id"] for channel in pixels["channels"]] == ["Channel:0:0", "Channel:0:1"]
+    assert pixels["channels"][1]["color"] == -1
+    value = document["structured_annotations"]["xml_annotations"][0]["value"]
+    assert value["any_elements"][0]["qname"].endswith("}a")
+    assert value["any_elements"][0]["attributes"] == {"x": "1"}
+    assert value["any_elements"][0]["text"] == "b"
+    channel = pixels["channels"][0]
+    assert set(channel) == {
+        "id",
+        "name",
+        "color",
+        "emission_wavelength",
+        "emission_wavelength_unit",
+        "excitation_wavelength_unit",
+        "pinhole_size_unit",
+        "annotation_refs",
+    }
+    assert channel["color"] == -16776961
+    assert channel["emission_wavelength_unit"] == "nm"
+
+
+def test_ome_types_model_validate():
+    from ome_types import OME
+
+    model = OME.model_validate(to_ome_types_dict(Ome.from_xml(OME_TYPES)))
+    pixels = model.images[0].pixels
+    assert pixels.physical_size_x_unit.value == "nm"
+    assert [channel.color.as_int() for channel in pixels.channels] == [-16776961, -1]
+    assert model.instruments[0].lasers[0].wavelength == 488
+    assert model.rois[0].union.points[0].x == 1
+    assert len(model.structured_annotations.xml_annotations[0].value.any_elements) == 1
+    for name in ("test.xml", "annotations.xml", "plate.xml", "roi_shapes.xml", "YTL378_JF552.xml"):
+        OME.model_validate(to_ome_types_dict(Ome.from_file(TESTS / name)))
+
+
+def test_new_ome():
+    metadata = new_ome(
+        images=[
+            new_image(
+                name="pos0",
+                size_x=512,
+                size_y=512,
+                size_c=2,
+                size_z=5,
+                size_t=10,
+                pixel_type="uint16",
+                physical_size_x=0.108,
+                channels=["GFP", "mCherry"],
+                colors=[16711935, -16776961],
+            ),
+            new_image(name="pos1", size_x=256, size_y=256),
+        ],
+        creator="acquisition",
+    )
+    parsed = Ome.from_xml(to_xml(metadata))
+    assert parsed.creator == "acquisition"
+    assert [image.id for image in parsed.images] == ["Image:0", "Image:1"]
+    image = parsed.images[0]
+    assert image.name == "pos0"
+    pixels = image.pixels
+    assert (pixels.size_x, pixels.size_y, pixels.size_z, pixels.size_c, pixels.size_t) == (
+        512,
+        512,
+        5,
+        2,
+        10,
+    )
+    assert str(PixelType(pixels.type)) == "uint16"
+    assert abs(pixels.physical_size_x - 0.108) < 1e-6
+    assert [c.id for c in pixels.channels] == ["Channel:0:0", "Channel:0:1"]
+    assert [c.name for c in pixels.channels] == ["GFP", "mCherry"]
+    assert [c.color for c in pixels.channels] == [16711935, -16776961]
+    assert parsed.images[1].pixels.channels[0].color == -1
+    assert [c.id for c in parsed.images[1].pixels.channels] == ["Channel:1:0"]
+    assert str(PixelType(parsed.images[1].pixels.type)) == "uint16"
+    assert to_xml(metadata) == metadata.to_xml()
+    assert not [e for e in parsed.validate() if e["severity"] == "error"]
+
+    try:
+        new_image(size_x=0, size_y=2, size_c=2, channels=["a"], pixel_type="uint17")
+    except OmeValidationError as error:
+        assert [entry["path"] for entry in error.report] == ["size_x", "channels", "pixel_type"]
+        assert "size_x: must be at least 1, not 0" in str(error)
+        assert "1 names given for size_c=2" in str(error)
+        assert "uint17" in str(error)
+    else:
+        raise AssertionError("invalid arguments are accepted")
+    raises(OmeValidationError, lambda: new_image(size_x=1, size_y=1, physical_size_x=-1))
+    for size in (float("inf"), float("nan")):
+        raises(OmeValidationError, lambda: new_image(size_x=1, size_y=1, time_increment=size))
+    error = raises(
+        OmeValidationError, lambda: new_image(size_x=1, size_y=1, acquisition_date="yesterday")
+    )
+    assert "yesterday is not an xsd:dateTime" in str(error)
+    raises(OmeValidationError, lambda: new_image(size_x=1, size_y=1, size_c=2, colors=[-1]))
+    image = new_image(size_x=1, size_y=1, acquisition_date="2025-01-29T14:42:42")
+    assert image.acquisition_date == "2025-01-29T14:42:42"
+    raises(TypeError, lambda: new_image(1, 1))
+
+
+QUANTITIES = """<OME xmlns="http://www.openmicroscopy.org/Schemas/OME/2016-06">
+    <Image ID="Image:0">
+        <Pixels ID="Pixels:0" DimensionOrder="XYZCT" Type="uint8" SizeX="1" SizeY="1" SizeZ="1" SizeC="1" SizeT="1"
+                PhysicalSizeX="650" PhysicalSizeXUnit="nm" PhysicalSizeY="0.65">
+            <MetadataOnly/>
+            <Plane TheZ="0" TheC="0" TheT="0" ExposureTime="20" ExposureTimeUnit="ms" PositionX="3.5" PositionXUnit="mm"/>
+        </Pixels>
+    </Image>
+</OME>"""
+
+
+def test_quantity():
+    metadata = Ome.from_xml(QUANTITIES)
+    for ome in (metadata, metadata.to_dict()):
+        size = pixel_size(ome)
+        assert size.value == 650 and size.unit == Length.nm
+        assert isclose(size.to(Length.um).value, 0.65)
+        assert isclose(size.to("um").value, pixel_size(ome, "Image:0", "y").value, rel_tol=1e-6)
+        assert pixel_size(ome, 0, "z") is None
+        assert isclose(exposure_time(ome, 0, 0).to("s").value, 0.02)
+        x, y, z = stage_position(ome, 0, 0)
+        assert x == Quantity(3.5, Length.mm) and isclose(x.to("um").value, 3500)
+        assert y is None and z is None
+        raises(IndexError, lambda: exposure_time(ome, 0, 1))
+        raises(ValueError, lambda: pixel_size(ome, 0, "t"))
+
+    size = Quantity(650, Length.nm)
+    assert repr(size) == "Quantity(650.0, Length.nm)"
+    assert str(size) == "650.0 nm"
+    assert str(Quantity(0.1, Length.um)) == "0.1 µm"
+    assert isclose((size + Quantity(0.35, Length.um)).to(Length.um).value, 1.0)
+    assert (size - Quantity(150, Length.nm)).value == 500
+    assert (2 * size).value == (size * 2).value == 1300
+    assert (size / 2).value == 325 and isclose(size / Quantity(1.3, Length.um), 0.5)
+    assert -size < size == abs(-size)
+    assert size != Quantity(650, Time.ms)
+    assert pickle.loads(pickle.dumps(size)) == size
+    raises(OmeUnitError, lambda: size + Quantity(1, Time.s))
+    raises(OmeUnitError, lambda: size.to(Time.s))
+    raises(OmeUnitError, lambda: size.to("s"))
+    raises(TypeError, lambda: size < Quantity(1, Time.s))
+    raises(TypeError, lambda: Quantity(1, "um"))
+    raises(TypeError, lambda: size / "x")